@@ -0,0 +1,15 @@
+-- Per-farm, per-month climatological baselines for spectral indices
+CREATE TABLE IF NOT EXISTS spectral_baselines (
+    id BIGSERIAL PRIMARY KEY,
+    farm_id BIGINT NOT NULL REFERENCES farms(id) ON DELETE CASCADE,
+    index_name VARCHAR(20) NOT NULL,
+    month SMALLINT NOT NULL CHECK (month BETWEEN 1 AND 12),
+    mean_value DOUBLE PRECISION NOT NULL,
+    std_dev DOUBLE PRECISION NOT NULL,
+    sample_count INTEGER NOT NULL,
+    years_covered INTEGER NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (farm_id, index_name, month)
+);
+
+CREATE INDEX IF NOT EXISTS idx_spectral_baselines_farm_id ON spectral_baselines(farm_id);
@@ -0,0 +1,29 @@
+-- Region used to group farms for calibration and regional reporting
+ALTER TABLE farms ADD COLUMN IF NOT EXISTS region VARCHAR(100);
+
+CREATE INDEX IF NOT EXISTS idx_farms_region ON farms(region);
+
+-- In-situ electrical conductivity readings from field sensors
+CREATE TABLE IF NOT EXISTS sensor_readings (
+    id BIGSERIAL PRIMARY KEY,
+    farm_id BIGINT NOT NULL REFERENCES farms(id) ON DELETE CASCADE,
+    ec_ds_m DOUBLE PRECISION NOT NULL CHECK (ec_ds_m >= 0),
+    sensor_id VARCHAR(100),
+    measured_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sensor_readings_farm_id ON sensor_readings(farm_id);
+CREATE INDEX IF NOT EXISTS idx_sensor_readings_measured_at ON sensor_readings(measured_at DESC);
+
+-- Per-region linear fit of EC (dS/m) against NDSI
+CREATE TABLE IF NOT EXISTS salinity_calibrations (
+    region VARCHAR(100) PRIMARY KEY,
+    slope DOUBLE PRECISION NOT NULL,
+    intercept DOUBLE PRECISION NOT NULL,
+    residual_std DOUBLE PRECISION NOT NULL,
+    r_squared DOUBLE PRECISION NOT NULL,
+    sample_count INTEGER NOT NULL,
+    ndsi_mean DOUBLE PRECISION NOT NULL,
+    ndsi_sxx DOUBLE PRECISION NOT NULL,
+    fitted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS farm_geometry_versions (
+    id BIGSERIAL PRIMARY KEY,
+    farm_id BIGINT NOT NULL REFERENCES farms(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    geometry GEOMETRY(POLYGON, 4326) NOT NULL,
+    area_hectares NUMERIC(12, 4),
+    changed_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    restored_from INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (farm_id, version)
+);
+
+-- Seed the current boundary of existing farms as their first version.
+INSERT INTO farm_geometry_versions (farm_id, version, geometry, area_hectares, changed_by, created_at)
+SELECT id, 1, geometry, area_hectares, user_id, updated_at
+FROM farms
+ON CONFLICT (farm_id, version) DO NOTHING;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS regional_metrics (
+    region VARCHAR(100) PRIMARY KEY,
+    farm_count INTEGER NOT NULL,
+    total_area_hectares DOUBLE PRECISION NOT NULL,
+    avg_ndsi DOUBLE PRECISION,
+    max_ndsi DOUBLE PRECISION,
+    open_alerts INTEGER NOT NULL,
+    critical_alerts INTEGER NOT NULL,
+    risk_level VARCHAR(20) NOT NULL CHECK (risk_level IN ('low', 'medium', 'high', 'critical')),
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
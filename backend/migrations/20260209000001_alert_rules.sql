@@ -0,0 +1,29 @@
+-- Salinity tolerance per crop; the 'default' row applies when a farm has no crop set
+CREATE TABLE IF NOT EXISTS crop_alert_defaults (
+    crop_type VARCHAR(30) PRIMARY KEY,
+    anomaly_multiplier DOUBLE PRECISION NOT NULL,
+    max_salinity_g_l DOUBLE PRECISION NOT NULL,
+    critical_salinity_g_l DOUBLE PRECISION NOT NULL
+);
+
+INSERT INTO crop_alert_defaults (crop_type, anomaly_multiplier, max_salinity_g_l, critical_salinity_g_l) VALUES
+    ('default', 2.0, 4.0, 8.0),
+    ('rice', 2.0, 2.0, 4.0),
+    ('shrimp', 2.5, 25.0, 35.0),
+    ('durian', 1.5, 0.5, 1.0)
+ON CONFLICT (crop_type) DO NOTHING;
+
+-- Per-farm crop assignment and threshold overrides; NULL falls back to the crop default
+CREATE TABLE IF NOT EXISTS farm_alert_rules (
+    farm_id BIGINT PRIMARY KEY REFERENCES farms(id) ON DELETE CASCADE,
+    crop_type VARCHAR(30) REFERENCES crop_alert_defaults(crop_type),
+    anomaly_multiplier DOUBLE PRECISION,
+    max_salinity_g_l DOUBLE PRECISION,
+    critical_salinity_g_l DOUBLE PRECISION,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER farm_alert_rules_updated_at BEFORE UPDATE ON farm_alert_rules
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
@@ -0,0 +1,3 @@
+-- NULL keeps data indefinitely
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS data_retention_days INTEGER
+    CHECK (data_retention_days IS NULL OR data_retention_days > 0);
@@ -0,0 +1,6 @@
+-- Set when the user confirms deletion; the account is purged once this passes
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deletion_scheduled_at TIMESTAMPTZ;
+
+ALTER TABLE auth_tokens DROP CONSTRAINT IF EXISTS auth_tokens_purpose_check;
+ALTER TABLE auth_tokens ADD CONSTRAINT auth_tokens_purpose_check
+    CHECK (purpose IN ('password_reset', 'email_verification', 'account_deletion'));
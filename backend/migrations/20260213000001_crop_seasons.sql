@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS crop_seasons (
+    id BIGSERIAL PRIMARY KEY,
+    farm_id BIGINT NOT NULL REFERENCES farms(id) ON DELETE CASCADE,
+    crop_type VARCHAR(30) NOT NULL,
+    variety VARCHAR(100),
+    planting_date DATE NOT NULL,
+    expected_harvest_date DATE,
+    growth_stage VARCHAR(20) NOT NULL DEFAULT 'germination'
+        CHECK (growth_stage IN ('germination', 'seedling', 'vegetative', 'reproductive', 'ripening', 'harvested')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (expected_harvest_date IS NULL OR expected_harvest_date > planting_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_crop_seasons_farm_planting ON crop_seasons(farm_id, planting_date DESC);
+
+CREATE TRIGGER crop_seasons_updated_at BEFORE UPDATE ON crop_seasons
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
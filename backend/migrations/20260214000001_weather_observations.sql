@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS weather_observations (
+    id BIGSERIAL PRIMARY KEY,
+    farm_id BIGINT NOT NULL REFERENCES farms(id) ON DELETE CASCADE,
+    observed_on DATE NOT NULL,
+    source VARCHAR(30) NOT NULL,
+    t_min_c DOUBLE PRECISION,
+    t_max_c DOUBLE PRECISION,
+    precipitation_mm DOUBLE PRECISION,
+    et0_mm DOUBLE PRECISION,
+    sea_level_max_m DOUBLE PRECISION,
+    -- Days still in the future when fetched; overwritten by later fetches
+    is_forecast BOOLEAN NOT NULL DEFAULT FALSE,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (farm_id, observed_on)
+);
+
+CREATE INDEX IF NOT EXISTS idx_weather_observations_farm_date ON weather_observations(farm_id, observed_on DESC);
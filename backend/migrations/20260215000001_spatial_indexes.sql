@@ -0,0 +1,4 @@
+-- Geodesic radius searches cast to geography; index that expression directly
+CREATE INDEX IF NOT EXISTS idx_farms_geography ON farms USING GIST((geometry::geography));
+
+CREATE INDEX IF NOT EXISTS idx_farm_geometry_versions_geometry ON farm_geometry_versions USING GIST(geometry);
@@ -0,0 +1,11 @@
+-- Single row of admin-adjustable runtime settings; absent keys use built-in defaults.
+CREATE TABLE IF NOT EXISTS system_settings (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    settings JSONB NOT NULL DEFAULT '{}'::jsonb,
+    updated_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER system_settings_updated_at BEFORE UPDATE ON system_settings
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
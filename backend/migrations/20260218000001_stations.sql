@@ -0,0 +1,33 @@
+-- Provincial monitoring stations: ground-truth salinity not tied to any farm.
+CREATE TABLE IF NOT EXISTS stations (
+    id BIGSERIAL PRIMARY KEY,
+    code VARCHAR(50) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    river VARCHAR(100),
+    agency VARCHAR(255),
+    location GEOMETRY(POINT, 4326) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stations_geography ON stations USING GIST((location::geography));
+
+CREATE TRIGGER stations_updated_at BEFORE UPDATE ON stations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
+
+-- A salinity reading belongs to exactly one farm or one station.
+ALTER TABLE salinity_logs ALTER COLUMN farm_id DROP NOT NULL;
+ALTER TABLE salinity_logs ADD COLUMN IF NOT EXISTS station_id BIGINT REFERENCES stations(id) ON DELETE CASCADE;
+ALTER TABLE salinity_logs ADD CONSTRAINT salinity_logs_owner_check CHECK ((farm_id IS NULL) <> (station_id IS NULL));
+
+CREATE INDEX IF NOT EXISTS idx_salinity_logs_station_id ON salinity_logs(station_id, recorded_at DESC) WHERE station_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS farm_station_subscriptions (
+    farm_id BIGINT NOT NULL REFERENCES farms(id) ON DELETE CASCADE,
+    station_id BIGINT NOT NULL REFERENCES stations(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (farm_id, station_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_farm_station_subscriptions_station_id ON farm_station_subscriptions(station_id);
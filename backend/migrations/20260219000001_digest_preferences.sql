@@ -0,0 +1,5 @@
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS email_alerts_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS digest_frequency VARCHAR(10) NOT NULL DEFAULT 'weekly'
+        CHECK (digest_frequency IN ('off', 'daily', 'weekly')),
+    ADD COLUMN IF NOT EXISTS last_digest_sent_at TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+-- Archived farms keep their history until the purge job removes them.
+ALTER TABLE farms ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_farms_deleted_at ON farms(deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,26 @@
+-- Accent-insensitive full-text search. There is no Vietnamese stemmer, so
+-- words are only lower-cased and unaccented (đ -> d, ệ -> e, ...).
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_ts_config WHERE cfgname = 'unaccent_simple') THEN
+        CREATE TEXT SEARCH CONFIGURATION unaccent_simple (COPY = simple);
+        ALTER TEXT SEARCH CONFIGURATION unaccent_simple
+            ALTER MAPPING FOR hword, hword_part, word WITH unaccent, simple;
+    END IF;
+END
+$$;
+
+ALTER TABLE farms ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('unaccent_simple', COALESCE(name, '')), 'A') ||
+        setweight(to_tsvector('unaccent_simple', COALESCE(region, '')), 'B')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_farms_search ON farms USING GIN(search_vector);
+
+ALTER TABLE alerts ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (to_tsvector('unaccent_simple', message)) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_alerts_search ON alerts USING GIN(search_vector);
@@ -0,0 +1,13 @@
+-- Where each analysed image found water, so intrusion vectors can be measured
+-- between two real observations instead of being extrapolated from one.
+ALTER TABLE salinity_logs ADD COLUMN IF NOT EXISTS water_centroid GEOMETRY(POINT, 4326);
+ALTER TABLE salinity_logs ADD COLUMN IF NOT EXISTS water_pixel_count INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_salinity_logs_water_observations
+    ON salinity_logs(farm_id, recorded_at DESC) WHERE water_centroid IS NOT NULL;
+
+-- The two observations a vector spans. NULL on vectors computed before this.
+ALTER TABLE intrusion_vectors ADD COLUMN IF NOT EXISTS previous_observed_at TIMESTAMPTZ;
+ALTER TABLE intrusion_vectors ADD COLUMN IF NOT EXISTS current_observed_at TIMESTAMPTZ;
+ALTER TABLE intrusion_vectors ADD COLUMN IF NOT EXISTS previous_pixel_count INTEGER;
+ALTER TABLE intrusion_vectors ADD COLUMN IF NOT EXISTS current_pixel_count INTEGER;
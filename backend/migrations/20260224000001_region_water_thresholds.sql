@@ -0,0 +1,8 @@
+-- Admin-set cut-off on the segmentation model's per-pixel water probability.
+-- Regions without a row use a threshold estimated from each image.
+CREATE TABLE IF NOT EXISTS region_water_thresholds (
+    region VARCHAR(100) PRIMARY KEY,
+    water_threshold DOUBLE PRECISION NOT NULL CHECK (water_threshold > 0 AND water_threshold < 1),
+    updated_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- Per-user home screen: which widgets appear and in what order
+CREATE TABLE IF NOT EXISTS dashboard_layouts (
+    user_id BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    widgets JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER dashboard_layouts_updated_at BEFORE UPDATE ON dashboard_layouts
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
@@ -0,0 +1,33 @@
+-- Registered segmentation model versions. One model serves analyses and at
+-- most one more runs in shadow next to it so the two can be compared.
+CREATE TABLE IF NOT EXISTS ai_models (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    config_path TEXT NOT NULL,
+    weights_path TEXT NOT NULL,
+    metrics JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status VARCHAR(20) NOT NULL DEFAULT 'inactive' CHECK (status IN ('inactive', 'active', 'shadow')),
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    activated_at TIMESTAMPTZ,
+    UNIQUE (name, version)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ai_models_single_status
+    ON ai_models (status) WHERE status IN ('active', 'shadow');
+
+-- "name@version" of the model behind an AI analysis.
+ALTER TABLE salinity_logs ADD COLUMN IF NOT EXISTS model_version VARCHAR(160);
+
+-- Shadow model output for an analysis, stored next to what the active model saw.
+CREATE TABLE IF NOT EXISTS shadow_analyses (
+    id BIGSERIAL PRIMARY KEY,
+    log_id BIGINT NOT NULL REFERENCES salinity_logs(id) ON DELETE CASCADE,
+    model_id BIGINT NOT NULL REFERENCES ai_models(id) ON DELETE CASCADE,
+    active_coverage_percent DOUBLE PRECISION NOT NULL,
+    shadow_coverage_percent DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_shadow_analyses_model ON shadow_analyses (model_id, created_at DESC);
@@ -0,0 +1,16 @@
+-- Simplifies a geometry with doubling tolerances until it has at most
+-- max_points vertices. Topology is preserved, so a geometry that cannot be
+-- reduced enough by 0.01 degrees is returned at its best attempt.
+CREATE OR REPLACE FUNCTION simplify_to_vertex_limit(geom geometry, max_points INTEGER)
+RETURNS geometry AS $$
+DECLARE
+    tolerance DOUBLE PRECISION := 1e-7;
+    result geometry := geom;
+BEGIN
+    WHILE ST_NPoints(result) > max_points AND tolerance <= 0.01 LOOP
+        result := ST_SimplifyPreserveTopology(geom, tolerance);
+        tolerance := tolerance * 2;
+    END LOOP;
+    RETURN result;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE STRICT;
@@ -0,0 +1,12 @@
+-- Subscription plan per user; limits for each plan live in system_settings.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS plan VARCHAR(20) NOT NULL DEFAULT 'free'
+        CHECK (plan IN ('free', 'pro'));
+
+-- Metered usage per user and calendar month (first day of the month).
+CREATE TABLE IF NOT EXISTS usage_counters (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period DATE NOT NULL,
+    analyses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, period)
+);
@@ -0,0 +1,14 @@
+-- Metered activity for attributing imagery and compute costs to users.
+-- quantity is a count, except for sentinel_download where it is gigabytes.
+CREATE TABLE IF NOT EXISTS usage_events (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    farm_id BIGINT REFERENCES farms(id) ON DELETE SET NULL,
+    kind VARCHAR(30) NOT NULL
+        CHECK (kind IN ('analysis_run', 'sentinel_download', 'report_generated')),
+    quantity DOUBLE PRECISION NOT NULL DEFAULT 1 CHECK (quantity >= 0),
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_events_created_at ON usage_events(created_at);
@@ -0,0 +1,59 @@
+-- Change feed for offline clients: one row per insert, update or delete of a
+-- synced row, in commit-safe order (see txid). Clients page through it by id.
+CREATE TABLE IF NOT EXISTS sync_changes (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    entity VARCHAR(10) NOT NULL CHECK (entity IN ('farm', 'alert', 'todo')),
+    entity_id BIGINT NOT NULL,
+    deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Writing transaction; rows are served only once every older transaction
+    -- has finished, so a cursor never skips a late commit.
+    txid XID8 NOT NULL DEFAULT pg_current_xact_id(),
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_changes_user ON sync_changes(user_id, id);
+
+-- TG_ARGV[0] is the entity name. Alerts belong to their farm's owner; alerts
+-- removed together with their farm are not recorded, the farm's deletion is.
+CREATE OR REPLACE FUNCTION record_sync_change() RETURNS TRIGGER AS $$
+DECLARE
+    r RECORD;
+    owner BIGINT;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        r := OLD;
+    ELSE
+        r := NEW;
+    END IF;
+
+    IF TG_ARGV[0] = 'alert' THEN
+        SELECT user_id INTO owner FROM farms WHERE id = r.farm_id;
+    ELSE
+        owner := r.user_id;
+    END IF;
+
+    -- Skip rows cascading from an account deletion.
+    IF owner IS NOT NULL AND EXISTS (SELECT 1 FROM users WHERE id = owner) THEN
+        INSERT INTO sync_changes (user_id, entity, entity_id, deleted)
+        VALUES (owner, TG_ARGV[0], r.id, TG_OP = 'DELETE');
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER farms_sync_change AFTER INSERT OR UPDATE OR DELETE ON farms
+    FOR EACH ROW EXECUTE FUNCTION record_sync_change('farm');
+CREATE TRIGGER alerts_sync_change AFTER INSERT OR UPDATE OR DELETE ON alerts
+    FOR EACH ROW EXECUTE FUNCTION record_sync_change('alert');
+CREATE TRIGGER todos_sync_change AFTER INSERT OR UPDATE OR DELETE ON todos
+    FOR EACH ROW EXECUTE FUNCTION record_sync_change('todo');
+
+-- Existing rows, so a first sync from cursor 0 sees everything.
+INSERT INTO sync_changes (user_id, entity, entity_id)
+SELECT user_id, 'farm', id FROM farms ORDER BY id;
+INSERT INTO sync_changes (user_id, entity, entity_id)
+SELECT f.user_id, 'alert', a.id FROM alerts a JOIN farms f ON f.id = a.farm_id ORDER BY a.id;
+INSERT INTO sync_changes (user_id, entity, entity_id)
+SELECT user_id, 'todo', id FROM todos ORDER BY id;
@@ -0,0 +1,17 @@
+-- Files attached to an alert or a todo; the bytes live in object storage.
+CREATE TABLE IF NOT EXISTS attachments (
+    id BIGSERIAL PRIMARY KEY,
+    alert_id BIGINT REFERENCES alerts(id) ON DELETE CASCADE,
+    todo_id BIGINT REFERENCES todos(id) ON DELETE CASCADE,
+    user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    storage_key VARCHAR(255) NOT NULL,
+    thumbnail_key VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((alert_id IS NULL) <> (todo_id IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_alert ON attachments(alert_id) WHERE alert_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_attachments_todo ON attachments(todo_id) WHERE todo_id IS NOT NULL;
@@ -0,0 +1,20 @@
+-- Field notes on alerts. Replies point at the comment they answer; deleted
+-- comments keep their row so the thread around them stays intact.
+CREATE TABLE IF NOT EXISTS alert_comments (
+    id BIGSERIAL PRIMARY KEY,
+    alert_id BIGINT NOT NULL REFERENCES alerts(id) ON DELETE CASCADE,
+    parent_id BIGINT REFERENCES alert_comments(id) ON DELETE CASCADE,
+    user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    edited_at TIMESTAMPTZ,
+    deleted_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_comments_alert ON alert_comments(alert_id, created_at);
+
+CREATE TABLE IF NOT EXISTS alert_comment_mentions (
+    comment_id BIGINT NOT NULL REFERENCES alert_comments(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (comment_id, user_id)
+);
@@ -0,0 +1,2 @@
+-- Scenes mosaicked into the image an analysis ran on; NULL for single images.
+ALTER TABLE salinity_logs ADD COLUMN IF NOT EXISTS scene_ids TEXT[];
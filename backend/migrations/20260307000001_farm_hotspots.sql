@@ -0,0 +1,17 @@
+-- Within-farm clusters of high water probability found by an analysis,
+-- clipped to the farm boundary.
+CREATE TABLE IF NOT EXISTS farm_hotspots (
+    id BIGSERIAL PRIMARY KEY,
+    farm_id BIGINT NOT NULL REFERENCES farms(id) ON DELETE CASCADE,
+    log_id BIGINT NOT NULL REFERENCES salinity_logs(id) ON DELETE CASCADE,
+    alert_id BIGINT REFERENCES alerts(id) ON DELETE SET NULL,
+    geometry GEOMETRY(MULTIPOLYGON, 4326) NOT NULL,
+    area_hectares DOUBLE PRECISION NOT NULL,
+    cell_count INTEGER NOT NULL,
+    mean_z DOUBLE PRECISION NOT NULL,
+    max_z DOUBLE PRECISION NOT NULL,
+    mean_probability DOUBLE PRECISION NOT NULL,
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_farm_hotspots_farm ON farm_hotspots(farm_id, detected_at DESC);
@@ -0,0 +1,10 @@
+-- Which channels carry alerts of each severity, and the local hours in which
+-- email and push are held back. The default matches AlertChannels::default().
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS alert_channels JSONB NOT NULL DEFAULT '{
+        "low": ["webhook", "sse"],
+        "medium": ["webhook", "sse"],
+        "high": ["email", "webhook", "sse"],
+        "critical": ["email", "push", "webhook", "sse"]
+    }',
+    ADD COLUMN IF NOT EXISTS quiet_hours JSONB;
@@ -0,0 +1,3 @@
+-- Inputs and outcome of the AI analysis behind a reading: water threshold,
+-- rule limits, severity and risk. Lets two runs be compared after the fact.
+ALTER TABLE salinity_logs ADD COLUMN IF NOT EXISTS analysis JSONB;
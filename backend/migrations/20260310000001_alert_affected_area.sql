@@ -0,0 +1,15 @@
+-- Part of the farm an alert concerns, such as the union of the hot spots
+-- found by the analysis that raised it. NULL means the whole farm.
+ALTER TABLE alerts ADD COLUMN IF NOT EXISTS affected_area GEOMETRY(MULTIPOLYGON, 4326);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_affected_area ON alerts USING GIST(affected_area);
+
+UPDATE alerts a
+SET affected_area = h.area
+FROM (
+    SELECT alert_id, ST_Multi(ST_Union(geometry)) AS area
+    FROM farm_hotspots
+    WHERE alert_id IS NOT NULL
+    GROUP BY alert_id
+) h
+WHERE h.alert_id = a.id;
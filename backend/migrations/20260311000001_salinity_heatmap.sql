@@ -0,0 +1,16 @@
+-- Gridded daily salinity per region for the regional overview map. Each day
+-- averages the readings of the week ending on it, so every cell has seen at
+-- least one satellite pass. Cells are indexed by floor(coordinate / resolution).
+CREATE TABLE IF NOT EXISTS salinity_heatmap_cells (
+    day DATE NOT NULL,
+    resolution_millideg INTEGER NOT NULL,
+    region VARCHAR(100) NOT NULL,
+    cell_x INTEGER NOT NULL,
+    cell_y INTEGER NOT NULL,
+    avg_ndsi DOUBLE PRECISION NOT NULL,
+    avg_salinity_g_l DOUBLE PRECISION,
+    farm_count INTEGER NOT NULL,
+    reading_count INTEGER NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (day, resolution_millideg, region, cell_x, cell_y)
+);
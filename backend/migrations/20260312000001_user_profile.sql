@@ -0,0 +1,15 @@
+-- Contact details a user maintains on their profile.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS full_name VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS phone VARCHAR(20),
+    ADD COLUMN IF NOT EXISTS province VARCHAR(100);
+
+-- Profile pictures are attachments owned by the user they depict.
+ALTER TABLE attachments
+    ADD COLUMN IF NOT EXISTS profile_user_id BIGINT REFERENCES users(id) ON DELETE CASCADE;
+
+ALTER TABLE attachments DROP CONSTRAINT IF EXISTS attachments_check;
+ALTER TABLE attachments
+    ADD CONSTRAINT attachments_single_parent CHECK (num_nonnulls(alert_id, todo_id, profile_user_id) = 1);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_profile_user ON attachments(profile_user_id) WHERE profile_user_id IS NOT NULL;
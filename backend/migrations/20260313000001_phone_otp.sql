@@ -0,0 +1,22 @@
+-- Farmers without an email address sign in with a one-time code sent to
+-- their phone, so an account needs either an email or a verified phone.
+ALTER TABLE users ALTER COLUMN email DROP NOT NULL;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS phone_verified_at TIMESTAMPTZ;
+ALTER TABLE users
+    ADD CONSTRAINT users_sign_in_method CHECK (email IS NOT NULL OR phone_verified_at IS NOT NULL);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_verified_phone ON users(phone) WHERE phone_verified_at IS NOT NULL;
+
+-- Codes in flight, stored as SHA-256 digests. Only the newest unconsumed code
+-- of a phone is accepted.
+CREATE TABLE IF NOT EXISTS phone_otps (
+    id BIGSERIAL PRIMARY KEY,
+    phone VARCHAR(20) NOT NULL,
+    code_hash VARCHAR(64) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_phone_otps_phone ON phone_otps(phone, created_at DESC);
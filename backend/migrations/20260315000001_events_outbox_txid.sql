@@ -0,0 +1,6 @@
+-- Writing transaction of each event. The event stream is ordered by it and
+-- serves an event only once every older transaction has finished, so a
+-- cursor never skips an event whose transaction commits late.
+ALTER TABLE events_outbox ADD COLUMN IF NOT EXISTS txid XID8 NOT NULL DEFAULT pg_current_xact_id();
+
+CREATE INDEX IF NOT EXISTS idx_events_outbox_stream ON events_outbox(txid, id);
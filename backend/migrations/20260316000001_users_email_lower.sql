@@ -0,0 +1,3 @@
+-- Emails are looked up case-insensitively; accounts created before emails
+-- were normalized may still hold mixed-case addresses.
+CREATE INDEX IF NOT EXISTS idx_users_email_lower ON users(LOWER(email));
@@ -0,0 +1,125 @@
+//! Command-line entry points. With no arguments the binary serves the API,
+//! as it always has; other commands run one task and exit, so deployment
+//! scripts can gate on the exit code.
+
+use crate::{seed, shared};
+
+pub const USAGE: &str = "\
+Usage:
+  backend [serve]            Run migrations, then serve the API
+  backend migrate            Apply pending migrations and exit
+  backend migrate --dry-run  List pending migrations without applying them
+  backend seed [--profile demo]
+                             Apply migrations and load sample data (not in production)
+";
+
+/// Exit code for success.
+pub const EXIT_OK: i32 = 0;
+/// Exit code for a failed command: bad configuration, database unreachable
+/// or a migration that failed.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code for unrecognised arguments.
+pub const EXIT_USAGE: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Serve,
+    Migrate { dry_run: bool },
+    Seed { profile: seed::Profile },
+}
+
+impl Command {
+    /// Parses the arguments after the program name.
+    pub fn parse(args: &[String]) -> Result<Command, String> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            [] | ["serve"] => Ok(Command::Serve),
+            ["migrate"] => Ok(Command::Migrate { dry_run: false }),
+            ["migrate", "--dry-run"] => Ok(Command::Migrate { dry_run: true }),
+            ["migrate", other, ..] => Err(format!("Unknown option for migrate: {}", other)),
+            ["seed"] => Ok(Command::Seed { profile: seed::Profile::Demo }),
+            ["seed", "--profile", profile] => Ok(Command::Seed { profile: profile.parse()? }),
+            ["seed", other, ..] => Err(format!("Unknown option for seed: {}", other)),
+            [other, ..] => Err(format!("Unknown command: {}", other)),
+        }
+    }
+}
+
+pub async fn migrate(config: &shared::config::AppConfig, dry_run: bool) -> i32 {
+    let pool = match shared::db::connect(&config.database_url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!("Failed to connect to the database: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let pending = match shared::db::pending_migrations(&pool).await {
+        Ok(pending) => pending,
+        Err(e) => {
+            tracing::error!("Failed to read migration state: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    if pending.is_empty() {
+        tracing::info!("Database is up to date");
+        return EXIT_OK;
+    }
+    for migration in &pending {
+        tracing::info!("Pending migration {} {}", migration.version, migration.description);
+    }
+    if dry_run {
+        tracing::info!("{} migration(s) would be applied", pending.len());
+        return EXIT_OK;
+    }
+
+    match shared::db::run_migrations(&pool).await {
+        Ok(()) => {
+            tracing::info!("Applied {} migration(s)", pending.len());
+            EXIT_OK
+        }
+        Err(e) => {
+            tracing::error!("Migration failed: {}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+pub async fn seed(config: &shared::config::AppConfig, profile: seed::Profile) -> i32 {
+    if config.environment == shared::config::Environment::Production {
+        tracing::error!("Refusing to seed sample data in production");
+        return EXIT_FAILURE;
+    }
+
+    let pool = match shared::db::init_pool(&config.database_url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!("Failed to prepare the database: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    match seed::run(&pool, profile).await {
+        Ok(Some(summary)) => {
+            tracing::info!(
+                "Seeded {} users, {} farms, {} readings, {} alerts and {} todos; sign in with password '{}'",
+                summary.users,
+                summary.farms,
+                summary.readings,
+                summary.alerts,
+                summary.todos,
+                seed::DEMO_PASSWORD,
+            );
+            EXIT_OK
+        }
+        Ok(None) => {
+            tracing::info!("Sample data already present; nothing to do");
+            EXIT_OK
+        }
+        Err(e) => {
+            tracing::error!("Seeding failed: {}", e);
+            EXIT_FAILURE
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Bio-Radar backend. The binary loads configuration, starts the background
+//! jobs and serves [`app`]; integration tests drive the same router in-process.
+
+pub mod cli;
+pub mod modules;
+pub mod shared;
+
+mod seed;
+
+use axum::{Router, middleware};
+use tower_http::compression::{
+    predicate::{DefaultPredicate, NotForContentType, Predicate},
+    CompressionLayer,
+};
+
+/// Every route, public and protected, with the middleware stack in front.
+pub fn app(state: shared::AppState, config: &shared::config::AppConfig) -> Router {
+    let cors = shared::cors::layer(&config.cors_origins);
+
+    // Downloads that are already compressed are not worth a second pass.
+    let compression = CompressionLayer::new().compress_when(
+        DefaultPredicate::new().and(NotForContentType::const_new("application/zip")),
+    );
+
+    let protected = Router::new()
+        .nest("/api/auth", modules::auth_router())
+        .nest("/api/monitoring", modules::monitoring_router())
+        .nest("/api/farms", modules::farm_mgmt_router())
+        .nest("/api/todos", modules::todos_router())
+        .nest("/api/settings", modules::settings_router())
+        .nest("/api/analytics", modules::analytics_router())
+        .nest("/api/dashboard", modules::dashboard_router())
+        .nest("/api/webhooks", modules::webhooks_router())
+        .nest("/api/reports", modules::reports_router())
+        .nest("/api/search", modules::search_router())
+        .nest("/api/satellites", modules::satellites_router())
+        .nest("/api/sync", modules::sync_router())
+        .nest("/api/attachments", modules::attachments_router())
+        .nest("/api/comments", modules::comments_router())
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            shared::audit::audit_middleware
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            modules::auth::middleware::auth_middleware
+        ));
+
+    Router::new()
+        .nest("/api/auth", modules::auth_public_router())
+        .nest("/api/attachments", modules::attachments_public_router())
+        .nest("/api/monitoring", modules::monitoring_public_router())
+        .nest("/api", modules::docs_router())
+        .nest("/health", modules::health_router())
+        .merge(protected)
+        .route_layer(middleware::from_fn(shared::access_log::route_middleware))
+        .layer(middleware::from_fn(shared::etag::etag_middleware))
+        .layer(compression)
+        .layer(cors)
+        .layer(middleware::from_fn(shared::access_log::access_log_middleware))
+        .layer(middleware::from_fn(shared::request_id::request_id_middleware))
+        .with_state(state)
+}
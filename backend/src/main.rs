@@ -1,9 +1,4 @@
-mod shared;
-mod modules;
-
-use axum::{Router, http::Method, middleware};
-use tower_http::cors::{CorsLayer, Any};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use backend::{cli, modules, shared};
 use std::net::SocketAddr;
 use modules::monitoring::ai::engine::AiEngine;
 
@@ -11,30 +6,85 @@ use modules::monitoring::ai::engine::AiEngine;
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "info,backend=debug,sqlx=warn".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match cli::Command::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(cli::EXIT_USAGE);
+        }
+    };
 
-    tracing::info!("Starting Bio-Radar Backend Server");
+    let telemetry = shared::telemetry::init();
+
+    let config = match shared::config::AppConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    tracing::info!("Configuration loaded ({:?})", config.environment);
+    shared::config::init(config.clone());
 
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set");
+    match command {
+        cli::Command::Serve => serve(config).await?,
+        cli::Command::Migrate { dry_run } => {
+            let code = cli::migrate(&config, dry_run).await;
+            telemetry.shutdown();
+            std::process::exit(code);
+        }
+        cli::Command::Seed { profile } => {
+            let code = cli::seed(&config, profile).await;
+            telemetry.shutdown();
+            std::process::exit(code);
+        }
+    }
+
+    telemetry.shutdown();
+    Ok(())
+}
+
+async fn serve(config: shared::config::AppConfig) -> anyhow::Result<()> {
+    tracing::info!("Starting Bio-Radar Backend Server");
 
     tracing::info!("Connecting to database...");
-    let db = shared::db::init_pool(&database_url).await?;
+    let db = shared::db::init_pool(&config.database_url).await?;
     tracing::info!("Database connected successfully");
 
-    let mut state = shared::AppState::new(db);
+    modules::settings::service::load_runtime_settings(&db).await?;
+
+    modules::monitoring::jobs::spawn_baseline_job(db.clone());
+    modules::monitoring::jobs::spawn_calibration_job(db.clone());
+    modules::webhooks::jobs::spawn_delivery_job(db.clone());
+    modules::analytics::jobs::spawn_regional_metrics_job(db.clone());
+    modules::analytics::jobs::spawn_salinity_heatmap_job(db.clone());
+    modules::settings::jobs::spawn_retention_job(db.clone());
+    modules::auth::jobs::spawn_account_purge_job(db.clone());
+    modules::farm_mgmt::jobs::spawn_archive_purge_job(db.clone());
+    modules::satellites::jobs::spawn_coverage_job(db.clone());
+
+    match shared::weather::provider_from_env() {
+        Some(provider) => modules::analytics::jobs::spawn_weather_job(db.clone(), provider),
+        None => tracing::info!("Weather ingestion disabled"),
+    }
 
-    if let (Ok(config_path), Ok(weights_path)) = (
-        std::env::var("AI_CONFIG_PATH"),
-        std::env::var("AI_WEIGHTS_PATH"),
-    ) {
-        match AiEngine::new(&config_path, &weights_path) {
+    let mut state = shared::AppState::new(db.clone());
+    modules::events::jobs::spawn_outbox_relay_job(db.clone(), state.notifier.clone());
+    modules::digest::jobs::spawn_digest_job(db, state.notifier.clone());
+
+    let registered = match modules::monitoring::service::load_registered_models(&state.models, &state.db).await {
+        Ok(found) => found,
+        Err(e) => {
+            tracing::warn!("Registered AI model failed to load: {}. Falling back to the configured paths.", e);
+            false
+        }
+    };
+
+    if registered {
+        tracing::info!("AI Engine initialized from the model registry");
+    } else if let Some((config_path, weights_path)) = &config.ai_paths {
+        match AiEngine::new(config_path, weights_path) {
             Ok(engine) => {
                 tracing::info!("AI Engine initialized successfully");
                 state = state.with_ai_engine(engine);
@@ -44,34 +94,18 @@ async fn main() -> anyhow::Result<()> {
             }
         }
     } else {
-        tracing::info!("AI Engine not configured (AI_CONFIG_PATH or AI_WEIGHTS_PATH missing)");
+        tracing::info!("AI Engine not configured (no active registered model, AI_CONFIG_PATH or AI_WEIGHTS_PATH missing)");
     }
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers(Any);
-
-    let app = Router::new()
-        .nest("/api/auth", modules::auth_router())
-        .nest("/api/monitoring", modules::monitoring_router())
-        .nest("/api/farms", modules::farm_mgmt_router())
-        .route_layer(middleware::from_fn_with_state(
-            state.clone(),
-            modules::auth::middleware::auth_middleware
-        ))
-        .layer(cors)
-        .with_state(state);
-
-    let host = std::env::var("SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-    let port = std::env::var("SERVER_PORT").unwrap_or_else(|_| "8000".to_string());
-    let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
+    let app = backend::app(state, &config);
+
+    let addr: SocketAddr = format!("{}:{}", config.server_host, config.server_port).parse()?;
 
     tracing::info!("Server listening on {}", addr);
 
     axum::serve(
         tokio::net::TcpListener::bind(addr).await?,
-        app,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
     ).await?;
 
     Ok(())
@@ -0,0 +1,183 @@
+use axum::{
+    extract::{Path, Query, State, Extension},
+    Json,
+};
+use crate::shared::{AppState, access, error::{AppError, ErrorCode, ErrorResponse}};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{
+        ComparisonQuery, HeatmapQuery, IrrigationAdvice, IrrigationAdviceQuery, KpiComparison, RecomputeResponse,
+        RegionComparisonResponse, RegionalMetric, SalinityHeatmap, WaterDemandQuery, WaterDemandResponse,
+        WeatherObservation, WeatherQuery,
+    },
+    repository, service,
+};
+
+const DEFAULT_COMPARISON_DAYS: i32 = 30;
+const MAX_COMPARISON_DAYS: i32 = 365;
+
+#[utoipa::path(
+    get,
+    path = "/regions",
+    tag = "analytics",
+    responses((status = 200, description = "Latest per-region rollup", body = [RegionalMetric])),
+)]
+pub async fn list_regional_metrics(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RegionalMetric>>, AppError> {
+    let metrics = repository::list_regional_metrics(&state.db).await?;
+    Ok(Json(metrics))
+}
+
+#[utoipa::path(
+    get,
+    path = "/regions/compare",
+    tag = "analytics",
+    params(ComparisonQuery),
+    responses((status = 200, description = "Per-region rollup of the window against the preceding window of equal length", body = RegionComparisonResponse)),
+)]
+pub async fn compare_regions(
+    State(state): State<AppState>,
+    Query(query): Query<ComparisonQuery>,
+) -> Result<Json<RegionComparisonResponse>, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_COMPARISON_DAYS).clamp(1, MAX_COMPARISON_DAYS);
+    let comparison = service::compare_regions(&state.db, days).await?;
+    Ok(Json(comparison))
+}
+
+#[utoipa::path(
+    get,
+    path = "/kpis",
+    tag = "analytics",
+    params(ComparisonQuery),
+    responses((status = 200, description = "The caller's farm count, alerts and mean NDSI against the preceding window", body = KpiComparison)),
+)]
+pub async fn get_kpis(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ComparisonQuery>,
+) -> Result<Json<KpiComparison>, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_COMPARISON_DAYS).clamp(1, MAX_COMPARISON_DAYS);
+    let comparison = service::compare_kpis(&state.db, claims.sub, days).await?;
+    Ok(Json(comparison))
+}
+
+#[utoipa::path(
+    get,
+    path = "/salinity-heatmap",
+    tag = "analytics",
+    params(HeatmapQuery),
+    responses(
+        (status = 200, description = "Gridded mean NDSI and salinity over the caller's regions", body = SalinityHeatmap),
+        (status = 400, description = "Future date or unsupported resolution", body = ErrorResponse),
+    ),
+)]
+pub async fn get_salinity_heatmap(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<HeatmapQuery>,
+) -> Result<Json<SalinityHeatmap>, AppError> {
+    let owner = (!claims.is_admin()).then_some(claims.sub);
+    let heatmap = service::salinity_heatmap(&state.db, owner, &query).await?;
+    Ok(Json(heatmap))
+}
+
+#[utoipa::path(
+    post,
+    path = "/recompute",
+    tag = "analytics",
+    responses(
+        (status = 200, description = "Regional metrics recomputed", body = RecomputeResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn recompute(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<RecomputeResponse>, AppError> {
+    access::require_admin(&claims)?;
+
+    let regions = service::recompute_regional_metrics(&state.db).await?;
+    Ok(Json(RecomputeResponse { regions }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/water-demand/{farm_id}",
+    tag = "analytics",
+    params(("farm_id" = i64, Path, description = "Farm id"), WaterDemandQuery),
+    responses(
+        (status = 200, description = "Estimated daily crop water demand", body = WaterDemandResponse),
+        (status = 400, description = "Invalid weather inputs", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_water_demand(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    Query(query): Query<WaterDemandQuery>,
+) -> Result<Json<WaterDemandResponse>, AppError> {
+    let demand = service::estimate_water_demand(&state.db, claims.sub, farm_id, &query).await?;
+    Ok(Json(demand))
+}
+
+const DEFAULT_ADVICE_DAYS: i64 = 3;
+/// The weather job stores a week of forecast.
+const MAX_ADVICE_DAYS: i64 = 7;
+
+#[utoipa::path(
+    get,
+    path = "/irrigation-advice/{farm_id}",
+    tag = "analytics",
+    params(("farm_id" = i64, Path, description = "Farm id"), IrrigationAdviceQuery),
+    responses(
+        (status = 200, description = "Whether to take in water each day, with reasoning in the caller's language", body = IrrigationAdvice),
+        (status = 401, description = "Farm belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_irrigation_advice(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    Query(query): Query<IrrigationAdviceQuery>,
+) -> Result<Json<IrrigationAdvice>, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_ADVICE_DAYS).clamp(1, MAX_ADVICE_DAYS);
+    let advice = service::irrigation_advice(&state.db, claims.sub, farm_id, days).await?;
+    Ok(Json(advice))
+}
+
+const DEFAULT_WEATHER_DAYS: i64 = 14;
+const MAX_WEATHER_DAYS: i64 = 365;
+
+#[utoipa::path(
+    get,
+    path = "/weather/{farm_id}",
+    tag = "analytics",
+    params(("farm_id" = i64, Path, description = "Farm id"), WeatherQuery),
+    responses(
+        (status = 200, description = "Stored daily weather, oldest first, including forecast days", body = [WeatherObservation]),
+        (status = 401, description = "Farm belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_weather(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    Query(query): Query<WeatherQuery>,
+) -> Result<Json<Vec<WeatherObservation>>, AppError> {
+    let profile = repository::get_farm_water_profile(&state.db, farm_id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    if profile.user_id != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let days = query.days.unwrap_or(DEFAULT_WEATHER_DAYS).clamp(1, MAX_WEATHER_DAYS);
+    let weather = repository::list_weather(&state.db, farm_id, days).await?;
+
+    Ok(Json(weather))
+}
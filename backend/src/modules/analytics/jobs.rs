@@ -0,0 +1,48 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+use crate::shared::{weather::WeatherProvider, worker::{interval_from_env, spawn_periodic}};
+use super::service;
+
+const REGIONAL_METRICS_JOB_DEFAULT_SECS: u64 = 60 * 60;
+const SALINITY_HEATMAP_JOB_DEFAULT_SECS: u64 = 60 * 60;
+const WEATHER_JOB_DEFAULT_SECS: u64 = 3 * 60 * 60;
+
+pub fn spawn_regional_metrics_job(db: PgPool) {
+    let period = interval_from_env("REGIONAL_METRICS_JOB_INTERVAL_SECS", REGIONAL_METRICS_JOB_DEFAULT_SECS);
+
+    spawn_periodic("regional_metrics", period, move || {
+        let db = db.clone();
+        async move {
+            let regions = service::recompute_regional_metrics(&db).await?;
+            tracing::info!("Recomputed regional metrics for {} regions", regions);
+            Ok(())
+        }
+    });
+}
+
+pub fn spawn_salinity_heatmap_job(db: PgPool) {
+    let period = interval_from_env("SALINITY_HEATMAP_JOB_INTERVAL_SECS", SALINITY_HEATMAP_JOB_DEFAULT_SECS);
+
+    spawn_periodic("salinity_heatmap", period, move || {
+        let db = db.clone();
+        async move {
+            let cells = service::recompute_heatmap(&db).await?;
+            tracing::info!("Rebuilt salinity heatmap ({} cells)", cells);
+            Ok(())
+        }
+    });
+}
+
+pub fn spawn_weather_job(db: PgPool, provider: Arc<dyn WeatherProvider>) {
+    let period = interval_from_env("WEATHER_JOB_INTERVAL_SECS", WEATHER_JOB_DEFAULT_SECS);
+
+    spawn_periodic("weather_ingest", period, move || {
+        let db = db.clone();
+        let provider = provider.clone();
+        async move {
+            let farms = service::ingest_weather(&db, provider.as_ref()).await?;
+            tracing::info!("Refreshed {} weather for {} farms", provider.name(), farms);
+            Ok(())
+        }
+    });
+}
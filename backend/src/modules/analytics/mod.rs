@@ -0,0 +1,38 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+pub mod jobs;
+
+use axum::{routing::{get, post}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/regions", get(controller::list_regional_metrics))
+        .route("/regions/compare", get(controller::compare_regions))
+        .route("/kpis", get(controller::get_kpis))
+        .route("/salinity-heatmap", get(controller::get_salinity_heatmap))
+        .route("/recompute", post(controller::recompute))
+        .route("/water-demand/{farm_id}", get(controller::get_water_demand))
+        .route("/irrigation-advice/{farm_id}", get(controller::get_irrigation_advice))
+        .route("/weather/{farm_id}", get(controller::get_weather))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::list_regional_metrics,
+    controller::compare_regions,
+    controller::get_kpis,
+    controller::get_salinity_heatmap,
+    controller::recompute,
+    controller::get_water_demand,
+    controller::get_irrigation_advice,
+    controller::get_weather,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -0,0 +1,273 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, NaiveDate, Utc};
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::monitoring::models::RiskScore;
+use crate::shared::i18n::Language;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct RegionalMetric {
+    pub region: String,
+    pub farm_count: i32,
+    pub total_area_hectares: f64,
+    pub avg_ndsi: Option<f64>,
+    pub max_ndsi: Option<f64>,
+    pub open_alerts: i32,
+    pub critical_alerts: i32,
+    pub risk_level: String,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Raw per-region rollup before a risk level is assigned.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RegionAggregate {
+    pub region: String,
+    pub farm_count: i32,
+    pub total_area_hectares: f64,
+    pub avg_ndsi: Option<f64>,
+    pub max_ndsi: Option<f64>,
+    pub open_alerts: i32,
+    pub critical_alerts: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecomputeResponse {
+    pub regions: usize,
+}
+
+/// Farm attributes needed to estimate its water demand.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FarmWaterProfile {
+    pub user_id: i64,
+    pub latitude: f64,
+    pub area_hectares: Option<f64>,
+    pub crop_type: Option<String>,
+    pub growth_stage: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct WaterDemandQuery {
+    /// Day to estimate for; defaults to today.
+    pub date: Option<NaiveDate>,
+    /// Daily minimum air temperature in °C; defaults to delta climatology.
+    pub t_min_c: Option<f64>,
+    /// Daily maximum air temperature in °C; defaults to delta climatology.
+    pub t_max_c: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WaterDemandResponse {
+    pub farm_id: i64,
+    pub date: NaiveDate,
+    pub method: String,
+    /// `query` when temperatures were supplied, the weather provider when an
+    /// observation is stored for the day, otherwise `climatology`.
+    pub weather_source: String,
+    pub t_min_c: f64,
+    pub t_max_c: f64,
+    /// Reference evapotranspiration in mm/day.
+    pub et0_mm: f64,
+    pub crop_type: Option<String>,
+    pub growth_stage: Option<String>,
+    pub crop_coefficient: f64,
+    /// Crop evapotranspiration (ET₀ × Kc) in mm/day.
+    pub etc_mm: f64,
+    pub litres_per_hectare: f64,
+    pub area_hectares: Option<f64>,
+    /// Whole-farm demand in litres/day; unknown when the farm has no area.
+    pub litres_total: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IrrigationAdviceQuery {
+    /// Days to plan, starting today; defaults to 3, at most 7.
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IrrigationAction {
+    /// Open the intake and take in river water.
+    TakeIn,
+    /// Hold off until `wait_until`, when a lower tide brings less salt in.
+    Wait,
+    /// Salinity stays high over the whole plan; irrigate from stored water only.
+    UseStored,
+    /// Rain covers the crop's demand, or the crop needs none.
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IrrigationDay {
+    pub date: NaiveDate,
+    pub action: IrrigationAction,
+    /// Crop evapotranspiration in mm/day.
+    pub etc_mm: f64,
+    /// Forecast rain; absent when no forecast is stored for the day.
+    pub precipitation_mm: Option<f64>,
+    /// Demand left after effective rain, in mm.
+    pub deficit_mm: f64,
+    /// Water to take in over the whole farm, in litres; unknown when the
+    /// farm has no area.
+    pub litres_total: Option<f64>,
+    /// 0–1; from the forecast sea level, or the lunar cycle without one.
+    pub tide_factor: f64,
+    /// Day to take water in instead, when the action is `wait`.
+    pub wait_until: Option<NaiveDate>,
+    /// Why, in the caller's language.
+    pub reasoning: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IrrigationAdvice {
+    pub farm_id: i64,
+    pub language: Language,
+    pub crop_type: Option<String>,
+    pub growth_stage: Option<String>,
+    pub crop_coefficient: f64,
+    /// The farm's current salinity intrusion risk, which gates intake.
+    pub salinity_risk: RiskScore,
+    /// Today first.
+    pub days: Vec<IrrigationDay>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FarmLocation {
+    pub id: i64,
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct WeatherObservation {
+    pub observed_on: NaiveDate,
+    pub source: String,
+    pub t_min_c: Option<f64>,
+    pub t_max_c: Option<f64>,
+    pub precipitation_mm: Option<f64>,
+    pub et0_mm: Option<f64>,
+    pub sea_level_max_m: Option<f64>,
+    pub is_forecast: bool,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct WeatherQuery {
+    /// Days of history to return, plus any stored forecast; defaults to 14.
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ComparisonQuery {
+    /// Length of the current window in days, ending now; defaults to 30. The
+    /// previous window has the same length and ends where the current one starts.
+    pub days: Option<i32>,
+}
+
+/// A metric over the current window next to the preceding one.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MetricChange {
+    pub current: Option<f64>,
+    pub previous: Option<f64>,
+    pub change: Option<f64>,
+    /// Relative to the previous value; absent when that is missing or zero.
+    pub change_percent: Option<f64>,
+}
+
+impl MetricChange {
+    pub fn new(current: Option<f64>, previous: Option<f64>) -> Self {
+        let change = current.zip(previous).map(|(c, p)| c - p);
+        let change_percent = change
+            .zip(previous)
+            .filter(|(_, p)| *p != 0.0)
+            .map(|(delta, p)| delta / p.abs() * 100.0);
+
+        Self { current, previous, change, change_percent }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KpiComparison {
+    pub window_days: i32,
+    pub current_from: DateTime<Utc>,
+    pub previous_from: DateTime<Utc>,
+    /// Active farms at the end of each window.
+    pub farm_count: MetricChange,
+    /// Alerts raised during each window.
+    pub new_alerts: MetricChange,
+    pub critical_alerts: MetricChange,
+    /// Mean NDSI of readings taken during each window.
+    pub average_ndsi: MetricChange,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegionComparison {
+    pub region: String,
+    pub farm_count: MetricChange,
+    pub total_area_hectares: MetricChange,
+    pub avg_ndsi: MetricChange,
+    pub max_ndsi: MetricChange,
+    pub new_alerts: MetricChange,
+    pub critical_alerts: MetricChange,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegionComparisonResponse {
+    pub window_days: i32,
+    pub current_from: DateTime<Utc>,
+    pub previous_from: DateTime<Utc>,
+    pub regions: Vec<RegionComparison>,
+}
+
+/// The caller's headline figures over one window.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct KpiWindow {
+    pub farm_count: i32,
+    pub new_alerts: i32,
+    pub critical_alerts: i32,
+    pub average_ndsi: Option<f64>,
+}
+
+/// Per-region rollup over one window.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RegionWindow {
+    pub region: String,
+    pub farm_count: i32,
+    pub total_area_hectares: f64,
+    pub avg_ndsi: Option<f64>,
+    pub max_ndsi: Option<f64>,
+    pub new_alerts: i32,
+    pub critical_alerts: i32,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct HeatmapQuery {
+    /// Day to show; defaults to today. Each day averages the readings of the
+    /// week ending on it.
+    pub date: Option<NaiveDate>,
+    /// Cell size in degrees: 0.01 (default), 0.05 or 0.1.
+    pub resolution: Option<f64>,
+}
+
+/// One grid cell of the salinity heatmap.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct HeatmapCell {
+    /// Longitude of the cell's west edge.
+    pub lon: f64,
+    /// Latitude of the cell's south edge.
+    pub lat: f64,
+    pub avg_ndsi: f64,
+    /// Mean of the analyses' salinity estimates; absent when none had one.
+    pub avg_salinity_g_l: Option<f64>,
+    pub farm_count: i32,
+    pub reading_count: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SalinityHeatmap {
+    pub date: NaiveDate,
+    pub resolution: f64,
+    pub window_days: i64,
+    /// When the worker last built this day; absent when it has no cells.
+    pub computed_at: Option<DateTime<Utc>>,
+    pub cells: Vec<HeatmapCell>,
+}
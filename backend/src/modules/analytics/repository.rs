@@ -0,0 +1,395 @@
+use sqlx::PgPool;
+use sqlx::types::chrono::{DateTime, NaiveDate, Utc};
+use crate::shared::{error::AppError, weather::DailyWeather};
+use super::models::{FarmLocation, FarmWaterProfile, HeatmapCell, KpiWindow, RegionAggregate, RegionWindow, RegionalMetric, WeatherObservation};
+
+/// Rolls farms, their last 30 days of NDSI readings and their open alerts up per region.
+pub async fn aggregate_regions(pool: &PgPool) -> Result<Vec<RegionAggregate>, AppError> {
+    sqlx::query_as::<_, RegionAggregate>(
+        r#"
+        WITH recent_ndsi AS (
+            SELECT farm_id, AVG(ndsi_value)::FLOAT8 AS avg_ndsi, MAX(ndsi_value)::FLOAT8 AS max_ndsi
+            FROM salinity_logs
+            WHERE farm_id IS NOT NULL AND recorded_at >= NOW() - INTERVAL '30 days'
+            GROUP BY farm_id
+        ),
+        open_alerts AS (
+            SELECT farm_id,
+                   COUNT(*)::INT AS open_alerts,
+                   COUNT(*) FILTER (WHERE severity = 'critical')::INT AS critical_alerts
+            FROM alerts
+            WHERE NOT acknowledged
+            GROUP BY farm_id
+        )
+        SELECT f.region,
+               COUNT(*)::INT AS farm_count,
+               COALESCE(SUM(f.area_hectares), 0)::FLOAT8 AS total_area_hectares,
+               AVG(n.avg_ndsi) AS avg_ndsi,
+               MAX(n.max_ndsi) AS max_ndsi,
+               COALESCE(SUM(a.open_alerts), 0)::INT AS open_alerts,
+               COALESCE(SUM(a.critical_alerts), 0)::INT AS critical_alerts
+        FROM farms f
+        LEFT JOIN recent_ndsi n ON n.farm_id = f.id
+        LEFT JOIN open_alerts a ON a.farm_id = f.id
+        WHERE f.region IS NOT NULL AND f.deleted_at IS NULL
+        GROUP BY f.region
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Replaces the whole table so regions that no longer have farms drop out.
+pub async fn replace_regional_metrics(
+    pool: &PgPool,
+    metrics: &[(RegionAggregate, &str)],
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM regional_metrics")
+        .execute(&mut *tx)
+        .await?;
+
+    for (aggregate, risk_level) in metrics {
+        sqlx::query(
+            r#"
+            INSERT INTO regional_metrics
+                (region, farm_count, total_area_hectares, avg_ndsi, max_ndsi, open_alerts, critical_alerts, risk_level)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
+        )
+        .bind(&aggregate.region)
+        .bind(aggregate.farm_count)
+        .bind(aggregate.total_area_hectares)
+        .bind(aggregate.avg_ndsi)
+        .bind(aggregate.max_ndsi)
+        .bind(aggregate.open_alerts)
+        .bind(aggregate.critical_alerts)
+        .bind(risk_level)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn list_regional_metrics(pool: &PgPool) -> Result<Vec<RegionalMetric>, AppError> {
+    sqlx::query_as::<_, RegionalMetric>(
+        r#"
+        SELECT region, farm_count, total_area_hectares, avg_ndsi, max_ndsi,
+               open_alerts, critical_alerts, risk_level, computed_at
+        FROM regional_metrics
+        ORDER BY region
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// The user's farms active at `to`, and their alerts and NDSI readings in
+/// `[from, to)`.
+pub async fn kpi_window(
+    pool: &PgPool,
+    user_id: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<KpiWindow, AppError> {
+    sqlx::query_as::<_, KpiWindow>(
+        r#"
+        WITH owned AS (
+            SELECT id FROM farms
+            WHERE user_id = $1 AND created_at < $3 AND (deleted_at IS NULL OR deleted_at >= $3)
+        )
+        SELECT
+            (SELECT COUNT(*) FROM owned)::INT AS farm_count,
+            (SELECT COUNT(*) FROM alerts a JOIN owned o ON o.id = a.farm_id
+              WHERE a.detected_at >= $2 AND a.detected_at < $3)::INT AS new_alerts,
+            (SELECT COUNT(*) FROM alerts a JOIN owned o ON o.id = a.farm_id
+              WHERE a.severity = 'critical' AND a.detected_at >= $2 AND a.detected_at < $3)::INT AS critical_alerts,
+            (SELECT AVG(l.ndsi_value)::FLOAT8 FROM salinity_logs l JOIN owned o ON o.id = l.farm_id
+              WHERE l.recorded_at >= $2 AND l.recorded_at < $3) AS average_ndsi
+        "#
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Same rollup as `aggregate_regions`, but over farms active at `to` and the
+/// alerts and NDSI readings of `[from, to)`.
+pub async fn region_window(
+    pool: &PgPool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<RegionWindow>, AppError> {
+    sqlx::query_as::<_, RegionWindow>(
+        r#"
+        WITH window_ndsi AS (
+            SELECT farm_id, AVG(ndsi_value)::FLOAT8 AS avg_ndsi, MAX(ndsi_value)::FLOAT8 AS max_ndsi
+            FROM salinity_logs
+            WHERE recorded_at >= $1 AND recorded_at < $2
+            GROUP BY farm_id
+        ),
+        window_alerts AS (
+            SELECT farm_id,
+                   COUNT(*)::INT AS new_alerts,
+                   COUNT(*) FILTER (WHERE severity = 'critical')::INT AS critical_alerts
+            FROM alerts
+            WHERE detected_at >= $1 AND detected_at < $2
+            GROUP BY farm_id
+        )
+        SELECT f.region,
+               COUNT(*)::INT AS farm_count,
+               COALESCE(SUM(f.area_hectares), 0)::FLOAT8 AS total_area_hectares,
+               AVG(n.avg_ndsi) AS avg_ndsi,
+               MAX(n.max_ndsi) AS max_ndsi,
+               COALESCE(SUM(a.new_alerts), 0)::INT AS new_alerts,
+               COALESCE(SUM(a.critical_alerts), 0)::INT AS critical_alerts
+        FROM farms f
+        LEFT JOIN window_ndsi n ON n.farm_id = f.id
+        LEFT JOIN window_alerts a ON a.farm_id = f.id
+        WHERE f.region IS NOT NULL
+          AND f.created_at < $2
+          AND (f.deleted_at IS NULL OR f.deleted_at >= $2)
+        GROUP BY f.region
+        "#
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Latitude, area and current crop season of a farm.
+pub async fn get_farm_water_profile(pool: &PgPool, farm_id: i64) -> Result<Option<FarmWaterProfile>, AppError> {
+    sqlx::query_as::<_, FarmWaterProfile>(
+        r#"
+        SELECT f.user_id,
+               ST_Y(ST_Centroid(f.geometry)) AS latitude,
+               f.area_hectares::FLOAT8 AS area_hectares,
+               s.crop_type,
+               s.growth_stage
+        FROM farms f
+        LEFT JOIN LATERAL (
+            SELECT crop_type, growth_stage
+            FROM crop_seasons
+            WHERE farm_id = f.id AND planting_date <= CURRENT_DATE
+            ORDER BY planting_date DESC
+            LIMIT 1
+        ) s ON TRUE
+        WHERE f.id = $1
+        "#
+    )
+    .bind(farm_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn list_farm_locations(pool: &PgPool) -> Result<Vec<FarmLocation>, AppError> {
+    sqlx::query_as::<_, FarmLocation>(
+        "SELECT id, ST_X(ST_Centroid(geometry)) AS longitude, ST_Y(ST_Centroid(geometry)) AS latitude FROM farms WHERE deleted_at IS NULL"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Stores one provider response for `farm_id`; later fetches overwrite
+/// earlier values for the same day, so forecasts are replaced by observations.
+pub async fn upsert_weather(
+    pool: &PgPool,
+    farm_id: i64,
+    source: &str,
+    days: &[DailyWeather],
+) -> Result<u64, AppError> {
+    let today = chrono::Utc::now().date_naive();
+    let dates: Vec<NaiveDate> = days.iter().map(|d| d.date).collect();
+    let t_min: Vec<Option<f64>> = days.iter().map(|d| d.t_min_c).collect();
+    let t_max: Vec<Option<f64>> = days.iter().map(|d| d.t_max_c).collect();
+    let precipitation: Vec<Option<f64>> = days.iter().map(|d| d.precipitation_mm).collect();
+    let et0: Vec<Option<f64>> = days.iter().map(|d| d.et0_mm).collect();
+    let sea_level: Vec<Option<f64>> = days.iter().map(|d| d.sea_level_max_m).collect();
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO weather_observations
+            (farm_id, source, observed_on, t_min_c, t_max_c, precipitation_mm, et0_mm, sea_level_max_m, is_forecast)
+        SELECT $1, $2, d.observed_on, d.t_min_c, d.t_max_c, d.precipitation_mm, d.et0_mm, d.sea_level_max_m,
+               d.observed_on > $9
+        FROM UNNEST($3::DATE[], $4::FLOAT8[], $5::FLOAT8[], $6::FLOAT8[], $7::FLOAT8[], $8::FLOAT8[])
+            AS d(observed_on, t_min_c, t_max_c, precipitation_mm, et0_mm, sea_level_max_m)
+        ON CONFLICT (farm_id, observed_on) DO UPDATE
+        SET source = EXCLUDED.source,
+            t_min_c = EXCLUDED.t_min_c,
+            t_max_c = EXCLUDED.t_max_c,
+            precipitation_mm = EXCLUDED.precipitation_mm,
+            et0_mm = EXCLUDED.et0_mm,
+            sea_level_max_m = EXCLUDED.sea_level_max_m,
+            is_forecast = EXCLUDED.is_forecast,
+            fetched_at = NOW()
+        "#
+    )
+    .bind(farm_id)
+    .bind(source)
+    .bind(&dates)
+    .bind(&t_min)
+    .bind(&t_max)
+    .bind(&precipitation)
+    .bind(&et0)
+    .bind(&sea_level)
+    .bind(today)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+const WEATHER_COLUMNS: &str =
+    "observed_on, source, t_min_c, t_max_c, precipitation_mm, et0_mm, sea_level_max_m, is_forecast, fetched_at";
+
+pub async fn list_weather(pool: &PgPool, farm_id: i64, days: i64) -> Result<Vec<WeatherObservation>, AppError> {
+    sqlx::query_as::<_, WeatherObservation>(&format!(
+        r#"
+        SELECT {WEATHER_COLUMNS}
+        FROM weather_observations
+        WHERE farm_id = $1 AND observed_on >= CURRENT_DATE - $2::INT
+        ORDER BY observed_on
+        "#
+    ))
+    .bind(farm_id)
+    .bind(days as i32)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn get_weather_on(pool: &PgPool, farm_id: i64, date: NaiveDate) -> Result<Option<WeatherObservation>, AppError> {
+    sqlx::query_as::<_, WeatherObservation>(&format!(
+        "SELECT {WEATHER_COLUMNS} FROM weather_observations WHERE farm_id = $1 AND observed_on = $2"
+    ))
+    .bind(farm_id)
+    .bind(date)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Rebuilds the heatmap cells of every day from `from` to today at each of
+/// `resolutions` (in thousandths of a degree). Farms are placed in the cell
+/// holding a point on their surface. Returns the number of cells written.
+pub async fn rebuild_heatmap(
+    pool: &PgPool,
+    from: NaiveDate,
+    window_days: i32,
+    resolutions: &[i32],
+) -> Result<u64, AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM salinity_heatmap_cells WHERE day >= $1")
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+
+    let written = sqlx::query(
+        r#"
+        INSERT INTO salinity_heatmap_cells
+            (day, resolution_millideg, region, cell_x, cell_y, avg_ndsi, avg_salinity_g_l, farm_count, reading_count)
+        SELECT d.day::DATE, r.millideg, f.region,
+               FLOOR(ST_X(ST_PointOnSurface(f.geometry)) * 1000 / r.millideg)::INT,
+               FLOOR(ST_Y(ST_PointOnSurface(f.geometry)) * 1000 / r.millideg)::INT,
+               AVG(l.ndsi_value)::FLOAT8,
+               AVG((l.analysis->>'estimated_g_l')::FLOAT8),
+               COUNT(DISTINCT f.id)::INT,
+               COUNT(*)::INT
+        FROM generate_series($1::DATE, CURRENT_DATE, INTERVAL '1 day') d(day)
+        CROSS JOIN UNNEST($3::INT[]) r(millideg)
+        JOIN salinity_logs l
+          ON l.recorded_at >= d.day - make_interval(days => $2 - 1)
+         AND l.recorded_at < d.day + INTERVAL '1 day'
+        JOIN farms f ON f.id = l.farm_id
+        WHERE f.region IS NOT NULL AND f.deleted_at IS NULL
+        GROUP BY 1, 2, 3, 4, 5
+        "#
+    )
+    .bind(from)
+    .bind(window_days)
+    .bind(resolutions)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok(written)
+}
+
+pub async fn heatmap_is_empty(pool: &PgPool) -> Result<bool, AppError> {
+    sqlx::query_scalar("SELECT NOT EXISTS (SELECT 1 FROM salinity_heatmap_cells)")
+        .fetch_one(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn purge_heatmap(pool: &PgPool, before: NaiveDate) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM salinity_heatmap_cells WHERE day < $1")
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Cells of `day`, merged across regions where a cell straddles a border.
+/// `user_id` limits the map to regions where that user has farms.
+pub async fn heatmap_cells(
+    pool: &PgPool,
+    day: NaiveDate,
+    resolution_millideg: i32,
+    user_id: Option<i64>,
+) -> Result<(Vec<HeatmapCell>, Option<DateTime<Utc>>), AppError> {
+    let regions = r#"
+        h.day = $1 AND h.resolution_millideg = $2
+        AND ($3::BIGINT IS NULL OR h.region IN (
+            SELECT region FROM farms WHERE user_id = $3 AND deleted_at IS NULL AND region IS NOT NULL
+        ))
+    "#;
+
+    let cells = sqlx::query_as::<_, HeatmapCell>(&format!(
+        r#"
+        SELECT (h.cell_x * $2 / 1000.0)::FLOAT8 AS lon,
+               (h.cell_y * $2 / 1000.0)::FLOAT8 AS lat,
+               (SUM(h.avg_ndsi * h.reading_count) / SUM(h.reading_count))::FLOAT8 AS avg_ndsi,
+               (SUM(h.avg_salinity_g_l * h.reading_count)
+                   / NULLIF(SUM(h.reading_count) FILTER (WHERE h.avg_salinity_g_l IS NOT NULL), 0))::FLOAT8
+                   AS avg_salinity_g_l,
+               SUM(h.farm_count)::INT AS farm_count,
+               SUM(h.reading_count)::INT AS reading_count
+        FROM salinity_heatmap_cells h
+        WHERE {regions}
+        GROUP BY h.cell_x, h.cell_y
+        ORDER BY h.cell_y, h.cell_x
+        "#
+    ))
+    .bind(day)
+    .bind(resolution_millideg)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let computed_at = sqlx::query_scalar(&format!(
+        "SELECT MAX(h.computed_at) FROM salinity_heatmap_cells h WHERE {regions}"
+    ))
+    .bind(day)
+    .bind(resolution_millideg)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok((cells, computed_at))
+}
@@ -0,0 +1,499 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use crate::modules::farm_mgmt::GrowthStage;
+use crate::modules::monitoring::{self, models::AlertSeverity, risk};
+use crate::shared::{error::{AppError, ErrorCode}, i18n::{self, t, Language}, weather::WeatherProvider};
+use super::models::{
+    FarmLocation, HeatmapQuery, IrrigationAction, IrrigationAdvice, IrrigationDay, KpiComparison, KpiWindow, MetricChange,
+    RegionAggregate, RegionComparison, RegionComparisonResponse, RegionWindow, SalinityHeatmap, WaterDemandQuery,
+    WaterDemandResponse, WeatherObservation,
+};
+use super::repository;
+
+/// Recomputes `regional_metrics` from the live farm, NDSI and alert tables.
+pub async fn recompute_regional_metrics(db: &PgPool) -> Result<usize, AppError> {
+    let aggregates = repository::aggregate_regions(db).await?;
+
+    let metrics: Vec<(RegionAggregate, &str)> = aggregates
+        .into_iter()
+        .map(|aggregate| {
+            let risk = classify_risk(&aggregate);
+            (aggregate, risk)
+        })
+        .collect();
+
+    repository::replace_regional_metrics(db, &metrics).await?;
+    Ok(metrics.len())
+}
+
+const RISK_LEVELS: [&str; 4] = ["low", "medium", "high", "critical"];
+/// Regional NDSI over the last 30 days, on average and at the worst farm,
+/// from which a region is at least medium or high risk. Salinity shows in the
+/// index before farms cross their alert rules, and regions whose farms have
+/// no rules configured raise no alerts at all.
+const NDSI_MEDIUM_AVG: f64 = 0.15;
+const NDSI_HIGH_AVG: f64 = 0.25;
+const NDSI_MEDIUM_PEAK: f64 = 0.25;
+const NDSI_HIGH_PEAK: f64 = 0.35;
+
+/// The higher of the risk shown by open alerts and by recent NDSI readings.
+fn classify_risk(aggregate: &RegionAggregate) -> &'static str {
+    RISK_LEVELS[alert_risk(aggregate).max(ndsi_risk(aggregate))]
+}
+
+/// Open critical alerts dominate; otherwise risk scales with unacknowledged
+/// alerts per farm so large regions are not penalised for their size.
+fn alert_risk(aggregate: &RegionAggregate) -> usize {
+    let alerts_per_farm = if aggregate.farm_count > 0 {
+        aggregate.open_alerts as f64 / aggregate.farm_count as f64
+    } else {
+        0.0
+    };
+
+    match (aggregate.critical_alerts, alerts_per_farm) {
+        (c, r) if c > 0 && r >= 1.0 => 3,
+        (c, r) if c > 0 || r >= 0.5 => 2,
+        (_, r) if r > 0.0 => 1,
+        _ => 0,
+    }
+}
+
+/// Readings alone never make a region critical; that takes critical alerts.
+fn ndsi_risk(aggregate: &RegionAggregate) -> usize {
+    let avg = aggregate.avg_ndsi.unwrap_or(f64::NEG_INFINITY);
+    let peak = aggregate.max_ndsi.unwrap_or(f64::NEG_INFINITY);
+
+    if avg >= NDSI_HIGH_AVG || peak >= NDSI_HIGH_PEAK {
+        2
+    } else if avg >= NDSI_MEDIUM_AVG || peak >= NDSI_MEDIUM_PEAK {
+        1
+    } else {
+        0
+    }
+}
+
+/// The caller's headline KPIs over the last `days` days against the `days`
+/// before that.
+pub async fn compare_kpis(db: &PgPool, user_id: i64, days: i32) -> Result<KpiComparison, AppError> {
+    let now = Utc::now();
+    let current_from = now - Duration::days(days as i64);
+    let previous_from = current_from - Duration::days(days as i64);
+
+    let (current, previous) = tokio::try_join!(
+        repository::kpi_window(db, user_id, current_from, now),
+        repository::kpi_window(db, user_id, previous_from, current_from),
+    )?;
+
+    let change = |f: fn(&KpiWindow) -> Option<f64>| MetricChange::new(f(&current), f(&previous));
+
+    Ok(KpiComparison {
+        window_days: days,
+        current_from,
+        previous_from,
+        farm_count: change(|w| Some(w.farm_count as f64)),
+        new_alerts: change(|w| Some(w.new_alerts as f64)),
+        critical_alerts: change(|w| Some(w.critical_alerts as f64)),
+        average_ndsi: change(|w| w.average_ndsi),
+    })
+}
+
+/// Regional rollups over the last `days` days against the `days` before that.
+/// A region present in only one window reports the other side as missing.
+pub async fn compare_regions(db: &PgPool, days: i32) -> Result<RegionComparisonResponse, AppError> {
+    let now = Utc::now();
+    let current_from = now - Duration::days(days as i64);
+    let previous_from = current_from - Duration::days(days as i64);
+
+    let (current, previous) = tokio::try_join!(
+        repository::region_window(db, current_from, now),
+        repository::region_window(db, previous_from, current_from),
+    )?;
+
+    let mut previous: HashMap<String, RegionWindow> = previous.into_iter().map(|w| (w.region.clone(), w)).collect();
+    let mut pairs: Vec<(String, Option<RegionWindow>, Option<RegionWindow>)> = current
+        .into_iter()
+        .map(|w| {
+            let before = previous.remove(&w.region);
+            (w.region.clone(), Some(w), before)
+        })
+        .collect();
+    pairs.extend(previous.into_values().map(|w| (w.region.clone(), None, Some(w))));
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let regions = pairs
+        .into_iter()
+        .map(|(region, current, previous)| {
+            let change = |f: fn(&RegionWindow) -> Option<f64>| {
+                MetricChange::new(current.as_ref().and_then(f), previous.as_ref().and_then(f))
+            };
+            RegionComparison {
+                region,
+                farm_count: change(|w| Some(w.farm_count as f64)),
+                total_area_hectares: change(|w| Some(w.total_area_hectares)),
+                avg_ndsi: change(|w| w.avg_ndsi),
+                max_ndsi: change(|w| w.max_ndsi),
+                new_alerts: change(|w| Some(w.new_alerts as f64)),
+                critical_alerts: change(|w| Some(w.critical_alerts as f64)),
+            }
+        })
+        .collect();
+
+    Ok(RegionComparisonResponse {
+        window_days: days,
+        current_from,
+        previous_from,
+        regions,
+    })
+}
+
+const WEATHER_PAST_DAYS: u32 = 7;
+const WEATHER_FORECAST_DAYS: u32 = 7;
+
+/// Refreshes stored weather for every farm. Farms whose centroids share a
+/// ~1 km grid cell share one provider request.
+pub async fn ingest_weather(db: &PgPool, provider: &dyn WeatherProvider) -> Result<usize, AppError> {
+    let farms = repository::list_farm_locations(db).await?;
+
+    let mut cells: HashMap<(i64, i64), Vec<FarmLocation>> = HashMap::new();
+    for farm in farms {
+        let key = ((farm.latitude * 100.0).round() as i64, (farm.longitude * 100.0).round() as i64);
+        cells.entry(key).or_default().push(farm);
+    }
+
+    let mut updated = 0;
+    for farms in cells.values() {
+        let (lat, lon) = (farms[0].latitude, farms[0].longitude);
+
+        let days = match provider.daily(lat, lon, WEATHER_PAST_DAYS, WEATHER_FORECAST_DAYS).await {
+            Ok(days) => days,
+            Err(e) => {
+                tracing::warn!("Weather fetch for ({:.3}, {:.3}) failed: {}", lat, lon, e);
+                continue;
+            }
+        };
+
+        for farm in farms {
+            repository::upsert_weather(db, farm.id, provider.name(), &days).await?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Mekong Delta daily temperature normals, used when no observations are given.
+const CLIMATOLOGY_T_MIN_C: f64 = 24.0;
+const CLIMATOLOGY_T_MAX_C: f64 = 33.0;
+/// Solar constant in MJ m⁻² min⁻¹ (FAO-56 eq. 21).
+const SOLAR_CONSTANT: f64 = 0.0820;
+/// One millimetre of water over one hectare.
+const LITRES_PER_MM_HECTARE: f64 = 10_000.0;
+
+pub async fn estimate_water_demand(
+    db: &PgPool,
+    user_id: i64,
+    farm_id: i64,
+    query: &WaterDemandQuery,
+) -> Result<WaterDemandResponse, AppError> {
+    let profile = repository::get_farm_water_profile(db, farm_id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    if profile.user_id != user_id {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let date = query.date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+
+    let (t_min_c, t_max_c, weather_source) = match (query.t_min_c, query.t_max_c) {
+        (Some(t_min), Some(t_max)) => (t_min, t_max, "query".to_string()),
+        (None, None) => match repository::get_weather_on(db, farm_id, date).await? {
+            Some(WeatherObservation { t_min_c: Some(t_min), t_max_c: Some(t_max), source, .. }) => (t_min, t_max, source),
+            _ => (CLIMATOLOGY_T_MIN_C, CLIMATOLOGY_T_MAX_C, "climatology".to_string()),
+        },
+        _ => return Err(AppError::Validation("t_min_c and t_max_c must be given together".to_string())),
+    };
+
+    if t_max_c < t_min_c {
+        return Err(AppError::Validation("t_max_c must not be below t_min_c".to_string()));
+    }
+
+    let et0_mm = hargreaves_et0(t_min_c, t_max_c, profile.latitude, date.ordinal());
+
+    let growth_stage = profile.growth_stage.as_deref().and_then(GrowthStage::from_code);
+    let crop_coefficient = crop_coefficient(profile.crop_type.as_deref(), growth_stage);
+    let etc_mm = et0_mm * crop_coefficient;
+    let litres_per_hectare = etc_mm * LITRES_PER_MM_HECTARE;
+
+    Ok(WaterDemandResponse {
+        farm_id,
+        date,
+        method: "hargreaves".to_string(),
+        weather_source,
+        t_min_c,
+        t_max_c,
+        et0_mm,
+        crop_type: profile.crop_type,
+        growth_stage: profile.growth_stage,
+        crop_coefficient,
+        etc_mm,
+        litres_per_hectare,
+        area_hectares: profile.area_hectares,
+        litres_total: profile.area_hectares.map(|area| area * litres_per_hectare),
+    })
+}
+
+/// Share of forecast rain that reaches the root zone.
+const EFFECTIVE_RAIN_FRACTION: f64 = 0.8;
+/// Smaller deficits are left to soil moisture.
+const MIN_DEFICIT_MM: f64 = 1.0;
+/// Tides at or below this bring little salt in, even while risk is high.
+const LOW_TIDE_FACTOR: f64 = 0.4;
+
+/// One planned day's water balance, before intake is decided.
+struct DayBalance {
+    date: NaiveDate,
+    etc_mm: f64,
+    precipitation_mm: Option<f64>,
+    deficit_mm: f64,
+    tide_factor: f64,
+}
+
+/// Daily advice on taking in river water over the next `days` days: the
+/// crop's demand net of forecast rain, held back while salinity risk is high
+/// until a low tide comes.
+pub async fn irrigation_advice(
+    db: &PgPool,
+    user_id: i64,
+    farm_id: i64,
+    days: i64,
+) -> Result<IrrigationAdvice, AppError> {
+    let profile = repository::get_farm_water_profile(db, farm_id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    if profile.user_id != user_id {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let (weather, salinity_risk, lang) = tokio::try_join!(
+        repository::list_weather(db, farm_id, 0),
+        monitoring::service::assess_risk(farm_id, db),
+        i18n::language_for_user(db, user_id),
+    )?;
+    let weather: HashMap<NaiveDate, WeatherObservation> = weather.into_iter().map(|w| (w.observed_on, w)).collect();
+
+    let growth_stage = profile.growth_stage.as_deref().and_then(GrowthStage::from_code);
+    let crop_coefficient = crop_coefficient(profile.crop_type.as_deref(), growth_stage);
+    let today = Utc::now().date_naive();
+
+    let balances: Vec<DayBalance> = (0..days)
+        .map(|offset| {
+            let date = today + Duration::days(offset);
+            let observation = weather.get(&date);
+            let et0_mm = observation.and_then(|w| w.et0_mm).unwrap_or_else(|| {
+                let (t_min_c, t_max_c) = match observation {
+                    Some(WeatherObservation { t_min_c: Some(t_min), t_max_c: Some(t_max), .. }) => (*t_min, *t_max),
+                    _ => (CLIMATOLOGY_T_MIN_C, CLIMATOLOGY_T_MAX_C),
+                };
+                hargreaves_et0(t_min_c, t_max_c, profile.latitude, date.ordinal())
+            });
+            let etc_mm = et0_mm * crop_coefficient;
+            let precipitation_mm = observation.and_then(|w| w.precipitation_mm);
+
+            DayBalance {
+                date,
+                etc_mm,
+                precipitation_mm,
+                deficit_mm: (etc_mm - precipitation_mm.unwrap_or(0.0) * EFFECTIVE_RAIN_FRACTION).max(0.0),
+                tide_factor: risk::tide_factor(
+                    observation.and_then(|w| w.sea_level_max_m),
+                    date.and_time(NaiveTime::MIN).and_utc(),
+                ),
+            }
+        })
+        .collect();
+
+    let level = t(lang, &format!("severity.{}", salinity_risk.level.as_str()), &[]);
+    let plan = balances
+        .iter()
+        .enumerate()
+        .map(|(index, day)| {
+            let (action, wait_until, key) = decide_intake(day, &balances[index + 1..], crop_coefficient, salinity_risk.level);
+            let reasoning = t(lang, key, &[
+                ("level", level.clone()),
+                ("score", salinity_risk.score.to_string()),
+                ("etc", format!("{:.1}", day.etc_mm)),
+                ("rain", format!("{:.1}", day.precipitation_mm.unwrap_or(0.0))),
+                ("deficit", format!("{:.1}", day.deficit_mm)),
+                ("date", wait_until.map(|date| advice_date(lang, date)).unwrap_or_default()),
+                ("days", days.to_string()),
+            ]);
+
+            IrrigationDay {
+                date: day.date,
+                action,
+                etc_mm: day.etc_mm,
+                precipitation_mm: day.precipitation_mm,
+                deficit_mm: day.deficit_mm,
+                litres_total: profile.area_hectares.map(|area| area * day.deficit_mm * LITRES_PER_MM_HECTARE),
+                tide_factor: day.tide_factor,
+                wait_until,
+                reasoning,
+            }
+        })
+        .collect();
+
+    Ok(IrrigationAdvice {
+        farm_id,
+        language: lang,
+        crop_type: profile.crop_type,
+        growth_stage: profile.growth_stage,
+        crop_coefficient,
+        salinity_risk,
+        days: plan,
+    })
+}
+
+/// Salt rides in on the tide, so at high risk intake waits for a low tide
+/// within the plan; at critical risk the intake stays shut.
+fn decide_intake(
+    day: &DayBalance,
+    later: &[DayBalance],
+    crop_coefficient: f64,
+    risk: AlertSeverity,
+) -> (IrrigationAction, Option<NaiveDate>, &'static str) {
+    if crop_coefficient == 0.0 {
+        return (IrrigationAction::Skip, None, "irrigation.no_demand");
+    }
+    if day.deficit_mm < MIN_DEFICIT_MM {
+        let key = if day.precipitation_mm.unwrap_or(0.0) > 0.0 { "irrigation.rain_covers" } else { "irrigation.low_demand" };
+        return (IrrigationAction::Skip, None, key);
+    }
+
+    match risk {
+        AlertSeverity::Low | AlertSeverity::Medium => (IrrigationAction::TakeIn, None, "irrigation.take_in"),
+        AlertSeverity::Critical => (IrrigationAction::UseStored, None, "irrigation.use_stored_critical"),
+        AlertSeverity::High if day.tide_factor <= LOW_TIDE_FACTOR => {
+            (IrrigationAction::TakeIn, None, "irrigation.take_in_low_tide")
+        }
+        AlertSeverity::High => match later.iter().find(|d| d.tide_factor <= LOW_TIDE_FACTOR) {
+            Some(low) => (IrrigationAction::Wait, Some(low.date), "irrigation.wait"),
+            None => (IrrigationAction::UseStored, None, "irrigation.use_stored"),
+        },
+    }
+}
+
+fn advice_date(lang: Language, date: NaiveDate) -> String {
+    match lang {
+        Language::En => date.format("%B %-d").to_string(),
+        Language::Vi => date.format("%d/%m").to_string(),
+    }
+}
+
+/// Hargreaves–Samani reference evapotranspiration in mm/day (FAO-56 eq. 52).
+fn hargreaves_et0(t_min_c: f64, t_max_c: f64, latitude_deg: f64, day_of_year: u32) -> f64 {
+    let t_mean = (t_min_c + t_max_c) / 2.0;
+    // 0.408 converts MJ m⁻² day⁻¹ into mm/day of evaporated water.
+    let ra_mm = 0.408 * extraterrestrial_radiation(latitude_deg, day_of_year);
+
+    (0.0023 * ra_mm * (t_mean + 17.8) * (t_max_c - t_min_c).sqrt()).max(0.0)
+}
+
+/// Daily extraterrestrial radiation in MJ m⁻² day⁻¹ (FAO-56 eqs. 21–25).
+fn extraterrestrial_radiation(latitude_deg: f64, day_of_year: u32) -> f64 {
+    use std::f64::consts::PI;
+
+    let phi = latitude_deg.to_radians();
+    let j = day_of_year as f64;
+    let inverse_distance = 1.0 + 0.033 * (2.0 * PI * j / 365.0).cos();
+    let declination = 0.409 * (2.0 * PI * j / 365.0 - 1.39).sin();
+    let sunset_angle = (-phi.tan() * declination.tan()).clamp(-1.0, 1.0).acos();
+
+    (24.0 * 60.0 / PI) * SOLAR_CONSTANT * inverse_distance
+        * (sunset_angle * phi.sin() * declination.sin() + phi.cos() * declination.cos() * sunset_angle.sin())
+}
+
+/// FAO-56 style crop coefficients for the delta's main crops. Without a
+/// recorded season the reference crop (Kc = 1) is assumed.
+fn crop_coefficient(crop_type: Option<&str>, stage: Option<GrowthStage>) -> f64 {
+    let Some(crop_type) = crop_type else {
+        return 1.0;
+    };
+
+    match (crop_type, stage) {
+        // Ponds evaporate like open water whatever the stock's age.
+        ("shrimp", _) => 1.05,
+        // Perennial orchards keep transpiring between harvests.
+        ("durian", _) => 0.9,
+        (_, Some(GrowthStage::Harvested)) => 0.0,
+        ("rice", Some(GrowthStage::Germination | GrowthStage::Seedling)) => 1.05,
+        ("rice", Some(GrowthStage::Vegetative)) => 1.1,
+        ("rice", Some(GrowthStage::Reproductive)) => 1.2,
+        ("rice", Some(GrowthStage::Ripening)) => 0.9,
+        (_, Some(GrowthStage::Germination | GrowthStage::Seedling)) => 0.7,
+        (_, Some(GrowthStage::Vegetative)) => 0.9,
+        (_, Some(GrowthStage::Reproductive)) => 1.05,
+        (_, Some(GrowthStage::Ripening)) => 0.85,
+        (_, None) => 1.0,
+    }
+}
+
+/// Heatmap cell sizes, in thousandths of a degree.
+const HEATMAP_RESOLUTIONS: &[i32] = &[10, 50, 100];
+const DEFAULT_HEATMAP_RESOLUTION: i32 = 10;
+/// Readings averaged into each day, so every cell has had a satellite pass.
+const HEATMAP_WINDOW_DAYS: i64 = 7;
+/// Days rebuilt on each run; imports can backfill readings this far.
+const HEATMAP_REBUILD_DAYS: i64 = 30;
+const HEATMAP_RETENTION_DAYS: i64 = 365;
+
+/// Rebuilds recent heatmap days, or the whole retention period on the first
+/// run, and drops days past retention. Returns the number of cells written.
+pub async fn recompute_heatmap(db: &PgPool) -> Result<u64, AppError> {
+    let today = Utc::now().date_naive();
+    let days = if repository::heatmap_is_empty(db).await? {
+        HEATMAP_RETENTION_DAYS
+    } else {
+        HEATMAP_REBUILD_DAYS
+    };
+
+    let written = repository::rebuild_heatmap(
+        db,
+        today - Duration::days(days - 1),
+        HEATMAP_WINDOW_DAYS as i32,
+        HEATMAP_RESOLUTIONS,
+    )
+    .await?;
+    repository::purge_heatmap(db, today - Duration::days(HEATMAP_RETENTION_DAYS - 1)).await?;
+    Ok(written)
+}
+
+/// The heatmap of the regions where `user_id` has farms, or of every region
+/// when `user_id` is `None`.
+pub async fn salinity_heatmap(db: &PgPool, user_id: Option<i64>, query: &HeatmapQuery) -> Result<SalinityHeatmap, AppError> {
+    let today = Utc::now().date_naive();
+    let date = query.date.unwrap_or(today);
+    if date > today {
+        return Err(AppError::Validation("date cannot be in the future".to_string()));
+    }
+
+    let resolution = match query.resolution {
+        Some(degrees) => HEATMAP_RESOLUTIONS
+            .iter()
+            .copied()
+            .find(|&millideg| (degrees * 1000.0 - millideg as f64).abs() < 1e-6)
+            .ok_or_else(|| AppError::Validation("resolution must be 0.01, 0.05 or 0.1".to_string()))?,
+        None => DEFAULT_HEATMAP_RESOLUTION,
+    };
+
+    let (cells, computed_at) = repository::heatmap_cells(db, date, resolution, user_id).await?;
+    Ok(SalinityHeatmap {
+        date,
+        resolution: resolution as f64 / 1000.0,
+        window_days: HEATMAP_WINDOW_DAYS,
+        computed_at,
+        cells,
+    })
+}
@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+use crate::shared::{AppState, access, audit::AuditDetails, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{ApiKeyResponse, CreateApiKeyRequest},
+    repository, service,
+};
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    tag = "settings",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "Key created; the key itself is only returned here", body = ApiKeyResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 422, description = "Blank name or no scopes", body = ErrorResponse),
+    ),
+)]
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateApiKeyRequest>,
+) -> Result<(Extension<AuditDetails>, Json<ApiKeyResponse>), AppError> {
+    access::require_admin(&claims)?;
+
+    let response = service::create_key(&state.db, claims.sub, payload).await?;
+
+    let audit = AuditDetails::new("api_key.create", "api_key", Some(response.id))
+        .after(&serde_json::json!({ "name": response.name, "prefix": response.prefix, "scopes": response.scopes }));
+
+    Ok((Extension(audit), Json(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    tag = "settings",
+    responses(
+        (status = 200, description = "All API keys, newest first, including revoked ones", body = [ApiKeyResponse]),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<ApiKeyResponse>>, AppError> {
+    access::require_admin(&claims)?;
+
+    let keys = repository::list(&state.db).await?;
+    Ok(Json(keys.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    tag = "settings",
+    params(("id" = i64, Path, description = "API key id")),
+    responses(
+        (status = 200, description = "Key revoked; requests using it are refused from now on", body = ApiKeyResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "API key not found", body = ErrorResponse),
+    ),
+)]
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<ApiKeyResponse>), AppError> {
+    access::require_admin(&claims)?;
+
+    let key = repository::revoke(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("API key {} not found", id)))?;
+
+    let audit = AuditDetails::new("api_key.revoke", "api_key", Some(id))
+        .after(&serde_json::json!({ "name": key.name, "prefix": key.prefix, "revoked_at": key.revoked_at }));
+
+    Ok((Extension(audit), Json(key.into())))
+}
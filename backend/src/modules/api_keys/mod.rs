@@ -0,0 +1,34 @@
+//! Keys for machine clients that cannot sign in, such as provincial
+//! early-warning systems. Admins issue them with scopes; routes outside the
+//! JWT-protected tree call `require_scope`.
+
+mod models;
+mod repository;
+mod service;
+mod controller;
+
+pub use models::ApiKeyScope;
+pub use service::{require_scope, API_KEY_HEADER};
+
+use axum::{routing::{delete, get}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+/// Admin key management, mounted alongside the settings routes.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api-keys", get(controller::list_api_keys).post(controller::create_api_key))
+        .route("/api-keys/{id}", delete(controller::revoke_api_key))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::create_api_key,
+    controller::list_api_keys,
+    controller::revoke_api_key,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
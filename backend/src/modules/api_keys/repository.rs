@@ -0,0 +1,67 @@
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::ApiKey;
+
+const KEY_COLUMNS: &str = "id, name, prefix, scopes, created_at, last_used_at, revoked_at";
+
+pub async fn create(
+    pool: &PgPool,
+    name: &str,
+    prefix: &str,
+    key_hash: &str,
+    scopes: &[String],
+    created_by: i64,
+) -> Result<ApiKey, AppError> {
+    sqlx::query_as::<_, ApiKey>(&format!(
+        r#"
+        INSERT INTO api_keys (name, prefix, key_hash, scopes, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {KEY_COLUMNS}
+        "#
+    ))
+    .bind(name)
+    .bind(prefix)
+    .bind(key_hash)
+    .bind(scopes)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn list(pool: &PgPool) -> Result<Vec<ApiKey>, AppError> {
+    sqlx::query_as::<_, ApiKey>(&format!("SELECT {KEY_COLUMNS} FROM api_keys ORDER BY created_at DESC"))
+        .fetch_all(pool)
+        .await
+        .map_err(Into::into)
+}
+
+/// Marks the key revoked; returns it, or `None` if it does not exist.
+pub async fn revoke(pool: &PgPool, id: i64) -> Result<Option<ApiKey>, AppError> {
+    sqlx::query_as::<_, ApiKey>(&format!(
+        r#"
+        UPDATE api_keys SET revoked_at = COALESCE(revoked_at, NOW())
+        WHERE id = $1
+        RETURNING {KEY_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// The unrevoked key with this hash, recording that it was just used.
+pub async fn use_active(pool: &PgPool, key_hash: &str) -> Result<Option<ApiKey>, AppError> {
+    sqlx::query_as::<_, ApiKey>(&format!(
+        r#"
+        UPDATE api_keys SET last_used_at = NOW()
+        WHERE key_hash = $1 AND revoked_at IS NULL
+        RETURNING {KEY_COLUMNS}
+        "#
+    ))
+    .bind(key_hash)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
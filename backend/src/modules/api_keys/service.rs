@@ -0,0 +1,67 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::http::HeaderMap;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use crate::shared::error::{AppError, ErrorCode};
+use super::models::{ApiKey, ApiKeyResponse, ApiKeyScope, CreateApiKeyRequest};
+use super::repository;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+const KEY_PREFIX: &str = "brk_";
+/// Characters of the key kept in the clear to tell keys apart.
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+pub async fn create_key(db: &PgPool, created_by: i64, request: CreateApiKeyRequest) -> Result<ApiKeyResponse, AppError> {
+    let key = generate_key();
+
+    let mut scopes: Vec<String> = request.scopes.iter().map(|s| s.as_str().to_string()).collect();
+    scopes.sort();
+    scopes.dedup();
+
+    let created = repository::create(
+        db,
+        request.name.trim(),
+        &key[..DISPLAY_PREFIX_LEN],
+        &hash_key(&key),
+        &scopes,
+        created_by,
+    )
+    .await?;
+
+    Ok(ApiKeyResponse {
+        key: Some(key),
+        ..created.into()
+    })
+}
+
+/// Authenticates the request's `X-API-Key` and checks it carries `scope`.
+pub async fn require_scope(db: &PgPool, headers: &HeaderMap, scope: ApiKeyScope) -> Result<ApiKey, AppError> {
+    let key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| AppError::Unauthorized("Missing X-API-Key header".to_string()))?;
+
+    let key = repository::use_active(db, &hash_key(key.trim()))
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid or revoked API key".to_string()))?;
+
+    if !key.scopes.iter().any(|s| s == scope.as_str()) {
+        return Err(AppError::Coded(
+            ErrorCode::InsufficientScope,
+            format!("API key lacks the '{}' scope", scope.as_str()),
+        ));
+    }
+
+    Ok(key)
+}
+
+fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", KEY_PREFIX, hex::encode(bytes))
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
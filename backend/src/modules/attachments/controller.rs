@@ -0,0 +1,221 @@
+use axum::{
+    extract::{Extension, Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use crate::shared::{AppState, audit::AuditDetails, error::{AppError, ErrorResponse}};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{AttachmentParent, AttachmentResponse, SignedFileQuery},
+    service,
+};
+
+/// Reads the `file` part of a multipart upload.
+async fn read_file(mut multipart: Multipart) -> Result<(String, Vec<u8>), AppError> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Invalid multipart body: {}", e)))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let filename = field.file_name().unwrap_or_default().to_string();
+        let bytes = field
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Failed to read upload: {}", e)))?;
+        return Ok((filename, bytes.to_vec()));
+    }
+    Err(AppError::Validation("Missing 'file' part".to_string()))
+}
+
+async fn upload(
+    state: &AppState,
+    claims: &Claims,
+    parent: AttachmentParent,
+    multipart: Multipart,
+) -> Result<(StatusCode, Extension<AuditDetails>, Json<AttachmentResponse>), AppError> {
+    service::ensure_access(&state.db, claims.sub, parent).await?;
+    let (filename, bytes) = read_file(multipart).await?;
+
+    let attachment = service::upload(&state.db, state.storage.as_ref(), claims.sub, parent, &filename, bytes).await?;
+    let response = service::to_response(attachment);
+    let audit = AuditDetails::new("attachment.upload", "attachment", Some(response.id)).after(&response);
+
+    Ok((StatusCode::CREATED, Extension(audit), Json(response)))
+}
+
+async fn list(
+    state: &AppState,
+    claims: &Claims,
+    parent: AttachmentParent,
+) -> Result<Json<Vec<AttachmentResponse>>, AppError> {
+    let attachments = service::list(&state.db, claims.sub, parent).await?;
+    Ok(Json(attachments.into_iter().map(service::to_response).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/alerts/{alert_id}/attachments",
+    tag = "attachments",
+    params(("alert_id" = i64, Path, description = "Alert id")),
+    request_body(content_type = "multipart/form-data", description = "A `file` part holding a JPEG, PNG, WebP or PDF of up to 10 MiB"),
+    responses(
+        (status = 201, description = "Attachment stored", body = AttachmentResponse),
+        (status = 400, description = "Missing, empty, oversized or unsupported file", body = ErrorResponse),
+        (status = 404, description = "Alert not found", body = ErrorResponse),
+    ),
+)]
+pub async fn upload_alert_attachment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(alert_id): Path<i64>,
+    multipart: Multipart,
+) -> Result<(StatusCode, Extension<AuditDetails>, Json<AttachmentResponse>), AppError> {
+    upload(&state, &claims, AttachmentParent::Alert(alert_id), multipart).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts/{alert_id}/attachments",
+    tag = "attachments",
+    params(("alert_id" = i64, Path, description = "Alert id")),
+    responses(
+        (status = 200, description = "Attachments of the alert, oldest first, with fresh signed links", body = [AttachmentResponse]),
+        (status = 404, description = "Alert not found", body = ErrorResponse),
+    ),
+)]
+pub async fn list_alert_attachments(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(alert_id): Path<i64>,
+) -> Result<Json<Vec<AttachmentResponse>>, AppError> {
+    list(&state, &claims, AttachmentParent::Alert(alert_id)).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/attachments",
+    tag = "attachments",
+    params(("id" = i64, Path, description = "Todo id")),
+    request_body(content_type = "multipart/form-data", description = "A `file` part holding a JPEG, PNG, WebP or PDF of up to 10 MiB"),
+    responses(
+        (status = 201, description = "Attachment stored", body = AttachmentResponse),
+        (status = 400, description = "Missing, empty, oversized or unsupported file", body = ErrorResponse),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+    ),
+)]
+pub async fn upload_todo_attachment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    multipart: Multipart,
+) -> Result<(StatusCode, Extension<AuditDetails>, Json<AttachmentResponse>), AppError> {
+    upload(&state, &claims, AttachmentParent::Todo(id), multipart).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/attachments",
+    tag = "attachments",
+    params(("id" = i64, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Attachments of the todo, oldest first, with fresh signed links", body = [AttachmentResponse]),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+    ),
+)]
+pub async fn list_todo_attachments(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<AttachmentResponse>>, AppError> {
+    list(&state, &claims, AttachmentParent::Todo(id)).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/profile/avatar",
+    tag = "auth",
+    request_body(content_type = "multipart/form-data", description = "A `file` part holding a JPEG, PNG or WebP of up to 10 MiB"),
+    responses(
+        (status = 200, description = "Profile picture replaced", body = AttachmentResponse),
+        (status = 400, description = "Missing, empty, oversized or non-image file", body = ErrorResponse),
+    ),
+)]
+pub async fn upload_avatar(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    multipart: Multipart,
+) -> Result<(Extension<AuditDetails>, Json<AttachmentResponse>), AppError> {
+    let (filename, bytes) = read_file(multipart).await?;
+
+    let (avatar, replaced) =
+        service::replace_avatar(&state.db, state.storage.as_ref(), claims.sub, &filename, bytes).await?;
+    let response = service::to_response(avatar);
+    let mut audit = AuditDetails::new("profile.avatar_update", "user", Some(claims.sub)).after(&response);
+    if let Some(previous) = replaced.into_iter().last() {
+        audit = audit.before(&service::to_response(previous));
+    }
+
+    Ok((Extension(audit), Json(response)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "attachments",
+    params(("id" = i64, Path, description = "Attachment id")),
+    responses(
+        (status = 200, description = "Attachment and its files deleted"),
+        (status = 404, description = "Attachment not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    let deleted = service::delete(&state.db, state.storage.as_ref(), claims.sub, id).await?;
+    let audit = AuditDetails::new("attachment.delete", "attachment", Some(id)).before(&service::to_response(deleted));
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/file",
+    tag = "attachments",
+    params(("id" = i64, Path, description = "Attachment id"), SignedFileQuery),
+    responses(
+        (status = 200, description = "File contents, or a JPEG preview for variant=thumbnail"),
+        (status = 401, description = "Signature invalid or expired", body = ErrorResponse),
+        (status = 404, description = "Attachment or thumbnail not found", body = ErrorResponse),
+    ),
+)]
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<SignedFileQuery>,
+) -> Result<Response, AppError> {
+    let (bytes, content_type, filename) = service::open_signed(
+        &state.db,
+        state.storage.as_ref(),
+        id,
+        query.variant,
+        query.expires,
+        &query.signature,
+    )
+    .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, format!("inline; filename=\"{}\"", filename)),
+            (header::CACHE_CONTROL, "private, max-age=900".to_string()),
+        ],
+        bytes,
+    )
+        .into_response())
+}
@@ -0,0 +1,98 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+
+pub use models::AttachmentResponse;
+
+use axum::{extract::DefaultBodyLimit, routing::{delete, get, put}, Router};
+use sqlx::PgPool;
+use utoipa::OpenApi;
+use crate::shared::{error::AppError, AppState};
+
+/// Room for a maximum-size file plus the multipart framing.
+const UPLOAD_BODY_LIMIT: usize = service::MAX_ATTACHMENT_BYTES + 64 * 1024;
+
+/// Deleting attachments, mounted under `/api/attachments`.
+pub fn router() -> Router<AppState> {
+    Router::new().route("/{id}", delete(controller::delete_attachment))
+}
+
+/// Signed downloads, which carry no bearer token.
+pub fn public_router() -> Router<AppState> {
+    Router::new().route("/{id}/file", get(controller::download_attachment))
+}
+
+/// Alert attachments, mounted alongside the monitoring routes.
+pub fn alert_router() -> Router<AppState> {
+    Router::new().route(
+        "/alerts/{alert_id}/attachments",
+        get(controller::list_alert_attachments)
+            .post(controller::upload_alert_attachment)
+            .layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+    )
+}
+
+/// Todo attachments, mounted alongside the todo routes.
+pub fn todo_router() -> Router<AppState> {
+    Router::new().route(
+        "/{id}/attachments",
+        get(controller::list_todo_attachments)
+            .post(controller::upload_todo_attachment)
+            .layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+    )
+}
+
+/// Profile pictures, mounted alongside the auth routes.
+pub fn profile_router() -> Router<AppState> {
+    Router::new().route(
+        "/profile/avatar",
+        put(controller::upload_avatar).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+    )
+}
+
+/// The profile picture of `user_id` with fresh signed links.
+pub async fn profile_avatar(db: &PgPool, user_id: i64) -> Result<Option<AttachmentResponse>, AppError> {
+    Ok(service::avatar(db, user_id).await?.map(service::to_response))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::delete_attachment,
+    controller::download_attachment,
+))]
+struct ApiDoc;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::list_alert_attachments,
+    controller::upload_alert_attachment,
+))]
+struct AlertApiDoc;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::list_todo_attachments,
+    controller::upload_todo_attachment,
+))]
+struct TodoApiDoc;
+
+#[derive(OpenApi)]
+#[openapi(paths(controller::upload_avatar))]
+struct ProfileApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+
+pub fn alert_openapi() -> utoipa::openapi::OpenApi {
+    AlertApiDoc::openapi()
+}
+
+pub fn todo_openapi() -> utoipa::openapi::OpenApi {
+    TodoApiDoc::openapi()
+}
+
+pub fn profile_openapi() -> utoipa::openapi::OpenApi {
+    ProfileApiDoc::openapi()
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+
+/// What an attachment hangs off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentParent {
+    Alert(i64),
+    Todo(i64),
+    /// The profile picture of a user.
+    Profile(i64),
+}
+
+/// A stored upload about to be recorded.
+#[derive(Debug, Clone)]
+pub struct NewAttachment {
+    pub parent: AttachmentParent,
+    pub user_id: i64,
+    pub filename: String,
+    pub content_type: &'static str,
+    pub size_bytes: i64,
+    pub storage_key: String,
+    pub thumbnail_key: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Attachment {
+    pub id: i64,
+    pub alert_id: Option<i64>,
+    pub todo_id: Option<i64>,
+    pub profile_user_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub storage_key: String,
+    pub thumbnail_key: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Attachment {
+    pub fn parent(&self) -> AttachmentParent {
+        match (self.alert_id, self.todo_id, self.profile_user_id) {
+            (Some(alert_id), _, _) => AttachmentParent::Alert(alert_id),
+            (None, Some(todo_id), _) => AttachmentParent::Todo(todo_id),
+            (None, None, profile_user_id) => AttachmentParent::Profile(profile_user_id.unwrap_or_default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AttachmentResponse {
+    pub id: i64,
+    pub alert_id: Option<i64>,
+    pub todo_id: Option<i64>,
+    /// Uploader; `null` once their account is deleted.
+    pub user_id: Option<i64>,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+    /// Signed download link, usable without a bearer token until `urls_expire_at`.
+    pub url: String,
+    /// Signed link to a JPEG preview, for images.
+    pub thumbnail_url: Option<String>,
+    pub urls_expire_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FileVariant {
+    #[default]
+    Original,
+    Thumbnail,
+}
+
+impl FileVariant {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FileVariant::Original => "original",
+            FileVariant::Thumbnail => "thumbnail",
+        }
+    }
+}
+
+/// Parameters of a signed download link.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SignedFileQuery {
+    #[serde(default)]
+    pub variant: FileVariant,
+    /// Unix time after which the link stops working.
+    pub expires: i64,
+    pub signature: String,
+}
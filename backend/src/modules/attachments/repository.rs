@@ -0,0 +1,83 @@
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::{Attachment, AttachmentParent, NewAttachment};
+
+const ATTACHMENT_COLUMNS: &str =
+    "id, alert_id, todo_id, profile_user_id, user_id, filename, content_type, size_bytes, storage_key, thumbnail_key, created_at";
+
+fn parent_ids(parent: AttachmentParent) -> (Option<i64>, Option<i64>, Option<i64>) {
+    match parent {
+        AttachmentParent::Alert(id) => (Some(id), None, None),
+        AttachmentParent::Todo(id) => (None, Some(id), None),
+        AttachmentParent::Profile(id) => (None, None, Some(id)),
+    }
+}
+
+pub async fn insert(pool: &PgPool, new: &NewAttachment) -> Result<Attachment, AppError> {
+    let (alert_id, todo_id, profile_user_id) = parent_ids(new.parent);
+
+    sqlx::query_as::<_, Attachment>(&format!(
+        r#"
+        INSERT INTO attachments
+            (alert_id, todo_id, profile_user_id, user_id, filename, content_type, size_bytes, storage_key, thumbnail_key)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING {ATTACHMENT_COLUMNS}
+        "#
+    ))
+    .bind(alert_id)
+    .bind(todo_id)
+    .bind(profile_user_id)
+    .bind(new.user_id)
+    .bind(&new.filename)
+    .bind(new.content_type)
+    .bind(new.size_bytes)
+    .bind(&new.storage_key)
+    .bind(&new.thumbnail_key)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn list(pool: &PgPool, parent: AttachmentParent) -> Result<Vec<Attachment>, AppError> {
+    let (alert_id, todo_id, profile_user_id) = parent_ids(parent);
+
+    sqlx::query_as::<_, Attachment>(&format!(
+        r#"
+        SELECT {ATTACHMENT_COLUMNS} FROM attachments
+        WHERE alert_id IS NOT DISTINCT FROM $1
+          AND todo_id IS NOT DISTINCT FROM $2
+          AND profile_user_id IS NOT DISTINCT FROM $3
+        ORDER BY created_at, id
+        "#
+    ))
+    .bind(alert_id)
+    .bind(todo_id)
+    .bind(profile_user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn get(pool: &PgPool, id: i64) -> Result<Option<Attachment>, AppError> {
+    sqlx::query_as::<_, Attachment>(&format!("SELECT {ATTACHMENT_COLUMNS} FROM attachments WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn delete(pool: &PgPool, id: i64) -> Result<Option<Attachment>, AppError> {
+    sqlx::query_as::<_, Attachment>(&format!("DELETE FROM attachments WHERE id = $1 RETURNING {ATTACHMENT_COLUMNS}"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn alert_owner(pool: &PgPool, alert_id: i64) -> Result<Option<i64>, AppError> {
+    sqlx::query_scalar("SELECT f.user_id FROM alerts a JOIN farms f ON f.id = a.farm_id WHERE a.id = $1")
+        .bind(alert_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
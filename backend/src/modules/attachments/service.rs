@@ -0,0 +1,291 @@
+//! Photo and document attachments on alerts and todos. Files live in the
+//! configured object store; downloads go through short-lived signed links so
+//! that `<img>` tags and native viewers can fetch them without a bearer token.
+
+use std::io::Cursor;
+use chrono::{Duration, Utc};
+use image::ImageFormat;
+use sqlx::PgPool;
+use crate::modules::todos;
+use crate::shared::{error::{AppError, ErrorCode}, signing, storage::ObjectStore};
+use super::models::{Attachment, AttachmentParent, AttachmentResponse, FileVariant, NewAttachment};
+use super::repository;
+
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+/// Longest edge of generated previews, in pixels.
+const THUMBNAIL_SIZE: u32 = 320;
+const URL_TTL_MINUTES: i64 = 15;
+const MAX_FILENAME_LEN: usize = 255;
+
+/// Fails unless `user_id` owns the alert's farm, the todo or the profile.
+pub async fn ensure_access(db: &PgPool, user_id: i64, parent: AttachmentParent) -> Result<(), AppError> {
+    match parent {
+        AttachmentParent::Alert(alert_id) => {
+            let owner = repository::alert_owner(db, alert_id)
+                .await?
+                .ok_or_else(|| AppError::Coded(ErrorCode::AlertNotFound, format!("Alert {} not found", alert_id)))?;
+            if owner != user_id {
+                return Err(AppError::Unauthorized("Not authorized to access this alert".to_string()));
+            }
+        }
+        AttachmentParent::Todo(todo_id) => {
+            todos::service::get_owned_todo(db, user_id, todo_id).await?;
+        }
+        AttachmentParent::Profile(profile_user_id) => {
+            if profile_user_id != user_id {
+                return Err(AppError::Unauthorized("Not authorized to access this profile".to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The MIME type of `bytes` judged by content, for the accepted formats.
+fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    match image::guess_format(bytes).ok()? {
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn extension(content_type: &str) -> &'static str {
+    match content_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/webp" => "webp",
+        _ => "pdf",
+    }
+}
+
+/// Drops path components and characters that would break a
+/// `Content-Disposition` header.
+fn clean_filename(filename: &str, content_type: &str) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(MAX_FILENAME_LEN)
+        .collect();
+    let cleaned = cleaned.trim();
+
+    if cleaned.is_empty() {
+        format!("attachment.{}", extension(content_type))
+    } else {
+        cleaned.to_string()
+    }
+}
+
+fn thumbnail(bytes: &[u8]) -> Result<Vec<u8>, AppError> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| AppError::Validation(format!("Unreadable image: {}", e)))?;
+    let preview = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8();
+
+    let mut out = Cursor::new(Vec::new());
+    preview
+        .write_to(&mut out, ImageFormat::Jpeg)
+        .map_err(|e| AppError::Internal(format!("Failed to encode thumbnail: {}", e)))?;
+    Ok(out.into_inner())
+}
+
+/// Validates and stores an upload, with a JPEG preview for images.
+pub async fn upload(
+    db: &PgPool,
+    storage: &dyn ObjectStore,
+    user_id: i64,
+    parent: AttachmentParent,
+    filename: &str,
+    bytes: Vec<u8>,
+) -> Result<Attachment, AppError> {
+    if bytes.is_empty() {
+        return Err(AppError::Validation("File is empty".to_string()));
+    }
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        return Err(AppError::Validation(format!(
+            "File is larger than {} MiB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    let content_type = sniff_content_type(&bytes)
+        .ok_or_else(|| AppError::Validation("Only JPEG, PNG, WebP and PDF files are accepted".to_string()))?;
+
+    let stem = format!("attachments/{}", uuid::Uuid::new_v4());
+    let storage_key = format!("{}.{}", stem, extension(content_type));
+    let thumbnail = if content_type.starts_with("image/") {
+        let source = bytes.clone();
+        Some(
+            tokio::task::spawn_blocking(move || thumbnail(&source))
+                .await
+                .map_err(|e| AppError::Internal(format!("Thumbnail task failed: {}", e)))??,
+        )
+    } else {
+        None
+    };
+    let thumbnail_key = thumbnail.as_ref().map(|_| format!("{}-thumb.jpg", stem));
+    let size_bytes = bytes.len() as i64;
+
+    storage.put(&storage_key, bytes).await?;
+    if let (Some(key), Some(preview)) = (&thumbnail_key, thumbnail) {
+        storage.put(key, preview).await?;
+    }
+
+    let new = NewAttachment {
+        parent,
+        user_id,
+        filename: clean_filename(filename, content_type),
+        content_type,
+        size_bytes,
+        storage_key,
+        thumbnail_key,
+    };
+    let inserted = repository::insert(db, &new).await;
+
+    if inserted.is_err() {
+        remove_objects(storage, &new.storage_key, new.thumbnail_key.as_deref()).await;
+    }
+    inserted
+}
+
+/// Stores a new profile picture for `user_id` and drops the previous ones.
+/// Only images are accepted.
+pub async fn replace_avatar(
+    db: &PgPool,
+    storage: &dyn ObjectStore,
+    user_id: i64,
+    filename: &str,
+    bytes: Vec<u8>,
+) -> Result<(Attachment, Vec<Attachment>), AppError> {
+    if !sniff_content_type(&bytes).is_some_and(|content_type| content_type.starts_with("image/")) {
+        return Err(AppError::Validation("Profile pictures must be JPEG, PNG or WebP images".to_string()));
+    }
+
+    let parent = AttachmentParent::Profile(user_id);
+    let avatar = upload(db, storage, user_id, parent, filename, bytes).await?;
+
+    let mut replaced = Vec::new();
+    for previous in repository::list(db, parent).await? {
+        if previous.id == avatar.id {
+            continue;
+        }
+        if let Some(deleted) = repository::delete(db, previous.id).await? {
+            remove_objects(storage, &deleted.storage_key, deleted.thumbnail_key.as_deref()).await;
+            replaced.push(deleted);
+        }
+    }
+    Ok((avatar, replaced))
+}
+
+/// The current profile picture of `user_id`, if any.
+pub async fn avatar(db: &PgPool, user_id: i64) -> Result<Option<Attachment>, AppError> {
+    Ok(repository::list(db, AttachmentParent::Profile(user_id)).await?.pop())
+}
+
+pub async fn list(db: &PgPool, user_id: i64, parent: AttachmentParent) -> Result<Vec<Attachment>, AppError> {
+    ensure_access(db, user_id, parent).await?;
+    repository::list(db, parent).await
+}
+
+/// Removes the attachment and its files; returns the deleted row.
+pub async fn delete(db: &PgPool, storage: &dyn ObjectStore, user_id: i64, id: i64) -> Result<Attachment, AppError> {
+    let attachment = repository::get(db, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Attachment {} not found", id)))?;
+    ensure_access(db, user_id, attachment.parent()).await?;
+
+    let deleted = repository::delete(db, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Attachment {} not found", id)))?;
+    remove_objects(storage, &deleted.storage_key, deleted.thumbnail_key.as_deref()).await;
+    Ok(deleted)
+}
+
+/// Orphaned files are only wasted space, so failures are logged and ignored.
+async fn remove_objects(storage: &dyn ObjectStore, key: &str, thumbnail_key: Option<&str>) {
+    for key in std::iter::once(key).chain(thumbnail_key) {
+        if let Err(e) = storage.delete(key).await {
+            tracing::warn!("Failed to delete stored object {}: {}", key, e);
+        }
+    }
+}
+
+const LINK_PURPOSE: &str = "attachment-download";
+
+fn link_payload(id: i64, variant: FileVariant, expires: i64) -> String {
+    format!("{}:{}:{}", id, variant.as_str(), expires)
+}
+
+fn signature(id: i64, variant: FileVariant, expires: i64) -> String {
+    signing::sign(LINK_PURPOSE, &link_payload(id, variant, expires))
+}
+
+fn signed_path(id: i64, variant: FileVariant, expires: i64) -> String {
+    format!(
+        "/api/attachments/{}/file?variant={}&expires={}&signature={}",
+        id,
+        variant.as_str(),
+        expires,
+        signature(id, variant, expires)
+    )
+}
+
+/// Attaches fresh signed links to `attachment`.
+pub fn to_response(attachment: Attachment) -> AttachmentResponse {
+    let expires_at = Utc::now() + Duration::minutes(URL_TTL_MINUTES);
+    let expires = expires_at.timestamp();
+
+    AttachmentResponse {
+        url: signed_path(attachment.id, FileVariant::Original, expires),
+        thumbnail_url: attachment
+            .thumbnail_key
+            .as_ref()
+            .map(|_| signed_path(attachment.id, FileVariant::Thumbnail, expires)),
+        urls_expire_at: expires_at,
+        id: attachment.id,
+        alert_id: attachment.alert_id,
+        todo_id: attachment.todo_id,
+        user_id: attachment.user_id,
+        filename: attachment.filename,
+        content_type: attachment.content_type,
+        size_bytes: attachment.size_bytes,
+        created_at: attachment.created_at,
+    }
+}
+
+/// Resolves a signed link to the file's bytes, content type and download name.
+pub async fn open_signed(
+    db: &PgPool,
+    storage: &dyn ObjectStore,
+    id: i64,
+    variant: FileVariant,
+    expires: i64,
+    provided: &str,
+) -> Result<(Vec<u8>, String, String), AppError> {
+    if !signing::verify(LINK_PURPOSE, &link_payload(id, variant, expires), provided) {
+        return Err(AppError::Coded(ErrorCode::InvalidToken, "Invalid download signature".to_string()));
+    }
+    if expires < Utc::now().timestamp() {
+        return Err(AppError::Coded(ErrorCode::InvalidToken, "Download link has expired".to_string()));
+    }
+
+    let attachment = repository::get(db, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Attachment {} not found", id)))?;
+
+    match variant {
+        FileVariant::Original => {
+            let bytes = storage.get(&attachment.storage_key).await?;
+            Ok((bytes, attachment.content_type, attachment.filename))
+        }
+        FileVariant::Thumbnail => {
+            let key = attachment
+                .thumbnail_key
+                .ok_or_else(|| AppError::NotFound(format!("Attachment {} has no thumbnail", id)))?;
+            let bytes = storage.get(&key).await?;
+            Ok((bytes, "image/jpeg".to_string(), format!("thumbnail-{}.jpg", attachment.id)))
+        }
+    }
+}
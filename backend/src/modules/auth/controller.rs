@@ -1,30 +1,41 @@
-use axum::{extract::{State, Extension}, Json};
-use crate::shared::{AppState, error::AppError};
+use axum::{extract::{ConnectInfo, Path, State, Extension}, http::StatusCode, Json};
+use std::{net::SocketAddr, time::Duration};
+use crate::shared::{
+    AppState,
+    audit::AuditDetails,
+    error::{AppError, ErrorCode, ErrorResponse},
+    runtime,
+    validation::ValidatedJson,
+};
 use super::{
-    models::{LoginRequest, LoginResponse, RegisterRequest, UserProfile, Claims},
+    models::{
+        LoginRequest, LoginResponse, RegisterRequest, UserProfile, Claims,
+        ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest, MessageResponse, TokenPurpose,
+        ConfirmAccountDeletionRequest, AccountDeletionResponse, OAuthAuthorizeResponse, OAuthCallbackRequest,
+        UpdateProfileRequest, ChangePasswordRequest, OtpRequest, OtpVerifyRequest, User,
+    },
+    oauth::OAuthProvider,
     repository, service,
 };
 
-pub async fn register(
-    State(state): State<AppState>,
-    Json(payload): Json<RegisterRequest>,
-) -> Result<Json<LoginResponse>, AppError> {
-    if payload.email.is_empty() || payload.password.is_empty() {
-        return Err(AppError::BadRequest("Email and password are required".to_string()));
-    }
-
-    if payload.password.len() < 8 {
-        return Err(AppError::BadRequest("Password must be at least 8 characters".to_string()));
-    }
+const CHANGE_PASSWORD_ATTEMPTS_PER_HOUR: usize = 10;
+const OTP_REQUESTS_PER_HOUR: usize = 5;
+const OTP_VERIFICATIONS_PER_HOUR: usize = 10;
+/// Per client address, across numbers; allows a shared network a few users.
+const OTP_REQUESTS_PER_IP_PER_HOUR: usize = 20;
+const OTP_VERIFICATIONS_PER_IP_PER_HOUR: usize = 40;
 
-    if repository::find_by_email(&state.db, &payload.email).await?.is_some() {
-        return Err(AppError::BadRequest("Email already registered".to_string()));
-    }
-
-    let password_hash = service::hash_password(&payload.password)?;
-    let user = repository::create_user(&state.db, &payload.email, &password_hash, &payload.role).await?;
+/// Counts an OTP attempt against both the number and the client address;
+/// `false` once either is over its hourly limit.
+fn otp_allowed(state: &AppState, action: &str, phone: &str, addr: SocketAddr, per_phone: usize, per_ip: usize) -> bool {
+    let hour = Duration::from_secs(3600);
+    state.rate_limiter.check(&format!("{}-ip:{}", action, addr.ip()), per_ip, hour)
+        && state.rate_limiter.check(&format!("{}:{}", action, phone), per_phone, hour)
+}
 
-    let token = service::generate_jwt(user.id, &user.email, &user.role)?;
+/// Issues the bearer token every sign-in method ends with.
+fn session(user: User) -> Result<Json<LoginResponse>, AppError> {
+    let token = service::generate_jwt(user.id, user.email.as_deref(), &user.role)?;
 
     Ok(Json(LoginResponse {
         token,
@@ -34,28 +45,82 @@ pub async fn register(
     }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, description = "User registered", body = LoginResponse),
+        (status = 400, description = "Email taken", body = ErrorResponse),
+        (status = 422, description = "Invalid email or password too short", body = ErrorResponse),
+    ),
+)]
+pub async fn register(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<RegisterRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let email = service::normalize_email(&payload.email);
+    if repository::find_by_email(&state.db, &email).await?.is_some() {
+        return Err(AppError::Coded(ErrorCode::EmailTaken, "Email already registered".to_string()));
+    }
+
+    let password_hash = service::hash_password(&payload.password)?;
+    let user = repository::create_user(&state.db, &email, &password_hash, "farmer").await?;
+
+    if let Err(e) = service::send_email_verification(&state.db, &state.notifier, &user).await {
+        tracing::warn!("Failed to send verification email to user {}: {}", user.id, e);
+    }
+
+    session(user)
+}
+
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Authenticated", body = LoginResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+    ),
+)]
 pub async fn login(
     State(state): State<AppState>,
-    Json(payload): Json<LoginRequest>,
+    ValidatedJson(payload): ValidatedJson<LoginRequest>,
 ) -> Result<Json<LoginResponse>, AppError> {
-    let user = repository::find_by_email(&state.db, &payload.email)
+    let user = repository::find_by_email(&state.db, &service::normalize_email(&payload.email))
         .await?
-        .ok_or_else(|| AppError::Unauthorized("Invalid credentials".to_string()))?;
+        .ok_or_else(|| AppError::Coded(ErrorCode::InvalidCredentials, "Invalid credentials".to_string()))?;
 
     if !service::verify_password(&payload.password, &user.password_hash)? {
-        return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+        return Err(AppError::Coded(ErrorCode::InvalidCredentials, "Invalid credentials".to_string()));
     }
 
-    let token = service::generate_jwt(user.id, &user.email, &user.role)?;
+    // Migrate legacy hashes opportunistically while the plaintext is available.
+    if service::needs_rehash(&user.password_hash) {
+        match service::hash_password(&payload.password) {
+            Ok(new_hash) => {
+                if let Err(e) = repository::update_password(&state.db, user.id, &new_hash).await {
+                    tracing::warn!("Failed to rehash password for user {}: {}", user.id, e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to rehash password for user {}: {}", user.id, e),
+        }
+    }
 
-    Ok(Json(LoginResponse {
-        token,
-        user_id: user.id,
-        email: user.email,
-        role: user.role,
-    }))
+    session(user)
 }
 
+#[utoipa::path(
+    get,
+    path = "/profile",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Current user profile", body = UserProfile),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    ),
+)]
 pub async fn get_profile(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -64,10 +129,352 @@ pub async fn get_profile(
         .await?
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-    Ok(Json(UserProfile {
-        id: user.id,
-        email: user.email,
-        role: user.role,
-        created_at: user.created_at,
+    Ok(Json(service::profile(&state.db, user).await?))
+}
+
+/// The editable part of a profile, as recorded in the audit log.
+fn editable_fields(profile: &UserProfile) -> serde_json::Value {
+    serde_json::json!({
+        "full_name": profile.full_name,
+        "phone": profile.phone,
+        "province": profile.province,
+        "locale": profile.locale,
+    })
+}
+
+#[utoipa::path(
+    put,
+    path = "/profile",
+    tag = "auth",
+    request_body = UpdateProfileRequest,
+    responses(
+        (status = 200, description = "Profile updated", body = UserProfile),
+        (status = 400, description = "Invalid phone, or a verified phone changed", body = ErrorResponse),
+        (status = 422, description = "Name or province too long, or unknown locale", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    ),
+)]
+pub async fn update_profile(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<UpdateProfileRequest>,
+) -> Result<(Extension<AuditDetails>, Json<UserProfile>), AppError> {
+    let user = repository::find_by_id(&state.db, claims.sub)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    let before = service::profile(&state.db, user).await?;
+
+    let after = service::update_profile(&state.db, claims.sub, &payload).await?;
+
+    let audit = AuditDetails::new("profile.update", "user", Some(claims.sub))
+        .before(&editable_fields(&before))
+        .after(&editable_fields(&after));
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/password",
+    tag = "auth",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed; outstanding reset links stop working", body = MessageResponse),
+        (status = 400, description = "New password unchanged", body = ErrorResponse),
+        (status = 401, description = "Current password is incorrect", body = ErrorResponse),
+        (status = 422, description = "New password too short", body = ErrorResponse),
+        (status = 429, description = "Too many attempts", body = ErrorResponse),
+    ),
+)]
+pub async fn change_password(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<ChangePasswordRequest>,
+) -> Result<(Extension<AuditDetails>, Json<MessageResponse>), AppError> {
+    // A stolen session token should not turn into unlimited password guesses.
+    if !state.rate_limiter.check(
+        &format!("change-password:{}", claims.sub),
+        CHANGE_PASSWORD_ATTEMPTS_PER_HOUR,
+        Duration::from_secs(3600),
+    ) {
+        return Err(AppError::Coded(ErrorCode::RateLimited, "Too many attempts, try again later".to_string()));
+    }
+
+    service::change_password(&state.db, claims.sub, &payload.current_password, &payload.new_password).await?;
+
+    let audit = AuditDetails::new("account.change_password", "user", Some(claims.sub));
+
+    Ok((
+        Extension(audit),
+        Json(MessageResponse {
+            message: "Password has been changed".to_string(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/forgot-password",
+    tag = "auth",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 202, description = "Reset email sent if the account exists", body = MessageResponse),
+        (status = 429, description = "Too many reset requests", body = ErrorResponse),
+    ),
+)]
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<ForgotPasswordRequest>,
+) -> Result<(StatusCode, Json<MessageResponse>), AppError> {
+    let email = service::normalize_email(&payload.email);
+
+    if !state.rate_limiter.check(
+        &format!("forgot-password:{}", email),
+        runtime::current().password_reset_requests_per_hour as usize,
+        Duration::from_secs(3600),
+    ) {
+        return Err(AppError::Coded(ErrorCode::RateLimited, "Too many reset requests, try again later".to_string()));
+    }
+
+    // Respond identically whether or not the account exists to avoid leaking registered emails.
+    if let Some(user) = repository::find_by_email(&state.db, &email).await? {
+        if let Err(e) = service::send_password_reset(&state.db, &state.notifier, &user).await {
+            tracing::warn!("Failed to send password reset email to user {}: {}", user.id, e);
+        }
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(MessageResponse {
+            message: "If the email is registered, a reset link has been sent".to_string(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/reset-password",
+    tag = "auth",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password updated", body = MessageResponse),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+        (status = 422, description = "New password too short", body = ErrorResponse),
+    ),
+)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<ResetPasswordRequest>,
+) -> Result<Json<MessageResponse>, AppError> {
+    let user_id = repository::consume_token(&state.db, TokenPurpose::PasswordReset, &service::hash_token(&payload.token))
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::InvalidToken, "Invalid or expired token".to_string()))?;
+
+    let password_hash = service::hash_password(&payload.new_password)?;
+    repository::update_password(&state.db, user_id, &password_hash).await?;
+    repository::invalidate_tokens(&state.db, user_id, TokenPurpose::PasswordReset).await?;
+
+    Ok(Json(MessageResponse {
+        message: "Password has been reset".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/verify-email",
+    tag = "auth",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = 200, description = "Email verified", body = MessageResponse),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+    ),
+)]
+pub async fn verify_email(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<VerifyEmailRequest>,
+) -> Result<Json<MessageResponse>, AppError> {
+    let user_id = repository::consume_token(&state.db, TokenPurpose::EmailVerification, &service::hash_token(&payload.token))
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::InvalidToken, "Invalid or expired token".to_string()))?;
+
+    repository::mark_email_verified(&state.db, user_id).await?;
+
+    Ok(Json(MessageResponse {
+        message: "Email verified".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/account",
+    tag = "auth",
+    responses(
+        (status = 202, description = "Confirmation email sent", body = MessageResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    ),
+)]
+pub async fn request_account_deletion(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<MessageResponse>), AppError> {
+    let user = repository::find_by_id(&state.db, claims.sub)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    service::send_account_deletion_confirmation(&state.db, &state.notifier, &user).await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(MessageResponse {
+            message: "Check your email to confirm account deletion".to_string(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/account/confirm-deletion",
+    tag = "auth",
+    request_body = ConfirmAccountDeletionRequest,
+    responses(
+        (status = 200, description = "Deletion scheduled after the grace period", body = AccountDeletionResponse),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+    ),
+)]
+pub async fn confirm_account_deletion(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<ConfirmAccountDeletionRequest>,
+) -> Result<Json<AccountDeletionResponse>, AppError> {
+    let user_id = repository::consume_token(&state.db, TokenPurpose::AccountDeletion, &service::hash_token(&payload.token))
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::InvalidToken, "Invalid or expired token".to_string()))?;
+
+    let scheduled_at = chrono::Utc::now() + chrono::Duration::days(service::ACCOUNT_DELETION_GRACE_DAYS);
+    repository::schedule_deletion(&state.db, user_id, scheduled_at).await?;
+
+    tracing::info!("Account {} scheduled for deletion at {}", user_id, scheduled_at);
+
+    Ok(Json(AccountDeletionResponse {
+        deletion_scheduled_at: Some(scheduled_at),
     }))
-}
\ No newline at end of file
+}
+
+#[utoipa::path(
+    post,
+    path = "/account/cancel-deletion",
+    tag = "auth",
+    responses((status = 200, description = "Scheduled deletion cancelled", body = AccountDeletionResponse)),
+)]
+pub async fn cancel_account_deletion(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(Extension<AuditDetails>, Json<AccountDeletionResponse>), AppError> {
+    repository::cancel_deletion(&state.db, claims.sub).await?;
+
+    let audit = AuditDetails::new("account.cancel_deletion", "user", Some(claims.sub));
+
+    Ok((Extension(audit), Json(AccountDeletionResponse { deletion_scheduled_at: None })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/otp/request",
+    tag = "auth",
+    request_body = OtpRequest,
+    responses(
+        (status = 202, description = "Code sent; it expires after 5 minutes", body = MessageResponse),
+        (status = 400, description = "Phone not in international format", body = ErrorResponse),
+        (status = 429, description = "Too many codes requested for this phone or from this address", body = ErrorResponse),
+    ),
+)]
+pub async fn request_otp(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    ValidatedJson(payload): ValidatedJson<OtpRequest>,
+) -> Result<(StatusCode, Json<MessageResponse>), AppError> {
+    // Every code costs an SMS, so the limit is per number rather than per
+    // guess. Keyed on the normalized number so spelling it differently
+    // does not start a fresh count.
+    let phone = service::international_phone(&payload.phone)?;
+    if !otp_allowed(&state, "otp-request", &phone, addr, OTP_REQUESTS_PER_HOUR, OTP_REQUESTS_PER_IP_PER_HOUR) {
+        return Err(AppError::Coded(ErrorCode::RateLimited, "Too many codes requested, try again later".to_string()));
+    }
+
+    service::request_otp(&state.db, &state.notifier, &phone, payload.locale).await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(MessageResponse {
+            message: "A sign-in code has been sent".to_string(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/otp/verify",
+    tag = "auth",
+    request_body = OtpVerifyRequest,
+    responses(
+        (status = 200, description = "Authenticated; an account is created for a new number", body = LoginResponse),
+        (status = 400, description = "Invalid, expired or used-up code", body = ErrorResponse),
+        (status = 422, description = "Code is not six digits", body = ErrorResponse),
+        (status = 429, description = "Too many attempts for this phone or from this address", body = ErrorResponse),
+    ),
+)]
+pub async fn verify_otp(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    ValidatedJson(payload): ValidatedJson<OtpVerifyRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let phone = service::international_phone(&payload.phone)?;
+    if !otp_allowed(&state, "otp-verify", &phone, addr, OTP_VERIFICATIONS_PER_HOUR, OTP_VERIFICATIONS_PER_IP_PER_HOUR) {
+        return Err(AppError::Coded(ErrorCode::RateLimited, "Too many attempts, try again later".to_string()));
+    }
+
+    let user = service::verify_otp(&state.db, &phone, &payload.code, payload.locale).await?;
+    session(user)
+}
+
+#[utoipa::path(
+    get,
+    path = "/oauth/{provider}/authorize",
+    tag = "auth",
+    params(("provider" = String, Path, description = "Sign-in provider, e.g. google")),
+    responses(
+        (status = 200, description = "Provider sign-in URL; the state expires after 10 minutes", body = OAuthAuthorizeResponse),
+        (status = 400, description = "Provider not configured", body = ErrorResponse),
+        (status = 404, description = "Unknown provider", body = ErrorResponse),
+    ),
+)]
+pub async fn oauth_authorize(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> Result<Json<OAuthAuthorizeResponse>, AppError> {
+    let provider = OAuthProvider::try_from(provider)?;
+    let response = service::start_oauth(&state.db, provider).await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/oauth/{provider}/callback",
+    tag = "auth",
+    params(("provider" = String, Path, description = "Sign-in provider, e.g. google")),
+    request_body = OAuthCallbackRequest,
+    responses(
+        (status = 200, description = "Authenticated; the account is created or linked by verified email on first sign-in", body = LoginResponse),
+        (status = 400, description = "Invalid or expired state, or the provider rejected the code", body = ErrorResponse),
+        (status = 401, description = "Provider account has no verified email", body = ErrorResponse),
+    ),
+)]
+pub async fn oauth_callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    ValidatedJson(payload): ValidatedJson<OAuthCallbackRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let provider = OAuthProvider::try_from(provider)?;
+    let user = service::finish_oauth(&state.db, provider, &payload.code, &payload.state).await?;
+
+    session(user)
+}
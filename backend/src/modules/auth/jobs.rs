@@ -0,0 +1,22 @@
+use sqlx::PgPool;
+use crate::shared::worker::{interval_from_env, spawn_periodic};
+use super::repository;
+
+const ACCOUNT_PURGE_JOB_DEFAULT_SECS: u64 = 60 * 60;
+
+pub fn spawn_account_purge_job(db: PgPool) {
+    let period = interval_from_env("ACCOUNT_PURGE_JOB_INTERVAL_SECS", ACCOUNT_PURGE_JOB_DEFAULT_SECS);
+
+    spawn_periodic("account_purge", period, move || {
+        let db = db.clone();
+        async move {
+            let deleted = repository::purge_scheduled_deletions(&db).await?;
+            if deleted > 0 {
+                tracing::info!("Deleted {} accounts past their deletion grace period", deleted);
+            }
+            repository::purge_expired_oauth_states(&db).await?;
+            repository::purge_expired_otps(&db).await?;
+            Ok(())
+        }
+    });
+}
@@ -4,7 +4,7 @@ use axum::{
     middleware::Next,
     response::Response,
 };
-use crate::shared::{AppState, error::AppError};
+use crate::shared::{access_log, AppState, error::AppError};
 use super::service;
 
 pub async fn auth_middleware(
@@ -23,7 +23,8 @@ pub async fn auth_middleware(
         .ok_or_else(|| AppError::Unauthorized("Invalid authorization format".to_string()))?;
 
     let claims = service::validate_jwt(token)?;
-    
+    access_log::record_user(&req, claims.sub);
+
     req.extensions_mut().insert(claims);
     
     Ok(next.run(req).await)
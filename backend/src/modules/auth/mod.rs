@@ -3,13 +3,56 @@ pub mod repository;
 pub mod service;
 pub mod controller;
 pub mod middleware;
+pub mod jobs;
+pub mod oauth;
 
-use axum::{routing::{post, get}, Router};
+use axum::{routing::{delete, post, get, put}, Router};
+use utoipa::OpenApi;
 use crate::shared::AppState;
 
-pub fn router() -> Router<AppState> {
+/// Routes reachable without a bearer token.
+pub fn public_router() -> Router<AppState> {
     Router::new()
         .route("/register", post(controller::register))
         .route("/login", post(controller::login))
-        .route("/profile", get(controller::get_profile))
+        .route("/forgot-password", post(controller::forgot_password))
+        .route("/reset-password", post(controller::reset_password))
+        .route("/verify-email", post(controller::verify_email))
+        .route("/account/confirm-deletion", post(controller::confirm_account_deletion))
+        .route("/otp/request", post(controller::request_otp))
+        .route("/otp/verify", post(controller::verify_otp))
+        .route("/oauth/{provider}/authorize", get(controller::oauth_authorize))
+        .route("/oauth/{provider}/callback", post(controller::oauth_callback))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/profile", get(controller::get_profile).put(controller::update_profile))
+        .route("/password", put(controller::change_password))
+        .route("/account", delete(controller::request_account_deletion))
+        .route("/account/cancel-deletion", post(controller::cancel_account_deletion))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::register,
+    controller::login,
+    controller::get_profile,
+    controller::update_profile,
+    controller::change_password,
+    controller::forgot_password,
+    controller::reset_password,
+    controller::verify_email,
+    controller::request_account_deletion,
+    controller::confirm_account_deletion,
+    controller::cancel_account_deletion,
+    controller::request_otp,
+    controller::verify_otp,
+    controller::oauth_authorize,
+    controller::oauth_callback,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
 }
\ No newline at end of file
@@ -1,55 +1,188 @@
 use serde::{Deserialize, Serialize};
 use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+use crate::modules::attachments::AttachmentResponse;
+use crate::shared::i18n::Language;
+
+pub const MIN_PASSWORD_LEN: u64 = 8;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: i64,
-    pub email: String,
+    /// `None` for accounts created by phone sign-in.
+    pub email: Option<String>,
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub role: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+    pub full_name: Option<String>,
+    pub phone: Option<String>,
+    pub province: Option<String>,
+    /// Set once `phone` was confirmed with a one-time code; it then signs the user in.
+    pub phone_verified_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub token: String,
     pub user_id: i64,
-    pub email: String,
+    pub email: Option<String>,
     pub role: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 pub struct RegisterRequest {
+    #[validate(email)]
     pub email: String,
+    #[validate(length(min = MIN_PASSWORD_LEN))]
     pub password: String,
-    #[serde(default = "default_role")]
-    pub role: String,
-}
-
-fn default_role() -> String {
-    "farmer".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: i64,
-    pub email: String,
+    pub email: Option<String>,
     pub role: String,
     pub exp: usize,
 }
 
-#[derive(Debug, Serialize)]
+impl Claims {
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserProfile {
     pub id: i64,
-    pub email: String,
+    pub email: Option<String>,
     pub role: String,
+    pub email_verified: bool,
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
-}
\ No newline at end of file
+    pub full_name: Option<String>,
+    pub phone: Option<String>,
+    /// Whether `phone` can be used to sign in.
+    pub phone_verified: bool,
+    pub province: Option<String>,
+    /// Same as the `language` preference.
+    pub locale: Language,
+    /// Profile picture with signed links, if one was uploaded.
+    pub avatar: Option<AttachmentResponse>,
+}
+
+/// Partial update; omitted fields are left unchanged and an empty string
+/// clears `full_name`, `phone` or `province`.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateProfileRequest {
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub full_name: Option<String>,
+    /// Digits with an optional leading `+`; spaces, dashes and dots are ignored.
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub province: Option<String>,
+    #[serde(default)]
+    pub locale: Option<Language>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    #[validate(length(min = MIN_PASSWORD_LEN))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TokenPurpose {
+    PasswordReset,
+    EmailVerification,
+    AccountDeletion,
+}
+
+impl TokenPurpose {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TokenPurpose::PasswordReset => "password_reset",
+            TokenPurpose::EmailVerification => "email_verification",
+            TokenPurpose::AccountDeletion => "account_deletion",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    #[validate(length(min = MIN_PASSWORD_LEN))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct ConfirmAccountDeletionRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountDeletionResponse {
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageResponse {
+    pub message: String,
+}
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct OtpRequest {
+    /// International format, e.g. `+84901234567`.
+    pub phone: String,
+    /// Language of the text message when the number is not yet registered.
+    #[serde(default)]
+    pub locale: Option<Language>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct OtpVerifyRequest {
+    pub phone: String,
+    #[validate(length(equal = 6))]
+    pub code: String,
+    /// Language preference of the account, if this sign-in creates one.
+    #[serde(default)]
+    pub locale: Option<Language>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthAuthorizeResponse {
+    /// Provider sign-in page to send the browser to.
+    pub authorization_url: String,
+    /// Echoed back by the provider; pass it to the callback unchanged.
+    pub state: String,
+}
+
+/// Query parameters the provider appended to the redirect URI.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct OAuthCallbackRequest {
+    pub code: String,
+    pub state: String,
+}
@@ -0,0 +1,164 @@
+//! OAuth 2.0 authorization-code sign-in with PKCE. Providers differ only in
+//! endpoints and credentials; what they say about the user is reduced to an
+//! `ExternalIdentity`.
+
+use std::sync::LazyLock;
+use std::time::Duration;
+use base64::Engine;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use crate::shared::{config::{self, OAuthCredentials}, error::{AppError, AppResult}};
+
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OAuthProvider {
+    Google,
+}
+
+struct Endpoints {
+    authorize: &'static str,
+    token: &'static str,
+    userinfo: &'static str,
+    scope: &'static str,
+}
+
+impl OAuthProvider {
+    pub fn as_str(&self) -> &str {
+        match self {
+            OAuthProvider::Google => "google",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "google" => Some(OAuthProvider::Google),
+            _ => None,
+        }
+    }
+
+    fn endpoints(&self) -> Endpoints {
+        match self {
+            OAuthProvider::Google => Endpoints {
+                authorize: "https://accounts.google.com/o/oauth2/v2/auth",
+                token: "https://oauth2.googleapis.com/token",
+                userinfo: "https://openidconnect.googleapis.com/v1/userinfo",
+                scope: "openid email",
+            },
+        }
+    }
+
+    fn credentials(&self) -> AppResult<&'static OAuthCredentials> {
+        let credentials = match self {
+            OAuthProvider::Google => config::get().google_oauth.as_ref(),
+        };
+        credentials.ok_or_else(|| AppError::BadRequest(format!("Sign-in with {} is not enabled", self.as_str())))
+    }
+
+    /// Frontend page the provider sends the user back to; it posts the code
+    /// and state to the callback endpoint.
+    pub fn redirect_uri(&self) -> String {
+        format!("{}/oauth/{}/callback", config::get().app_base_url.trim_end_matches('/'), self.as_str())
+    }
+}
+
+impl TryFrom<String> for OAuthProvider {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        OAuthProvider::from_code(&value).ok_or_else(|| AppError::NotFound(format!("Unknown sign-in provider: {}", value)))
+    }
+}
+
+/// The user as the provider knows them.
+#[derive(Debug, Deserialize)]
+pub struct ExternalIdentity {
+    #[serde(rename = "sub")]
+    pub subject: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+}
+
+/// S256 code challenge for a PKCE verifier.
+pub fn code_challenge(verifier: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Where to send the browser to start signing in.
+pub fn authorization_url(provider: OAuthProvider, state: &str, verifier: &str) -> AppResult<String> {
+    let credentials = provider.credentials()?;
+    let endpoints = provider.endpoints();
+    let redirect_uri = provider.redirect_uri();
+    let challenge = code_challenge(verifier);
+
+    reqwest::Url::parse_with_params(
+        endpoints.authorize,
+        [
+            ("response_type", "code"),
+            ("client_id", credentials.client_id.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("scope", endpoints.scope),
+            ("state", state),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map(String::from)
+    .map_err(|e| AppError::Internal(format!("Invalid authorization URL: {}", e)))
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Trades the authorization code for an access token, then asks the provider
+/// who it belongs to.
+pub async fn fetch_identity(provider: OAuthProvider, code: &str, verifier: &str) -> AppResult<ExternalIdentity> {
+    let credentials = provider.credentials()?;
+    let endpoints = provider.endpoints();
+    let redirect_uri = provider.redirect_uri();
+
+    let response = HTTP
+        .post(endpoints.token)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+            ("code_verifier", verifier),
+        ])
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(format!("{} token request failed: {}", provider.as_str(), e)))?;
+
+    if response.status().is_client_error() {
+        return Err(AppError::BadRequest(format!(
+            "{} rejected the authorization code",
+            provider.as_str()
+        )));
+    }
+    let token: TokenResponse = response
+        .error_for_status()
+        .map_err(|e| AppError::Internal(format!("{} token request failed: {}", provider.as_str(), e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::Internal(format!("Invalid {} token response: {}", provider.as_str(), e)))?;
+
+    HTTP.get(endpoints.userinfo)
+        .bearer_auth(&token.access_token)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| AppError::Internal(format!("{} user info request failed: {}", provider.as_str(), e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::Internal(format!("Invalid {} user info: {}", provider.as_str(), e)))
+}
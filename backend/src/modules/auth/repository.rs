@@ -1,6 +1,7 @@
 use sqlx::PgPool;
+use sqlx::types::chrono::{DateTime, Utc};
 use crate::shared::error::AppError;
-use super::models::User;
+use super::models::{TokenPurpose, User};
 
 pub async fn create_user(
     pool: &PgPool,
@@ -21,7 +22,7 @@ pub async fn create_user(
 }
 
 pub async fn find_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, AppError> {
-    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = LOWER($1)")
         .bind(email)
         .fetch_optional(pool)
         .await?;
@@ -29,6 +30,28 @@ pub async fn find_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, A
     Ok(user)
 }
 
+pub async fn find_by_verified_phone(pool: &PgPool, phone: &str) -> Result<Option<User>, AppError> {
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE phone = $1 AND phone_verified_at IS NOT NULL")
+        .bind(phone)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(user)
+}
+
+/// Creates an account without an email, signed into by `phone` alone.
+pub async fn create_phone_user(pool: &PgPool, phone: &str, password_hash: &str) -> Result<User, AppError> {
+    let user = sqlx::query_as::<_, User>(
+        "INSERT INTO users (phone, phone_verified_at, password_hash) VALUES ($1, NOW(), $2) RETURNING *"
+    )
+    .bind(phone)
+    .bind(password_hash)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(user)
+}
+
 pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<User>, AppError> {
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
         .bind(id)
@@ -37,3 +60,314 @@ pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<User>, AppError
 
     Ok(user)
 }
+
+pub async fn update_password(pool: &PgPool, user_id: i64, password_hash: &str) -> Result<(), AppError> {
+    sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(password_hash)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the contact details; `None` leaves a column unchanged and an empty
+/// string clears it.
+pub async fn update_profile(
+    pool: &PgPool,
+    user_id: i64,
+    full_name: Option<&str>,
+    phone: Option<&str>,
+    province: Option<&str>,
+) -> Result<Option<User>, AppError> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET full_name = CASE WHEN $2::VARCHAR IS NULL THEN full_name ELSE NULLIF($2, '') END,
+            phone = CASE WHEN $3::VARCHAR IS NULL THEN phone ELSE NULLIF($3, '') END,
+            province = CASE WHEN $4::VARCHAR IS NULL THEN province ELSE NULLIF($4, '') END
+        WHERE id = $1
+        RETURNING *
+        "#
+    )
+    .bind(user_id)
+    .bind(full_name)
+    .bind(phone)
+    .bind(province)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user)
+}
+
+pub async fn mark_email_verified(pool: &PgPool, user_id: i64) -> Result<(), AppError> {
+    sqlx::query("UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn schedule_deletion(pool: &PgPool, user_id: i64, at: DateTime<Utc>) -> Result<(), AppError> {
+    sqlx::query("UPDATE users SET deletion_scheduled_at = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn cancel_deletion(pool: &PgPool, user_id: i64) -> Result<(), AppError> {
+    sqlx::query("UPDATE users SET deletion_scheduled_at = NULL WHERE id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes accounts whose grace period has ended. Owned data goes with them
+/// through `ON DELETE CASCADE`; audit entries keep a NULL user.
+pub async fn purge_scheduled_deletions(pool: &PgPool) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM users WHERE deletion_scheduled_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn create_token(
+    pool: &PgPool,
+    user_id: i64,
+    purpose: TokenPurpose,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO auth_tokens (user_id, purpose, token_hash, expires_at) VALUES ($1, $2, $3, $4)"
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .bind(token_hash)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Atomically marks an unexpired, unused token as used and returns its owner.
+pub async fn consume_token(
+    pool: &PgPool,
+    purpose: TokenPurpose,
+    token_hash: &str,
+) -> Result<Option<i64>, AppError> {
+    let user_id = sqlx::query_scalar(
+        r#"
+        UPDATE auth_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > NOW()
+        RETURNING user_id
+        "#
+    )
+    .bind(token_hash)
+    .bind(purpose.as_str())
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user_id)
+}
+
+pub async fn invalidate_tokens(pool: &PgPool, user_id: i64, purpose: TokenPurpose) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE auth_tokens SET used_at = NOW() WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL"
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Records a new code for `phone`, retiring any earlier one.
+pub async fn create_otp(pool: &PgPool, phone: &str, code_hash: &str, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE phone_otps SET consumed_at = NOW() WHERE phone = $1 AND consumed_at IS NULL")
+        .bind(phone)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT INTO phone_otps (phone, code_hash, expires_at) VALUES ($1, $2, $3)")
+        .bind(phone)
+        .bind(code_hash)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Counts a guess against the live code of `phone` and returns its id and
+/// digest, or `None` when there is no live code or its guesses are used up.
+pub async fn take_otp_attempt(pool: &PgPool, phone: &str, max_attempts: i32) -> Result<Option<(i64, String)>, AppError> {
+    let otp = sqlx::query_as(
+        r#"
+        UPDATE phone_otps SET attempts = attempts + 1
+        WHERE id = (
+            SELECT id FROM phone_otps
+            WHERE phone = $1 AND consumed_at IS NULL AND expires_at > NOW()
+            ORDER BY created_at DESC
+            LIMIT 1
+        )
+          AND attempts < $2
+        RETURNING id, code_hash
+        "#
+    )
+    .bind(phone)
+    .bind(max_attempts)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(otp)
+}
+
+/// Marks the code used; `false` if a concurrent request got there first.
+pub async fn consume_otp(pool: &PgPool, id: i64) -> Result<bool, AppError> {
+    let result = sqlx::query("UPDATE phone_otps SET consumed_at = NOW() WHERE id = $1 AND consumed_at IS NULL")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn purge_expired_otps(pool: &PgPool) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM phone_otps WHERE expires_at < NOW() - INTERVAL '1 day'")
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn save_oauth_state(
+    pool: &PgPool,
+    state_hash: &str,
+    provider: &str,
+    code_verifier: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO oauth_states (state_hash, provider, code_verifier, expires_at) VALUES ($1, $2, $3, $4)"
+    )
+    .bind(state_hash)
+    .bind(provider)
+    .bind(code_verifier)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Deletes an unexpired state so it cannot be replayed and returns its PKCE verifier.
+pub async fn consume_oauth_state(pool: &PgPool, state_hash: &str, provider: &str) -> Result<Option<String>, AppError> {
+    let verifier = sqlx::query_scalar(
+        "DELETE FROM oauth_states WHERE state_hash = $1 AND provider = $2 AND expires_at > NOW() RETURNING code_verifier"
+    )
+    .bind(state_hash)
+    .bind(provider)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(verifier)
+}
+
+pub async fn purge_expired_oauth_states(pool: &PgPool) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM oauth_states WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn find_by_identity(pool: &PgPool, provider: &str, subject: &str) -> Result<Option<User>, AppError> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        SELECT u.* FROM users u
+        JOIN user_identities i ON i.user_id = u.id
+        WHERE i.provider = $1 AND i.subject = $2
+        "#
+    )
+    .bind(provider)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user)
+}
+
+/// Hands an account whose email was never verified to whoever just proved
+/// they own the address: the password and every other way in are replaced,
+/// since they may have been set up by someone else. Does nothing once the
+/// email is verified.
+pub async fn reclaim_unverified(pool: &PgPool, user_id: i64, password_hash: &str) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    let reclaimed = sqlx::query(
+        r#"
+        UPDATE users
+        SET password_hash = $2, phone_verified_at = NULL, email_verified_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND email_verified_at IS NULL
+        "#
+    )
+    .bind(user_id)
+    .bind(password_hash)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if reclaimed {
+        sqlx::query("DELETE FROM user_identities WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE auth_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Links the identity to `user_id`, or records another sign-in if it already is.
+pub async fn link_identity(
+    pool: &PgPool,
+    user_id: i64,
+    provider: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_identities (user_id, provider, subject, email)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (provider, subject) DO UPDATE
+        SET email = EXCLUDED.email, last_login_at = NOW()
+        "#
+    )
+    .bind(user_id)
+    .bind(provider)
+    .bind(subject)
+    .bind(email)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,34 +1,67 @@
 use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    password_hash::{rand_core::{OsRng, RngCore}, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
 };
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use crate::shared::error::AppError;
-use super::models::Claims;
+use crate::shared::{
+    config,
+    error::{AppError, ErrorCode},
+    i18n::{self, t, Language},
+    notifications::{NotificationDispatcher, email::EmailMessage, sms::SmsMessage},
+};
+use crate::modules::{attachments, settings};
+use super::models::{Claims, OAuthAuthorizeResponse, TokenPurpose, UpdateProfileRequest, User, UserProfile};
+use super::oauth::{self, OAuthProvider};
+use super::repository;
 use std::sync::LazyLock;
 
-static JWT_SECRET: LazyLock<String> = LazyLock::new(|| {
-    std::env::var("JWT_SECRET").expect("JWT_SECRET environment variable not set")
-});
+const PASSWORD_RESET_TTL_HOURS: i64 = 1;
+const EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
+const ACCOUNT_DELETION_TTL_HOURS: i64 = 24;
+pub const ACCOUNT_DELETION_GRACE_DAYS: i64 = 14;
+const OAUTH_STATE_TTL_MINUTES: i64 = 10;
+const OTP_TTL_MINUTES: i64 = 5;
+/// Wrong guesses allowed per code before a new one must be requested.
+const OTP_MAX_ATTEMPTS: i32 = 5;
+const MIN_PHONE_DIGITS: usize = 8;
+const MAX_PHONE_DIGITS: usize = 15;
 
 static JWT_ENCODING_KEY: LazyLock<EncodingKey> = LazyLock::new(|| {
-    EncodingKey::from_secret(JWT_SECRET.as_bytes())
+    EncodingKey::from_secret(config::get().jwt_secret.as_bytes())
 });
 
 static JWT_DECODING_KEY: LazyLock<DecodingKey> = LazyLock::new(|| {
-    DecodingKey::from_secret(JWT_SECRET.as_bytes())
+    DecodingKey::from_secret(config::get().jwt_secret.as_bytes())
 });
 
+// Current password hashing parameters (OWASP Argon2id baseline: 19 MiB, 2 passes, 1 lane).
+// Bumping any of these makes existing hashes eligible for rehash on the next successful login.
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+
+fn password_hasher() -> Result<Argon2<'static>, AppError> {
+    let params = Params::new(ARGON2_MEMORY_KIB, ARGON2_ITERATIONS, ARGON2_PARALLELISM, None)
+        .map_err(|e| AppError::Internal(format!("Invalid Argon2 parameters: {}", e)))?;
+
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+/// Hashes with a fresh per-user salt into a PHC string, which records algorithm,
+/// version and cost parameters alongside the hash.
 pub fn hash_password(password: &str) -> Result<String, AppError> {
     let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    
-    argon2
+
+    password_hasher()?
         .hash_password(password.as_bytes(), &salt)
         .map(|h| h.to_string())
         .map_err(|e| AppError::Internal(format!("Password hashing failed: {}", e)))
 }
 
+/// Verifies against whatever Argon2 variant and parameters the stored hash was created with.
 pub fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
     let parsed_hash = PasswordHash::new(hash)
         .map_err(|e| AppError::Internal(format!("Invalid password hash: {}", e)))?;
@@ -38,7 +71,201 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
         .is_ok())
 }
 
-pub fn generate_jwt(user_id: i64, email: &str, role: &str) -> Result<String, AppError> {
+/// True when the stored hash predates the current algorithm, version or cost parameters.
+pub fn needs_rehash(hash: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return true;
+    };
+
+    if parsed.algorithm != Algorithm::Argon2id.ident() || parsed.version != Some(Version::V0x13 as u32) {
+        return true;
+    }
+
+    match Params::try_from(&parsed) {
+        Ok(params) => {
+            params.m_cost() != ARGON2_MEMORY_KIB
+                || params.t_cost() != ARGON2_ITERATIONS
+                || params.p_cost() != ARGON2_PARALLELISM
+        }
+        Err(_) => true,
+    }
+}
+
+/// The profile of `user` as shown to themselves, with locale and avatar.
+pub async fn profile(db: &PgPool, user: User) -> Result<UserProfile, AppError> {
+    let preferences = settings::service::preferences(db, user.id).await?;
+    let avatar = attachments::profile_avatar(db, user.id).await?;
+
+    Ok(UserProfile {
+        id: user.id,
+        email: user.email,
+        role: user.role,
+        email_verified: user.email_verified_at.is_some(),
+        deletion_scheduled_at: user.deletion_scheduled_at,
+        created_at: user.created_at,
+        full_name: user.full_name,
+        phone_verified: user.phone_verified_at.is_some(),
+        phone: user.phone,
+        province: user.province,
+        locale: preferences.language,
+        avatar,
+    })
+}
+
+/// Trims a free-text profile field; an empty result means "clear it".
+fn clean_text(field: &str, value: &str) -> Result<String, AppError> {
+    let value = value.trim();
+    if value.chars().any(char::is_control) {
+        return Err(AppError::Validation(format!("{} must not contain control characters", field)));
+    }
+    Ok(value.to_string())
+}
+
+/// Strips separators so numbers are stored as `+84901234567` or `0901234567`.
+fn clean_phone(value: &str) -> Result<String, AppError> {
+    let value = value.trim();
+    let (plus, rest) = match value.strip_prefix('+') {
+        Some(rest) => ("+", rest),
+        None => ("", value),
+    };
+    let digits: String = rest.chars().filter(|c| !matches!(c, ' ' | '-' | '.')).collect();
+
+    if digits.is_empty() && plus.is_empty() {
+        return Ok(String::new());
+    }
+    if !digits.chars().all(|c| c.is_ascii_digit()) || !(MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits.len()) {
+        return Err(AppError::Validation(format!(
+            "phone must be {} to {} digits, optionally starting with +",
+            MIN_PHONE_DIGITS, MAX_PHONE_DIGITS
+        )));
+    }
+    Ok(format!("{}{}", plus, digits))
+}
+
+/// An email address as accounts are stored and looked up by.
+pub fn normalize_email(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+/// A phone number in international format, as codes are sent and accounts
+/// keyed by it.
+pub fn international_phone(value: &str) -> Result<String, AppError> {
+    let phone = clean_phone(value)?;
+    if !phone.starts_with('+') {
+        return Err(AppError::Validation("phone must be in international format, e.g. +84901234567".to_string()));
+    }
+    Ok(phone)
+}
+
+/// Validates and applies a profile update, returning the updated profile.
+pub async fn update_profile(db: &PgPool, user_id: i64, changes: &UpdateProfileRequest) -> Result<UserProfile, AppError> {
+    let current = repository::find_by_id(db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let full_name = changes
+        .full_name
+        .as_deref()
+        .map(|name| clean_text("full_name", name))
+        .transpose()?;
+    let phone = changes.phone.as_deref().map(clean_phone).transpose()?;
+    if current.phone_verified_at.is_some() && phone.is_some() && phone != current.phone {
+        return Err(AppError::Validation("phone is used to sign in and cannot be changed from the profile".to_string()));
+    }
+    let province = changes
+        .province
+        .as_deref()
+        .map(|province| clean_text("province", province))
+        .transpose()?;
+
+    let user = repository::update_profile(db, user_id, full_name.as_deref(), phone.as_deref(), province.as_deref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    if let Some(locale) = changes.locale {
+        settings::service::set_language(db, user_id, locale).await?;
+    }
+
+    profile(db, user).await
+}
+
+/// Replaces the password after checking the current one, and voids any
+/// outstanding reset links.
+pub async fn change_password(db: &PgPool, user_id: i64, current_password: &str, new_password: &str) -> Result<(), AppError> {
+    let user = repository::find_by_id(db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    if !verify_password(current_password, &user.password_hash)? {
+        return Err(AppError::Coded(ErrorCode::InvalidCredentials, "Current password is incorrect".to_string()));
+    }
+    if current_password == new_password {
+        return Err(AppError::BadRequest("New password must differ from the current one".to_string()));
+    }
+
+    let password_hash = hash_password(new_password)?;
+    repository::update_password(db, user_id, &password_hash).await?;
+    repository::invalidate_tokens(db, user_id, TokenPurpose::PasswordReset).await?;
+    Ok(())
+}
+
+/// Sends a fresh sign-in code to `phone`. Unknown numbers get one too: the
+/// account is created when the code is verified.
+pub async fn request_otp(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    phone: &str,
+    locale: Option<Language>,
+) -> Result<(), AppError> {
+    let phone = international_phone(phone)?;
+
+    let code = format!("{:06}", OsRng.next_u32() % 1_000_000);
+    let expires_at = chrono::Utc::now() + chrono::Duration::minutes(OTP_TTL_MINUTES);
+    repository::create_otp(db, &phone, &otp_hash(&phone, &code), expires_at).await?;
+
+    let lang = match repository::find_by_verified_phone(db, &phone).await? {
+        Some(user) => i18n::language_for_user(db, user.id).await?,
+        None => locale.unwrap_or_default(),
+    };
+    notifier
+        .send_sms(SmsMessage {
+            to: phone,
+            body: t(lang, "sms.otp", &[("code", code), ("minutes", OTP_TTL_MINUTES.to_string())]),
+        })
+        .await
+}
+
+/// Six digits are guessable offline, so the phone salts the digest.
+fn otp_hash(phone: &str, code: &str) -> String {
+    hash_token(&format!("{}:{}", phone, code))
+}
+
+/// Checks a sign-in code and returns the account of `phone`, creating one
+/// on first sign-in.
+pub async fn verify_otp(db: &PgPool, phone: &str, code: &str, locale: Option<Language>) -> Result<User, AppError> {
+    let phone = international_phone(phone)?;
+    let invalid = || AppError::Coded(ErrorCode::InvalidToken, "Invalid or expired code".to_string());
+
+    let (id, code_hash) = repository::take_otp_attempt(db, &phone, OTP_MAX_ATTEMPTS)
+        .await?
+        .ok_or_else(invalid)?;
+    if otp_hash(&phone, code.trim()) != code_hash || !repository::consume_otp(db, id).await? {
+        return Err(invalid());
+    }
+
+    if let Some(user) = repository::find_by_verified_phone(db, &phone).await? {
+        return Ok(user);
+    }
+
+    // As with OAuth sign-up, a random password keeps password login closed.
+    let (password, _) = generate_token();
+    let user = repository::create_phone_user(db, &phone, &hash_password(&password)?).await?;
+    if let Some(locale) = locale {
+        settings::service::set_language(db, user.id, locale).await?;
+    }
+    tracing::info!("Created user {} by phone sign-in", user.id);
+    Ok(user)
+}
+
+pub fn generate_jwt(user_id: i64, email: Option<&str>, role: &str) -> Result<String, AppError> {
     let expiration = chrono::Utc::now()
         .checked_add_signed(chrono::Duration::hours(24))
         .ok_or_else(|| AppError::Internal("Failed to calculate expiration".to_string()))?
@@ -46,7 +273,7 @@ pub fn generate_jwt(user_id: i64, email: &str, role: &str) -> Result<String, App
 
     let claims = Claims {
         sub: user_id,
-        email: email.to_string(),
+        email: email.map(str::to_string),
         role: role.to_string(),
         exp: expiration,
     };
@@ -59,4 +286,183 @@ pub fn validate_jwt(token: &str) -> Result<Claims, AppError> {
     decode::<Claims>(token, &JWT_DECODING_KEY, &Validation::default())
         .map(|data| data.claims)
         .map_err(|e| AppError::Unauthorized(format!("Invalid token: {}", e)))
-}
\ No newline at end of file
+}
+
+/// Returns a random URL-safe token and the SHA-256 hex digest to persist.
+pub fn generate_token() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn email_address(user: &User) -> Result<String, AppError> {
+    user.email
+        .clone()
+        .ok_or_else(|| AppError::BadRequest("Account has no email address".to_string()))
+}
+
+pub async fn send_password_reset(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    user: &User,
+) -> Result<(), AppError> {
+    let to = email_address(user)?;
+    repository::invalidate_tokens(db, user.id, TokenPurpose::PasswordReset).await?;
+
+    let (token, token_hash) = generate_token();
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(PASSWORD_RESET_TTL_HOURS);
+    repository::create_token(db, user.id, TokenPurpose::PasswordReset, &token_hash, expires_at).await?;
+
+    let lang = i18n::language_for_user(db, user.id).await?;
+    notifier
+        .send_email(EmailMessage {
+            to,
+            subject: t(lang, "email.password_reset.subject", &[]),
+            body: t(lang, "email.password_reset.body", &[
+                ("hours", PASSWORD_RESET_TTL_HOURS.to_string()),
+                ("link", format!("{}/reset-password?token={}", config::get().app_base_url, token)),
+            ]),
+            html: None,
+        })
+        .await
+}
+
+pub async fn send_email_verification(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    user: &User,
+) -> Result<(), AppError> {
+    let to = email_address(user)?;
+    let (token, token_hash) = generate_token();
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(EMAIL_VERIFICATION_TTL_HOURS);
+    repository::create_token(db, user.id, TokenPurpose::EmailVerification, &token_hash, expires_at).await?;
+
+    let lang = i18n::language_for_user(db, user.id).await?;
+    notifier
+        .send_email(EmailMessage {
+            to,
+            subject: t(lang, "email.verify.subject", &[]),
+            body: t(lang, "email.verify.body", &[
+                ("link", format!("{}/verify-email?token={}", config::get().app_base_url, token)),
+            ]),
+            html: None,
+        })
+        .await
+}
+
+pub async fn send_account_deletion_confirmation(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    user: &User,
+) -> Result<(), AppError> {
+    repository::invalidate_tokens(db, user.id, TokenPurpose::AccountDeletion).await?;
+
+    let (token, token_hash) = generate_token();
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(ACCOUNT_DELETION_TTL_HOURS);
+    repository::create_token(db, user.id, TokenPurpose::AccountDeletion, &token_hash, expires_at).await?;
+
+    let lang = i18n::language_for_user(db, user.id).await?;
+    let link = format!("{}/confirm-account-deletion?token={}", config::get().app_base_url, token);
+
+    // Phone-only accounts get the link by text message.
+    match (&user.email, &user.phone, user.phone_verified_at) {
+        (Some(email), _, _) => {
+            notifier
+                .send_email(EmailMessage {
+                    to: email.clone(),
+                    subject: t(lang, "email.account_deletion.subject", &[]),
+                    body: t(lang, "email.account_deletion.body", &[
+                        ("days", ACCOUNT_DELETION_GRACE_DAYS.to_string()),
+                        ("link", link),
+                    ]),
+                    html: None,
+                })
+                .await
+        }
+        (None, Some(phone), Some(_)) => {
+            notifier
+                .send_sms(SmsMessage {
+                    to: phone.clone(),
+                    body: t(lang, "sms.account_deletion", &[
+                        ("days", ACCOUNT_DELETION_GRACE_DAYS.to_string()),
+                        ("link", link),
+                    ]),
+                })
+                .await
+        }
+        _ => Err(AppError::BadRequest("Account has no email address or verified phone".to_string())),
+    }
+}
+
+/// Records a fresh state and PKCE verifier and returns where to send the user.
+pub async fn start_oauth(db: &PgPool, provider: OAuthProvider) -> Result<OAuthAuthorizeResponse, AppError> {
+    let (state, state_hash) = generate_token();
+    let (verifier, _) = generate_token();
+    let authorization_url = oauth::authorization_url(provider, &state, &verifier)?;
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::minutes(OAUTH_STATE_TTL_MINUTES);
+    repository::save_oauth_state(db, &state_hash, provider.as_str(), &verifier, expires_at).await?;
+
+    Ok(OAuthAuthorizeResponse { authorization_url, state })
+}
+
+/// Completes the code exchange and returns the local user, creating one or
+/// linking an existing one by verified email on first sign-in.
+pub async fn finish_oauth(db: &PgPool, provider: OAuthProvider, code: &str, state: &str) -> Result<User, AppError> {
+    let verifier = repository::consume_oauth_state(db, &hash_token(state), provider.as_str())
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::InvalidToken, "Invalid or expired sign-in state".to_string()))?;
+
+    let identity = oauth::fetch_identity(provider, code, &verifier).await?;
+
+    if let Some(user) = repository::find_by_identity(db, provider.as_str(), &identity.subject).await? {
+        repository::link_identity(db, user.id, provider.as_str(), &identity.subject, identity.email.as_deref()).await?;
+        return Ok(user);
+    }
+
+    // Linking by email hands over the local account, so only an address the
+    // provider has verified will do.
+    let email = identity
+        .email
+        .as_deref()
+        .map(normalize_email)
+        .filter(|_| identity.email_verified)
+        .ok_or_else(|| {
+            AppError::Coded(
+                ErrorCode::InvalidCredentials,
+                format!("Your {} account has no verified email address", provider.as_str()),
+            )
+        })?;
+
+    let user = match repository::find_by_email(db, &email).await? {
+        Some(user) if user.email_verified_at.is_some() => {
+            tracing::info!("Linking {} identity to existing user {}", provider.as_str(), user.id);
+            user
+        }
+        Some(user) => {
+            // Anyone could have registered this address with a password of
+            // their choosing; the provider's verified owner takes it over.
+            tracing::warn!("Reclaiming unverified user {} for its {} identity", user.id, provider.as_str());
+            let (password, _) = generate_token();
+            repository::reclaim_unverified(db, user.id, &hash_password(&password)?).await?;
+            user
+        }
+        None => {
+            // No password was chosen; a random one keeps password login closed
+            // until the user sets one through the reset flow.
+            let (password, _) = generate_token();
+            repository::create_user(db, &email, &hash_password(&password)?, "farmer").await?
+        }
+    };
+
+    repository::mark_email_verified(db, user.id).await?;
+    repository::link_identity(db, user.id, provider.as_str(), &identity.subject, Some(&email)).await?;
+
+    Ok(user)
+}
@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use crate::shared::{AppState, audit::AuditDetails, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{AlertComment, CreateCommentRequest, UpdateCommentRequest},
+    service::{self, Commenter},
+};
+
+fn commenter(claims: &Claims) -> Commenter {
+    Commenter { user_id: claims.sub, is_admin: claims.is_admin() }
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts/{alert_id}/comments",
+    tag = "comments",
+    params(("alert_id" = i64, Path, description = "Alert id")),
+    responses(
+        (status = 200, description = "Comments on the alert, oldest first; replies reference their parent", body = [AlertComment]),
+        (status = 404, description = "Alert not found", body = ErrorResponse),
+    ),
+)]
+pub async fn list_comments(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(alert_id): Path<i64>,
+) -> Result<Json<Vec<AlertComment>>, AppError> {
+    let comments = service::list(&state.db, commenter(&claims), alert_id).await?;
+    Ok(Json(comments))
+}
+
+#[utoipa::path(
+    post,
+    path = "/alerts/{alert_id}/comments",
+    tag = "comments",
+    params(("alert_id" = i64, Path, description = "Alert id")),
+    request_body = CreateCommentRequest,
+    responses(
+        (status = 201, description = "Comment added; mentioned users are notified", body = AlertComment),
+        (status = 400, description = "Parent comment is on another alert", body = ErrorResponse),
+        (status = 422, description = "Empty or oversized body", body = ErrorResponse),
+        (status = 404, description = "Alert or parent comment not found", body = ErrorResponse),
+    ),
+)]
+pub async fn create_comment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(alert_id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<CreateCommentRequest>,
+) -> Result<(StatusCode, Extension<AuditDetails>, Json<AlertComment>), AppError> {
+    let comment = service::create(&state.db, &state.notifier, commenter(&claims), alert_id, payload).await?;
+    let audit = AuditDetails::new("comment.create", "alert_comment", Some(comment.id)).after(&comment);
+
+    Ok((StatusCode::CREATED, Extension(audit), Json(comment)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = "comments",
+    params(("id" = i64, Path, description = "Comment id")),
+    request_body = UpdateCommentRequest,
+    responses(
+        (status = 200, description = "Comment edited; newly mentioned users are notified", body = AlertComment),
+        (status = 401, description = "Caller is not the author", body = ErrorResponse),
+        (status = 404, description = "Comment not found", body = ErrorResponse),
+        (status = 422, description = "Empty or oversized body", body = ErrorResponse),
+    ),
+)]
+pub async fn update_comment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<UpdateCommentRequest>,
+) -> Result<(Extension<AuditDetails>, Json<AlertComment>), AppError> {
+    let (before, after) = service::update(&state.db, &state.notifier, commenter(&claims), id, payload).await?;
+    let audit = AuditDetails::new("comment.update", "alert_comment", Some(id))
+        .before(&before)
+        .after(&after);
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "comments",
+    params(("id" = i64, Path, description = "Comment id")),
+    responses(
+        (status = 200, description = "Comment deleted; its replies remain"),
+        (status = 401, description = "Caller is neither the author nor an admin", body = ErrorResponse),
+        (status = 404, description = "Comment not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_comment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    let before = service::delete(&state.db, commenter(&claims), id).await?;
+    let audit = AuditDetails::new("comment.delete", "alert_comment", Some(id)).before(&before);
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
@@ -0,0 +1,43 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+
+use axum::{routing::{get, put}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+/// Editing and deleting comments, mounted under `/api/comments`.
+pub fn router() -> Router<AppState> {
+    Router::new().route("/{id}", put(controller::update_comment).delete(controller::delete_comment))
+}
+
+/// Alert threads, mounted alongside the monitoring routes.
+pub fn alert_router() -> Router<AppState> {
+    Router::new().route(
+        "/alerts/{alert_id}/comments",
+        get(controller::list_comments).post(controller::create_comment),
+    )
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::update_comment,
+    controller::delete_comment,
+))]
+struct ApiDoc;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::list_comments,
+    controller::create_comment,
+))]
+struct AlertApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+
+pub fn alert_openapi() -> utoipa::openapi::OpenApi {
+    AlertApiDoc::openapi()
+}
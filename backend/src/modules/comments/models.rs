@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::Validate;
+use crate::shared::validation::not_blank;
+
+pub const MAX_COMMENT_CHARS: u64 = 5000;
+
+/// A user named in a comment with `@email`.
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct CommentMention {
+    pub user_id: i64,
+    pub email: String,
+}
+
+/// A note on an alert. Replies carry the id of the comment they answer in
+/// `parent_id`; deleted comments stay in the list, without a body, so their
+/// replies keep their place in the thread.
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct AlertComment {
+    pub id: i64,
+    pub alert_id: i64,
+    pub parent_id: Option<i64>,
+    /// Author; `null` once their account is deleted.
+    pub user_id: Option<i64>,
+    pub author_email: Option<String>,
+    pub body: Option<String>,
+    #[sqlx(skip)]
+    pub mentions: Vec<CommentMention>,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateCommentRequest {
+    /// Plain text; `@email` mentions of the farm owner or an admin notify them.
+    #[validate(custom(function = "not_blank"), length(max = MAX_COMMENT_CHARS))]
+    pub body: String,
+    /// The comment being answered.
+    pub parent_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateCommentRequest {
+    #[validate(custom(function = "not_blank"), length(max = MAX_COMMENT_CHARS))]
+    pub body: String,
+}
+
+/// The farm an alert belongs to and who owns it.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AlertContext {
+    pub farm_id: i64,
+    pub owner_id: i64,
+    pub farm_name: String,
+}
@@ -0,0 +1,146 @@
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::{AlertComment, AlertContext, CommentMention};
+
+const COMMENT_SELECT: &str = r#"
+    SELECT c.id, c.alert_id, c.parent_id, c.user_id, u.email AS author_email,
+           CASE WHEN c.deleted_at IS NULL THEN c.body END AS body,
+           c.created_at, c.edited_at, c.deleted_at IS NOT NULL AS deleted
+    FROM alert_comments c
+    LEFT JOIN users u ON u.id = c.user_id
+"#;
+
+pub async fn alert_context(pool: &PgPool, alert_id: i64) -> Result<Option<AlertContext>, AppError> {
+    sqlx::query_as::<_, AlertContext>(
+        r#"
+        SELECT f.id AS farm_id, f.user_id AS owner_id, f.name AS farm_name
+        FROM alerts a
+        JOIN farms f ON f.id = a.farm_id
+        WHERE a.id = $1
+        "#,
+    )
+    .bind(alert_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn list(pool: &PgPool, alert_id: i64) -> Result<Vec<AlertComment>, AppError> {
+    sqlx::query_as::<_, AlertComment>(&format!("{COMMENT_SELECT} WHERE c.alert_id = $1 ORDER BY c.created_at, c.id"))
+        .bind(alert_id)
+        .fetch_all(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn get(pool: &PgPool, id: i64) -> Result<Option<AlertComment>, AppError> {
+    sqlx::query_as::<_, AlertComment>(&format!("{COMMENT_SELECT} WHERE c.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn insert(
+    pool: &PgPool,
+    alert_id: i64,
+    parent_id: Option<i64>,
+    user_id: i64,
+    body: &str,
+) -> Result<i64, AppError> {
+    sqlx::query_scalar(
+        "INSERT INTO alert_comments (alert_id, parent_id, user_id, body) VALUES ($1, $2, $3, $4) RETURNING id",
+    )
+    .bind(alert_id)
+    .bind(parent_id)
+    .bind(user_id)
+    .bind(body)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn update_body(pool: &PgPool, id: i64, body: &str) -> Result<(), AppError> {
+    sqlx::query("UPDATE alert_comments SET body = $2, edited_at = NOW() WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .bind(body)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Clears the body as well, so deleted text is not kept around.
+pub async fn soft_delete(pool: &PgPool, id: i64) -> Result<(), AppError> {
+    sqlx::query("UPDATE alert_comments SET body = '', deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM alert_comment_mentions WHERE comment_id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Mentions of `comment_ids`, keyed by comment.
+pub async fn mentions(pool: &PgPool, comment_ids: &[i64]) -> Result<Vec<(i64, CommentMention)>, AppError> {
+    let rows: Vec<(i64, i64, String)> = sqlx::query_as(
+        r#"
+        SELECT m.comment_id, m.user_id, u.email
+        FROM alert_comment_mentions m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.comment_id = ANY($1)
+        ORDER BY u.email
+        "#,
+    )
+    .bind(comment_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(comment_id, user_id, email)| (comment_id, CommentMention { user_id, email }))
+        .collect())
+}
+
+/// Users named by `emails` who can see alerts of `owner_id`'s farms.
+pub async fn mentionable_users(pool: &PgPool, emails: &[String], owner_id: i64) -> Result<Vec<i64>, AppError> {
+    sqlx::query_scalar(
+        r#"
+        SELECT id FROM users
+        WHERE LOWER(email) = ANY($1) AND (id = $2 OR role = 'admin')
+        "#,
+    )
+    .bind(emails)
+    .bind(owner_id)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Replaces the mentions of `comment_id` and returns the users not mentioned before.
+pub async fn set_mentions(pool: &PgPool, comment_id: i64, user_ids: &[i64]) -> Result<Vec<i64>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM alert_comment_mentions WHERE comment_id = $1 AND NOT (user_id = ANY($2))")
+        .bind(comment_id)
+        .bind(user_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    let added = sqlx::query_scalar(
+        r#"
+        INSERT INTO alert_comment_mentions (comment_id, user_id)
+        SELECT $1, UNNEST($2::BIGINT[])
+        ON CONFLICT DO NOTHING
+        RETURNING user_id
+        "#,
+    )
+    .bind(comment_id)
+    .bind(user_ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(added)
+}
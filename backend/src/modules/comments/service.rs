@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, HashMap};
+use sqlx::PgPool;
+use crate::modules::settings;
+use crate::shared::{
+    error::{AppError, ErrorCode},
+    i18n::{self, t},
+    notifications::{push::PushMessage, NotificationDispatcher},
+};
+use super::models::{AlertComment, AlertContext, CreateCommentRequest, UpdateCommentRequest};
+use super::repository;
+
+const MAX_MENTIONS: usize = 20;
+/// Push notifications quote this much of the comment.
+const PUSH_PREVIEW_CHARS: usize = 200;
+
+/// The caller as far as comment permissions go.
+#[derive(Debug, Clone, Copy)]
+pub struct Commenter {
+    pub user_id: i64,
+    pub is_admin: bool,
+}
+
+fn not_found(id: i64) -> AppError {
+    AppError::Coded(ErrorCode::CommentNotFound, format!("Comment {} not found", id))
+}
+
+/// The alert's farm, provided `commenter` owns it or is an admin.
+async fn alert_access(db: &PgPool, commenter: Commenter, alert_id: i64) -> Result<AlertContext, AppError> {
+    let context = repository::alert_context(db, alert_id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::AlertNotFound, format!("Alert {} not found", alert_id)))?;
+
+    if context.owner_id != commenter.user_id && !commenter.is_admin {
+        return Err(AppError::Unauthorized("Not authorized to access this alert".to_string()));
+    }
+    Ok(context)
+}
+
+/// Lower-cased addresses written as `@name@example.com`, in order of first use.
+fn mentioned_emails(body: &str) -> Vec<String> {
+    let mut emails: Vec<String> = Vec::new();
+    for word in body.split_whitespace() {
+        let Some(candidate) = word.strip_prefix('@') else {
+            continue;
+        };
+        let candidate = candidate.trim_end_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+        let valid = candidate
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+        if valid && !emails.contains(&candidate) {
+            emails.push(candidate);
+        }
+    }
+    emails
+}
+
+async fn attach_mentions(db: &PgPool, comments: &mut [AlertComment]) -> Result<(), AppError> {
+    let ids: Vec<i64> = comments.iter().map(|c| c.id).collect();
+    let mut by_comment: HashMap<i64, Vec<_>> = HashMap::new();
+    for (comment_id, mention) in repository::mentions(db, &ids).await? {
+        by_comment.entry(comment_id).or_default().push(mention);
+    }
+    for comment in comments {
+        comment.mentions = by_comment.remove(&comment.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+async fn load(db: &PgPool, id: i64) -> Result<AlertComment, AppError> {
+    let mut comment = repository::get(db, id).await?.ok_or_else(|| not_found(id))?;
+    attach_mentions(db, std::slice::from_mut(&mut comment)).await?;
+    Ok(comment)
+}
+
+/// All comments on the alert, oldest first.
+pub async fn list(db: &PgPool, commenter: Commenter, alert_id: i64) -> Result<Vec<AlertComment>, AppError> {
+    alert_access(db, commenter, alert_id).await?;
+
+    let mut comments = repository::list(db, alert_id).await?;
+    attach_mentions(db, &mut comments).await?;
+    Ok(comments)
+}
+
+pub async fn create(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    commenter: Commenter,
+    alert_id: i64,
+    request: CreateCommentRequest,
+) -> Result<AlertComment, AppError> {
+    let context = alert_access(db, commenter, alert_id).await?;
+    let body = request.body.trim();
+
+    if let Some(parent_id) = request.parent_id {
+        let parent = repository::get(db, parent_id).await?.ok_or_else(|| not_found(parent_id))?;
+        if parent.alert_id != alert_id {
+            return Err(AppError::Validation(format!("Comment {} belongs to another alert", parent_id)));
+        }
+    }
+
+    let id = repository::insert(db, alert_id, request.parent_id, commenter.user_id, body).await?;
+    let added = update_mentions(db, &context, id, body).await?;
+
+    let comment = load(db, id).await?;
+    notify_mentioned(db, notifier, &context, &comment, &added, commenter.user_id).await;
+    Ok(comment)
+}
+
+/// Only the author may edit. Returns the comment before and after.
+pub async fn update(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    commenter: Commenter,
+    id: i64,
+    request: UpdateCommentRequest,
+) -> Result<(AlertComment, AlertComment), AppError> {
+    let before = load(db, id).await?;
+    if before.deleted {
+        return Err(not_found(id));
+    }
+    if before.user_id != Some(commenter.user_id) {
+        return Err(AppError::Unauthorized("Only the author can edit a comment".to_string()));
+    }
+    let context = alert_access(db, commenter, before.alert_id).await?;
+    let body = request.body.trim();
+
+    repository::update_body(db, id, body).await?;
+    let added = update_mentions(db, &context, id, body).await?;
+
+    let after = load(db, id).await?;
+    notify_mentioned(db, notifier, &context, &after, &added, commenter.user_id).await;
+    Ok((before, after))
+}
+
+/// The author or an admin may delete. Returns the comment as it was.
+pub async fn delete(db: &PgPool, commenter: Commenter, id: i64) -> Result<AlertComment, AppError> {
+    let before = load(db, id).await?;
+    if before.deleted {
+        return Err(not_found(id));
+    }
+    if before.user_id != Some(commenter.user_id) && !commenter.is_admin {
+        return Err(AppError::Unauthorized("Only the author or an admin can delete a comment".to_string()));
+    }
+
+    repository::soft_delete(db, id).await?;
+    Ok(before)
+}
+
+/// Mentions that do not resolve to someone who can see the alert are left
+/// as plain text.
+async fn update_mentions(db: &PgPool, context: &AlertContext, comment_id: i64, body: &str) -> Result<Vec<i64>, AppError> {
+    let emails = mentioned_emails(body);
+    if emails.len() > MAX_MENTIONS {
+        return Err(AppError::Validation(format!("A comment can mention at most {} users", MAX_MENTIONS)));
+    }
+
+    let user_ids = repository::mentionable_users(db, &emails, context.owner_id).await?;
+    repository::set_mentions(db, comment_id, &user_ids).await
+}
+
+/// Best-effort: a failed push must not fail the comment.
+async fn notify_mentioned(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    context: &AlertContext,
+    comment: &AlertComment,
+    user_ids: &[i64],
+    author_id: i64,
+) {
+    let author = comment.author_email.clone().unwrap_or_default();
+    let preview: String = comment.body.as_deref().unwrap_or_default().chars().take(PUSH_PREVIEW_CHARS).collect();
+
+    for &user_id in user_ids.iter().filter(|&&id| id != author_id) {
+        let result = async {
+            let lang = i18n::language_for_user(db, user_id).await?;
+            let message = PushMessage {
+                tokens: Vec::new(),
+                title: t(
+                    lang,
+                    "push.comment_mention.title",
+                    &[("author", author.clone()), ("farm", context.farm_name.clone())],
+                ),
+                body: preview.clone(),
+                data: BTreeMap::from([
+                    ("type".to_string(), "alert_comment".to_string()),
+                    ("alert_id".to_string(), comment.alert_id.to_string()),
+                    ("comment_id".to_string(), comment.id.to_string()),
+                    ("farm_id".to_string(), context.farm_id.to_string()),
+                ]),
+            };
+            settings::service::push_to_user(db, notifier, user_id, message).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to notify user {} of mention in comment {}: {}", user_id, comment.id, e);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Extension, State},
+    Json,
+};
+use crate::shared::{AppState, audit::AuditDetails, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{DashboardLayout, UpdateDashboardLayoutRequest},
+    service,
+};
+
+#[utoipa::path(
+    get,
+    path = "/layout",
+    tag = "dashboard",
+    responses((status = 200, description = "The caller's dashboard layout, or the default one", body = DashboardLayout)),
+)]
+pub async fn get_layout(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<DashboardLayout>, AppError> {
+    let layout = service::get_layout(&state.db, claims.sub).await?;
+    Ok(Json(layout))
+}
+
+#[utoipa::path(
+    put,
+    path = "/layout",
+    tag = "dashboard",
+    request_body = UpdateDashboardLayoutRequest,
+    responses(
+        (status = 200, description = "Layout saved", body = DashboardLayout),
+        (status = 422, description = "Too many widgets or malformed widget", body = ErrorResponse),
+    ),
+)]
+pub async fn update_layout(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<UpdateDashboardLayoutRequest>,
+) -> Result<(Extension<AuditDetails>, Json<DashboardLayout>), AppError> {
+    let before = service::get_layout(&state.db, claims.sub).await?;
+    let after = service::save_layout(&state.db, claims.sub, payload.widgets).await?;
+
+    let audit = AuditDetails::new("dashboard.update", "user", Some(claims.sub))
+        .before(&before)
+        .after(&after);
+
+    Ok((Extension(audit), Json(after)))
+}
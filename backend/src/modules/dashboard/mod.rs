@@ -0,0 +1,20 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+
+use axum::{routing::get, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/layout", get(controller::get_layout).put(controller::update_layout))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(controller::get_layout, controller::update_layout))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+pub const MAX_WIDGETS: u64 = 40;
+const MAX_WIDGET_ID_LEN: u64 = 64;
+const MAX_WIDTH: u8 = 4;
+const MAX_OPTIONS_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetKind {
+    KpiCard,
+    Chart,
+    FarmList,
+}
+
+fn default_width() -> u8 {
+    1
+}
+
+/// Widget names belong to the frontend's catalogue, so only their shape is
+/// checked here.
+fn widget_name(name: &str) -> Result<(), ValidationError> {
+    if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err(ValidationError::new("widget_name")
+            .with_message("must be lowercase letters, digits or underscores".into()));
+    }
+    Ok(())
+}
+
+fn widget_options(options: &serde_json::Value) -> Result<(), ValidationError> {
+    if !(options.is_null() || options.is_object()) {
+        return Err(ValidationError::new("widget_options").with_message("must be an object".into()));
+    }
+    if options.to_string().len() > MAX_OPTIONS_BYTES {
+        return Err(ValidationError::new("widget_options")
+            .with_message(format!("must be at most {} bytes", MAX_OPTIONS_BYTES).into()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct DashboardWidget {
+    pub kind: WidgetKind,
+    /// Which card, chart or list the frontend renders, e.g. `open_alerts`.
+    #[validate(length(min = 1, max = MAX_WIDGET_ID_LEN), custom(function = "widget_name"))]
+    pub widget: String,
+    /// Grid columns the widget spans, 1 to 4.
+    #[serde(default = "default_width")]
+    #[validate(range(min = 1, max = MAX_WIDTH))]
+    pub width: u8,
+    /// Widget-specific options such as a farm filter or chart range.
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    #[schema(value_type = Option<Object>)]
+    #[validate(custom(function = "widget_options"))]
+    pub options: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DashboardLayout {
+    /// Widgets in display order.
+    pub widgets: Vec<DashboardWidget>,
+    /// True until the user saves a layout of their own.
+    pub is_default: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateDashboardLayoutRequest {
+    /// Widgets in display order. An empty list leaves the dashboard blank.
+    #[validate(length(max = MAX_WIDGETS), nested)]
+    pub widgets: Vec<DashboardWidget>,
+}
@@ -0,0 +1,39 @@
+use sqlx::{types::Json, PgPool, Row};
+use sqlx::types::chrono::{DateTime, Utc};
+use crate::shared::error::AppError;
+use super::models::DashboardWidget;
+
+pub async fn get_layout(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<Option<(Vec<DashboardWidget>, DateTime<Utc>)>, AppError> {
+    let row = sqlx::query("SELECT widgets, updated_at FROM dashboard_layouts WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| {
+        let Json(widgets): Json<Vec<DashboardWidget>> = row.get("widgets");
+        (widgets, row.get("updated_at"))
+    }))
+}
+
+pub async fn save_layout(
+    pool: &PgPool,
+    user_id: i64,
+    widgets: &[DashboardWidget],
+) -> Result<DateTime<Utc>, AppError> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO dashboard_layouts (user_id, widgets)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE SET widgets = EXCLUDED.widgets
+        RETURNING updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(Json(widgets))
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
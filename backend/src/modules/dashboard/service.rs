@@ -0,0 +1,51 @@
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::{DashboardLayout, DashboardWidget, WidgetKind};
+use super::repository;
+
+/// The caller's saved layout, or the default one if they never saved any.
+pub async fn get_layout(db: &PgPool, user_id: i64) -> Result<DashboardLayout, AppError> {
+    Ok(match repository::get_layout(db, user_id).await? {
+        Some((widgets, updated_at)) => DashboardLayout {
+            widgets,
+            is_default: false,
+            updated_at: Some(updated_at),
+        },
+        None => DashboardLayout {
+            widgets: default_widgets(),
+            is_default: true,
+            updated_at: None,
+        },
+    })
+}
+
+pub async fn save_layout(
+    db: &PgPool,
+    user_id: i64,
+    widgets: Vec<DashboardWidget>,
+) -> Result<DashboardLayout, AppError> {
+    let updated_at = repository::save_layout(db, user_id, &widgets).await?;
+    Ok(DashboardLayout {
+        widgets,
+        is_default: false,
+        updated_at: Some(updated_at),
+    })
+}
+
+fn default_widgets() -> Vec<DashboardWidget> {
+    let widget = |kind, name: &str, width| DashboardWidget {
+        kind,
+        widget: name.to_string(),
+        width,
+        options: serde_json::Value::Null,
+    };
+
+    vec![
+        widget(WidgetKind::KpiCard, "farm_count", 1),
+        widget(WidgetKind::KpiCard, "open_alerts", 1),
+        widget(WidgetKind::KpiCard, "critical_alerts", 1),
+        widget(WidgetKind::KpiCard, "average_ndsi", 1),
+        widget(WidgetKind::Chart, "ndsi_trend", 4),
+        widget(WidgetKind::FarmList, "farms_at_risk", 4),
+    ]
+}
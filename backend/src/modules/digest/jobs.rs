@@ -0,0 +1,22 @@
+use sqlx::PgPool;
+use crate::shared::notifications::NotificationDispatcher;
+use crate::shared::worker::{interval_from_env, spawn_periodic};
+use super::service;
+
+const DIGEST_JOB_DEFAULT_SECS: u64 = 60 * 60;
+
+pub fn spawn_digest_job(db: PgPool, notifier: NotificationDispatcher) {
+    let period = interval_from_env("DIGEST_JOB_INTERVAL_SECS", DIGEST_JOB_DEFAULT_SECS);
+
+    spawn_periodic("email_digest", period, move || {
+        let db = db.clone();
+        let notifier = notifier.clone();
+        async move {
+            let sent = service::send_due_digests(&db, &notifier).await?;
+            if sent > 0 {
+                tracing::info!("Sent {} farm status digests", sent);
+            }
+            Ok(())
+        }
+    });
+}
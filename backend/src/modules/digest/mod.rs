@@ -0,0 +1,7 @@
+//! Periodic email summaries of each user's farms. Frequency and opt-out live in
+//! the user's preferences; the job runs often and only mails users who are due.
+
+mod models;
+mod repository;
+mod service;
+pub mod jobs;
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use crate::modules::monitoring::models::RiskScore;
+use crate::modules::settings::DigestFrequency;
+use crate::shared::i18n::Language;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DigestRecipient {
+    pub user_id: i64,
+    pub email: String,
+    #[sqlx(try_from = "String")]
+    pub language: Language,
+    #[sqlx(try_from = "String")]
+    pub digest_frequency: DigestFrequency,
+    pub last_digest_sent_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FarmSummary {
+    pub farm_id: i64,
+    pub name: String,
+    pub latest_ndsi: Option<f64>,
+    /// Alerts detected since the previous digest.
+    pub new_alerts: i64,
+    pub critical_alerts: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct FarmDigest {
+    pub summary: FarmSummary,
+    pub risk: RiskScore,
+    /// The latest shift in the farm's NDSI, in words.
+    pub trend: Option<String>,
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::{DigestRecipient, FarmSummary};
+
+/// Verified users with at least one farm whose digest is due. Missing
+/// preference rows mean the defaults: emails on, weekly digest. An hour of
+/// slack keeps the send time from drifting later on each run.
+pub async fn due_recipients(pool: &PgPool) -> Result<Vec<DigestRecipient>, AppError> {
+    sqlx::query_as::<_, DigestRecipient>(
+        r#"
+        SELECT u.id AS user_id,
+               u.email,
+               COALESCE(p.language, 'en') AS language,
+               COALESCE(p.digest_frequency, 'weekly') AS digest_frequency,
+               p.last_digest_sent_at
+        FROM users u
+        LEFT JOIN user_preferences p ON p.user_id = u.id
+        WHERE u.email_verified_at IS NOT NULL
+          AND u.deletion_scheduled_at IS NULL
+          AND COALESCE(p.email_alerts_enabled, TRUE)
+          AND COALESCE(p.digest_frequency, 'weekly') <> 'off'
+          AND (
+              p.last_digest_sent_at IS NULL
+              OR p.last_digest_sent_at <= NOW() + INTERVAL '1 hour' - CASE p.digest_frequency
+                  WHEN 'daily' THEN INTERVAL '1 day'
+                  ELSE INTERVAL '7 days'
+              END
+          )
+          AND EXISTS (SELECT 1 FROM farms f WHERE f.user_id = u.id AND f.deleted_at IS NULL)
+        ORDER BY u.id
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn farm_summaries(pool: &PgPool, user_id: i64, since: DateTime<Utc>) -> Result<Vec<FarmSummary>, AppError> {
+    sqlx::query_as::<_, FarmSummary>(
+        r#"
+        SELECT f.id AS farm_id,
+               f.name,
+               (SELECT l.ndsi_value::FLOAT8 FROM salinity_logs l
+                 WHERE l.farm_id = f.id ORDER BY l.recorded_at DESC LIMIT 1) AS latest_ndsi,
+               COALESCE(a.new_alerts, 0) AS new_alerts,
+               COALESCE(a.critical_alerts, 0) AS critical_alerts
+        FROM farms f
+        LEFT JOIN LATERAL (
+            SELECT COUNT(*) AS new_alerts,
+                   COUNT(*) FILTER (WHERE severity = 'critical') AS critical_alerts
+            FROM alerts
+            WHERE farm_id = f.id AND detected_at >= $2
+        ) a ON TRUE
+        WHERE f.user_id = $1 AND f.deleted_at IS NULL
+        ORDER BY f.name
+        "#
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn mark_sent(pool: &PgPool, user_id: i64, sent_at: DateTime<Utc>) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_preferences (user_id, last_digest_sent_at)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE SET last_digest_sent_at = EXCLUDED.last_digest_sent_at
+        "#
+    )
+    .bind(user_id)
+    .bind(sent_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
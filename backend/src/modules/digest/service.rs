@@ -0,0 +1,143 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use crate::modules::monitoring::{
+    models::AlertSeverity,
+    service::{assess_risk, ndsi_breakpoints, trend_narrative},
+};
+use crate::modules::settings::DigestFrequency;
+use crate::shared::error::AppResult;
+use crate::shared::i18n::{t, Language};
+use crate::shared::notifications::{email::EmailMessage, NotificationDispatcher};
+use super::models::{DigestRecipient, FarmDigest};
+use super::repository;
+
+/// History searched for the trend sentence of each farm.
+const TREND_WINDOW_DAYS: i32 = 90;
+
+/// Mails every user whose digest is due. A failure for one user is logged and
+/// retried on the next run without holding back the others.
+pub async fn send_due_digests(db: &PgPool, notifier: &NotificationDispatcher) -> AppResult<usize> {
+    let recipients = repository::due_recipients(db).await?;
+    let mut sent = 0;
+
+    for recipient in &recipients {
+        match send_digest(db, notifier, recipient).await {
+            Ok(()) => sent += 1,
+            Err(e) => tracing::warn!("Failed to send digest to user {}: {}", recipient.user_id, e),
+        }
+    }
+
+    Ok(sent)
+}
+
+async fn send_digest(db: &PgPool, notifier: &NotificationDispatcher, recipient: &DigestRecipient) -> AppResult<()> {
+    let now = Utc::now();
+    let since = recipient
+        .last_digest_sent_at
+        .unwrap_or_else(|| now - period(recipient.digest_frequency));
+
+    let summaries = repository::farm_summaries(db, recipient.user_id, since).await?;
+    let mut farms = Vec::with_capacity(summaries.len());
+    for summary in summaries {
+        let risk = assess_risk(summary.farm_id, db).await?;
+        let trend = ndsi_breakpoints(summary.farm_id, TREND_WINDOW_DAYS, db)
+            .await?
+            .breakpoints
+            .last()
+            .map(|breakpoint| trend_narrative(recipient.language, breakpoint));
+        farms.push(FarmDigest { summary, risk, trend });
+    }
+
+    notifier.send_email(render(recipient, since, &farms)).await?;
+    repository::mark_sent(db, recipient.user_id, now).await
+}
+
+fn period(frequency: DigestFrequency) -> Duration {
+    match frequency {
+        DigestFrequency::Daily => Duration::days(1),
+        DigestFrequency::Weekly | DigestFrequency::Off => Duration::days(7),
+    }
+}
+
+fn render(recipient: &DigestRecipient, since: DateTime<Utc>, farms: &[FarmDigest]) -> EmailMessage {
+    let lang = recipient.language;
+    let period_name = t(lang, &format!("digest.period.{}", recipient.digest_frequency.as_str()), &[]);
+    let subject = t(lang, "digest.subject", &[("period", period_name)]);
+    let intro = t(lang, "digest.intro", &[("since", since.format("%Y-%m-%d").to_string())]);
+    let footer = t(lang, "digest.footer", &[]);
+
+    let lines: Vec<String> = farms
+        .iter()
+        .map(|farm| {
+            let line = t(lang, "digest.farm", &[
+                ("name", farm.summary.name.clone()),
+                ("ndsi", format_ndsi(lang, farm.summary.latest_ndsi)),
+                ("alerts", farm.summary.new_alerts.to_string()),
+                ("critical", farm.summary.critical_alerts.to_string()),
+                ("risk", farm.risk.score.to_string()),
+                ("level", severity_name(lang, farm.risk.level)),
+            ]);
+            match &farm.trend {
+                Some(trend) => format!("{} {}.", line, trend),
+                None => line,
+            }
+        })
+        .collect();
+
+    let body = format!("{}\n\n{}\n\n{}", intro, lines.join("\n"), footer);
+
+    let rows: String = farms
+        .iter()
+        .map(|farm| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{} ({})</td><td>{}/100 ({})</td><td>{}</td></tr>",
+                escape_html(&farm.summary.name),
+                escape_html(&format_ndsi(lang, farm.summary.latest_ndsi)),
+                farm.summary.new_alerts,
+                farm.summary.critical_alerts,
+                farm.risk.score,
+                escape_html(&severity_name(lang, farm.risk.level)),
+                escape_html(farm.trend.as_deref().unwrap_or_default()),
+            )
+        })
+        .collect();
+
+    let html = format!(
+        "<h2>{}</h2><p>{}</p><table><thead><tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr></thead><tbody>{}</tbody></table><p>{}</p>",
+        escape_html(&subject),
+        escape_html(&intro),
+        escape_html(&t(lang, "digest.column.farm", &[])),
+        escape_html(&t(lang, "digest.column.ndsi", &[])),
+        escape_html(&t(lang, "digest.column.alerts", &[])),
+        escape_html(&t(lang, "digest.column.risk", &[])),
+        escape_html(&t(lang, "digest.column.trend", &[])),
+        rows,
+        escape_html(&footer),
+    );
+
+    EmailMessage {
+        to: recipient.email.clone(),
+        subject,
+        body,
+        html: Some(html),
+    }
+}
+
+fn format_ndsi(lang: Language, ndsi: Option<f64>) -> String {
+    match ndsi {
+        Some(value) => format!("{:.3}", value),
+        None => t(lang, "digest.no_data", &[]),
+    }
+}
+
+fn severity_name(lang: Language, severity: AlertSeverity) -> String {
+    t(lang, &format!("severity.{}", severity.as_str()), &[])
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,83 @@
+mod controller;
+
+use axum::{routing::get, Router};
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use crate::shared::{AppState, error::ErrorResponse};
+use super::{analytics, api_keys, attachments, auth, comments, dashboard, events, farm_mgmt, health, monitoring, reports, satellites, search, settings, stations, sync, todos, webhooks};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(controller::openapi_json))
+        .route("/docs", get(controller::swagger_ui))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Bio-Radar API", description = "Salinity intrusion monitoring for Mekong Delta farms"),
+    components(schemas(ErrorResponse)),
+    modifiers(&SecuritySchemes),
+    security(("bearer_auth" = [])),
+)]
+struct ApiDoc;
+
+/// Bearer tokens for users; API keys for the machine-to-machine feeds.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(api_keys::API_KEY_HEADER))),
+        );
+    }
+}
+
+/// Full specification with every module mounted under the same prefix the
+/// router in `main.rs` uses.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    [
+        ("/health", health::openapi()),
+        ("/api/auth", auth::openapi()),
+        ("/api/auth", attachments::profile_openapi()),
+        ("/api/monitoring", monitoring::openapi()),
+        ("/api/monitoring", attachments::alert_openapi()),
+        ("/api/monitoring", comments::alert_openapi()),
+        ("/api/monitoring/stations", stations::openapi()),
+        ("/api/monitoring/events", events::openapi()),
+        ("/api/farms", farm_mgmt::openapi()),
+        ("/api/farms", stations::farm_openapi()),
+        ("/api/todos", todos::openapi()),
+        ("/api/todos", attachments::todo_openapi()),
+        ("/api/settings", settings::openapi()),
+        ("/api/settings", api_keys::openapi()),
+        ("/api/webhooks", webhooks::openapi()),
+        ("/api/analytics", analytics::openapi()),
+        ("/api/dashboard", dashboard::openapi()),
+        ("/api/reports", reports::openapi()),
+        ("/api/search", search::openapi()),
+        ("/api/satellites", satellites::openapi()),
+        ("/api/sync", sync::openapi()),
+        ("/api/attachments", attachments::openapi()),
+        ("/api/comments", comments::openapi()),
+    ]
+    .into_iter()
+    .fold(ApiDoc::openapi(), |doc, (prefix, module)| {
+        doc.nest_with_path_composer(prefix, module, |base, path| match path {
+            "/" => base.to_string(),
+            _ => format!("{base}{path}"),
+        })
+    })
+}
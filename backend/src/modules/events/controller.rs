@@ -0,0 +1,88 @@
+use std::convert::Infallible;
+use axum::{
+    extract::{Extension, Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures_util::Stream;
+use crate::shared::{AppState, error::{AppError, ErrorResponse}};
+use crate::modules::auth::models::Claims;
+use super::{models::{EventStreamQuery, SignedStreamQuery, StreamTokenResponse}, service};
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "monitoring",
+    params(
+        EventStreamQuery,
+        ("Last-Event-ID" = Option<i64>, Header, description = "Id of the last event received; sent by EventSource clients on reconnect"),
+    ),
+    responses(
+        (status = 200, description = "Stream of events on the caller's farms. Each message is named after the event \
+            (alert.created, analysis.completed, ...), carries its outbox id, and has the webhook payload as JSON data. \
+            Needs a bearer token, so browsers must read it with fetch; EventSource clients use /events/stream instead",
+            content_type = "text/event-stream", body = String),
+    ),
+)]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<EventStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    open_stream(&state, claims.sub, &headers, query.last_event_id).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/token",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "Token for /events/stream, valid for a few minutes", body = StreamTokenResponse),
+    ),
+)]
+pub async fn create_stream_token(Extension(claims): Extension<Claims>) -> Json<StreamTokenResponse> {
+    Json(service::issue_stream_token(claims.sub))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stream",
+    tag = "monitoring",
+    params(
+        SignedStreamQuery,
+        ("Last-Event-ID" = Option<i64>, Header, description = "Id of the last event received; sent by EventSource clients on reconnect"),
+    ),
+    responses(
+        (status = 200, description = "The same stream as /events/, authenticated by a stream token so EventSource can open it",
+            content_type = "text/event-stream", body = String),
+        (status = 401, description = "Stream token invalid or expired", body = ErrorResponse),
+    ),
+)]
+pub async fn stream_events_signed(
+    State(state): State<AppState>,
+    Query(query): Query<SignedStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let user_id = service::verify_stream_token(&query.token)?;
+    open_stream(&state, user_id, &headers, query.last_event_id).await
+}
+
+async fn open_stream(
+    state: &AppState,
+    user_id: i64,
+    headers: &HeaderMap,
+    last_event_id: Option<i64>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>> + use<>>, AppError> {
+    let resume_from = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .or(last_event_id);
+
+    let start = service::stream_start(&state.db, resume_from).await?;
+    let stream = service::stream_for_user(state.db.clone(), user_id, start);
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
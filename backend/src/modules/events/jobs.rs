@@ -0,0 +1,22 @@
+use sqlx::PgPool;
+use crate::shared::notifications::NotificationDispatcher;
+use crate::shared::worker::{interval_from_env, spawn_periodic};
+use super::service;
+
+const OUTBOX_RELAY_DEFAULT_SECS: u64 = 5;
+
+pub fn spawn_outbox_relay_job(db: PgPool, notifier: NotificationDispatcher) {
+    let period = interval_from_env("OUTBOX_RELAY_INTERVAL_SECS", OUTBOX_RELAY_DEFAULT_SECS);
+
+    spawn_periodic("outbox_relay", period, move || {
+        let db = db.clone();
+        let notifier = notifier.clone();
+        async move {
+            let published = service::relay_pending(&db, &notifier).await?;
+            if published > 0 {
+                tracing::debug!("Published {} outbox events", published);
+            }
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,37 @@
+//! Transactional outbox. Domain changes record their events in the same
+//! transaction; the relay job publishes them afterwards, so an event is never
+//! lost to a crash between the write and the publish. The outbox also backs
+//! the server-sent event stream, whose event ids are outbox ids. The stream
+//! takes a bearer token, or a short-lived stream token in the query for
+//! `EventSource` clients, which cannot send headers.
+
+mod models;
+mod repository;
+mod service;
+mod controller;
+pub mod jobs;
+
+pub use repository::record;
+
+use axum::{routing::{get, post}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(controller::stream_events))
+        .route("/token", post(controller::create_stream_token))
+}
+
+/// The stream for `EventSource` clients, authenticated by a stream token.
+pub fn public_router() -> Router<AppState> {
+    Router::new().route("/stream", get(controller::stream_events_signed))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(controller::stream_events, controller::create_stream_token, controller::stream_events_signed))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
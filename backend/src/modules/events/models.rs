@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub event: String,
+    pub farm_id: Option<i64>,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a stream has got to: events are streamed by writing transaction,
+/// then id, since ids alone are not in commit order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, sqlx::FromRow)]
+pub struct StreamPosition {
+    pub txid: i64,
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StreamedEvent {
+    #[sqlx(flatten)]
+    pub event: OutboxEvent,
+    #[sqlx(flatten)]
+    pub position: StreamPosition,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct EventStreamQuery {
+    /// Resume after this event id. The `Last-Event-ID` header takes precedence.
+    pub last_event_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SignedStreamQuery {
+    /// Stream token from `POST /api/monitoring/events/token`.
+    pub token: String,
+    /// Resume after this event id. The `Last-Event-ID` header takes precedence.
+    pub last_event_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StreamTokenResponse {
+    /// Opens `GET /api/monitoring/events/stream?token=...`, for clients such
+    /// as `EventSource` that cannot send an Authorization header.
+    pub token: String,
+    /// The stream must be opened before then; an open stream is not cut off.
+    pub expires_at: DateTime<Utc>,
+}
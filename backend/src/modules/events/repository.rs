@@ -0,0 +1,130 @@
+use sqlx::{PgConnection, PgPool};
+use sqlx::types::chrono::{DateTime, Utc};
+use crate::modules::webhooks::WebhookEvent;
+use crate::shared::error::AppError;
+use super::models::{OutboxEvent, StreamPosition, StreamedEvent};
+
+/// Records `event` on `conn`. Call it inside the transaction that makes the
+/// change the event describes.
+pub async fn record(
+    conn: &mut PgConnection,
+    event: WebhookEvent,
+    farm_id: Option<i64>,
+    payload: &serde_json::Value,
+) -> Result<(), AppError> {
+    sqlx::query("INSERT INTO events_outbox (event, farm_id, payload) VALUES ($1, $2, $3)")
+        .bind(event.as_str())
+        .bind(farm_id)
+        .bind(payload)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Claims due events by pushing their next attempt out by `lease_secs`, so an
+/// overlapping relay run does not publish them twice.
+pub async fn claim_pending(pool: &PgPool, limit: i64, lease_secs: i64) -> Result<Vec<OutboxEvent>, AppError> {
+    sqlx::query_as::<_, OutboxEvent>(
+        r#"
+        WITH due AS (
+            SELECT id
+            FROM events_outbox
+            WHERE published_at IS NULL AND next_attempt_at <= NOW()
+            ORDER BY id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE events_outbox e
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        FROM due
+        WHERE e.id = due.id
+        RETURNING e.id, e.event, e.farm_id, e.payload, e.attempts, e.created_at
+        "#
+    )
+    .bind(limit)
+    .bind(lease_secs as f64)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn mark_published(conn: &mut PgConnection, id: i64) -> Result<(), AppError> {
+    sqlx::query("UPDATE events_outbox SET published_at = NOW(), attempts = attempts + 1, last_error = NULL WHERE id = $1")
+        .bind(id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn mark_failed(pool: &PgPool, id: i64, error: &str, next_attempt_at: DateTime<Utc>) -> Result<(), AppError> {
+    sqlx::query("UPDATE events_outbox SET attempts = attempts + 1, last_error = $2, next_attempt_at = $3 WHERE id = $1")
+        .bind(id)
+        .bind(error)
+        .bind(next_attempt_at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Drops published events older than `days`; they are only kept for debugging.
+pub async fn purge_published(pool: &PgPool, days: i32) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM events_outbox WHERE published_at < NOW() - make_interval(days => $1)")
+        .bind(days)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Events on farms owned by `user_id` after `after`, in stream order. Only
+/// transactions older than every running one are read: ids are taken before
+/// commit, so a running transaction could still add an event with a lower id.
+pub async fn list_for_user_since(
+    pool: &PgPool,
+    user_id: i64,
+    after: StreamPosition,
+    limit: i64,
+) -> Result<Vec<StreamedEvent>, AppError> {
+    sqlx::query_as::<_, StreamedEvent>(
+        r#"
+        SELECT e.id, e.event, e.farm_id, e.payload, e.attempts, e.created_at, e.txid::TEXT::BIGINT AS txid
+        FROM events_outbox e
+        JOIN farms f ON f.id = e.farm_id
+        WHERE f.user_id = $1
+          AND (e.txid, e.id) > ($2::TEXT::XID8, $3)
+          AND e.txid < pg_snapshot_xmin(pg_current_snapshot())
+        ORDER BY e.txid, e.id
+        LIMIT $4
+        "#
+    )
+    .bind(user_id)
+    .bind(after.txid)
+    .bind(after.id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Position of event `id`, or `None` if it was never recorded or has been purged.
+pub async fn position_of(pool: &PgPool, id: i64) -> Result<Option<StreamPosition>, AppError> {
+    sqlx::query_as::<_, StreamPosition>("SELECT txid::TEXT::BIGINT AS txid, id FROM events_outbox WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
+
+/// The position now: every event before it has been committed, so a stream
+/// starting here misses nothing that is yet to commit.
+pub async fn current_position(pool: &PgPool) -> Result<StreamPosition, AppError> {
+    sqlx::query_as::<_, StreamPosition>(
+        "SELECT pg_snapshot_xmin(pg_current_snapshot())::TEXT::BIGINT AS txid, 0::BIGINT AS id"
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::time::Duration;
+use axum::response::sse::Event;
+use chrono::Utc;
+use futures_util::{stream, Stream};
+use sqlx::PgPool;
+use crate::modules::monitoring::{self, models::{Alert, AlertSeverity}};
+use crate::modules::settings::{self, AlertChannel, UserPreferences};
+use crate::modules::webhooks::{self, WebhookEvent};
+use crate::shared::{error::{AppError, ErrorCode}, notifications::NotificationDispatcher, signing};
+use super::models::{OutboxEvent, StreamPosition, StreamTokenResponse};
+use super::repository;
+
+const RELAY_BATCH_SIZE: i64 = 100;
+const RELAY_LEASE_SECS: i64 = 60;
+const BASE_BACKOFF_SECS: i64 = 5;
+const MAX_BACKOFF_SECS: i64 = 10 * 60;
+const PUBLISHED_RETENTION_DAYS: i32 = 7;
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const STREAM_BATCH_SIZE: i64 = 100;
+const STREAM_TOKEN_TTL_MINUTES: i64 = 5;
+
+/// Publishes every due outbox event. Each event is handed to its subscribers
+/// and marked published in one transaction, so a crash between the two
+/// cannot lose or duplicate it; failures are retried indefinitely with backoff.
+/// Push notifications go out after the commit and are best effort.
+pub async fn relay_pending(db: &PgPool, notifier: &NotificationDispatcher) -> Result<usize, AppError> {
+    let pending = repository::claim_pending(db, RELAY_BATCH_SIZE, RELAY_LEASE_SECS).await?;
+    let mut published = 0;
+
+    for event in pending {
+        match publish(db, &event).await {
+            Ok(kind) => {
+                published += 1;
+                notify(db, notifier, kind, &event).await;
+            }
+            Err(e) => {
+                tracing::warn!("Outbox event {} ({}) failed to publish: {}", event.id, event.event, e);
+                let next_attempt_at = chrono::Utc::now() + chrono::Duration::seconds(backoff_secs(event.attempts + 1));
+                repository::mark_failed(db, event.id, &e.to_string(), next_attempt_at).await?;
+            }
+        }
+    }
+
+    if published > 0 {
+        repository::purge_published(db, PUBLISHED_RETENTION_DAYS).await?;
+    }
+
+    Ok(published)
+}
+
+async fn publish(db: &PgPool, event: &OutboxEvent) -> Result<WebhookEvent, AppError> {
+    let kind = WebhookEvent::from_code(&event.event)
+        .ok_or_else(|| AppError::Internal(format!("Unknown outbox event: {}", event.event)))?;
+
+    let mut tx = db.begin().await?;
+
+    if let Some(farm_id) = event.farm_id {
+        let wanted = match alert_severity(kind, event) {
+            Some(severity) => settings::service::farm_owner_preferences(db, farm_id)
+                .await?
+                .delivers(severity, AlertChannel::Webhook, event.created_at),
+            None => true,
+        };
+        if wanted {
+            webhooks::service::enqueue_for_farm(&mut tx, farm_id, kind, event.payload.clone(), event.created_at).await?;
+        }
+    }
+
+    repository::mark_published(&mut tx, event.id).await?;
+    tx.commit().await?;
+
+    Ok(kind)
+}
+
+async fn notify(db: &PgPool, notifier: &NotificationDispatcher, kind: WebhookEvent, event: &OutboxEvent) {
+    if kind != WebhookEvent::AlertCreated {
+        return;
+    }
+
+    let result = match serde_json::from_value::<Alert>(event.payload.clone()) {
+        Ok(alert) => monitoring::service::deliver_alert(&alert, notifier, db).await,
+        Err(e) => Err(AppError::Internal(format!("Invalid alert payload: {}", e))),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Alert notification for outbox event {} failed: {}", event.id, e);
+    }
+}
+
+/// Severity of a new alert, which decides the channels it goes out on. Other
+/// events reach every channel.
+fn alert_severity(kind: WebhookEvent, event: &OutboxEvent) -> Option<AlertSeverity> {
+    if kind != WebhookEvent::AlertCreated {
+        return None;
+    }
+    serde_json::from_value(event.payload.get("severity")?.clone()).ok()
+}
+
+fn streamed(preferences: &UserPreferences, event: &OutboxEvent) -> bool {
+    let severity = WebhookEvent::from_code(&event.event).and_then(|kind| alert_severity(kind, event));
+    severity.is_none_or(|severity| preferences.delivers(severity, AlertChannel::Sse, event.created_at))
+}
+
+fn backoff_secs(attempt: i32) -> i64 {
+    let exp = (attempt - 1).clamp(0, 16) as u32;
+    (BASE_BACKOFF_SECS * 2i64.pow(exp)).min(MAX_BACKOFF_SECS)
+}
+
+struct StreamCursor {
+    db: PgPool,
+    user_id: i64,
+    position: StreamPosition,
+    buffer: VecDeque<OutboxEvent>,
+}
+
+/// Server-sent events for the user's farms, read straight from the outbox so a
+/// client resuming with `Last-Event-ID` gets everything it missed. Events are
+/// streamed in commit order once every older transaction has finished, so a
+/// late commit is never skipped, independently of webhook delivery.
+pub fn stream_for_user(
+    db: PgPool,
+    user_id: i64,
+    position: StreamPosition,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let cursor = StreamCursor {
+        db,
+        user_id,
+        position,
+        buffer: VecDeque::new(),
+    };
+
+    stream::unfold(cursor, |mut cursor| async move {
+        loop {
+            if let Some(event) = cursor.buffer.pop_front() {
+                let sse = Event::default()
+                    .id(event.id.to_string())
+                    .event(event.event)
+                    .data(event.payload.to_string());
+                return Some((Ok(sse), cursor));
+            }
+
+            match poll(&cursor).await {
+                Ok((position, events)) if position > cursor.position => {
+                    // Events the user opted out of are skipped past, so they
+                    // are not read again on the next poll.
+                    cursor.position = position;
+                    cursor.buffer.extend(events);
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Event stream for user {} failed to poll: {}", cursor.user_id, e),
+            }
+
+            tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+        }
+    })
+}
+
+/// The next batch after the cursor, without the alerts the user's channel
+/// preferences keep off the stream, and the position of the last event read.
+async fn poll(cursor: &StreamCursor) -> Result<(StreamPosition, Vec<OutboxEvent>), AppError> {
+    let events =
+        repository::list_for_user_since(&cursor.db, cursor.user_id, cursor.position, STREAM_BATCH_SIZE).await?;
+    let Some(position) = events.last().map(|streamed| streamed.position) else {
+        return Ok((cursor.position, Vec::new()));
+    };
+
+    let preferences = settings::service::preferences(&cursor.db, cursor.user_id).await?;
+    let events = events
+        .into_iter()
+        .map(|streamed| streamed.event)
+        .filter(|event| streamed(&preferences, event))
+        .collect();
+    Ok((position, events))
+}
+
+/// Where a new stream starts: after event `resume_from` if the client has
+/// seen events before (from the start of the outbox if it has been purged),
+/// otherwise after everything committed so far.
+pub async fn stream_start(db: &PgPool, resume_from: Option<i64>) -> Result<StreamPosition, AppError> {
+    match resume_from {
+        Some(id) => Ok(repository::position_of(db, id).await?.unwrap_or_default()),
+        None => repository::current_position(db).await,
+    }
+}
+
+const STREAM_TOKEN_PURPOSE: &str = "event-stream";
+
+fn stream_token_payload(user_id: i64, expires: i64) -> String {
+    format!("{}:{}", user_id, expires)
+}
+
+/// A short-lived token that opens the user's stream without a bearer token,
+/// since `EventSource` cannot set headers.
+pub fn issue_stream_token(user_id: i64) -> StreamTokenResponse {
+    let expires_at = Utc::now() + chrono::Duration::minutes(STREAM_TOKEN_TTL_MINUTES);
+    let expires = expires_at.timestamp();
+    let signature = signing::sign(STREAM_TOKEN_PURPOSE, &stream_token_payload(user_id, expires));
+
+    StreamTokenResponse {
+        token: format!("{}.{}.{}", user_id, expires, signature),
+        expires_at,
+    }
+}
+
+/// The user a stream token was issued to.
+pub fn verify_stream_token(token: &str) -> Result<i64, AppError> {
+    let invalid = || AppError::Coded(ErrorCode::InvalidToken, "Invalid stream token".to_string());
+
+    let mut parts = token.splitn(3, '.');
+    let (Some(user_id), Some(expires), Some(provided)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let user_id = user_id.parse::<i64>().map_err(|_| invalid())?;
+    let expires = expires.parse::<i64>().map_err(|_| invalid())?;
+
+    if !signing::verify(STREAM_TOKEN_PURPOSE, &stream_token_payload(user_id, expires), provided) {
+        return Err(invalid());
+    }
+    if expires < Utc::now().timestamp() {
+        return Err(AppError::Coded(ErrorCode::InvalidToken, "Stream token has expired".to_string()));
+    }
+
+    Ok(user_id)
+}
@@ -1,36 +1,98 @@
 use axum::{
     extract::{Path, State, Extension, Query},
+    response::Response,
     Json,
 };
-use crate::shared::{AppState, error::AppError, utils::parse_geojson_to_wkt};
-use crate::modules::auth::models::Claims;
+use crate::shared::{
+    AppState, audit::AuditDetails, error::{AppError, ErrorCode, ErrorResponse}, tiles::{self, TileCoord},
+    utils::parse_geojson_to_wkt, validation::ValidatedJson,
+};
+use crate::modules::{auth::models::Claims, settings::quota};
 use super::{
-    models::{CreateFarmRequest, UpdateFarmRequest, FarmResponse, ConvertRequest, ConvertResponse, IntersectionQuery},
+    models::{
+        CreateFarmRequest, UpdateFarmRequest, FarmResponse, ConvertRequest, ConvertResponse, IntersectionQuery,
+        FarmGeometryVersion, BulkCreateFarmsRequest, BulkCreateFarmsResponse,
+        CropSeason, CreateCropSeasonRequest, UpdateCropSeasonRequest,
+        ListFarmsQuery, NearbyQuery, IntersectingFarmResponse, NearbyFarmResponse, SimplifyQuery, FarmTileQuery,
+    },
     repository, service,
 };
 
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "farms",
+    request_body = CreateFarmRequest,
+    responses(
+        (status = 200, description = "Farm created", body = FarmResponse),
+        (status = 400, description = "Invalid polygon; details.problems lists every problem found", body = ErrorResponse),
+        (status = 403, description = "Farm quota of the caller's plan reached", body = ErrorResponse),
+        (status = 422, description = "Blank or overlong name", body = ErrorResponse),
+    ),
+)]
 pub async fn create_farm(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
-    Json(payload): Json<CreateFarmRequest>,
-) -> Result<Json<FarmResponse>, AppError> {
-    service::validate_polygon(&payload.geojson)?;
-    let normalized_geojson = service::normalize_geojson(&payload.geojson)?;
+    ValidatedJson(payload): ValidatedJson<CreateFarmRequest>,
+) -> Result<(Extension<AuditDetails>, Json<FarmResponse>), AppError> {
+    quota::ensure_farm_capacity(&state.db, claims.sub, 1).await?;
+    let boundary = service::prepare_boundary(&state.db, &payload.geojson, payload.crs.as_deref()).await?;
 
-    let farm = repository::create(&state.db, claims.sub, &payload.name, &normalized_geojson).await?;
+    let farm = repository::create(&state.db, claims.sub, &payload.name, payload.region.as_deref(), &boundary.geojson).await?;
     
-    let geojson = repository::get_geojson(&state.db, farm.id)
+    let geojson = repository::get_geojson(&state.db, farm.id, None)
         .await?
         .ok_or_else(|| AppError::Internal("Failed to retrieve GeoJSON".to_string()))?;
 
-    Ok(Json(FarmResponse::from_farm(farm, geojson)))
+    let response = FarmResponse {
+        geometry_repairs: boundary.repairs,
+        ..FarmResponse::from_farm(farm, geojson)
+    };
+    let audit = AuditDetails::new("farm.create", "farm", Some(response.id)).after(&response);
+
+    Ok((Extension(audit), Json(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/bulk",
+    tag = "farms",
+    request_body = BulkCreateFarmsRequest,
+    responses(
+        (status = 200, description = "Per-item creation report", body = BulkCreateFarmsResponse),
+        (status = 422, description = "Empty or oversized batch", body = ErrorResponse),
+        (status = 403, description = "Batch would exceed the farm quota of the caller's plan", body = ErrorResponse),
+    ),
+)]
+pub async fn bulk_create_farms(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<BulkCreateFarmsRequest>,
+) -> Result<(Extension<AuditDetails>, Json<BulkCreateFarmsResponse>), AppError> {
+    quota::ensure_farm_capacity(&state.db, claims.sub, payload.farms.len()).await?;
+    let response = service::bulk_create(&state.db, claims.sub, payload).await?;
+
+    let created_ids: Vec<i64> = response.results.iter().filter_map(|r| r.id).collect();
+    let audit = AuditDetails::new("farm.bulk_create", "farm", None)
+        .after(&serde_json::json!({ "created_ids": created_ids, "failed": response.failed }));
+
+    Ok((Extension(audit), Json(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "farms",
+    params(ListFarmsQuery),
+    responses((status = 200, description = "Farms owned by the caller; archived farms only when requested", body = [FarmResponse])),
+)]
 pub async fn list_farms(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
+    Query(query): Query<ListFarmsQuery>,
 ) -> Result<Json<Vec<FarmResponse>>, AppError> {
-    let farms_with_geojson = repository::get_by_user_with_geojson(&state.db, claims.sub).await?;
+    service::validate_simplify(query.simplify)?;
+    let farms_with_geojson = repository::get_by_user_with_geojson(&state.db, claims.sub, query.simplify, query.include_archived).await?;
     
     let responses = farms_with_geojson
         .into_iter()
@@ -40,69 +102,189 @@ pub async fn list_farms(
     Ok(Json(responses))
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id"), SimplifyQuery),
+    responses(
+        (status = 200, description = "Farm details", body = FarmResponse),
+        (status = 400, description = "Invalid tolerance", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
 pub async fn get_farm(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Path(id): Path<i64>,
+    Query(query): Query<SimplifyQuery>,
 ) -> Result<Json<FarmResponse>, AppError> {
+    service::validate_simplify(query.simplify)?;
     let farm = repository::get_by_id(&state.db, id)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Farm {} not found", id)))?;
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
 
     if farm.user_id != claims.sub {
         return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
     }
 
-    let geojson = repository::get_geojson(&state.db, farm.id)
+    let geojson = repository::get_geojson(&state.db, farm.id, query.simplify)
         .await?
         .ok_or_else(|| AppError::Internal("Failed to retrieve GeoJSON".to_string()))?;
 
     Ok(Json(FarmResponse::from_farm(farm, geojson)))
 }
 
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id")),
+    request_body = UpdateFarmRequest,
+    responses(
+        (status = 200, description = "Farm updated", body = FarmResponse),
+        (status = 400, description = "Invalid polygon; details.problems lists every problem found", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+        (status = 409, description = "Farm is archived", body = ErrorResponse),
+        (status = 422, description = "Blank or overlong name", body = ErrorResponse),
+    ),
+)]
 pub async fn update_farm(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Path(id): Path<i64>,
-    Json(payload): Json<UpdateFarmRequest>,
-) -> Result<Json<FarmResponse>, AppError> {
+    ValidatedJson(payload): ValidatedJson<UpdateFarmRequest>,
+) -> Result<(Extension<AuditDetails>, Json<FarmResponse>), AppError> {
     let existing = repository::get_by_id(&state.db, id)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Farm {} not found", id)))?;
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
 
     if existing.user_id != claims.sub {
         return Err(AppError::Unauthorized("Not authorized to update this farm".to_string()));
     }
+    service::ensure_active(&existing)?;
 
-    let normalized_geojson = if let Some(ref geojson) = payload.geojson {
-        service::validate_polygon(geojson)?;
-        Some(service::normalize_geojson(geojson)?)
-    } else {
-        None
+    let boundary = match payload.geojson {
+        Some(ref geojson) => Some(service::prepare_boundary(&state.db, geojson, payload.crs.as_deref()).await?),
+        None => None,
     };
 
     let farm = repository::update(
         &state.db,
         id,
+        claims.sub,
         payload.name.as_deref(),
-        normalized_geojson.as_deref(),
+        payload.region.as_deref(),
+        boundary.as_ref().map(|b| b.geojson.as_str()),
     ).await?;
 
-    let geojson = repository::get_geojson(&state.db, farm.id)
+    let geojson = repository::get_geojson(&state.db, farm.id, None)
         .await?
         .ok_or_else(|| AppError::Internal("Failed to retrieve GeoJSON".to_string()))?;
 
-    Ok(Json(FarmResponse::from_farm(farm, geojson)))
+    let response = FarmResponse {
+        geometry_repairs: boundary.map(|b| b.repairs).unwrap_or_default(),
+        ..FarmResponse::from_farm(farm, geojson)
+    };
+    let audit = AuditDetails::new("farm.update", "farm", Some(id))
+        .before(&existing)
+        .after(&response);
+
+    Ok((Extension(audit), Json(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/history",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id"), SimplifyQuery),
+    responses(
+        (status = 200, description = "Boundary versions, newest first", body = [FarmGeometryVersion]),
+        (status = 400, description = "Invalid tolerance", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_farm_history(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    Query(query): Query<SimplifyQuery>,
+) -> Result<Json<Vec<FarmGeometryVersion>>, AppError> {
+    service::validate_simplify(query.simplify)?;
+    let farm = repository::get_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
+
+    if farm.user_id != claims.sub {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let versions = repository::list_geometry_versions(&state.db, id, query.simplify).await?;
+    Ok(Json(versions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/history/{version}/restore",
+    tag = "farms",
+    params(
+        ("id" = i64, Path, description = "Farm id"),
+        ("version" = i32, Path, description = "Boundary version to restore"),
+    ),
+    responses(
+        (status = 200, description = "Boundary restored as a new version", body = FarmResponse),
+        (status = 404, description = "Farm or version not found", body = ErrorResponse),
+        (status = 409, description = "Farm is archived", body = ErrorResponse),
+    ),
+)]
+pub async fn restore_farm_geometry(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, version)): Path<(i64, i32)>,
+) -> Result<(Extension<AuditDetails>, Json<FarmResponse>), AppError> {
+    let existing = repository::get_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
+
+    if existing.user_id != claims.sub {
+        return Err(AppError::Unauthorized("Not authorized to update this farm".to_string()));
+    }
+    service::ensure_active(&existing)?;
+
+    let farm = repository::restore_geometry(&state.db, id, version, claims.sub)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Geometry version {} not found for farm {}", version, id)))?;
+
+    let geojson = repository::get_geojson(&state.db, farm.id, None)
+        .await?
+        .ok_or_else(|| AppError::Internal("Failed to retrieve GeoJSON".to_string()))?;
+
+    let response = FarmResponse::from_farm(farm, geojson);
+    let audit = AuditDetails::new("farm.restore_geometry", "farm", Some(id))
+        .before(&existing)
+        .after(&response);
+
+    Ok((Extension(audit), Json(response)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id")),
+    responses(
+        (status = 200, description = "Farm and its history permanently deleted"),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
 pub async fn delete_farm(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Path(id): Path<i64>,
-) -> Result<Json<serde_json::Value>, AppError> {
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
     let existing = repository::get_by_id(&state.db, id)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Farm {} not found", id)))?;
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
 
     if existing.user_id != claims.sub {
         return Err(AppError::Unauthorized("Not authorized to delete this farm".to_string()));
@@ -110,28 +292,355 @@ pub async fn delete_farm(
 
     repository::delete(&state.db, id).await?;
 
-    Ok(Json(serde_json::json!({ "success": true })))
+    let audit = AuditDetails::new("farm.delete", "farm", Some(id)).before(&existing);
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/archive",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id")),
+    responses(
+        (status = 200, description = "Farm archived; it is hidden from listings and purged after the retention period", body = FarmResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+        (status = 409, description = "Farm is already archived", body = ErrorResponse),
+    ),
+)]
+pub async fn archive_farm(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<FarmResponse>), AppError> {
+    set_archived(&state, &claims, id, true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/restore",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id")),
+    responses(
+        (status = 200, description = "Farm restored from the archive", body = FarmResponse),
+        (status = 403, description = "Farm quota of the caller's plan reached", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+        (status = 409, description = "Farm is not archived", body = ErrorResponse),
+    ),
+)]
+pub async fn restore_farm(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<FarmResponse>), AppError> {
+    set_archived(&state, &claims, id, false).await
+}
+
+async fn set_archived(
+    state: &AppState,
+    claims: &Claims,
+    id: i64,
+    archived: bool,
+) -> Result<(Extension<AuditDetails>, Json<FarmResponse>), AppError> {
+    let existing = repository::get_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
+
+    if existing.user_id != claims.sub {
+        return Err(AppError::Unauthorized("Not authorized to update this farm".to_string()));
+    }
+    if !archived && existing.deleted_at.is_some() {
+        quota::ensure_farm_capacity(&state.db, claims.sub, 1).await?;
+    }
+
+    let farm = repository::set_archived(&state.db, id, archived)
+        .await?
+        .ok_or_else(|| AppError::Coded(
+            ErrorCode::FarmArchived,
+            format!("Farm {} is {}", id, if archived { "already archived" } else { "not archived" }),
+        ))?;
+
+    let geojson = repository::get_geojson(&state.db, farm.id, None)
+        .await?
+        .ok_or_else(|| AppError::Internal("Failed to retrieve GeoJSON".to_string()))?;
+
+    let response = FarmResponse::from_farm(farm, geojson);
+    let action = if archived { "farm.archive" } else { "farm.restore" };
+    let audit = AuditDetails::new(action, "farm", Some(id))
+        .before(&existing)
+        .after(&response);
+
+    Ok((Extension(audit), Json(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/convert/wkt",
+    tag = "farms",
+    request_body = ConvertRequest,
+    responses(
+        (status = 200, description = "WKT representation", body = ConvertResponse),
+        (status = 400, description = "Invalid GeoJSON", body = ErrorResponse),
+    ),
+)]
 pub async fn convert_to_wkt(
-    Json(payload): Json<ConvertRequest>,
+    ValidatedJson(payload): ValidatedJson<ConvertRequest>,
 ) -> Result<Json<ConvertResponse>, AppError> {
     let wkt = parse_geojson_to_wkt(&payload.geojson)?;
     Ok(Json(ConvertResponse { wkt }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/intersect",
+    tag = "farms",
+    params(IntersectionQuery),
+    responses(
+        (status = 200, description = "Farms intersecting the geometry, largest overlap first. \
+            Admins search every owner's farms.", body = [IntersectingFarmResponse]),
+        (status = 400, description = "Invalid geometry or tolerance", body = ErrorResponse),
+    ),
+)]
 pub async fn find_intersecting_farms(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Query(query): Query<IntersectionQuery>,
-) -> Result<Json<Vec<FarmResponse>>, AppError> {
-    let farms = repository::find_intersecting(&state.db, &query.bbox_geojson).await?;
-    
-    let mut responses = Vec::with_capacity(farms.len());
-    for farm in farms {
-        if let Some(geojson) = repository::get_geojson(&state.db, farm.id).await? {
-            responses.push(FarmResponse::from_farm(farm, geojson));
-        }
+) -> Result<Json<Vec<IntersectingFarmResponse>>, AppError> {
+    service::validate_simplify(query.simplify)?;
+    let geometry = service::prepare_boundary(&state.db, &query.bbox_geojson, None).await?.geojson;
+
+    let owner = (!claims.is_admin()).then_some(claims.sub);
+    let farms = repository::find_intersecting(&state.db, owner, &geometry, query.simplify).await?;
+
+    let responses = farms
+        .into_iter()
+        .map(|(farm, geojson, overlap_hectares)| IntersectingFarmResponse {
+            farm: FarmResponse::from_farm(farm, geojson),
+            overlap_hectares,
+        })
+        .collect();
+
+    Ok(Json(responses))
+}
+
+#[utoipa::path(
+    get,
+    path = "/nearby",
+    tag = "farms",
+    params(NearbyQuery),
+    responses(
+        (status = 200, description = "Farms within the radius, nearest first. \
+            Admins search every owner's farms.", body = [NearbyFarmResponse]),
+        (status = 400, description = "Invalid point, radius or tolerance", body = ErrorResponse),
+    ),
+)]
+pub async fn find_nearby_farms(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<NearbyQuery>,
+) -> Result<Json<Vec<NearbyFarmResponse>>, AppError> {
+    if !(-180.0..=180.0).contains(&query.lon) || !(-90.0..=90.0).contains(&query.lat) {
+        return Err(AppError::Validation("lon/lat out of range".to_string()));
+    }
+
+    if !(query.radius_m > 0.0 && query.radius_m <= service::MAX_NEARBY_RADIUS_M) {
+        return Err(AppError::Validation(format!(
+            "radius_m must be greater than 0 and at most {}",
+            service::MAX_NEARBY_RADIUS_M
+        )));
     }
+    service::validate_simplify(query.simplify)?;
+
+    let owner = (!claims.is_admin()).then_some(claims.sub);
+    let farms = repository::find_nearby(&state.db, owner, query.lon, query.lat, query.radius_m, query.simplify).await?;
+
+    let responses = farms
+        .into_iter()
+        .map(|(farm, geojson, distance_m)| NearbyFarmResponse {
+            farm: FarmResponse::from_farm(farm, geojson),
+            distance_m,
+        })
+        .collect();
 
     Ok(Json(responses))
-}
\ No newline at end of file
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/seasons",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id")),
+    responses(
+        (status = 200, description = "Crop seasons, most recent planting first", body = [CropSeason]),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn list_crop_seasons(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<CropSeason>>, AppError> {
+    let farm = repository::get_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
+
+    if farm.user_id != claims.sub {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let seasons = repository::list_seasons(&state.db, id).await?;
+    Ok(Json(seasons))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/seasons",
+    tag = "farms",
+    params(("id" = i64, Path, description = "Farm id")),
+    request_body = CreateCropSeasonRequest,
+    responses(
+        (status = 200, description = "Crop season created", body = CropSeason),
+        (status = 400, description = "Invalid dates", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+        (status = 409, description = "Farm is archived", body = ErrorResponse),
+        (status = 422, description = "Blank crop type or overlong variety", body = ErrorResponse),
+    ),
+)]
+pub async fn create_crop_season(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<CreateCropSeasonRequest>,
+) -> Result<(Extension<AuditDetails>, Json<CropSeason>), AppError> {
+    let farm = repository::get_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
+
+    if farm.user_id != claims.sub {
+        return Err(AppError::Unauthorized("Not authorized to update this farm".to_string()));
+    }
+    service::ensure_active(&farm)?;
+
+    service::validate_season_dates(payload.planting_date, payload.expected_harvest_date)?;
+
+    let season = repository::create_season(&state.db, id, &payload).await?;
+    let audit = AuditDetails::new("crop_season.create", "crop_season", Some(season.id)).after(&season);
+
+    Ok((Extension(audit), Json(season)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}/seasons/{season_id}",
+    tag = "farms",
+    params(
+        ("id" = i64, Path, description = "Farm id"),
+        ("season_id" = i64, Path, description = "Crop season id"),
+    ),
+    request_body = UpdateCropSeasonRequest,
+    responses(
+        (status = 200, description = "Crop season updated", body = CropSeason),
+        (status = 400, description = "Invalid dates", body = ErrorResponse),
+        (status = 404, description = "Farm or season not found", body = ErrorResponse),
+        (status = 409, description = "Farm is archived", body = ErrorResponse),
+        (status = 422, description = "Overlong variety", body = ErrorResponse),
+    ),
+)]
+pub async fn update_crop_season(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, season_id)): Path<(i64, i64)>,
+    ValidatedJson(payload): ValidatedJson<UpdateCropSeasonRequest>,
+) -> Result<(Extension<AuditDetails>, Json<CropSeason>), AppError> {
+    let farm = repository::get_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
+
+    if farm.user_id != claims.sub {
+        return Err(AppError::Unauthorized("Not authorized to update this farm".to_string()));
+    }
+    service::ensure_active(&farm)?;
+
+    let existing = repository::get_season(&state.db, id, season_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Crop season {} not found for farm {}", season_id, id)))?;
+
+    service::validate_season_dates(
+        existing.planting_date,
+        payload.expected_harvest_date.or(existing.expected_harvest_date),
+    )?;
+
+    let season = repository::update_season(&state.db, season_id, &payload).await?;
+    let audit = AuditDetails::new("crop_season.update", "crop_season", Some(season_id))
+        .before(&existing)
+        .after(&season);
+
+    Ok((Extension(audit), Json(season)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}/seasons/{season_id}",
+    tag = "farms",
+    params(
+        ("id" = i64, Path, description = "Farm id"),
+        ("season_id" = i64, Path, description = "Crop season id"),
+    ),
+    responses(
+        (status = 200, description = "Crop season deleted"),
+        (status = 404, description = "Farm or season not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_crop_season(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, season_id)): Path<(i64, i64)>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    let farm = repository::get_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)))?;
+
+    if farm.user_id != claims.sub {
+        return Err(AppError::Unauthorized("Not authorized to update this farm".to_string()));
+    }
+
+    let existing = repository::get_season(&state.db, id, season_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Crop season {} not found for farm {}", season_id, id)))?;
+
+    repository::delete_season(&state.db, season_id).await?;
+
+    let audit = AuditDetails::new("crop_season.delete", "crop_season", Some(season_id)).before(&existing);
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/mvt/{z}/{x}/{y}",
+    tag = "farms",
+    params(
+        ("z" = u32, Path, description = "Zoom level"),
+        ("x" = u32, Path, description = "Tile column"),
+        ("y" = u32, Path, description = "Tile row"),
+        FarmTileQuery,
+    ),
+    responses(
+        (status = 200, description = "Mapbox Vector Tile with a `farms` layer of active farm boundaries, keyed by farm id. \
+            Admins see every owner's farms.", content_type = "application/vnd.mapbox-vector-tile", body = Vec<u8>),
+        (status = 400, description = "Tile out of range or unknown field", body = ErrorResponse),
+    ),
+)]
+pub async fn get_farm_tile(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(tile): Path<TileCoord>,
+    Query(query): Query<FarmTileQuery>,
+) -> Result<Response, AppError> {
+    tile.validate()?;
+    let fields = tiles::select_fields(query.fields.as_deref(), repository::FARM_TILE_FIELDS)?;
+
+    let owner = (!claims.is_admin()).then_some(claims.sub);
+    let tile = repository::farm_tile(&state.db, owner, tile, &fields).await?;
+    Ok(tiles::response(tile))
+}
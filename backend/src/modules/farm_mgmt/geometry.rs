@@ -0,0 +1,201 @@
+//! Structural checks and safe repairs for uploaded farm boundaries. Checks
+//! that need a geometry engine, such as self-intersections, run in PostGIS.
+
+use geojson::{GeoJson, Geometry, JsonObject, Position, Value};
+use serde::Serialize;
+use utoipa::ToSchema;
+use crate::shared::crs::Crs;
+
+/// Uploads above this are rejected outright; smaller ones are simplified on
+/// ingest once they pass the stored-vertex limit.
+const MAX_UPLOAD_VERTICES: usize = 100_000;
+/// Bad coordinates are reported one by one up to this many.
+const MAX_REPORTED_COORDINATES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GeometryProblemKind {
+    InvalidGeojson,
+    UnsupportedCrs,
+    UnsupportedType,
+    NoRings,
+    TooManyVertices,
+    InvalidCoordinate,
+    CoordinateOutOfRange,
+    UnclosedRing,
+    TooFewPoints,
+    WrongWinding,
+    SelfIntersection,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GeometryProblem {
+    pub kind: GeometryProblemKind,
+    /// Ring index; 0 is the exterior ring.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ring: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertex: Option<usize>,
+    pub message: String,
+    /// Fixed automatically, so the stored boundary differs from the upload.
+    pub repaired: bool,
+}
+
+impl GeometryProblem {
+    pub fn new(kind: GeometryProblemKind, message: impl Into<String>) -> Self {
+        Self { kind, ring: None, vertex: None, message: message.into(), repaired: false }
+    }
+
+    fn at(mut self, ring: usize, vertex: Option<usize>) -> Self {
+        self.ring = Some(ring);
+        self.vertex = vertex;
+        self
+    }
+
+    pub fn repaired(mut self) -> Self {
+        self.repaired = true;
+        self
+    }
+}
+
+/// Checks a GeoJSON Polygon (bare or wrapped in a Feature). Unclosed rings
+/// are closed and rings are rewound to RFC 7946 order (exterior
+/// counter-clockwise, holes clockwise). The coordinates are in `crs` when
+/// given, else in the CRS the GeoJSON declares, else WGS 84. Returns the
+/// repaired geometry, its CRS and the repairs made, or every problem found
+/// when any cannot be repaired.
+pub fn inspect_polygon(
+    geojson_str: &str,
+    crs: Option<Crs>,
+) -> Result<(Geometry, Crs, Vec<GeometryProblem>), Vec<GeometryProblem>> {
+    let (geometry, declared) = parse_geometry(geojson_str)
+        .map_err(|message| vec![GeometryProblem::new(GeometryProblemKind::InvalidGeojson, message)])?;
+
+    let crs = match crs {
+        Some(crs) => crs,
+        None => Crs::from_geojson_member(declared.as_ref())
+            .map_err(|e| vec![GeometryProblem::new(GeometryProblemKind::UnsupportedCrs, e.to_string())])?
+            .unwrap_or(Crs::WGS84),
+    };
+
+    let Value::Polygon(mut rings) = geometry.value else {
+        return Err(vec![GeometryProblem::new(
+            GeometryProblemKind::UnsupportedType,
+            "Only Polygon geometry is supported",
+        )]);
+    };
+
+    if rings.is_empty() {
+        return Err(vec![GeometryProblem::new(GeometryProblemKind::NoRings, "Polygon has no rings")]);
+    }
+
+    let vertices: usize = rings.iter().map(Vec::len).sum();
+    if vertices > MAX_UPLOAD_VERTICES {
+        return Err(vec![GeometryProblem::new(
+            GeometryProblemKind::TooManyVertices,
+            format!("Polygon has {} vertices; at most {} are accepted", vertices, MAX_UPLOAD_VERTICES),
+        )]);
+    }
+
+    let mut problems = Vec::new();
+    let mut bad_coordinates = 0;
+
+    for (r, ring) in rings.iter_mut().enumerate() {
+        for (v, point) in ring.iter().enumerate() {
+            let problem = coordinate_problem(point, crs).map(|p| p.at(r, Some(v)));
+            if let Some(problem) = problem {
+                bad_coordinates += 1;
+                if bad_coordinates <= MAX_REPORTED_COORDINATES {
+                    problems.push(problem);
+                }
+            }
+        }
+
+        if ring.len() >= 3 && ring.first() != ring.last() {
+            ring.push(ring[0].clone());
+            problems.push(
+                GeometryProblem::new(GeometryProblemKind::UnclosedRing, "Ring was closed by repeating its first point")
+                    .at(r, None)
+                    .repaired(),
+            );
+        }
+
+        if ring.len() < 4 {
+            problems.push(
+                GeometryProblem::new(GeometryProblemKind::TooFewPoints, "Ring must have at least 4 points")
+                    .at(r, None),
+            );
+        }
+    }
+
+    if bad_coordinates > MAX_REPORTED_COORDINATES {
+        problems.push(GeometryProblem::new(
+            GeometryProblemKind::InvalidCoordinate,
+            format!("{} more invalid coordinates not listed", bad_coordinates - MAX_REPORTED_COORDINATES),
+        ));
+    }
+
+    if problems.iter().any(|p| !p.repaired) {
+        return Err(problems);
+    }
+
+    for (r, ring) in rings.iter_mut().enumerate() {
+        let area = signed_area(ring);
+        let counter_clockwise = area > 0.0;
+        if area != 0.0 && counter_clockwise != (r == 0) {
+            ring.reverse();
+            let expected = if r == 0 { "counter-clockwise" } else { "clockwise" };
+            problems.push(
+                GeometryProblem::new(GeometryProblemKind::WrongWinding, format!("Ring was rewound {}", expected))
+                    .at(r, None)
+                    .repaired(),
+            );
+        }
+    }
+
+    Ok((Geometry::new(Value::Polygon(rings)), crs, problems))
+}
+
+/// The geometry plus the foreign members that may carry a legacy `crs`.
+fn parse_geometry(geojson_str: &str) -> Result<(Geometry, Option<JsonObject>), String> {
+    let geojson: GeoJson = geojson_str.parse().map_err(|e| format!("Invalid GeoJSON: {}", e))?;
+
+    match geojson {
+        GeoJson::Geometry(mut geometry) => {
+            let members = geometry.foreign_members.take();
+            Ok((geometry, members))
+        }
+        GeoJson::Feature(feature) => feature
+            .geometry
+            .map(|geometry| (geometry, feature.foreign_members))
+            .ok_or_else(|| "Feature has no geometry".to_string()),
+        GeoJson::FeatureCollection(_) => Err("FeatureCollection not supported, use single Polygon".to_string()),
+    }
+}
+
+fn coordinate_problem(point: &Position, crs: Crs) -> Option<GeometryProblem> {
+    if point.len() < 2 || !point[0].is_finite() || !point[1].is_finite() {
+        return Some(GeometryProblem::new(GeometryProblemKind::InvalidCoordinate, "Invalid coordinate"));
+    }
+    if !crs.is_geographic() {
+        return None;
+    }
+
+    let (lon, lat) = (point[0], point[1]);
+    if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+        return Some(GeometryProblem::new(
+            GeometryProblemKind::CoordinateOutOfRange,
+            format!("Invalid coordinates: [{}, {}]", lon, lat),
+        ));
+    }
+
+    None
+}
+
+/// Shoelace area in squared degrees; positive for counter-clockwise rings.
+fn signed_area(ring: &[Position]) -> f64 {
+    ring.windows(2)
+        .map(|pair| pair[0][0] * pair[1][1] - pair[1][0] * pair[0][1])
+        .sum::<f64>()
+        / 2.0
+}
@@ -0,0 +1,21 @@
+use sqlx::PgPool;
+use crate::shared::{runtime, worker::{interval_from_env, spawn_periodic}};
+use super::repository;
+
+const FARM_PURGE_JOB_DEFAULT_SECS: u64 = 60 * 60;
+
+pub fn spawn_archive_purge_job(db: PgPool) {
+    let period = interval_from_env("FARM_PURGE_JOB_INTERVAL_SECS", FARM_PURGE_JOB_DEFAULT_SECS);
+
+    spawn_periodic("farm_archive_purge", period, move || {
+        let db = db.clone();
+        async move {
+            let retention_days = runtime::current().archived_farm_retention_days as i32;
+            let deleted = repository::purge_archived(&db, retention_days).await?;
+            if deleted > 0 {
+                tracing::info!("Deleted {} farms archived more than {} days ago", deleted, retention_days);
+            }
+            Ok(())
+        }
+    });
+}
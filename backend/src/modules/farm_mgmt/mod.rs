@@ -1,18 +1,63 @@
+mod geometry;
 mod models;
 mod repository;
 mod service;
 mod controller;
+pub mod jobs;
 
-use axum::{routing::{get, post, put, delete}, Router};
+pub use models::{CropSeason, GrowthStage};
+pub use repository::insert as insert_farm;
+
+use axum::{extract::DefaultBodyLimit, routing::{get, post, put, delete}, Router};
+use utoipa::OpenApi;
 use crate::shared::AppState;
 
+// Several hundred detailed parcel polygons easily exceed axum's 2 MB default.
+const BULK_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", post(controller::create_farm))
         .route("/", get(controller::list_farms))
+        .route("/bulk", post(controller::bulk_create_farms).layer(DefaultBodyLimit::max(BULK_BODY_LIMIT)))
         .route("/{id}", get(controller::get_farm))
         .route("/{id}", put(controller::update_farm))
         .route("/{id}", delete(controller::delete_farm))
+        .route("/{id}/archive", post(controller::archive_farm))
+        .route("/{id}/restore", post(controller::restore_farm))
+        .route("/{id}/history", get(controller::get_farm_history))
+        .route("/{id}/history/{version}/restore", post(controller::restore_farm_geometry))
+        .route("/{id}/seasons", get(controller::list_crop_seasons).post(controller::create_crop_season))
+        .route("/{id}/seasons/{season_id}", put(controller::update_crop_season).delete(controller::delete_crop_season))
         .route("/convert/wkt", post(controller::convert_to_wkt))
         .route("/intersect", get(controller::find_intersecting_farms))
+        .route("/nearby", get(controller::find_nearby_farms))
+        .route("/mvt/{z}/{x}/{y}", get(controller::get_farm_tile))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::create_farm,
+    controller::bulk_create_farms,
+    controller::list_farms,
+    controller::get_farm,
+    controller::update_farm,
+    controller::delete_farm,
+    controller::archive_farm,
+    controller::restore_farm,
+    controller::get_farm_history,
+    controller::restore_farm_geometry,
+    controller::list_crop_seasons,
+    controller::create_crop_season,
+    controller::update_crop_season,
+    controller::delete_crop_season,
+    controller::convert_to_wkt,
+    controller::find_intersecting_farms,
+    controller::find_nearby_farms,
+    controller::get_farm_tile,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
 }
\ No newline at end of file
@@ -1,38 +1,105 @@
 use serde::{Deserialize, Serialize};
-use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::types::chrono::{DateTime, NaiveDate, Utc};
+use crate::shared::error::AppError;
 use bigdecimal::{BigDecimal, ToPrimitive};
+use utoipa::{IntoParams, ToSchema};
+use super::geometry::GeometryProblem;
+use validator::Validate;
+use crate::shared::validation::not_blank;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Farm {
     pub id: i64,
     pub user_id: i64,
     pub name: String,
+    pub region: Option<String>,
     pub area_hectares: Option<BigDecimal>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Farm {
+    pub fn is_archived(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateFarmRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255))]
     pub name: String,
+    #[serde(default)]
+    pub region: Option<String>,
     pub geojson: String,
+    /// CRS of the coordinates, e.g. `EPSG:3405` for VN-2000 / UTM 48N.
+    /// Defaults to the GeoJSON `crs` member, then WGS 84.
+    #[serde(default)]
+    pub crs: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkMode {
+    /// All farms are created or none are.
+    #[default]
+    Atomic,
+    /// Valid farms are created; invalid ones are reported.
+    Partial,
+}
+
+pub const MAX_BULK_FARMS: u64 = 500;
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct BulkCreateFarmsRequest {
+    #[validate(length(min = 1, max = MAX_BULK_FARMS))]
+    pub farms: Vec<CreateFarmRequest>,
+    #[serde(default)]
+    pub mode: BulkMode,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkFarmResult {
+    /// Position of the farm in the request array.
+    pub index: usize,
+    pub id: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkCreateFarmsResponse {
+    pub created: usize,
+    pub failed: usize,
+    pub results: Vec<BulkFarmResult>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 pub struct UpdateFarmRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255))]
     pub name: Option<String>,
+    pub region: Option<String>,
     pub geojson: Option<String>,
+    /// CRS of `geojson`; see `CreateFarmRequest::crs`.
+    #[serde(default)]
+    pub crs: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FarmResponse {
     pub id: i64,
     pub user_id: i64,
     pub name: String,
+    pub region: Option<String>,
     pub geojson: String,
     pub area_hectares: Option<f64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the farm was archived; archived farms are purged after the
+    /// configured retention period.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Fixes applied to the uploaded boundary; only set on create and update.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub geometry_repairs: Vec<GeometryProblem>,
 }
 
 impl FarmResponse {
@@ -41,25 +108,170 @@ impl FarmResponse {
             id: farm.id,
             user_id: farm.user_id,
             name: farm.name,
+            region: farm.region,
             geojson,
             area_hectares: farm.area_hectares.and_then(|bd| bd.to_f64()),
             created_at: farm.created_at,
             updated_at: farm.updated_at,
+            deleted_at: farm.deleted_at,
+            geometry_repairs: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// A historical farm boundary; the highest version is the current one.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct FarmGeometryVersion {
+    pub version: i32,
+    pub geojson: String,
+    pub area_hectares: Option<f64>,
+    pub changed_by: Option<i64>,
+    pub restored_from: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 pub struct ConvertRequest {
     pub geojson: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ConvertResponse {
     pub wkt: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct IntersectionQuery {
     pub bbox_geojson: String,
-}
\ No newline at end of file
+    /// Simplification tolerance in degrees applied to returned boundaries.
+    pub simplify: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SimplifyQuery {
+    /// Simplification tolerance in degrees applied to returned boundaries.
+    pub simplify: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListFarmsQuery {
+    /// Simplification tolerance in degrees applied to returned boundaries.
+    pub simplify: Option<f64>,
+    /// Also list archived farms.
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct NearbyQuery {
+    pub lon: f64,
+    pub lat: f64,
+    pub radius_m: f64,
+    /// Simplification tolerance in degrees applied to returned boundaries.
+    pub simplify: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FarmTileQuery {
+    /// Comma-separated attributes to include: `name`, `region`,
+    /// `area_hectares`, `latest_ndsi`. All of them by default.
+    pub fields: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IntersectingFarmResponse {
+    #[serde(flatten)]
+    pub farm: FarmResponse,
+    /// Area of the farm inside the query geometry.
+    pub overlap_hectares: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NearbyFarmResponse {
+    #[serde(flatten)]
+    pub farm: FarmResponse,
+    /// Distance from the query point to the farm boundary; 0 inside the farm.
+    pub distance_m: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GrowthStage {
+    Germination,
+    Seedling,
+    Vegetative,
+    Reproductive,
+    Ripening,
+    Harvested,
+}
+
+impl GrowthStage {
+    pub fn as_str(&self) -> &str {
+        match self {
+            GrowthStage::Germination => "germination",
+            GrowthStage::Seedling => "seedling",
+            GrowthStage::Vegetative => "vegetative",
+            GrowthStage::Reproductive => "reproductive",
+            GrowthStage::Ripening => "ripening",
+            GrowthStage::Harvested => "harvested",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "germination" => Some(GrowthStage::Germination),
+            "seedling" => Some(GrowthStage::Seedling),
+            "vegetative" => Some(GrowthStage::Vegetative),
+            "reproductive" => Some(GrowthStage::Reproductive),
+            "ripening" => Some(GrowthStage::Ripening),
+            "harvested" => Some(GrowthStage::Harvested),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for GrowthStage {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        GrowthStage::from_code(&value)
+            .ok_or_else(|| AppError::Validation(format!("Unsupported growth stage: {}", value)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CropSeason {
+    pub id: i64,
+    pub farm_id: i64,
+    pub crop_type: String,
+    pub variety: Option<String>,
+    pub planting_date: NaiveDate,
+    pub expected_harvest_date: Option<NaiveDate>,
+    #[sqlx(try_from = "String")]
+    pub growth_stage: GrowthStage,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateCropSeasonRequest {
+    #[validate(custom(function = "not_blank"), length(max = 30))]
+    pub crop_type: String,
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub variety: Option<String>,
+    pub planting_date: NaiveDate,
+    #[serde(default)]
+    pub expected_harvest_date: Option<NaiveDate>,
+    /// Defaults to `germination`.
+    #[serde(default)]
+    pub growth_stage: Option<GrowthStage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateCropSeasonRequest {
+    #[validate(length(max = 100))]
+    pub variety: Option<String>,
+    pub expected_harvest_date: Option<NaiveDate>,
+    pub growth_stage: Option<GrowthStage>,
+}
@@ -1,32 +1,60 @@
-use sqlx::{PgPool, Row};
-use crate::shared::error::AppError;
-use super::models::Farm;
+use sqlx::{postgres::PgRow, PgConnection, PgPool, Row};
+use crate::shared::{error::{AppError, ErrorCode}, postgis, tiles::{TileCoord, TileField}};
+use super::models::{CreateCropSeasonRequest, CropSeason, Farm, FarmGeometryVersion, UpdateCropSeasonRequest};
+
+/// Boundaries above this are simplified on ingest. Area is still computed from
+/// the boundary as uploaded.
+const MAX_STORED_VERTICES: usize = 2_000;
 
 pub async fn create(
     pool: &PgPool,
     user_id: i64,
     name: &str,
+    region: Option<&str>,
     geojson: &str,
 ) -> Result<Farm, AppError> {
-    sqlx::query_as::<_, Farm>(
+    let mut tx = pool.begin().await?;
+    let farm = insert(&mut tx, user_id, name, region, geojson).await?;
+    tx.commit().await?;
+
+    Ok(farm)
+}
+
+/// Inserts a farm and its first geometry version on an existing connection,
+/// letting callers batch several farms into one transaction.
+pub async fn insert(
+    conn: &mut PgConnection,
+    user_id: i64,
+    name: &str,
+    region: Option<&str>,
+    geojson: &str,
+) -> Result<Farm, AppError> {
+    let uploaded = postgis::from_geojson("$3");
+    let farm = sqlx::query_as::<_, Farm>(&format!(
         r#"
-        INSERT INTO farms (user_id, name, geometry, area_hectares)
-        VALUES ($1, $2, ST_GeomFromGeoJSON($3), ST_Area(ST_GeomFromGeoJSON($3)::geography) / 10000)
-        RETURNING id, user_id, name, area_hectares, created_at, updated_at
-        "#
-    )
+        INSERT INTO farms (user_id, name, region, geometry, area_hectares)
+        VALUES ($1, $2, $4, {geometry}, {area})
+        RETURNING id, user_id, name, region, area_hectares, created_at, updated_at, deleted_at
+        "#,
+        geometry = postgis::limit_vertices(&uploaded, MAX_STORED_VERTICES),
+        area = postgis::area_hectares(&uploaded),
+    ))
     .bind(user_id)
     .bind(name)
     .bind(geojson)
-    .fetch_one(pool)
-    .await
-    .map_err(Into::into)
+    .bind(region)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    record_geometry_version(conn, farm.id, user_id, None).await?;
+
+    Ok(farm)
 }
 
 pub async fn get_by_id(pool: &PgPool, id: i64) -> Result<Option<Farm>, AppError> {
     sqlx::query_as::<_, Farm>(
         r#"
-        SELECT id, user_id, name, area_hectares, created_at, updated_at 
+        SELECT id, user_id, name, region, area_hectares, created_at, updated_at, deleted_at
         FROM farms WHERE id = $1
         "#
     )
@@ -36,74 +64,83 @@ pub async fn get_by_id(pool: &PgPool, id: i64) -> Result<Option<Farm>, AppError>
     .map_err(Into::into)
 }
 
+/// Farms of `user_id` with their boundaries, simplified by `simplify` degrees
+/// when given.
 pub async fn get_by_user_with_geojson(
     pool: &PgPool, 
-    user_id: i64
+    user_id: i64,
+    simplify: Option<f64>,
+    include_archived: bool,
 ) -> Result<Vec<(Farm, String)>, AppError> {
-    let rows = sqlx::query(
+    let rows = sqlx::query(&format!(
         r#"
         SELECT 
-            f.id, f.user_id, f.name, f.area_hectares, f.created_at, f.updated_at,
-            ST_AsGeoJSON(f.geometry) as geojson
+            f.id, f.user_id, f.name, f.region, f.area_hectares, f.created_at, f.updated_at, f.deleted_at,
+            {geojson} as geojson
         FROM farms f
-        WHERE f.user_id = $1
+        WHERE f.user_id = $1 AND ($3 OR f.deleted_at IS NULL)
         ORDER BY f.created_at DESC
         "#,
-    )
+        geojson = postgis::as_geojson("f.geometry", "$2"),
+    ))
     .bind(user_id)
+    .bind(simplify)
+    .bind(include_archived)
     .fetch_all(pool)
     .await?;
 
-    rows.into_iter()
-        .map(|row| {
-            let farm = Farm {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                name: row.get("name"),
-                area_hectares: row.get("area_hectares"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            let geojson: Option<String> = row.get("geojson");
-            Ok((farm, geojson.unwrap_or_else(|| "{}".to_string())))
-        })
-        .collect()
+    Ok(rows.iter().map(|row| (farm_from_row(row), geojson_from_row(row))).collect())
 }
 
 pub async fn update(
     pool: &PgPool,
     id: i64,
+    changed_by: i64,
     name: Option<&str>,
+    region: Option<&str>,
     geojson: Option<&str>,
 ) -> Result<Farm, AppError> {
     let farm = if let Some(geo) = geojson {
-        sqlx::query_as::<_, Farm>(
+        let mut tx = pool.begin().await?;
+
+        let uploaded = postgis::from_geojson("$3");
+        let farm = sqlx::query_as::<_, Farm>(&format!(
             r#"
             UPDATE farms
             SET name = COALESCE($2, name),
-                geometry = ST_GeomFromGeoJSON($3),
-                area_hectares = ST_Area(ST_GeomFromGeoJSON($3)::geography) / 10000,
+                region = COALESCE($4, region),
+                geometry = {geometry},
+                area_hectares = {area},
                 updated_at = NOW()
             WHERE id = $1
-            RETURNING id, user_id, name, area_hectares, created_at, updated_at
-            "#
-        )
+            RETURNING id, user_id, name, region, area_hectares, created_at, updated_at, deleted_at
+            "#,
+            geometry = postgis::limit_vertices(&uploaded, MAX_STORED_VERTICES),
+            area = postgis::area_hectares(&uploaded),
+        ))
         .bind(id)
         .bind(name)
         .bind(geo)
-        .fetch_one(pool)
-        .await?
+        .bind(region)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        record_geometry_version(&mut tx, id, changed_by, None).await?;
+        tx.commit().await?;
+
+        farm
     } else {
         sqlx::query_as::<_, Farm>(
             r#"
             UPDATE farms 
-            SET name = COALESCE($2, name), updated_at = NOW() 
+            SET name = COALESCE($2, name), region = COALESCE($3, region), updated_at = NOW() 
             WHERE id = $1 
-            RETURNING id, user_id, name, area_hectares, created_at, updated_at
+            RETURNING id, user_id, name, region, area_hectares, created_at, updated_at, deleted_at
             "#
         )
         .bind(id)
         .bind(name)
+        .bind(region)
         .fetch_one(pool)
         .await?
     };
@@ -111,6 +148,89 @@ pub async fn update(
     Ok(farm)
 }
 
+/// Points the farm back at the boundary of `version`, recording the restore
+/// itself as a new version. Returns `None` if the version does not exist.
+pub async fn restore_geometry(
+    pool: &PgPool,
+    id: i64,
+    version: i32,
+    changed_by: i64,
+) -> Result<Option<Farm>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let farm = sqlx::query_as::<_, Farm>(
+        r#"
+        UPDATE farms f
+        SET geometry = v.geometry,
+            area_hectares = v.area_hectares,
+            updated_at = NOW()
+        FROM farm_geometry_versions v
+        WHERE f.id = $1 AND v.farm_id = $1 AND v.version = $2
+        RETURNING f.id, f.user_id, f.name, f.region, f.area_hectares, f.created_at, f.updated_at, f.deleted_at
+        "#
+    )
+    .bind(id)
+    .bind(version)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if farm.is_some() {
+        record_geometry_version(&mut tx, id, changed_by, Some(version)).await?;
+        tx.commit().await?;
+    }
+
+    Ok(farm)
+}
+
+pub async fn list_geometry_versions(
+    pool: &PgPool,
+    farm_id: i64,
+    simplify: Option<f64>,
+) -> Result<Vec<FarmGeometryVersion>, AppError> {
+    sqlx::query_as::<_, FarmGeometryVersion>(&format!(
+        r#"
+        SELECT version, {geojson} AS geojson, area_hectares::FLOAT8 AS area_hectares,
+               changed_by, restored_from, created_at
+        FROM farm_geometry_versions
+        WHERE farm_id = $1
+        ORDER BY version DESC
+        "#,
+        geojson = postgis::as_geojson("geometry", "$2"),
+    ))
+    .bind(farm_id)
+    .bind(simplify)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Snapshots the farm's current boundary as the next version number. Must run
+/// in the same transaction as the geometry change so the two never diverge.
+async fn record_geometry_version(
+    conn: &mut PgConnection,
+    farm_id: i64,
+    changed_by: i64,
+    restored_from: Option<i32>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO farm_geometry_versions (farm_id, version, geometry, area_hectares, changed_by, restored_from)
+        SELECT id,
+               COALESCE((SELECT MAX(version) FROM farm_geometry_versions WHERE farm_id = $1), 0) + 1,
+               geometry, area_hectares, $2, $3
+        FROM farms
+        WHERE id = $1
+        "#
+    )
+    .bind(farm_id)
+    .bind(changed_by)
+    .bind(restored_from)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn delete(pool: &PgPool, id: i64) -> Result<(), AppError> {
     let result = sqlx::query("DELETE FROM farms WHERE id = $1")
         .bind(id)
@@ -118,33 +238,288 @@ pub async fn delete(pool: &PgPool, id: i64) -> Result<(), AppError> {
         .await?;
 
     if result.rows_affected() == 0 {
-        return Err(AppError::NotFound(format!("Farm {} not found", id)));
+        return Err(AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", id)));
     }
 
     Ok(())
 }
 
-pub async fn find_intersecting(
-    pool: &PgPool,
-    bbox_geojson: &str,
-) -> Result<Vec<Farm>, AppError> {
+/// Sets or clears `deleted_at`. Returns `None` if the farm was already in the
+/// requested state.
+pub async fn set_archived(pool: &PgPool, id: i64, archived: bool) -> Result<Option<Farm>, AppError> {
     sqlx::query_as::<_, Farm>(
         r#"
-        SELECT id, user_id, name, area_hectares, created_at, updated_at 
-        FROM farms 
-        WHERE ST_Intersects(geometry, ST_GeomFromGeoJSON($1))
+        UPDATE farms
+        SET deleted_at = CASE WHEN $2 THEN NOW() END,
+            updated_at = NOW()
+        WHERE id = $1 AND (deleted_at IS NULL) = $2
+        RETURNING id, user_id, name, region, area_hectares, created_at, updated_at, deleted_at
         "#
     )
-    .bind(bbox_geojson)
+    .bind(id)
+    .bind(archived)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Permanently deletes farms archived more than `retention_days` ago, with
+/// everything that cascades from them.
+pub async fn purge_archived(pool: &PgPool, retention_days: i32) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        "DELETE FROM farms WHERE deleted_at < NOW() - make_interval(days => $1)"
+    )
+    .bind(retention_days)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Farms intersecting `geojson` with the overlapping area in hectares, largest
+/// overlap first. `user_id = None` searches every owner's farms.
+pub async fn find_intersecting(
+    pool: &PgPool,
+    user_id: Option<i64>,
+    geojson: &str,
+    simplify: Option<f64>,
+) -> Result<Vec<(Farm, String, f64)>, AppError> {
+    let rows = sqlx::query(&format!(
+        r#"
+        WITH area AS (SELECT {area} AS geometry)
+        SELECT f.id, f.user_id, f.name, f.region, f.area_hectares, f.created_at, f.updated_at, f.deleted_at,
+               {geojson} AS geojson,
+               {overlap}::FLOAT8 AS measure
+        FROM farms f, area a
+        WHERE ST_Intersects(f.geometry, a.geometry)
+          AND f.deleted_at IS NULL
+          AND ($2::BIGINT IS NULL OR f.user_id = $2)
+        ORDER BY measure DESC
+        "#,
+        area = postgis::from_geojson("$1"),
+        geojson = postgis::as_geojson("f.geometry", "$3"),
+        overlap = postgis::overlap_hectares("f.geometry", "a.geometry"),
+    ))
+    .bind(geojson)
+    .bind(user_id)
+    .bind(simplify)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|row| (farm_from_row(row), geojson_from_row(row), row.get("measure"))).collect())
+}
+
+/// Farms within `radius_m` metres of a point with their distance, nearest
+/// first. `user_id = None` searches every owner's farms.
+pub async fn find_nearby(
+    pool: &PgPool,
+    user_id: Option<i64>,
+    lon: f64,
+    lat: f64,
+    radius_m: f64,
+    simplify: Option<f64>,
+) -> Result<Vec<(Farm, String, f64)>, AppError> {
+    let origin = postgis::point("$1", "$2");
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT f.id, f.user_id, f.name, f.region, f.area_hectares, f.created_at, f.updated_at, f.deleted_at,
+               {geojson} AS geojson,
+               {distance} AS measure
+        FROM farms f
+        WHERE {within}
+          AND f.deleted_at IS NULL
+          AND ($4::BIGINT IS NULL OR f.user_id = $4)
+        ORDER BY measure
+        "#,
+        geojson = postgis::as_geojson("f.geometry", "$5"),
+        distance = postgis::distance_metres("f.geometry", &origin),
+        within = postgis::within_metres("f.geometry", &origin, "$3"),
+    ))
+    .bind(lon)
+    .bind(lat)
+    .bind(radius_m)
+    .bind(user_id)
+    .bind(simplify)
     .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|row| (farm_from_row(row), geojson_from_row(row), row.get("measure"))).collect())
+}
+
+/// Attributes the farms tile layer can carry.
+pub const FARM_TILE_FIELDS: &[TileField] = &[
+    ("name", "f.name"),
+    ("region", "f.region"),
+    ("area_hectares", "f.area_hectares::FLOAT8"),
+    ("latest_ndsi", "(SELECT l.ndsi_value::FLOAT8 FROM salinity_logs l WHERE l.farm_id = f.id ORDER BY l.recorded_at DESC LIMIT 1)"),
+];
+
+/// Active farm boundaries in tile `tile` as an MVT `farms` layer. `fields`
+/// are select expressions built from `FARM_TILE_FIELDS`.
+pub async fn farm_tile(
+    pool: &PgPool,
+    user_id: Option<i64>,
+    tile: TileCoord,
+    fields: &[String],
+) -> Result<Vec<u8>, AppError> {
+    let envelope = postgis::tile_envelope("$1", "$2", "$3");
+    let layer = format!(
+        r#"
+        SELECT f.id, {geom} AS geom{fields}
+        FROM farms f
+        WHERE {in_tile}
+          AND f.deleted_at IS NULL
+          AND ($4::BIGINT IS NULL OR f.user_id = $4)
+        "#,
+        geom = postgis::as_mvt_geom("f.geometry", &envelope),
+        fields = fields.iter().map(|field| format!(", {}", field)).collect::<String>(),
+        in_tile = postgis::in_tile("f.geometry", &envelope),
+    );
+
+    sqlx::query_scalar(&postgis::as_mvt(&layer, "farms"))
+        .bind(tile.z as i32)
+        .bind(tile.x as i32)
+        .bind(tile.y as i32)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(Into::into)
+}
+
+fn farm_from_row(row: &PgRow) -> Farm {
+    Farm {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        name: row.get("name"),
+        region: row.get("region"),
+        area_hectares: row.get("area_hectares"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        deleted_at: row.get("deleted_at"),
+    }
+}
+
+fn geojson_from_row(row: &PgRow) -> String {
+    row.get::<Option<String>, _>("geojson").unwrap_or_else(|| "{}".to_string())
+}
+
+/// Reprojects a GeoJSON geometry in `srid` to WGS 84.
+pub async fn reproject_to_wgs84(pool: &PgPool, geojson: &str, srid: i32) -> Result<String, AppError> {
+    sqlx::query_scalar(&format!(
+        "SELECT ST_AsGeoJSON({})",
+        postgis::transform(&postgis::from_geojson_in("$1", "$2"), "4326"),
+    ))
+    .bind(geojson)
+    .bind(srid)
+    .fetch_one(pool)
     .await
     .map_err(Into::into)
 }
 
-pub async fn get_geojson(pool: &PgPool, id: i64) -> Result<Option<String>, AppError> {
-    sqlx::query_scalar("SELECT ST_AsGeoJSON(geometry) FROM farms WHERE id = $1")
+/// PostGIS validity of a boundary, the reason when invalid, and the
+/// ST_MakeValid repair when that is still a single polygon.
+pub async fn check_validity(pool: &PgPool, geojson: &str) -> Result<(bool, String, Option<String>), AppError> {
+    sqlx::query_as(&format!(
+        r#"
+        WITH g AS (SELECT {geometry} AS geom)
+        SELECT ST_IsValid(geom), ST_IsValidReason(geom),
+               CASE WHEN NOT ST_IsValid(geom) AND GeometryType(ST_MakeValid(geom)) = 'POLYGON'
+                    THEN ST_AsGeoJSON(ST_MakeValid(geom)) END
+        FROM g
+        "#,
+        geometry = postgis::from_geojson("$1"),
+    ))
+    .bind(geojson)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// The farm's boundary, simplified by `simplify` degrees when given.
+pub async fn get_geojson(pool: &PgPool, id: i64, simplify: Option<f64>) -> Result<Option<String>, AppError> {
+    sqlx::query_scalar(&format!("SELECT {} FROM farms WHERE id = $1", postgis::as_geojson("geometry", "$2")))
         .bind(id)
+        .bind(simplify)
         .fetch_optional(pool)
         .await
         .map_err(Into::into)
-}
\ No newline at end of file
+}
+const SEASON_COLUMNS: &str =
+    "id, farm_id, crop_type, variety, planting_date, expected_harvest_date, growth_stage, created_at, updated_at";
+
+pub async fn list_seasons(pool: &PgPool, farm_id: i64) -> Result<Vec<CropSeason>, AppError> {
+    sqlx::query_as::<_, CropSeason>(&format!(
+        "SELECT {SEASON_COLUMNS} FROM crop_seasons WHERE farm_id = $1 ORDER BY planting_date DESC"
+    ))
+    .bind(farm_id)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn get_season(pool: &PgPool, farm_id: i64, season_id: i64) -> Result<Option<CropSeason>, AppError> {
+    sqlx::query_as::<_, CropSeason>(&format!(
+        "SELECT {SEASON_COLUMNS} FROM crop_seasons WHERE id = $1 AND farm_id = $2"
+    ))
+    .bind(season_id)
+    .bind(farm_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn create_season(
+    pool: &PgPool,
+    farm_id: i64,
+    request: &CreateCropSeasonRequest,
+) -> Result<CropSeason, AppError> {
+    sqlx::query_as::<_, CropSeason>(&format!(
+        r#"
+        INSERT INTO crop_seasons (farm_id, crop_type, variety, planting_date, expected_harvest_date, growth_stage)
+        VALUES ($1, $2, $3, $4, $5, COALESCE($6, 'germination'))
+        RETURNING {SEASON_COLUMNS}
+        "#
+    ))
+    .bind(farm_id)
+    .bind(&request.crop_type)
+    .bind(&request.variety)
+    .bind(request.planting_date)
+    .bind(request.expected_harvest_date)
+    .bind(request.growth_stage.as_ref().map(|s| s.as_str()))
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn update_season(
+    pool: &PgPool,
+    season_id: i64,
+    request: &UpdateCropSeasonRequest,
+) -> Result<CropSeason, AppError> {
+    sqlx::query_as::<_, CropSeason>(&format!(
+        r#"
+        UPDATE crop_seasons
+        SET variety = COALESCE($2, variety),
+            expected_harvest_date = COALESCE($3, expected_harvest_date),
+            growth_stage = COALESCE($4, growth_stage)
+        WHERE id = $1
+        RETURNING {SEASON_COLUMNS}
+        "#
+    ))
+    .bind(season_id)
+    .bind(&request.variety)
+    .bind(request.expected_harvest_date)
+    .bind(request.growth_stage.as_ref().map(|s| s.as_str()))
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn delete_season(pool: &PgPool, season_id: i64) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM crop_seasons WHERE id = $1")
+        .bind(season_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
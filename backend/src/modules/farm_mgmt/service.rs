@@ -1,76 +1,171 @@
-use geojson::{GeoJson, Geometry, Value};
-use crate::shared::error::AppError;
+use sqlx::PgPool;
+use sqlx::types::chrono::NaiveDate;
+use crate::shared::{crs::Crs, error::{AppError, ErrorCode}};
+use super::geometry::{self, GeometryProblem, GeometryProblemKind};
+use super::models::{BulkCreateFarmsRequest, BulkCreateFarmsResponse, BulkFarmResult, BulkMode, Farm};
+use super::repository;
 
-pub fn validate_polygon(geojson_str: &str) -> Result<(), AppError> {
-    let geojson: GeoJson = geojson_str.parse()
-        .map_err(|e| AppError::BadRequest(format!("Invalid GeoJSON: {}", e)))?;
 
-    match geojson {
-        GeoJson::Geometry(geometry) => {
-            validate_geometry(&geometry)?;
-        }
-        GeoJson::Feature(feature) => {
-            if let Some(geometry) = feature.geometry {
-                validate_geometry(&geometry)?;
-            } else {
-                return Err(AppError::BadRequest("Feature has no geometry".to_string()));
-            }
+/// A boundary ready to store and the repairs made to get there.
+pub struct PreparedBoundary {
+    pub geojson: String,
+    pub repairs: Vec<GeometryProblem>,
+}
+
+/// Runs an uploaded boundary through the structural checks, reprojects it to
+/// WGS 84 when it arrives in another CRS, then runs PostGIS's validity check.
+/// Self-intersections are repaired with ST_MakeValid only when the result is
+/// still a single polygon; anything else is rejected with the full list of
+/// problems.
+pub async fn prepare_boundary(db: &PgPool, geojson_str: &str, crs: Option<&str>) -> Result<PreparedBoundary, AppError> {
+    let requested = crs.map(Crs::parse).transpose()?;
+    let (geometry, crs, mut repairs) = geometry::inspect_polygon(geojson_str, requested).map_err(geometry_error)?;
+    let mut geojson = serde_json::to_string(&geometry)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize geometry: {}", e)))?;
+
+    if crs != Crs::WGS84 {
+        geojson = repository::reproject_to_wgs84(db, &geojson, crs.srid).await?;
+    }
+
+    let (valid, reason, repaired) = repository::check_validity(db, &geojson).await?;
+    if valid {
+        return Ok(PreparedBoundary { geojson, repairs });
+    }
+
+    let problem = GeometryProblem::new(GeometryProblemKind::SelfIntersection, reason);
+    match repaired {
+        Some(repaired) => {
+            repairs.push(problem.repaired());
+            Ok(PreparedBoundary { geojson: repaired, repairs })
         }
-        GeoJson::FeatureCollection(_) => {
-            return Err(AppError::BadRequest("FeatureCollection not supported, use single Polygon".to_string()));
+        None => {
+            repairs.push(problem);
+            Err(geometry_error(repairs))
         }
     }
+}
 
-    Ok(())
+fn geometry_error(problems: Vec<GeometryProblem>) -> AppError {
+    let message = problems
+        .iter()
+        .filter(|p| !p.repaired)
+        .map(|p| p.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    AppError::Detailed(ErrorCode::GeometryInvalid, message, serde_json::json!({ "problems": problems }))
 }
 
-fn validate_geometry(geometry: &Geometry) -> Result<(), AppError> {
-    match &geometry.value {
-        Value::Polygon(coords) => {
-            if coords.is_empty() {
-                return Err(AppError::BadRequest("Polygon has no rings".to_string()));
-            }
-            
-            let exterior = &coords[0];
-            if exterior.len() < 4 {
-                return Err(AppError::BadRequest("Polygon must have at least 4 points".to_string()));
-            }
+/// Validates every farm up front, then inserts them either in one transaction
+/// (`Atomic`) or independently (`Partial`), reporting the outcome per item.
+pub async fn bulk_create(
+    db: &PgPool,
+    user_id: i64,
+    request: BulkCreateFarmsRequest,
+) -> Result<BulkCreateFarmsResponse, AppError> {
+    let mut prepared: Vec<Result<String, AppError>> = Vec::with_capacity(request.farms.len());
+    for farm in &request.farms {
+        prepared.push(if farm.name.trim().is_empty() {
+            Err(AppError::Validation("Farm name is required".to_string()))
+        } else {
+            prepare_boundary(db, &farm.geojson, farm.crs.as_deref()).await.map(|boundary| boundary.geojson)
+        });
+    }
 
-            if exterior.first() != exterior.last() {
-                return Err(AppError::BadRequest("Polygon must be closed (first point = last point)".to_string()));
-            }
+    let mut results: Vec<BulkFarmResult> = prepared
+        .iter()
+        .enumerate()
+        .map(|(index, p)| BulkFarmResult {
+            index,
+            id: None,
+            error: p.as_ref().err().map(AppError::public_message),
+        })
+        .collect();
+
+    match request.mode {
+        BulkMode::Atomic => {
+            if results.iter().all(|r| r.error.is_none()) {
+                let mut tx = db.begin().await?;
+                let mut failure = None;
 
-            for point in exterior {
-                if point.len() < 2 {
-                    return Err(AppError::BadRequest("Invalid coordinate".to_string()));
+                for (index, (farm, geojson)) in request.farms.iter().zip(&prepared).enumerate() {
+                    let geojson = geojson.as_deref().unwrap_or_default();
+                    match repository::insert(&mut tx, user_id, &farm.name, farm.region.as_deref(), geojson).await {
+                        Ok(created) => results[index].id = Some(created.id),
+                        Err(e) => {
+                            failure = Some((index, e.public_message()));
+                            break;
+                        }
+                    }
                 }
-                let lon = point[0];
-                let lat = point[1];
-                if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
-                    return Err(AppError::BadRequest(format!("Invalid coordinates: [{}, {}]", lon, lat)));
+
+                match failure {
+                    None => tx.commit().await?,
+                    Some((index, error)) => {
+                        tx.rollback().await?;
+                        results[index].error = Some(error);
+                    }
                 }
             }
 
-            Ok(())
+            // Nothing is persisted when any item fails, so clear ids and flag the rest.
+            if results.iter().any(|r| r.error.is_some()) {
+                for result in results.iter_mut() {
+                    result.id = None;
+                    result.error.get_or_insert_with(|| "Not created: batch rolled back".to_string());
+                }
+            }
+        }
+        BulkMode::Partial => {
+            for (index, (farm, geojson)) in request.farms.iter().zip(&prepared).enumerate() {
+                let Ok(geojson) = geojson else { continue };
+                match repository::create(db, user_id, &farm.name, farm.region.as_deref(), geojson).await {
+                    Ok(created) => results[index].id = Some(created.id),
+                    Err(e) => results[index].error = Some(e.public_message()),
+                }
+            }
         }
-        _ => Err(AppError::BadRequest("Only Polygon geometry is supported".to_string())),
     }
+
+    let created = results.iter().filter(|r| r.id.is_some()).count();
+
+    Ok(BulkCreateFarmsResponse {
+        created,
+        failed: results.len() - created,
+        results,
+    })
 }
 
-pub fn normalize_geojson(geojson_str: &str) -> Result<String, AppError> {
-    let geojson: GeoJson = geojson_str.parse()
-        .map_err(|e| AppError::BadRequest(format!("Invalid GeoJSON: {}", e)))?;
+/// Coarser tolerances would visibly distort parcels, which are often under a hectare.
+const MAX_SIMPLIFY_DEGREES: f64 = 0.01;
+/// Radius searches are meant for "farms around here", not whole provinces.
+pub const MAX_NEARBY_RADIUS_M: f64 = 50_000.0;
 
-    let geometry = match geojson {
-        GeoJson::Geometry(g) => g,
-        GeoJson::Feature(f) => {
-            f.geometry.ok_or_else(|| AppError::BadRequest("Feature has no geometry".to_string()))?
-        }
-        GeoJson::FeatureCollection(_) => {
-            return Err(AppError::BadRequest("FeatureCollection not supported".to_string()));
-        }
-    };
+pub fn validate_simplify(simplify: Option<f64>) -> Result<(), AppError> {
+    match simplify {
+        Some(tolerance) if !(tolerance > 0.0 && tolerance <= MAX_SIMPLIFY_DEGREES) => Err(AppError::Validation(
+            format!("simplify must be greater than 0 and at most {}", MAX_SIMPLIFY_DEGREES),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_season_dates(planting_date: NaiveDate, expected_harvest_date: Option<NaiveDate>) -> Result<(), AppError> {
+    if expected_harvest_date.is_some_and(|harvest| harvest <= planting_date) {
+        return Err(AppError::Validation("expected_harvest_date must be after planting_date".to_string()));
+    }
 
-    serde_json::to_string(&geometry)
-        .map_err(|e| AppError::Internal(format!("Failed to serialize geometry: {}", e)))
+    Ok(())
+}
+
+/// Archived farms are read-only until restored.
+pub fn ensure_active(farm: &Farm) -> Result<(), AppError> {
+    if farm.is_archived() {
+        return Err(AppError::Coded(
+            ErrorCode::FarmArchived,
+            format!("Farm {} is archived; restore it before making changes", farm.id),
+        ));
+    }
+
+    Ok(())
 }
@@ -0,0 +1,40 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use crate::shared::AppState;
+use super::{
+    models::{ReadinessResponse, ReadinessStatus},
+    service,
+};
+
+#[utoipa::path(
+    get,
+    path = "/live",
+    tag = "health",
+    responses((status = 200, description = "Process is running")),
+)]
+pub async fn live() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "alive" }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready, possibly degraded; see per-dependency checks", body = ReadinessResponse),
+        (status = 503, description = "A required dependency is down", body = ReadinessResponse),
+    ),
+)]
+pub async fn ready(State(state): State<AppState>) -> impl IntoResponse {
+    let readiness = service::readiness(&state).await;
+    let status = match readiness.status {
+        ReadinessStatus::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+
+    (status, Json(readiness))
+}
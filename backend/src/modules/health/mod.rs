@@ -0,0 +1,22 @@
+mod models;
+mod service;
+mod controller;
+
+use axum::{routing::get, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+/// Unauthenticated probes for orchestrators and load balancers.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/live", get(controller::live))
+        .route("/ready", get(controller::ready))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(controller::live, controller::ready))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -0,0 +1,39 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Optional dependency that this deployment does not use.
+    NotConfigured,
+    /// Background job that has not completed its first run yet.
+    Pending,
+    /// Background job whose last run is older than twice its period.
+    Stale,
+    Failing,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Only reported for probes that make a round trip.
+    pub latency_ms: Option<f64>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessStatus {
+    Ready,
+    /// Serving requests, but an optional dependency or a job is unhealthy.
+    Degraded,
+    Unavailable,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: ReadinessStatus,
+    pub checks: Vec<DependencyCheck>,
+}
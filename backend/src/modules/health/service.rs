@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+use crate::shared::{worker, AppState};
+use super::models::{CheckStatus, DependencyCheck, ReadinessResponse, ReadinessStatus};
+
+const DB_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Slack on top of twice the job period before a heartbeat counts as stale.
+const HEARTBEAT_GRACE: Duration = Duration::from_secs(60);
+
+/// Probes every dependency. Only the database is required to serve traffic;
+/// the AI engine and background jobs degrade the service without stopping it.
+pub async fn readiness(state: &AppState) -> ReadinessResponse {
+    let database = check_database(state).await;
+    let database_ok = database.status == CheckStatus::Ok;
+
+    let mut checks = vec![database, check_ai_engine(state), check_inference_batcher(state)];
+    checks.extend(check_workers());
+
+    let degraded = checks
+        .iter()
+        .any(|c| matches!(c.status, CheckStatus::Failing | CheckStatus::Stale));
+
+    let status = match (database_ok, degraded) {
+        (false, _) => ReadinessStatus::Unavailable,
+        (true, true) => ReadinessStatus::Degraded,
+        (true, false) => ReadinessStatus::Ready,
+    };
+
+    ReadinessResponse { status, checks }
+}
+
+async fn check_database(state: &AppState) -> DependencyCheck {
+    let started = Instant::now();
+    let probe = tokio::time::timeout(DB_PROBE_TIMEOUT, sqlx::query("SELECT 1").execute(&state.db)).await;
+    let latency_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+
+    let (status, detail) = match probe {
+        Ok(Ok(_)) => (CheckStatus::Ok, None),
+        Ok(Err(e)) => (CheckStatus::Failing, Some(e.to_string())),
+        Err(_) => (CheckStatus::Failing, Some(format!("No response within {:?}", DB_PROBE_TIMEOUT))),
+    };
+
+    DependencyCheck {
+        name: "database".to_string(),
+        status,
+        latency_ms,
+        detail,
+    }
+}
+
+fn check_ai_engine(state: &AppState) -> DependencyCheck {
+    let (status, detail) = match state.models.active() {
+        Some(model) => (CheckStatus::Ok, Some(format!("{} on {}", model.version, model.engine.device_name()))),
+        None => (CheckStatus::NotConfigured, None),
+    };
+
+    DependencyCheck {
+        name: "ai_engine".to_string(),
+        status,
+        latency_ms: None,
+        detail,
+    }
+}
+
+/// Always ok; the detail carries the queue depth and batch sizes so far.
+fn check_inference_batcher(state: &AppState) -> DependencyCheck {
+    let stats = state.batcher.stats();
+
+    DependencyCheck {
+        name: "inference_batcher".to_string(),
+        status: CheckStatus::Ok,
+        latency_ms: None,
+        detail: Some(format!(
+            "queue depth {}, {} batches, mean size {:.1}, max size {}",
+            stats.queue_depth,
+            stats.batches,
+            stats.mean_batch(),
+            stats.max_batch,
+        )),
+    }
+}
+
+fn check_workers() -> Vec<DependencyCheck> {
+    let now = chrono::Utc::now();
+
+    worker::heartbeats()
+        .into_iter()
+        .map(|(name, heartbeat)| {
+            let max_age = heartbeat.period * 2 + HEARTBEAT_GRACE;
+            let (status, detail) = match (heartbeat.last_finished_at, heartbeat.last_error) {
+                (None, _) => (CheckStatus::Pending, None),
+                (Some(_), Some(error)) => (CheckStatus::Failing, Some(error)),
+                (Some(at), None) if (now - at).to_std().unwrap_or_default() > max_age => {
+                    (CheckStatus::Stale, Some(format!("Last run finished at {}", at)))
+                }
+                (Some(_), None) => (CheckStatus::Ok, None),
+            };
+
+            DependencyCheck {
+                name: format!("job:{}", name),
+                status,
+                latency_ms: None,
+                detail,
+            }
+        })
+        .collect()
+}
@@ -1,18 +1,103 @@
+pub mod analytics;
+pub mod api_keys;
+pub mod attachments;
 pub mod auth;
+pub mod comments;
+pub mod dashboard;
+pub mod digest;
+pub mod docs;
+pub mod events;
 pub mod farm_mgmt;
+pub mod health;
 pub mod monitoring;
+pub mod reports;
+pub mod satellites;
+pub mod search;
+pub mod settings;
+pub mod stations;
+pub mod sync;
+pub mod todos;
+pub mod webhooks;
 
 use crate::shared::AppState;
 use axum::Router;
 
+pub fn analytics_router() -> Router<AppState> {
+    analytics::router()
+}
+
+pub fn attachments_router() -> Router<AppState> {
+    attachments::router()
+}
+
+pub fn attachments_public_router() -> Router<AppState> {
+    attachments::public_router()
+}
+
 pub fn auth_router() -> Router<AppState> {
-    auth::router()
+    auth::router().merge(attachments::profile_router())
+}
+
+pub fn auth_public_router() -> Router<AppState> {
+    auth::public_router()
+}
+
+pub fn comments_router() -> Router<AppState> {
+    comments::router()
+}
+
+pub fn dashboard_router() -> Router<AppState> {
+    dashboard::router()
+}
+
+pub fn docs_router() -> Router<AppState> {
+    docs::router()
+}
+
+pub fn health_router() -> Router<AppState> {
+    health::router()
 }
 
 pub fn farm_mgmt_router() -> Router<AppState> {
-    farm_mgmt::router()
+    farm_mgmt::router().merge(stations::farm_router())
 }
 
 pub fn monitoring_router() -> Router<AppState> {
     monitoring::router()
-}
\ No newline at end of file
+        .merge(attachments::alert_router())
+        .merge(comments::alert_router())
+        .nest("/stations", stations::router())
+        .nest("/events", events::router())
+}
+
+pub fn monitoring_public_router() -> Router<AppState> {
+    monitoring::public_router().nest("/events", events::public_router())
+}
+
+pub fn reports_router() -> Router<AppState> {
+    reports::router()
+}
+
+pub fn satellites_router() -> Router<AppState> {
+    satellites::router()
+}
+
+pub fn search_router() -> Router<AppState> {
+    search::router()
+}
+
+pub fn settings_router() -> Router<AppState> {
+    settings::router().merge(api_keys::router())
+}
+
+pub fn sync_router() -> Router<AppState> {
+    sync::router()
+}
+
+pub fn todos_router() -> Router<AppState> {
+    todos::router().merge(attachments::todo_router())
+}
+
+pub fn webhooks_router() -> Router<AppState> {
+    webhooks::router()
+}
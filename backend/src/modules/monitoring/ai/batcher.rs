@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use candle_core::Tensor;
+use tokio::sync::{mpsc, oneshot};
+use crate::shared::error::{AppError, AppResult};
+use super::registry::LoadedModel;
+
+/// Pending requests beyond this make callers wait for room in the queue.
+const QUEUE_CAPACITY: usize = 256;
+
+struct Job {
+    model: Arc<LoadedModel>,
+    input: Tensor,
+    reply: oneshot::Sender<AppResult<Tensor>>,
+}
+
+#[derive(Default)]
+struct Counters {
+    queue_depth: AtomicUsize,
+    batches: AtomicU64,
+    items: AtomicU64,
+    max_batch: AtomicUsize,
+}
+
+/// Snapshot of the batcher's counters since startup.
+#[derive(Debug, Clone, Copy)]
+pub struct BatcherStats {
+    pub queue_depth: usize,
+    pub batches: u64,
+    pub items: u64,
+    pub max_batch: usize,
+}
+
+impl BatcherStats {
+    pub fn mean_batch(&self) -> f64 {
+        if self.batches == 0 {
+            return 0.0;
+        }
+        self.items as f64 / self.batches as f64
+    }
+}
+
+/// Collects inference requests for up to `max_wait` or `max_batch` items and
+/// runs each model's share as one forward pass, so concurrent analyses share
+/// the device instead of queueing behind each other one image at a time.
+#[derive(Clone)]
+pub struct InferenceBatcher {
+    jobs: mpsc::Sender<Job>,
+    counters: Arc<Counters>,
+}
+
+impl InferenceBatcher {
+    /// Starts the batching task; must be called inside the Tokio runtime.
+    pub fn spawn(max_batch: usize, max_wait: Duration) -> Self {
+        let (jobs, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let counters = Arc::new(Counters::default());
+        tokio::spawn(run(receiver, max_batch.max(1), max_wait, counters.clone()));
+
+        Self { jobs, counters }
+    }
+
+    /// Runs `input`, a batch of one, through `model` and returns its output.
+    pub async fn predict(&self, model: Arc<LoadedModel>, input: Tensor) -> AppResult<Tensor> {
+        let (reply, response) = oneshot::channel();
+
+        self.counters.queue_depth.fetch_add(1, Ordering::Relaxed);
+        if self.jobs.send(Job { model, input, reply }).await.is_err() {
+            self.counters.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(AppError::AiEngine("Inference queue is closed".to_string()));
+        }
+
+        response
+            .await
+            .map_err(|_| AppError::AiEngine("Inference batch was dropped".to_string()))?
+    }
+
+    pub fn stats(&self) -> BatcherStats {
+        BatcherStats {
+            queue_depth: self.counters.queue_depth.load(Ordering::Relaxed),
+            batches: self.counters.batches.load(Ordering::Relaxed),
+            items: self.counters.items.load(Ordering::Relaxed),
+            max_batch: self.counters.max_batch.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Batches run one after another: while the device is busy, new requests
+/// pile up and form the next batch.
+async fn run(mut receiver: mpsc::Receiver<Job>, max_batch: usize, max_wait: Duration, counters: Arc<Counters>) {
+    while let Some(first) = receiver.recv().await {
+        let mut jobs = vec![first];
+        let deadline = tokio::time::Instant::now() + max_wait;
+
+        while jobs.len() < max_batch {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(job)) => jobs.push(job),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        counters.queue_depth.fetch_sub(jobs.len(), Ordering::Relaxed);
+
+        for (model, group) in group_by_model(jobs) {
+            counters.batches.fetch_add(1, Ordering::Relaxed);
+            counters.items.fetch_add(group.len() as u64, Ordering::Relaxed);
+            counters.max_batch.fetch_max(group.len(), Ordering::Relaxed);
+
+            if let Err(e) = tokio::task::spawn_blocking(move || run_batch(&model, group)).await {
+                tracing::error!("Inference batch panicked: {}", e);
+            }
+        }
+    }
+}
+
+/// Requests can straddle a model swap, so a batch may mix models.
+fn group_by_model(jobs: Vec<Job>) -> Vec<(Arc<LoadedModel>, Vec<Job>)> {
+    let mut groups: Vec<(Arc<LoadedModel>, Vec<Job>)> = Vec::new();
+    for job in jobs {
+        match groups.iter_mut().find(|(model, _)| Arc::ptr_eq(model, &job.model)) {
+            Some((_, group)) => group.push(job),
+            None => groups.push((job.model.clone(), vec![job])),
+        }
+    }
+    groups
+}
+
+fn run_batch(model: &LoadedModel, jobs: Vec<Job>) {
+    let inputs: Vec<&Tensor> = jobs.iter().map(|job| &job.input).collect();
+    let output = Tensor::cat(&inputs, 0)
+        .map_err(|e| AppError::AiEngine(format!("Failed to assemble batch: {}", e)))
+        .and_then(|batch| model.engine.predict(&batch));
+
+    match output {
+        Ok(output) => {
+            for (i, job) in jobs.into_iter().enumerate() {
+                let item = output
+                    .narrow(0, i, 1)
+                    .map_err(|e| AppError::AiEngine(format!("Failed to split batch output: {}", e)));
+                let _ = job.reply.send(item);
+            }
+        }
+        Err(e) => {
+            let message = match e {
+                AppError::AiEngine(message) => message,
+                other => other.to_string(),
+            };
+            for job in jobs {
+                let _ = job.reply.send(Err(AppError::AiEngine(message.clone())));
+            }
+        }
+    }
+}
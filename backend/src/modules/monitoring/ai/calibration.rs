@@ -0,0 +1,86 @@
+use crate::modules::monitoring::models::{SalinityCalibration, SalinityEstimate};
+
+/// Approximate conversion from electrical conductivity (dS/m) to total
+/// dissolved salts (g/L) for Mekong Delta surface water.
+const EC_TO_GRAMS_PER_LITRE: f64 = 0.64;
+const MIN_CALIBRATION_SAMPLES: usize = 5;
+const Z_95: f64 = 1.96;
+
+#[derive(Debug, Clone)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    pub residual_std: f64,
+    pub r_squared: f64,
+    pub sample_count: usize,
+    pub x_mean: f64,
+    pub sxx: f64,
+}
+
+/// Ordinary least squares fit of `y = slope * x + intercept` over
+/// `(ndsi, ec_ds_m)` samples. Returns `None` when there are too few samples
+/// or the NDSI values have no spread.
+pub fn fit_linear(samples: &[(f64, f64)]) -> Option<LinearFit> {
+    if samples.len() < MIN_CALIBRATION_SAMPLES {
+        return None;
+    }
+
+    let n = samples.len() as f64;
+    let x_mean = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let y_mean = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let (sxx, sxy, syy) = samples.iter().fold((0.0, 0.0, 0.0), |(sxx, sxy, syy), (x, y)| {
+        let dx = x - x_mean;
+        let dy = y - y_mean;
+        (sxx + dx * dx, sxy + dx * dy, syy + dy * dy)
+    });
+
+    if sxx <= f64::EPSILON {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let intercept = y_mean - slope * x_mean;
+
+    let sse = samples
+        .iter()
+        .map(|(x, y)| {
+            let residual = y - (slope * x + intercept);
+            residual * residual
+        })
+        .sum::<f64>();
+
+    let residual_std = (sse / (n - 2.0)).sqrt();
+    let r_squared = if syy > f64::EPSILON { 1.0 - sse / syy } else { 0.0 };
+
+    Some(LinearFit {
+        slope,
+        intercept,
+        residual_std,
+        r_squared,
+        sample_count: samples.len(),
+        x_mean,
+        sxx,
+    })
+}
+
+/// Estimates salinity in g/L for an NDSI value with a 95% prediction interval.
+pub fn estimate_salinity(calibration: &SalinityCalibration, ndsi: f64) -> SalinityEstimate {
+    let ec = calibration.slope * ndsi + calibration.intercept;
+
+    let n = calibration.sample_count.max(1) as f64;
+    let leverage = if calibration.ndsi_sxx > f64::EPSILON {
+        (ndsi - calibration.ndsi_mean).powi(2) / calibration.ndsi_sxx
+    } else {
+        0.0
+    };
+    let margin = Z_95 * calibration.residual_std * (1.0 + 1.0 / n + leverage).sqrt();
+
+    SalinityEstimate {
+        ec_ds_m: ec.max(0.0),
+        grams_per_litre: (ec * EC_TO_GRAMS_PER_LITRE).max(0.0),
+        lower_g_l: ((ec - margin) * EC_TO_GRAMS_PER_LITRE).max(0.0),
+        upper_g_l: ((ec + margin) * EC_TO_GRAMS_PER_LITRE).max(0.0),
+        region: calibration.region.clone(),
+    }
+}
@@ -1,7 +1,9 @@
-use candle_core::{Device, Tensor, DType};
+use candle_core::{Device, DeviceLocation, Tensor, DType};
 use candle_nn::VarBuilder;
 use std::path::Path;
+use std::time::Instant;
 use anyhow::Result;
+use crate::shared::config::{self, AiDevice};
 use crate::shared::error::AppError;
 use super::architecture::ModelConfig;
 
@@ -12,26 +14,47 @@ pub struct AiEngine {
 }
 
 impl AiEngine {
+    /// Loads the model on the device picked by `AI_DEVICE` and runs a
+    /// warm-up pass. A GPU that cannot run the model is dropped for the CPU.
     pub fn new(config_path: &str, weights_path: &str) -> Result<Self> {
         let config = ModelConfig::from_file(config_path)?;
-        
-        let device = if candle_core::utils::cuda_is_available() {
-            Device::new_cuda(0)?
-        } else {
-            Device::Cpu
-        };
+        let device = select_device(config::get().ai_device);
 
         tracing::info!(
-            "AI Engine initializing on device: {:?}, model: {}",
-            device,
+            "AI Engine initializing on device: {}, model: {}",
+            device_name(&device),
             config.model_type
         );
 
-        Ok(Self {
+        let engine = Self {
             config,
             device,
             weights_path: weights_path.to_string(),
-        })
+        };
+
+        match engine.warm_up() {
+            Ok(()) => Ok(engine),
+            Err(e) if !engine.device.is_cpu() => {
+                tracing::warn!("Warm-up on {} failed: {}. Falling back to CPU.", engine.device_name(), e);
+                let engine = Self { device: Device::Cpu, ..engine };
+                engine.warm_up()?;
+                Ok(engine)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Runs one forward pass on a blank input so unreadable weights or an
+    /// unusable device fail at load time instead of on the first analysis.
+    fn warm_up(&self) -> Result<(), AppError> {
+        let started = Instant::now();
+        let shape = (1, self.config.num_frames, self.config.in_chans, self.config.img_size, self.config.img_size);
+        let input = Tensor::zeros(shape, DType::F32, &self.device)
+            .map_err(|e| AppError::AiEngine(format!("Failed to create warm-up input: {}", e)))?;
+
+        self.predict(&input)?;
+        tracing::info!("AI Engine warm-up on {} took {:?}", self.device_name(), started.elapsed());
+        Ok(())
     }
 
     pub fn predict(&self, input: &Tensor) -> Result<Tensor, AppError> {
@@ -74,4 +97,36 @@ impl AiEngine {
     pub fn device(&self) -> &Device {
         &self.device
     }
+
+    pub fn device_name(&self) -> String {
+        device_name(&self.device)
+    }
+}
+
+fn select_device(preference: AiDevice) -> Device {
+    let gpu_id = match preference {
+        AiDevice::Cpu => return Device::Cpu,
+        AiDevice::Auto => 0,
+        AiDevice::Cuda(gpu_id) => gpu_id,
+    };
+
+    if !candle_core::utils::cuda_is_available() {
+        if preference != AiDevice::Auto {
+            tracing::warn!("AI_DEVICE requests cuda:{} but this build has no CUDA support; using CPU", gpu_id);
+        }
+        return Device::Cpu;
+    }
+
+    Device::new_cuda(gpu_id).unwrap_or_else(|e| {
+        tracing::warn!("CUDA device {} unavailable: {}. Using CPU.", gpu_id, e);
+        Device::Cpu
+    })
+}
+
+fn device_name(device: &Device) -> String {
+    match device.location() {
+        DeviceLocation::Cpu => "cpu".to_string(),
+        DeviceLocation::Cuda { gpu_id } => format!("cuda:{}", gpu_id),
+        DeviceLocation::Metal { gpu_id } => format!("metal:{}", gpu_id),
+    }
 }
\ No newline at end of file
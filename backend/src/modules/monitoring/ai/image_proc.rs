@@ -1,4 +1,4 @@
-use candle_core::{Device, Tensor};
+use candle_core::{DType, Device, Tensor};
 use crate::shared::error::{AppError, AppResult};
 use super::architecture::ModelConfig;
 
@@ -76,36 +76,102 @@ fn normalize_tensor(tensor: &Tensor, config: &ModelConfig) -> AppResult<Tensor>
         .map_err(|e| AppError::AiEngine(format!("Normalization failed: {}", e)))
 }
 
-pub fn postprocess_segmentation(
+/// Softmax probability of `water_class_idx` for every pixel, row-major, plus
+/// the image width.
+pub fn water_probabilities(
     output: &Tensor,
     water_class_idx: usize,
-) -> AppResult<Vec<(f64, f64)>> {
-    let (batch, _num_classes, _height, width) = output
+) -> AppResult<(Vec<f32>, usize)> {
+    let (batch, num_classes, _height, width) = output
         .dims4()
         .map_err(|e| AppError::AiEngine(format!("Invalid output shape: {}", e)))?;
 
     if batch != 1 {
         return Err(AppError::AiEngine(format!("Expected batch size 1, got {}", batch)));
     }
+    if water_class_idx >= num_classes {
+        return Err(AppError::AiEngine(format!("Water class {} out of range for {} classes", water_class_idx, num_classes)));
+    }
 
-    let mask_data = output
-        .argmax(1)
+    let probabilities = candle_nn::ops::softmax(output, 1)
+        .and_then(|t| t.narrow(1, water_class_idx, 1))
         .and_then(|t| t.flatten_all())
-        .and_then(|t| t.to_vec1::<u32>())
+        .and_then(|t| t.to_dtype(DType::F32))
+        .and_then(|t| t.to_vec1::<f32>())
         .map_err(|e| AppError::AiEngine(format!("Postprocess failed: {}", e)))?;
 
-    let water_class = water_class_idx as u32;
-    // let width_f64 = width as f64; // Removed unused variable
-    
-    Ok(mask_data
+    Ok((probabilities, width))
+}
+
+/// Pixels whose water probability reaches `threshold`, as (x, y).
+pub fn water_pixels(probabilities: &[f32], width: usize, threshold: f64) -> Vec<(f64, f64)> {
+    probabilities
         .iter()
         .enumerate()
-        .filter_map(|(idx, &class)| {
-            (class == water_class).then(|| {
-                let x = (idx % width) as f64;
-                let y = (idx / width) as f64;
-                (x, y)
-            })
-        })
-        .collect())
-}
\ No newline at end of file
+        .filter(|(_, &p)| p as f64 >= threshold)
+        .map(|(idx, _)| ((idx % width) as f64, (idx / width) as f64))
+        .collect()
+}
+
+/// Otsu's method over a 256-bin histogram of the probabilities: the cut that
+/// maximises the between-class variance of the water and non-water pixels.
+pub fn otsu_threshold(probabilities: &[f32]) -> f64 {
+    const BINS: usize = 256;
+
+    let mut histogram = [0u64; BINS];
+    for &p in probabilities {
+        let bin = ((p.clamp(0.0, 1.0) as f64) * (BINS - 1) as f64).round() as usize;
+        histogram[bin] += 1;
+    }
+
+    let total = probabilities.len() as f64;
+    let weighted_sum: f64 = histogram.iter().enumerate().map(|(i, &n)| i as f64 * n as f64).sum();
+
+    let mut background = 0.0;
+    let mut background_sum = 0.0;
+    let mut best = (0.0, BINS / 2);
+    for (i, &n) in histogram.iter().enumerate() {
+        background += n as f64;
+        if background == 0.0 {
+            continue;
+        }
+        let foreground = total - background;
+        if foreground == 0.0 {
+            break;
+        }
+        background_sum += i as f64 * n as f64;
+
+        let mean_background = background_sum / background;
+        let mean_foreground = (weighted_sum - background_sum) / foreground;
+        let between = background * foreground * (mean_background - mean_foreground).powi(2);
+        if between > best.0 {
+            best = (between, i);
+        }
+    }
+
+    // Pixels above the cut are water, so the threshold sits just past the bin.
+    (best.1 as f64 + 0.5) / (BINS - 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn otsu_separates_two_clusters() {
+        let probabilities: Vec<f32> = [0.05, 0.1, 0.15, 0.85, 0.9, 0.95].repeat(50);
+        let threshold = otsu_threshold(&probabilities);
+        assert!(threshold > 0.15 && threshold < 0.85, "threshold {}", threshold);
+        assert_eq!(water_pixels(&probabilities, 6, threshold).len(), 150);
+    }
+
+    #[test]
+    fn otsu_of_empty_input_is_the_midpoint() {
+        assert_eq!(otsu_threshold(&[]), 128.5 / 255.0);
+    }
+
+    #[test]
+    fn otsu_of_constant_input_is_the_midpoint() {
+        assert_eq!(otsu_threshold(&[0.3; 100]), 128.5 / 255.0);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod architecture;
+pub mod batcher;
+pub mod calibration;
 pub mod engine;
-pub mod image_proc;
\ No newline at end of file
+pub mod image_proc;
+pub mod registry;
\ No newline at end of file
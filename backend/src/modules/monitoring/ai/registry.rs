@@ -0,0 +1,75 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use candle_core::Tensor;
+use crate::shared::error::{AppError, AppResult};
+use super::engine::AiEngine;
+use super::image_proc::{preprocess_image, water_probabilities};
+
+/// Version recorded for analyses run by the model configured through
+/// AI_CONFIG_PATH/AI_WEIGHTS_PATH rather than the registry.
+pub const UNREGISTERED_VERSION: &str = "unregistered";
+
+/// A segmentation model held in memory, tagged with the registry entry it
+/// was loaded from.
+pub struct LoadedModel {
+    pub id: Option<i64>,
+    pub version: String,
+    pub engine: AiEngine,
+}
+
+impl LoadedModel {
+    pub fn load(id: Option<i64>, version: String, config_path: &str, weights_path: &str) -> AppResult<Self> {
+        if !Path::new(weights_path).is_file() {
+            return Err(AppError::Validation(format!("Weights file {} not found", weights_path)));
+        }
+        let engine = AiEngine::new(config_path, weights_path)
+            .map_err(|e| AppError::Validation(format!("Cannot load model config {}: {}", config_path, e)))?;
+
+        Ok(Self { id, version, engine })
+    }
+
+    pub fn img_size(&self) -> usize {
+        self.engine.config().img_size
+    }
+
+    /// Model input for an image, as a batch of one.
+    pub fn preprocess(&self, image_bytes: &[u8]) -> AppResult<Tensor> {
+        preprocess_image(image_bytes, self.engine.config(), self.engine.device())
+    }
+
+    /// Per-pixel water probabilities from the model's output, with the row width.
+    pub fn water_probabilities(&self, output: &Tensor) -> AppResult<(Vec<f32>, usize)> {
+        let water_class_idx = self.engine.config().classes
+            .iter()
+            .position(|c| c == "water")
+            .unwrap_or(1);
+
+        water_probabilities(output, water_class_idx)
+    }
+}
+
+/// Models serving this process: the active one answers analyses, the shadow
+/// one only runs alongside it. Both can be swapped while the server runs.
+#[derive(Default)]
+pub struct ModelRegistry {
+    active: RwLock<Option<Arc<LoadedModel>>>,
+    shadow: RwLock<Option<Arc<LoadedModel>>>,
+}
+
+impl ModelRegistry {
+    pub fn active(&self) -> Option<Arc<LoadedModel>> {
+        self.active.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn shadow(&self) -> Option<Arc<LoadedModel>> {
+        self.shadow.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_active(&self, model: Option<LoadedModel>) {
+        *self.active.write().unwrap_or_else(|e| e.into_inner()) = model.map(Arc::new);
+    }
+
+    pub fn set_shadow(&self, model: Option<LoadedModel>) {
+        *self.shadow.write().unwrap_or_else(|e| e.into_inner()) = model.map(Arc::new);
+    }
+}
@@ -0,0 +1,136 @@
+//! Changepoints in the mean of an index series, found by binary
+//! segmentation: the split that most reduces the squared error is kept when
+//! the reduction beats a BIC-style penalty scaled by the series' noise, and
+//! both halves are searched again.
+
+/// Shortest regime reported, in samples.
+const MIN_SEGMENT: usize = 3;
+const MAX_BREAKPOINTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Breakpoint {
+    /// First sample of the new regime.
+    pub index: usize,
+    pub before_mean: f64,
+    pub after_mean: f64,
+}
+
+/// Mean shifts in `values`, oldest first.
+pub fn detect(values: &[f64]) -> Vec<Breakpoint> {
+    let n = values.len();
+    if n < 2 * MIN_SEGMENT {
+        return Vec::new();
+    }
+
+    let noise = noise_variance(values);
+    if noise <= f64::EPSILON {
+        return Vec::new();
+    }
+    let penalty = 2.0 * noise * (n as f64).ln();
+
+    let mut sums = vec![0.0; n + 1];
+    let mut squares = vec![0.0; n + 1];
+    for (i, &v) in values.iter().enumerate() {
+        sums[i + 1] = sums[i] + v;
+        squares[i + 1] = squares[i] + v * v;
+    }
+    let cost = |start: usize, end: usize| {
+        let len = (end - start) as f64;
+        let sum = sums[end] - sums[start];
+        (squares[end] - squares[start]) - sum * sum / len
+    };
+
+    let mut splits = Vec::new();
+    let mut pending = vec![(0, n)];
+    while let Some((start, end)) = pending.pop() {
+        if splits.len() >= MAX_BREAKPOINTS || end - start < 2 * MIN_SEGMENT {
+            continue;
+        }
+
+        let whole = cost(start, end);
+        let best = (start + MIN_SEGMENT..=end - MIN_SEGMENT)
+            .map(|k| (k, whole - cost(start, k) - cost(k, end)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((k, _)) = best.filter(|&(_, gain)| gain > penalty) {
+            splits.push(k);
+            pending.push((start, k));
+            pending.push((k, end));
+        }
+    }
+    splits.sort_unstable();
+
+    let mean = |start: usize, end: usize| (sums[end] - sums[start]) / (end - start) as f64;
+    let bounds: Vec<usize> = std::iter::once(0).chain(splits.iter().copied()).chain(std::iter::once(n)).collect();
+    bounds
+        .windows(3)
+        .map(|w| Breakpoint { index: w[1], before_mean: mean(w[0], w[1]), after_mean: mean(w[1], w[2]) })
+        .collect()
+}
+
+/// Robust noise estimate from first differences, so level shifts do not
+/// inflate it: MAD of the differences, rescaled to a normal variance.
+fn noise_variance(values: &[f64]) -> f64 {
+    let diffs: Vec<f64> = values.windows(2).map(|w| w[1] - w[0]).collect();
+    let center = median(&mut diffs.clone());
+    let mut deviations: Vec<f64> = diffs.iter().map(|d| (d - center).abs()).collect();
+    let sigma = 1.4826 * median(&mut deviations) / std::f64::consts::SQRT_2;
+    if sigma > f64::EPSILON {
+        return sigma * sigma;
+    }
+
+    // Heavily quantised series can have a zero MAD; fall back to the mean
+    // squared difference, which a single level shift barely moves.
+    diffs.iter().map(|d| d * d).sum::<f64>() / (2.0 * diffs.len() as f64)
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_a_single_level_shift() {
+        let values: Vec<f64> = (0..20).map(|i| if i < 10 { 0.2 } else { 0.8 }).collect();
+        let found = detect(&values);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].index, 10);
+        assert!((found[0].before_mean - 0.2).abs() < 1e-9);
+        assert!((found[0].after_mean - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn finds_a_shift_through_noise() {
+        let values: Vec<f64> = (0..30)
+            .map(|i| {
+                let jitter = if i % 2 == 0 { 0.01 } else { -0.01 };
+                (if i < 18 { 0.3 } else { 0.6 }) + jitter
+            })
+            .collect();
+        let found = detect(&values);
+        assert_eq!(found.iter().map(|b| b.index).collect::<Vec<_>>(), vec![18]);
+    }
+
+    #[test]
+    fn empty_and_short_series_have_no_breakpoints() {
+        assert!(detect(&[]).is_empty());
+        assert!(detect(&[0.1, 0.9, 0.1, 0.9, 0.1]).is_empty());
+    }
+
+    #[test]
+    fn constant_series_has_no_breakpoints() {
+        assert!(detect(&[0.5; 40]).is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Write;
+use crate::shared::config;
+use crate::shared::error::AppError;
+use crate::shared::i18n::{t, Language};
+use super::models::{AlertSeverity, DistrictAlerts};
+
+/// Alerts older than this are left out of the feed; a district's warning
+/// expires this long after its latest alert.
+pub const FEED_WINDOW_DAYS: i32 = 7;
+
+const CAP_NAMESPACE: &str = "urn:oasis:names:tc:emergency:cap:1.2";
+/// CAP forbids `Z`; times carry an explicit offset.
+const CAP_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S+00:00";
+/// Every message carries both languages, Vietnamese first for the provinces.
+const CAP_LANGUAGES: [(Language, &str); 2] = [(Language::Vi, "vi-VN"), (Language::En, "en-US")];
+
+/// An Atom feed with one CAP 1.2 alert per district, the format provincial
+/// early-warning aggregators poll. A new alert in a district changes the
+/// identifier, so aggregators see it as a fresh message.
+pub fn atom_feed(districts: &[DistrictAlerts], generated_at: DateTime<Utc>) -> String {
+    let base_url = config::get().app_base_url.trim_end_matches('/');
+    let feed_url = format!("{}/api/monitoring/alerts/feed.cap", base_url);
+    let sender = sender(base_url);
+    let updated = generated_at.to_rfc3339();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(xml, "  <id>{}</id>", escape_xml(&feed_url));
+    let _ = writeln!(xml, "  <title>{}</title>", escape_xml(&t(Language::Vi, "cap.feed_title", &[])));
+    let _ = writeln!(xml, "  <updated>{}</updated>", updated);
+    let _ = writeln!(xml, "  <author><name>{}</name></author>", escape_xml(&sender));
+    let _ = writeln!(xml, "  <link rel=\"self\" href=\"{}\"/>", escape_xml(&feed_url));
+
+    for district in districts {
+        let identifier = identifier(district);
+        let _ = writeln!(xml, "  <entry>");
+        let _ = writeln!(xml, "    <id>{}#{}</id>", escape_xml(&feed_url), identifier);
+        let _ = writeln!(xml, "    <title>{}</title>", escape_xml(&headline(district, Language::Vi)));
+        let _ = writeln!(xml, "    <updated>{}</updated>", district.last_detected_at.to_rfc3339());
+        let _ = writeln!(xml, "    <content type=\"application/cap+xml\">");
+        write_cap_alert(&mut xml, district, &identifier, &sender, generated_at);
+        let _ = writeln!(xml, "    </content>");
+        let _ = writeln!(xml, "  </entry>");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// The same districts as a bulletin spreadsheet, one row per district.
+pub fn bulletin_csv(districts: &[DistrictAlerts], lang: Language) -> Result<Vec<u8>, AppError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let csv_error = |e: csv::Error| AppError::Internal(format!("Failed to write CSV: {}", e));
+    writer
+        .write_record([
+            "district",
+            "severity",
+            "alerts",
+            "critical_alerts",
+            "farms",
+            "first_detected_at",
+            "last_detected_at",
+            "expires_at",
+            "headline",
+        ])
+        .map_err(csv_error)?;
+    for district in districts {
+        writer
+            .write_record([
+                spreadsheet_text(district_name(district, lang)),
+                district.severity().as_str().to_string(),
+                district.alert_count.to_string(),
+                district.critical_count.to_string(),
+                district.farm_count.to_string(),
+                district.first_detected_at.to_rfc3339(),
+                district.last_detected_at.to_rfc3339(),
+                expires_at(district).to_rfc3339(),
+                spreadsheet_text(headline(district, lang)),
+            ])
+            .map_err(csv_error)?;
+    }
+
+    writer.into_inner().map_err(|e| AppError::Internal(format!("Failed to write CSV: {}", e)))
+}
+
+/// Region names are free text; one starting like a formula would run as one
+/// when the bulletin is opened in a spreadsheet, so it is quoted as text.
+fn spreadsheet_text(text: String) -> String {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text
+    }
+}
+
+fn write_cap_alert(xml: &mut String, district: &DistrictAlerts, identifier: &str, sender: &str, sent: DateTime<Utc>) {
+    let severity = district.severity();
+    let (urgency, cap_severity) = match severity {
+        AlertSeverity::Critical => ("Immediate", "Extreme"),
+        _ => ("Expected", "Severe"),
+    };
+
+    let _ = writeln!(xml, "      <alert xmlns=\"{}\">", CAP_NAMESPACE);
+    let _ = writeln!(xml, "        <identifier>{}</identifier>", identifier);
+    let _ = writeln!(xml, "        <sender>{}</sender>", escape_xml(sender));
+    let _ = writeln!(xml, "        <sent>{}</sent>", sent.format(CAP_TIME_FORMAT));
+    xml.push_str("        <status>Actual</status>\n");
+    xml.push_str("        <msgType>Alert</msgType>\n");
+    xml.push_str("        <scope>Public</scope>\n");
+
+    for (lang, code) in CAP_LANGUAGES {
+        let _ = writeln!(xml, "        <info>");
+        let _ = writeln!(xml, "          <language>{}</language>", code);
+        xml.push_str("          <category>Env</category>\n");
+        let _ = writeln!(xml, "          <event>{}</event>", escape_xml(&t(lang, "cap.event", &[])));
+        let _ = writeln!(xml, "          <urgency>{}</urgency>", urgency);
+        let _ = writeln!(xml, "          <severity>{}</severity>", cap_severity);
+        xml.push_str("          <certainty>Likely</certainty>\n");
+        let _ = writeln!(xml, "          <onset>{}</onset>", district.first_detected_at.format(CAP_TIME_FORMAT));
+        let _ = writeln!(xml, "          <expires>{}</expires>", expires_at(district).format(CAP_TIME_FORMAT));
+        let _ = writeln!(xml, "          <senderName>{}</senderName>", escape_xml(&t(lang, "cap.sender_name", &[])));
+        let _ = writeln!(xml, "          <headline>{}</headline>", escape_xml(&headline(district, lang)));
+        let _ = writeln!(xml, "          <description>{}</description>", escape_xml(&description(district, lang)));
+        let _ = writeln!(
+            xml,
+            "          <instruction>{}</instruction>",
+            escape_xml(&t(lang, &format!("cap.instruction.{}", severity.as_str()), &[])),
+        );
+        let _ = writeln!(xml, "          <area>");
+        let _ = writeln!(xml, "            <areaDesc>{}</areaDesc>", escape_xml(&district_name(district, lang)));
+        if let Some(polygon) = district.bbox.map(polygon) {
+            let _ = writeln!(xml, "            <polygon>{}</polygon>", polygon);
+        }
+        let _ = writeln!(xml, "          </area>");
+        let _ = writeln!(xml, "        </info>");
+    }
+
+    let _ = writeln!(xml, "      </alert>");
+}
+
+fn identifier(district: &DistrictAlerts) -> String {
+    format!("bio-radar-salinity-{}", district.latest_alert_id)
+}
+
+/// The deployment's host, which identifies this system as the CAP sender.
+fn sender(base_url: &str) -> String {
+    let host = base_url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    if host.is_empty() { "bio-radar".to_string() } else { host.to_string() }
+}
+
+fn expires_at(district: &DistrictAlerts) -> DateTime<Utc> {
+    district.last_detected_at + Duration::days(i64::from(FEED_WINDOW_DAYS))
+}
+
+fn district_name(district: &DistrictAlerts, lang: Language) -> String {
+    district
+        .district
+        .clone()
+        .unwrap_or_else(|| t(lang, "cap.unassigned_area", &[]))
+}
+
+fn headline(district: &DistrictAlerts, lang: Language) -> String {
+    t(
+        lang,
+        "cap.headline",
+        &[
+            ("district", district_name(district, lang)),
+            ("severity", t(lang, &format!("severity.{}", district.severity().as_str()), &[])),
+        ],
+    )
+}
+
+fn description(district: &DistrictAlerts, lang: Language) -> String {
+    t(
+        lang,
+        "cap.description",
+        &[
+            ("alerts", district.alert_count.to_string()),
+            ("critical", district.critical_count.to_string()),
+            ("farms", district.farm_count.to_string()),
+            ("since", district.first_detected_at.format("%Y-%m-%d").to_string()),
+        ],
+    )
+}
+
+/// The bounding box as a closed CAP polygon of `lat,lon` pairs.
+fn polygon([min_lon, min_lat, max_lon, max_lat]: [f64; 4]) -> String {
+    [
+        (min_lat, min_lon),
+        (min_lat, max_lon),
+        (max_lat, max_lon),
+        (max_lat, min_lon),
+        (min_lat, min_lon),
+    ]
+    .iter()
+    .map(|(lat, lon)| format!("{:.5},{:.5}", lat, lon))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -1,60 +1,157 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::Bytes,
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use crate::shared::{AppState, AppResult, error::AppError};
-use super::models::{AnalysisRequest, AnalysisResult};
-use super::service;
+use chrono::Utc;
+use crate::shared::{
+    AppState, AppResult, access, audit::AuditDetails, error::{AppError, ErrorCode, ErrorResponse}, tiles::{self, TileCoord},
+    i18n::Language, validation::ValidatedJson,
+};
+use crate::modules::{api_keys::{self, ApiKeyScope}, events, settings::{self, quota, UsageKind}, webhooks::WebhookEvent};
+use super::models::{
+    Alert, AlertBboxQuery, AlertFeedQuery, FeedFormat, AlertTileQuery, AnalysisRequest, AnalysisResult, CreateSensorReading, FarmStatus, IntrusionVector,
+    SalinityCalibration, SalinityLog, VectorFormat, VectorQuery, AlertRulesResponse, UpdateAlertRulesRequest,
+    SalinityImportQuery, SalinityImportResponse, SimulationRequest, SimulationResponse, RegionThreshold,
+    SetRegionThresholdRequest, AiModel, ModelStatus, RegisterModelRequest, ShadowReport, HotspotQuery,
+    BreakpointQuery, BreakpointReport, AnalysisComparison, AnalysisSnapshot, CompareRunsQuery,
+};
+use crate::modules::auth::models::Claims;
+use super::{cap, service};
 use super::repository;
-use super::ai::image_proc::{preprocess_image, postprocess_segmentation};
+use super::ai::image_proc::water_pixels;
+
+const DEFAULT_BREAKPOINT_DAYS: i32 = 180;
+const MAX_BREAKPOINT_DAYS: i32 = 730;
+const DEFAULT_BBOX_ALERT_LIMIT: i64 = 500;
+const MAX_BBOX_ALERT_LIMIT: i64 = 2000;
+const DEFAULT_ALERT_TILE_DAYS: i32 = 30;
+const MAX_ALERT_TILE_DAYS: i32 = 365;
+const DEFAULT_HOTSPOT_LIMIT: i64 = 50;
+const MAX_HOTSPOT_LIMIT: i64 = 500;
 
+#[utoipa::path(
+    post,
+    path = "/analyze",
+    tag = "monitoring",
+    request_body = AnalysisRequest,
+    responses(
+        (status = 200, description = "Analysis completed", body = AnalysisResult),
+        (status = 400, description = "Invalid image payload", body = ErrorResponse),
+        (status = 401, description = "Caller does not own the farm", body = ErrorResponse),
+        (status = 403, description = "Monthly analysis quota of the farm owner's plan reached", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+        (status = 500, description = "AI engine unavailable or failed", body = ErrorResponse),
+    ),
+)]
 pub async fn trigger_analysis(
     State(state): State<AppState>,
-    Json(payload): Json<AnalysisRequest>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<AnalysisRequest>,
 ) -> AppResult<impl IntoResponse> {
     let farm_id = payload.farm_id;
+    let owner_id = repository::get_farm_owner(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+    if owner_id != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to analyze this farm".to_string()));
+    }
 
-    let ai_engine = state.ai_engine.as_ref()
-        .ok_or_else(|| AppError::AiEngine("AI Engine not initialized".to_string()))?;
-
-    let image_bytes = payload.image_base64
-        .ok_or_else(|| AppError::BadRequest("image_base64 is required".to_string()))
-        .and_then(|b64| {
-            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, b64)
-                .map_err(|e| AppError::BadRequest(format!("Invalid base64: {}", e)))
-        })?;
-
-    let config = ai_engine.config();
-    let device = ai_engine.device();
+    let model = state.models.active()
+        .ok_or_else(|| AppError::Coded(ErrorCode::AiEngineUnavailable, "AI Engine not initialized".to_string()))?;
+    service::validate_image_bounds(payload.image_bounds)?;
+    if !payload.scenes.is_empty() && (payload.image_base64.is_some() || payload.image_bounds.is_some()) {
+        return Err(AppError::Validation("Send either scenes or image_base64 with image_bounds, not both".to_string()));
+    }
+    quota::ensure_analysis_available(&state.db, owner_id).await?;
 
-    let input_tensor = preprocess_image(&image_bytes, config, device)?;
-    let output_tensor = ai_engine.predict(&input_tensor)?;
-
-    let water_class_idx = config.classes
-        .iter()
-        .position(|c| c == "water")
-        .unwrap_or(1);
-
-    let water_pixels = postprocess_segmentation(&output_tensor, water_class_idx)?;
-
-    let water_coverage_percent = if config.img_size > 0 {
-        (water_pixels.len() as f64 / (config.img_size * config.img_size) as f64) * 100.0
+    let (image_bytes, image_bounds, scene_ids) = if payload.scenes.is_empty() {
+        let image_bytes = payload.image_base64
+            .ok_or_else(|| AppError::BadRequest("image_base64 is required".to_string()))
+            .and_then(|b64| {
+                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, b64)
+                    .map_err(|e| AppError::BadRequest(format!("Invalid base64: {}", e)))
+            })?;
+        (image_bytes, payload.image_bounds, Vec::new())
     } else {
-        0.0
+        let mosaic = service::mosaic_scenes(payload.scenes).await?;
+        (mosaic.png, Some(mosaic.bounds), mosaic.scene_ids)
     };
 
+    let img_size = model.img_size();
+    let input = model.preprocess(&image_bytes)?;
+    let output = state.batcher.predict(model.clone(), input).await?;
+    let (probabilities, width) = model.water_probabilities(&output)?;
+    let (water_threshold, threshold_source) = service::water_threshold(farm_id, &probabilities, &state.db).await?;
+    let water_pixels = water_pixels(&probabilities, width, water_threshold);
+    let water_coverage_percent = service::water_coverage_percent(water_pixels.len(), img_size);
+
     let ndsi_value = water_coverage_percent / 100.0;
-    service::save_ndsi_measurement(farm_id, ndsi_value, "ai_analysis", &state.db).await?;
+    quota::charge_analysis(&state.db, owner_id).await?;
+    let log_id = service::save_ndsi_measurement(farm_id, ndsi_value, "ai_analysis", Some(&model.version), &state.db).await?;
+    if !scene_ids.is_empty() {
+        repository::save_scene_ids(log_id, &scene_ids, &state.db).await?;
+    }
+    settings::service::record_usage(
+        &state.db,
+        UsageKind::AnalysisRun,
+        Some(owner_id),
+        Some(farm_id),
+        1.0,
+        serde_json::json!({ "model_version": model.version }),
+    )
+    .await;
 
-    let alert = service::detect_salinity_anomaly(farm_id, &state.db).await?;
+    if let Some(shadow) = state.models.shadow() {
+        service::spawn_shadow_analysis(
+            shadow,
+            image_bytes,
+            log_id,
+            water_threshold,
+            water_coverage_percent,
+            state.batcher.clone(),
+            state.db.clone(),
+        );
+    }
 
-    let intrusion_vector = if !water_pixels.is_empty() {
-        service::calculate_intrusion_vector(farm_id, &water_pixels, &state.db).await?
-    } else {
-        None
-    };
+    let evaluation = service::detect_salinity_anomaly(farm_id, &state.db).await?;
+    if let Some(risk) = &evaluation.risk {
+        let snapshot = AnalysisSnapshot {
+            water_coverage_percent,
+            water_threshold,
+            threshold_source,
+            estimated_g_l: evaluation.estimated_g_l,
+            severity: evaluation.alert.as_ref().map(|alert| alert.severity),
+            risk_score: risk.score,
+            risk_level: risk.level,
+            thresholds: evaluation.thresholds,
+        };
+        repository::save_analysis_snapshot(log_id, &snapshot, &state.db).await?;
+    }
+    let mut alert = evaluation.alert;
+
+    let intrusion_vector = service::calculate_intrusion_vector(
+        farm_id,
+        log_id,
+        &water_pixels,
+        img_size,
+        image_bounds,
+        &state.db,
+    )
+    .await?;
+
+    let hotspot_ids = service::record_hotspots(
+        farm_id,
+        log_id,
+        alert.as_mut(),
+        &probabilities,
+        width,
+        image_bounds,
+        &state.db,
+    )
+    .await?;
 
     let result = AnalysisResult {
         farm_id,
@@ -62,11 +159,130 @@ pub async fn trigger_analysis(
         alert,
         intrusion_vector,
         water_coverage_percent,
+        water_threshold,
+        threshold_source,
+        model_version: model.version.clone(),
+        scene_ids,
+        hotspot_ids,
     };
 
+    let mut conn = state.db.acquire().await?;
+    events::record(&mut conn, WebhookEvent::AnalysisCompleted, Some(farm_id), &serde_json::json!(result)).await?;
+
     Ok((StatusCode::OK, Json(result)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/alerts",
+    tag = "monitoring",
+    params(AlertBboxQuery),
+    responses(
+        (status = 200, description = "Alerts on the caller's farms whose affected area, or farm when they have \
+            none, intersects the map extent, newest first; admins see every farm", body = [Alert]),
+        (status = 400, description = "Invalid bbox or limit", body = ErrorResponse),
+    ),
+)]
+pub async fn get_alerts_in_bbox(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AlertBboxQuery>,
+) -> AppResult<impl IntoResponse> {
+    let bbox = service::parse_bbox(&query.bbox)?;
+    let limit = query.limit.unwrap_or(DEFAULT_BBOX_ALERT_LIMIT);
+    if !(1..=MAX_BBOX_ALERT_LIMIT).contains(&limit) {
+        return Err(AppError::Validation(format!("limit must be between 1 and {}", MAX_BBOX_ALERT_LIMIT)));
+    }
+
+    let user_id = (!claims.is_admin()).then_some(claims.sub);
+    let alerts = repository::get_alerts_in_bbox(bbox, user_id, limit, &state.db).await?;
+    Ok(Json(alerts))
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts/mvt/{z}/{x}/{y}",
+    tag = "monitoring",
+    params(
+        ("z" = u32, Path, description = "Zoom level"),
+        ("x" = u32, Path, description = "Tile column"),
+        ("y" = u32, Path, description = "Tile row"),
+        AlertTileQuery,
+    ),
+    responses(
+        (status = 200, description = "Mapbox Vector Tile with an `alerts` layer of each alert's affected area, or its whole \
+            farm when it has none, on the caller's farms, keyed by alert id. Admins see every farm.", content_type = "application/vnd.mapbox-vector-tile", body = Vec<u8>),
+        (status = 400, description = "Tile out of range, unknown field or invalid window", body = ErrorResponse),
+    ),
+)]
+pub async fn get_alert_tile(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(tile): Path<TileCoord>,
+    Query(query): Query<AlertTileQuery>,
+) -> AppResult<Response> {
+    tile.validate()?;
+    let fields = tiles::select_fields(query.fields.as_deref(), repository::ALERT_TILE_FIELDS)?;
+    let days = query.days.unwrap_or(DEFAULT_ALERT_TILE_DAYS);
+    if !(1..=MAX_ALERT_TILE_DAYS).contains(&days) {
+        return Err(AppError::Validation(format!("days must be between 1 and {}", MAX_ALERT_TILE_DAYS)));
+    }
+
+    let user_id = (!claims.is_admin()).then_some(claims.sub);
+    let tile = repository::alert_tile(tile, user_id, days, &fields, &state.db).await?;
+    Ok(tiles::response(tile))
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts/feed.cap",
+    tag = "monitoring",
+    params(AlertFeedQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Unacknowledged high and critical alerts of the last week per district, as an Atom \
+            feed of CAP 1.2 alerts; with format=csv, the same districts as a bulletin", content_type = "application/atom+xml", body = String),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorResponse),
+        (status = 403, description = "API key lacks the `alert_feed` scope", body = ErrorResponse),
+    ),
+)]
+pub async fn get_alert_feed(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AlertFeedQuery>,
+) -> AppResult<Response> {
+    api_keys::require_scope(&state.db, &headers, ApiKeyScope::AlertFeed).await?;
+
+    let districts = repository::list_district_alerts(cap::FEED_WINDOW_DAYS, &state.db).await?;
+    let now = Utc::now();
+
+    if query.format == FeedFormat::Cap {
+        return Ok((
+            [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+            cap::atom_feed(&districts, now),
+        )
+            .into_response());
+    }
+
+    let csv = cap::bulletin_csv(&districts, query.lang.unwrap_or(Language::Vi))?;
+    let filename = format!("bio-radar-salinity-bulletin-{}.csv", now.format("%Y-%m-%d"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts/{farm_id}",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id")),
+    responses((status = 200, description = "Most recent alerts for the farm", body = [Alert])),
+)]
 pub async fn get_alerts(
     State(state): State<AppState>,
     Path(farm_id): Path<i64>,
@@ -75,22 +291,204 @@ pub async fn get_alerts(
     Ok(Json(alerts))
 }
 
+#[utoipa::path(
+    post,
+    path = "/alerts/{alert_id}/acknowledge",
+    tag = "monitoring",
+    params(("alert_id" = i64, Path, description = "Alert id")),
+    responses(
+        (status = 200, description = "Alert acknowledged", body = Alert),
+        (status = 401, description = "Caller does not own the alert's farm", body = ErrorResponse),
+        (status = 404, description = "Alert not found", body = ErrorResponse),
+    ),
+)]
+pub async fn acknowledge_alert(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(alert_id): Path<i64>,
+) -> AppResult<impl IntoResponse> {
+    let before = repository::get_alert(alert_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::AlertNotFound, format!("Alert {} not found", alert_id)))?;
+
+    let owner = repository::get_farm_owner(before.farm_id, &state.db).await?;
+    if owner != Some(claims.sub) && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to access this alert".to_string()));
+    }
+
+    let alert = repository::acknowledge_alert(alert_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::AlertNotFound, format!("Alert {} not found", alert_id)))?;
+
+    let audit = AuditDetails::new("alert.acknowledge", "alert", Some(alert_id))
+        .before(&before)
+        .after(&alert);
+
+    Ok((Extension(audit), Json(alert)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/salinity/{farm_id}",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id")),
+    responses((status = 200, description = "NDSI history of the last 30 days", body = [SalinityLog])),
+)]
 pub async fn get_salinity_history(
     State(state): State<AppState>,
     Path(farm_id): Path<i64>,
 ) -> AppResult<impl IntoResponse> {
-    let history = repository::get_ndsi_history(farm_id, 30, &state.db).await?;
+    let history = service::get_salinity_history(farm_id, 30, &state.db).await?;
     Ok(Json(history))
 }
 
-pub async fn get_intrusion_vector(
+#[utoipa::path(
+    get,
+    path = "/salinity/{farm_id}/breakpoints",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id"), BreakpointQuery),
+    responses(
+        (status = 200, description = "Shifts in the farm's daily mean NDSI, with the latest described in words", body = BreakpointReport),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+        (status = 401, description = "Farm belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_salinity_breakpoints(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    Query(query): Query<BreakpointQuery>,
+) -> AppResult<impl IntoResponse> {
+    let owner_id = repository::get_farm_owner(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+    if owner_id != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let days = query.days.unwrap_or(DEFAULT_BREAKPOINT_DAYS);
+    if !(1..=MAX_BREAKPOINT_DAYS).contains(&days) {
+        return Err(AppError::Validation(format!("days must be between 1 and {}", MAX_BREAKPOINT_DAYS)));
+    }
+
+    let report = service::ndsi_breakpoints(farm_id, days, &state.db).await?;
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    get,
+    path = "/analyses/{farm_id}/compare",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id"), CompareRunsQuery),
+    responses(
+        (status = 200, description = "Changes from run_a to run_b and the rule limits that flipped", body = AnalysisComparison),
+        (status = 401, description = "Farm belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Farm not found, or a run is not an AI analysis of the farm", body = ErrorResponse),
+    ),
+)]
+pub async fn compare_analyses(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(farm_id): Path<i64>,
+    Query(query): Query<CompareRunsQuery>,
 ) -> AppResult<impl IntoResponse> {
+    let owner_id = repository::get_farm_owner(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+    if owner_id != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let comparison = service::compare_runs(farm_id, query.run_a, query.run_b, &state.db).await?;
+    Ok(Json(comparison))
+}
+
+#[utoipa::path(
+    get,
+    path = "/vector/{farm_id}",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id"), VectorQuery),
+    responses(
+        (status = 200, description = "Latest intrusion vector, if any. With format=geojson, a FeatureCollection \
+            holding the vector as a LineString and the predicted affected area as a Polygon", body = Option<IntrusionVector>),
+        (status = 401, description = "Farm belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_intrusion_vector(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    Query(query): Query<VectorQuery>,
+) -> AppResult<Response> {
+    let owner_id = repository::get_farm_owner(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+    if owner_id != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
     let vector = repository::get_latest_intrusion_vector(farm_id, &state.db).await?;
-    Ok(Json(vector))
+
+    if query.format == VectorFormat::Json {
+        return Ok(Json(vector).into_response());
+    }
+
+    let origin = repository::get_farm_centroid(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    let collection = match vector {
+        Some(vector) => service::intrusion_feature_collection(origin, &vector),
+        None => geojson::FeatureCollection { bbox: None, features: vec![], foreign_members: None },
+    };
+
+    Ok(([(header::CONTENT_TYPE, "application/geo+json")], collection.to_string()).into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/hotspots/{farm_id}",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id"), HotspotQuery),
+    responses(
+        (status = 200, description = "GeoJSON FeatureCollection of within-farm hot spots, newest first", content_type = "application/geo+json"),
+        (status = 401, description = "Farm belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_hotspots(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    Query(query): Query<HotspotQuery>,
+) -> AppResult<Response> {
+    let owner_id = repository::get_farm_owner(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+    if owner_id != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_HOTSPOT_LIMIT);
+    if !(1..=MAX_HOTSPOT_LIMIT).contains(&limit) {
+        return Err(AppError::Validation(format!("limit must be between 1 and {}", MAX_HOTSPOT_LIMIT)));
+    }
+
+    let hotspots = repository::list_hotspots(farm_id, query.log_id, limit, &state.db).await?;
+    let collection = service::hotspot_feature_collection(&hotspots)?;
+
+    Ok(([(header::CONTENT_TYPE, "application/geo+json")], collection.to_string()).into_response())
 }
 
+#[utoipa::path(
+    get,
+    path = "/status/{farm_id}",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id")),
+    responses((status = 200, description = "Current monitoring status", body = FarmStatus)),
+)]
 pub async fn get_farm_status(
     State(state): State<AppState>,
     Path(farm_id): Path<i64>,
@@ -99,9 +497,387 @@ pub async fn get_farm_status(
     Ok(Json(status))
 }
 
+#[utoipa::path(
+    post,
+    path = "/sensors/readings",
+    tag = "monitoring",
+    request_body = CreateSensorReading,
+    responses(
+        (status = 201, description = "Reading stored"),
+        (status = 404, description = "Farm not found or not owned by the caller", body = ErrorResponse),
+        (status = 422, description = "Negative ec_ds_m", body = ErrorResponse),
+    ),
+)]
+pub async fn record_sensor_reading(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateSensorReading>,
+) -> AppResult<impl IntoResponse> {
+    // Readings feed the regional calibration, so like CSV imports they are
+    // only taken for the caller's own farms.
+    let owner = repository::get_farm_owner(payload.farm_id, &state.db).await?;
+    if !owner.is_some_and(|owner| owner == claims.sub || claims.is_admin()) {
+        return Err(AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", payload.farm_id)));
+    }
+
+    let id = repository::save_sensor_reading(payload, &state.db).await?;
+    Ok((StatusCode::CREATED, Json(serde_json::json!({ "id": id }))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/salinity/import",
+    tag = "monitoring",
+    params(SalinityImportQuery),
+    request_body(content = String, content_type = "text/csv", description = "CSV with a header row"),
+    responses(
+        (status = 200, description = "Import report with row-level errors", body = SalinityImportResponse),
+        (status = 400, description = "Missing column, bad mapping or too many rows", body = ErrorResponse),
+    ),
+)]
+pub async fn import_salinity(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<SalinityImportQuery>,
+    body: Bytes,
+) -> AppResult<impl IntoResponse> {
+    let report = service::import_salinity_csv(&body, &query, claims.sub, claims.is_admin(), &state.db).await?;
+
+    let audit = AuditDetails::new("salinity.import", "farm", query.farm_id).after(&serde_json::json!({
+        "source": query.source,
+        "imported": report.imported,
+        "duplicates": report.duplicates,
+        "failed": report.failed,
+        "dry_run": report.dry_run,
+    }));
+
+    Ok((Extension(audit), Json(report)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/simulate",
+    tag = "monitoring",
+    request_body = SimulationRequest,
+    responses(
+        (status = 200, description = "Farms of the caller the scenario would affect within the horizon", body = SimulationResponse),
+        (status = 400, description = "No scenario given", body = ErrorResponse),
+        (status = 422, description = "Value out of range", body = ErrorResponse),
+    ),
+)]
+pub async fn simulate(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<SimulationRequest>,
+) -> AppResult<Json<SimulationResponse>> {
+    let result = service::simulate(claims.sub, payload, &state.db).await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/calibrations",
+    tag = "monitoring",
+    responses((status = 200, description = "Per-region NDSI to EC calibrations", body = [SalinityCalibration])),
+)]
+pub async fn list_calibrations(
+    State(state): State<AppState>,
+) -> AppResult<impl IntoResponse> {
+    let calibrations = repository::list_calibrations(&state.db).await?;
+    Ok(Json(calibrations))
+}
+
+#[utoipa::path(
+    get,
+    path = "/thresholds",
+    tag = "monitoring",
+    responses((status = 200, description = "Regions with a configured water threshold; others use an Otsu estimate per image", body = [RegionThreshold])),
+)]
+pub async fn list_region_thresholds(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<RegionThreshold>>> {
+    let thresholds = repository::list_region_thresholds(&state.db).await?;
+    Ok(Json(thresholds))
+}
+
+#[utoipa::path(
+    put,
+    path = "/thresholds/{region}",
+    tag = "monitoring",
+    params(("region" = String, Path, description = "Farm region")),
+    request_body = SetRegionThresholdRequest,
+    responses(
+        (status = 200, description = "Threshold stored", body = RegionThreshold),
+        (status = 400, description = "Blank region", body = ErrorResponse),
+        (status = 422, description = "Threshold outside (0, 1)", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn set_region_threshold(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(region): Path<String>,
+    ValidatedJson(payload): ValidatedJson<SetRegionThresholdRequest>,
+) -> AppResult<(Extension<AuditDetails>, Json<RegionThreshold>)> {
+    access::require_admin(&claims)?;
+
+    let before = repository::get_region_threshold(region.trim(), &state.db).await?;
+    let after = service::set_region_threshold(&region, payload.water_threshold, claims.sub, &state.db).await?;
+
+    let mut audit = AuditDetails::new("threshold.update", "region_threshold", None).after(&after);
+    if let Some(before) = &before {
+        audit = audit.before(before);
+    }
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/thresholds/{region}",
+    tag = "monitoring",
+    params(("region" = String, Path, description = "Farm region")),
+    responses(
+        (status = 200, description = "Threshold removed; the region falls back to per-image estimation"),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No threshold configured for the region", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_region_threshold(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(region): Path<String>,
+) -> AppResult<(Extension<AuditDetails>, Json<serde_json::Value>)> {
+    access::require_admin(&claims)?;
+
+    let existing = repository::get_region_threshold(region.trim(), &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No threshold configured for region {}", region.trim())))?;
+    repository::delete_region_threshold(&existing.region, &state.db).await?;
+
+    let audit = AuditDetails::new("threshold.delete", "region_threshold", None).before(&existing);
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/rules/{farm_id}",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id")),
+    responses(
+        (status = 200, description = "Effective alert thresholds and the farm's overrides", body = AlertRulesResponse),
+        (status = 401, description = "Farm belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_alert_rules(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+) -> AppResult<impl IntoResponse> {
+    let owner = repository::get_farm_owner(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    if owner != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to view rules for this farm".to_string()));
+    }
+
+    let rules = service::get_alert_rules(farm_id, &state.db).await?;
+    Ok(Json(rules))
+}
+
+#[utoipa::path(
+    put,
+    path = "/rules/{farm_id}",
+    tag = "monitoring",
+    params(("farm_id" = i64, Path, description = "Farm id")),
+    request_body = UpdateAlertRulesRequest,
+    responses(
+        (status = 200, description = "Rules replaced", body = AlertRulesResponse),
+        (status = 400, description = "Unknown crop type or inconsistent thresholds", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn update_alert_rules(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<UpdateAlertRulesRequest>,
+) -> AppResult<impl IntoResponse> {
+    let owner = repository::get_farm_owner(farm_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    if owner != claims.sub && !claims.is_admin() {
+        return Err(AppError::Unauthorized("Not authorized to change rules for this farm".to_string()));
+    }
+
+    let before = service::get_alert_rules(farm_id, &state.db).await?;
+    let after = service::update_alert_rules(farm_id, payload, &state.db).await?;
+
+    let audit = AuditDetails::new("alert_rules.update", "farm", Some(farm_id))
+        .before(&before)
+        .after(&after);
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "monitoring",
+    responses((status = 200, description = "Module is up")),
+)]
 pub async fn health_check() -> impl IntoResponse {
     Json(serde_json::json!({
         "status": "healthy",
         "module": "monitoring"
     }))
-}
\ No newline at end of file
+}
+#[utoipa::path(
+    get,
+    path = "/models",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "Registered segmentation models, newest first", body = [AiModel]),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn list_models(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<Json<Vec<AiModel>>> {
+    access::require_admin(&claims)?;
+
+    let models = repository::list_ai_models(&state.db).await?;
+    Ok(Json(models))
+}
+
+#[utoipa::path(
+    post,
+    path = "/models",
+    tag = "monitoring",
+    request_body = RegisterModelRequest,
+    responses(
+        (status = 200, description = "Model registered as inactive", body = AiModel),
+        (status = 400, description = "Duplicate version or unreadable model files", body = ErrorResponse),
+        (status = 422, description = "Blank or overlong name or version, or metrics not an object", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn register_model(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<RegisterModelRequest>,
+) -> AppResult<(Extension<AuditDetails>, Json<AiModel>)> {
+    access::require_admin(&claims)?;
+
+    let model = service::register_model(payload, claims.sub, &state.db).await?;
+    let audit = AuditDetails::new("model.register", "ai_model", Some(model.id)).after(&model);
+
+    Ok((Extension(audit), Json(model)))
+}
+
+async fn change_model_status(
+    state: &AppState,
+    claims: &Claims,
+    id: i64,
+    status: ModelStatus,
+    action: &'static str,
+) -> AppResult<(Extension<AuditDetails>, Json<AiModel>)> {
+    access::require_admin(claims)?;
+
+    let before = repository::get_ai_model(id, &state.db).await?;
+    let after = service::set_model_status(id, status, &state.models, &state.db).await?;
+
+    let mut audit = AuditDetails::new(action, "ai_model", Some(id)).after(&after);
+    if let Some(before) = &before {
+        audit = audit.before(before);
+    }
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/models/{id}/activate",
+    tag = "monitoring",
+    params(("id" = i64, Path, description = "Model id")),
+    responses(
+        (status = 200, description = "Model now serves analyses; the previous one is inactive", body = AiModel),
+        (status = 400, description = "Model files can no longer be loaded", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Model not found", body = ErrorResponse),
+    ),
+)]
+pub async fn activate_model(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> AppResult<(Extension<AuditDetails>, Json<AiModel>)> {
+    change_model_status(&state, &claims, id, ModelStatus::Active, "model.activate").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/models/{id}/shadow",
+    tag = "monitoring",
+    params(("id" = i64, Path, description = "Model id")),
+    responses(
+        (status = 200, description = "Model runs next to the active one on every analysis", body = AiModel),
+        (status = 400, description = "Model is active or its files cannot be loaded", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Model not found", body = ErrorResponse),
+    ),
+)]
+pub async fn shadow_model(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> AppResult<(Extension<AuditDetails>, Json<AiModel>)> {
+    change_model_status(&state, &claims, id, ModelStatus::Shadow, "model.shadow").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/models/{id}/deactivate",
+    tag = "monitoring",
+    params(("id" = i64, Path, description = "Model id")),
+    responses(
+        (status = 200, description = "Model no longer runs", body = AiModel),
+        (status = 400, description = "Model is the active one", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Model not found", body = ErrorResponse),
+    ),
+)]
+pub async fn deactivate_model(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> AppResult<(Extension<AuditDetails>, Json<AiModel>)> {
+    change_model_status(&state, &claims, id, ModelStatus::Inactive, "model.deactivate").await
+}
+
+#[utoipa::path(
+    get,
+    path = "/models/{id}/shadow-report",
+    tag = "monitoring",
+    params(("id" = i64, Path, description = "Model id")),
+    responses(
+        (status = 200, description = "Water coverage of the model's shadow runs against the active model", body = ShadowReport),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Model not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_shadow_report(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<ShadowReport>> {
+    access::require_admin(&claims)?;
+
+    let report = service::shadow_report(id, &state.db).await?;
+    Ok(Json(report))
+}
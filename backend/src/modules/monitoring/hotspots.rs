@@ -0,0 +1,197 @@
+//! Local hot spots of water probability inside a farm. The segmentation
+//! output is averaged onto a coarse grid and each cell gets a Getis-Ord Gi*
+//! z-score over its 3x3 neighbourhood; adjacent significant cells form one
+//! hot spot. A farm whose mean looks normal can still have a flooded corner.
+
+/// Cells per side of the grid the raster is averaged onto.
+const GRID_SIZE: usize = 32;
+/// Gi* above this is significant at the 99% level.
+const Z_THRESHOLD: f64 = 2.58;
+/// Lone significant cells are treated as noise.
+const MIN_CELLS: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct HotspotCells {
+    /// `(column, row)` of each cell; row 0 is the northern edge.
+    pub cells: Vec<(usize, usize)>,
+    pub mean_z: f64,
+    pub max_z: f64,
+    pub mean_probability: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hotspots {
+    pub columns: usize,
+    pub rows: usize,
+    /// Strongest first.
+    pub spots: Vec<HotspotCells>,
+}
+
+impl Hotspots {
+    /// Ground extent of a cell as `[min_lon, min_lat, max_lon, max_lat]`
+    /// within an image covering `bounds`.
+    pub fn cell_bounds(&self, (column, row): (usize, usize), bounds: [f64; 4]) -> [f64; 4] {
+        let [min_lon, min_lat, max_lon, max_lat] = bounds;
+        let lon_step = (max_lon - min_lon) / self.columns as f64;
+        let lat_step = (max_lat - min_lat) / self.rows as f64;
+        [
+            min_lon + column as f64 * lon_step,
+            max_lat - (row + 1) as f64 * lat_step,
+            min_lon + (column + 1) as f64 * lon_step,
+            max_lat - row as f64 * lat_step,
+        ]
+    }
+}
+
+/// Finds hot spots in a row-major probability raster `width` pixels wide.
+pub fn detect(probabilities: &[f32], width: usize) -> Hotspots {
+    let height = probabilities.len().checked_div(width).unwrap_or(0);
+    let columns = GRID_SIZE.min(width);
+    let rows = GRID_SIZE.min(height);
+    let empty = Hotspots { columns, rows, spots: Vec::new() };
+    if columns == 0 || rows == 0 {
+        return empty;
+    }
+
+    let grid = average_grid(probabilities, width, height, columns, rows);
+    let n = grid.len() as f64;
+    let mean = grid.iter().sum::<f64>() / n;
+    let std_dev = (grid.iter().map(|x| x * x).sum::<f64>() / n - mean * mean).max(0.0).sqrt();
+    if std_dev < 1e-9 || grid.len() < 2 {
+        return empty;
+    }
+
+    let z: Vec<f64> = (0..grid.len())
+        .map(|i| gi_star(&grid, columns, rows, i % columns, i / columns, mean, std_dev))
+        .collect();
+    let hot: Vec<bool> = z.iter().zip(&grid).map(|(&z, &x)| z >= Z_THRESHOLD && x > mean).collect();
+
+    let mut seen = vec![false; grid.len()];
+    let mut spots = Vec::new();
+    for start in 0..grid.len() {
+        if !hot[start] || seen[start] {
+            continue;
+        }
+
+        let mut stack = vec![start];
+        let mut members = Vec::new();
+        seen[start] = true;
+        while let Some(i) = stack.pop() {
+            members.push(i);
+            let (column, row) = (i % columns, i / columns);
+            let neighbours = [
+                (column > 0).then(|| i - 1),
+                (column + 1 < columns).then(|| i + 1),
+                (row > 0).then(|| i - columns),
+                (row + 1 < rows).then(|| i + columns),
+            ];
+            for j in neighbours.into_iter().flatten() {
+                if hot[j] && !seen[j] {
+                    seen[j] = true;
+                    stack.push(j);
+                }
+            }
+        }
+
+        if members.len() < MIN_CELLS {
+            continue;
+        }
+        let count = members.len() as f64;
+        spots.push(HotspotCells {
+            mean_z: members.iter().map(|&i| z[i]).sum::<f64>() / count,
+            max_z: members.iter().map(|&i| z[i]).fold(f64::MIN, f64::max),
+            mean_probability: members.iter().map(|&i| grid[i]).sum::<f64>() / count,
+            cells: members.into_iter().map(|i| (i % columns, i / columns)).collect(),
+        });
+    }
+
+    spots.sort_by(|a, b| b.max_z.total_cmp(&a.max_z));
+    Hotspots { columns, rows, spots }
+}
+
+fn average_grid(probabilities: &[f32], width: usize, height: usize, columns: usize, rows: usize) -> Vec<f64> {
+    let mut sums = vec![0.0; columns * rows];
+    let mut counts = vec![0u32; columns * rows];
+
+    for (i, &p) in probabilities.iter().take(width * height).enumerate() {
+        let column = (i % width) * columns / width;
+        let row = (i / width) * rows / height;
+        sums[row * columns + column] += p as f64;
+        counts[row * columns + column] += 1;
+    }
+
+    sums.iter().zip(&counts).map(|(&sum, &count)| if count == 0 { 0.0 } else { sum / count as f64 }).collect()
+}
+
+/// Gi* with binary weights over the cell and its in-grid neighbours.
+fn gi_star(grid: &[f64], columns: usize, rows: usize, column: usize, row: usize, mean: f64, std_dev: f64) -> f64 {
+    let n = grid.len() as f64;
+    let mut weight = 0.0;
+    let mut sum = 0.0;
+
+    for r in row.saturating_sub(1)..=(row + 1).min(rows - 1) {
+        for c in column.saturating_sub(1)..=(column + 1).min(columns - 1) {
+            weight += 1.0;
+            sum += grid[r * columns + c];
+        }
+    }
+
+    let denominator = std_dev * ((n * weight - weight * weight) / (n - 1.0)).sqrt();
+    if denominator <= 0.0 {
+        return 0.0;
+    }
+    (sum - mean * weight) / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flooded_corner_is_one_hot_spot() {
+        let mut probabilities = vec![0.0f32; 32 * 32];
+        for row in 0..4 {
+            for column in 0..4 {
+                probabilities[row * 32 + column] = 1.0;
+            }
+        }
+
+        let hotspots = detect(&probabilities, 32);
+        assert_eq!((hotspots.columns, hotspots.rows), (32, 32));
+        assert_eq!(hotspots.spots.len(), 1);
+
+        let spot = &hotspots.spots[0];
+        let mut cells = spot.cells.clone();
+        cells.sort_unstable();
+        let expected: Vec<_> = (0..4).flat_map(|c| (0..4).map(move |r| (c, r))).collect();
+        assert_eq!(cells, expected);
+        assert_eq!(spot.mean_probability, 1.0);
+        assert!(spot.max_z >= spot.mean_z && spot.mean_z >= Z_THRESHOLD);
+    }
+
+    #[test]
+    fn lone_cell_is_noise() {
+        let mut probabilities = vec![0.0f32; 32 * 32];
+        probabilities[16 * 32 + 16] = 1.0;
+        assert!(detect(&probabilities, 32).spots.is_empty());
+    }
+
+    #[test]
+    fn empty_raster_has_no_hot_spots() {
+        let hotspots = detect(&[], 0);
+        assert_eq!((hotspots.columns, hotspots.rows), (0, 0));
+        assert!(hotspots.spots.is_empty());
+        assert!(detect(&[], 16).spots.is_empty());
+    }
+
+    #[test]
+    fn constant_raster_has_no_hot_spots() {
+        assert!(detect(&[0.7; 64 * 64], 64).spots.is_empty());
+    }
+
+    #[test]
+    fn cell_bounds_count_rows_from_the_north() {
+        let hotspots = Hotspots { columns: 2, rows: 2, spots: Vec::new() };
+        assert_eq!(hotspots.cell_bounds((1, 0), [0.0, 0.0, 2.0, 2.0]), [1.0, 1.0, 2.0, 2.0]);
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use crate::shared::error::{AppError, AppResult};
+use super::models::{ImportRowError, SalinityImportQuery};
+
+pub const MAX_IMPORT_ROWS: usize = 50_000;
+
+const NAIVE_DATETIME_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%d/%m/%Y %H:%M:%S", "%d/%m/%Y %H:%M"];
+const NAIVE_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d/%m/%Y"];
+
+/// A CSV row that passed validation.
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    pub line: u64,
+    pub farm_id: i64,
+    pub ndsi: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+enum FarmColumn {
+    Fixed(i64),
+    Column(usize),
+}
+
+/// Parses `data` with the column mapping in `query`. Problems with the file as
+/// a whole (missing columns, too many rows) fail the request; problems with a
+/// single row are returned alongside the valid rows.
+pub fn parse_salinity_csv(
+    data: &[u8],
+    query: &SalinityImportQuery,
+) -> AppResult<(Vec<ImportRow>, Vec<ImportRowError>)> {
+    let delimiter = match query.delimiter {
+        None => b',',
+        Some(c) if c.is_ascii() => c as u8,
+        Some(c) => return Err(AppError::Validation(format!("Unsupported delimiter '{}'", c))),
+    };
+    let offset = FixedOffset::east_opt(query.utc_offset_hours * 3600)
+        .ok_or_else(|| AppError::Validation("utc_offset_hours must be between -23 and 23".to_string()))?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(data);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| AppError::Validation(format!("Unreadable CSV header: {}", e)))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim_start_matches('\u{feff}').eq_ignore_ascii_case(name))
+            .ok_or_else(|| AppError::Validation(format!(
+                "Column '{}' not found; header has: {}",
+                name,
+                headers.iter().collect::<Vec<_>>().join(", ")
+            )))
+    };
+
+    let farm = match (&query.farm_id_column, query.farm_id) {
+        (Some(name), _) => FarmColumn::Column(column(name)?),
+        (None, Some(farm_id)) => FarmColumn::Fixed(farm_id),
+        (None, None) => return Err(AppError::Validation("Either farm_id or farm_id_column is required".to_string())),
+    };
+    let value_idx = column(&query.value_column)?;
+    let recorded_at_idx = column(&query.recorded_at_column)?;
+
+    let now = Utc::now();
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, record) in reader.records().enumerate() {
+        if index >= MAX_IMPORT_ROWS {
+            return Err(AppError::Validation(format!("At most {} rows can be imported per file", MAX_IMPORT_ROWS)));
+        }
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or(index as u64 + 2);
+                errors.push(row_error(line, None, format!("Unreadable row: {}", e)));
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line()).unwrap_or(index as u64 + 2);
+
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+
+        let field = |idx: usize| record.get(idx).unwrap_or_default();
+
+        let farm_id = match farm {
+            FarmColumn::Fixed(id) => id,
+            FarmColumn::Column(idx) => match field(idx).parse::<i64>() {
+                Ok(id) => id,
+                Err(_) => {
+                    errors.push(row_error(line, query.farm_id_column.as_deref(), format!("Invalid farm id '{}'", field(idx))));
+                    continue;
+                }
+            },
+        };
+
+        let ndsi = match parse_decimal(field(value_idx)) {
+            Some(v) if (-1.0..=1.0).contains(&v) => v,
+            _ => {
+                errors.push(row_error(
+                    line,
+                    Some(&query.value_column),
+                    format!("'{}' is not an NDSI value between -1 and 1", field(value_idx)),
+                ));
+                continue;
+            }
+        };
+
+        let recorded_at = match parse_timestamp(field(recorded_at_idx), &offset) {
+            Some(t) if t <= now => t,
+            Some(_) => {
+                errors.push(row_error(line, Some(&query.recorded_at_column), "Timestamp is in the future".to_string()));
+                continue;
+            }
+            None => {
+                errors.push(row_error(
+                    line,
+                    Some(&query.recorded_at_column),
+                    format!("Unrecognised timestamp '{}'", field(recorded_at_idx)),
+                ));
+                continue;
+            }
+        };
+
+        rows.push(ImportRow { line, farm_id, ndsi, recorded_at });
+    }
+
+    Ok((rows, errors))
+}
+
+pub fn row_error(line: u64, column: Option<&str>, error: String) -> ImportRowError {
+    ImportRowError {
+        line,
+        column: column.map(str::to_string),
+        error,
+    }
+}
+
+/// Accepts a decimal comma when no decimal point is present.
+fn parse_decimal(value: &str) -> Option<f64> {
+    let normalized = if value.contains('.') { value.to_string() } else { value.replace(',', ".") };
+    normalized.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// RFC 3339, or a local date/time interpreted in `offset`.
+fn parse_timestamp(value: &str, offset: &FixedOffset) -> Option<DateTime<Utc>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return Some(t.with_timezone(&Utc));
+    }
+
+    let naive = NAIVE_DATETIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(value, f).ok())
+        .or_else(|| {
+            NAIVE_DATE_FORMATS
+                .iter()
+                .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+                .map(|d| d.and_time(NaiveTime::MIN))
+        })?;
+
+    offset.from_local_datetime(&naive).single().map(|t| t.with_timezone(&Utc))
+}
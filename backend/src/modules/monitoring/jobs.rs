@@ -0,0 +1,32 @@
+use sqlx::PgPool;
+use crate::shared::worker::{interval_from_env, spawn_periodic};
+use super::{repository, service};
+
+const BASELINE_JOB_DEFAULT_SECS: u64 = 24 * 60 * 60;
+const CALIBRATION_JOB_DEFAULT_SECS: u64 = 24 * 60 * 60;
+
+pub fn spawn_baseline_job(db: PgPool) {
+    let period = interval_from_env("BASELINE_JOB_INTERVAL_SECS", BASELINE_JOB_DEFAULT_SECS);
+
+    spawn_periodic("spectral_baselines", period, move || {
+        let db = db.clone();
+        async move {
+            let updated = repository::recompute_ndsi_baselines(&db).await?;
+            tracing::info!("Recomputed {} NDSI baseline rows", updated);
+            Ok(())
+        }
+    });
+}
+
+pub fn spawn_calibration_job(db: PgPool) {
+    let period = interval_from_env("CALIBRATION_JOB_INTERVAL_SECS", CALIBRATION_JOB_DEFAULT_SECS);
+
+    spawn_periodic("salinity_calibration", period, move || {
+        let db = db.clone();
+        async move {
+            let fitted = service::recompute_calibrations(&db).await?;
+            tracing::info!("Refitted salinity calibration for {} regions", fitted);
+            Ok(())
+        }
+    });
+}
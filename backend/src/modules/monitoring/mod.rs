@@ -1,18 +1,90 @@
 pub mod ai;
+pub mod breakpoints;
+pub mod cap;
 pub mod controller;
+pub mod hotspots;
+pub mod import;
+pub mod jobs;
 pub mod models;
+pub mod mosaic;
 pub mod repository;
+pub mod risk;
+pub mod rules;
 pub mod service;
 
-use axum::{routing::{get, post}, Router};
+use axum::{extract::DefaultBodyLimit, routing::{get, post, put}, Router};
+use utoipa::OpenApi;
 use crate::shared::AppState;
 
+/// CSV imports carry up to `import::MAX_IMPORT_ROWS` rows.
+const IMPORT_BODY_LIMIT: usize = 8 * 1024 * 1024;
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/health", get(controller::health_check))
         .route("/analyze", post(controller::trigger_analysis))
+        .route("/alerts", get(controller::get_alerts_in_bbox))
+        .route("/alerts/mvt/{z}/{x}/{y}", get(controller::get_alert_tile))
         .route("/alerts/{farm_id}", get(controller::get_alerts))
+        .route("/alerts/{alert_id}/acknowledge", post(controller::acknowledge_alert))
+        .route("/salinity/import", post(controller::import_salinity).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)))
         .route("/salinity/{farm_id}", get(controller::get_salinity_history))
+        .route("/salinity/{farm_id}/breakpoints", get(controller::get_salinity_breakpoints))
+        .route("/analyses/{farm_id}/compare", get(controller::compare_analyses))
         .route("/vector/{farm_id}", get(controller::get_intrusion_vector))
+        .route("/hotspots/{farm_id}", get(controller::get_hotspots))
+        .route("/simulate", post(controller::simulate))
         .route("/status/{farm_id}", get(controller::get_farm_status))
+        .route("/sensors/readings", post(controller::record_sensor_reading))
+        .route("/calibrations", get(controller::list_calibrations))
+        .route("/thresholds", get(controller::list_region_thresholds))
+        .route("/thresholds/{region}", put(controller::set_region_threshold).delete(controller::delete_region_threshold))
+        .route("/models", get(controller::list_models).post(controller::register_model))
+        .route("/models/{id}/activate", post(controller::activate_model))
+        .route("/models/{id}/shadow", post(controller::shadow_model))
+        .route("/models/{id}/deactivate", post(controller::deactivate_model))
+        .route("/models/{id}/shadow-report", get(controller::get_shadow_report))
+        .route("/rules/{farm_id}", get(controller::get_alert_rules).put(controller::update_alert_rules))
+}
+
+/// The provincial alert feed, authenticated by API key instead of a bearer token.
+pub fn public_router() -> Router<AppState> {
+    Router::new().route("/alerts/feed.cap", get(controller::get_alert_feed))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::health_check,
+    controller::trigger_analysis,
+    controller::get_alerts_in_bbox,
+    controller::get_alert_tile,
+    controller::get_alert_feed,
+    controller::get_alerts,
+    controller::acknowledge_alert,
+    controller::import_salinity,
+    controller::get_salinity_history,
+    controller::get_salinity_breakpoints,
+    controller::compare_analyses,
+    controller::get_intrusion_vector,
+    controller::get_hotspots,
+    controller::simulate,
+    controller::get_farm_status,
+    controller::record_sensor_reading,
+    controller::list_calibrations,
+    controller::list_region_thresholds,
+    controller::set_region_threshold,
+    controller::delete_region_threshold,
+    controller::list_models,
+    controller::register_model,
+    controller::activate_model,
+    controller::shadow_model,
+    controller::deactivate_model,
+    controller::get_shadow_report,
+    controller::get_alert_rules,
+    controller::update_alert_rules,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
 }
@@ -1,20 +1,35 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+use crate::shared::{i18n::Language, validation::not_blank};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Alert {
     pub id: i64,
     pub farm_id: i64,
     pub severity: AlertSeverity,
     pub message: String,
     pub metadata: Option<serde_json::Value>,
+    /// GeoJSON MultiPolygon of the part of the farm affected; absent when the
+    /// alert concerns the whole farm.
+    pub affected_area: Option<String>,
     pub detected_at: DateTime<Utc>,
     pub acknowledged: bool,
     pub acknowledged_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The farm owner an alert is delivered to.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AlertRecipient {
+    pub user_id: i64,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub farm_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AlertSeverity {
     Low,
@@ -40,16 +55,21 @@ impl fmt::Display for AlertSeverity {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SalinityLog {
     pub id: i64,
     pub farm_id: i64,
     pub ndsi_value: f64,
     pub source: String,
     pub recorded_at: DateTime<Utc>,
+    /// Segmentation model behind an AI analysis, as `name@version`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_salinity: Option<SalinityEstimate>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct IntrusionVector {
     pub id: i64,
     pub farm_id: i64,
@@ -57,30 +77,342 @@ pub struct IntrusionVector {
     pub angle_degrees: f64,
     pub magnitude_km: f64,
     pub calculated_at: DateTime<Utc>,
+    /// When the two analyses the vector spans were recorded and how many
+    /// water pixels each found. Absent on vectors from before two-date tracking.
+    pub previous_observed_at: Option<DateTime<Utc>>,
+    pub current_observed_at: Option<DateTime<Utc>>,
+    pub previous_pixel_count: Option<i32>,
+    pub current_pixel_count: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorFormat {
+    #[default]
+    Json,
+    Geojson,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VectorQuery {
+    /// `geojson` returns a FeatureCollection with the vector and predicted affected area.
+    #[serde(default)]
+    pub format: VectorFormat,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AlertBboxQuery {
+    /// Map viewport as `min_lon,min_lat,max_lon,max_lat` in WGS 84.
+    pub bbox: String,
+    /// Newest first; defaults to 500.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AlertTileQuery {
+    /// Comma-separated attributes to include: `farm_id`, `severity`,
+    /// `message`, `acknowledged`, `detected_at` (Unix seconds). All of them by default.
+    pub fields: Option<String>,
+    /// Only alerts detected in the last this many days; defaults to 30.
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    #[default]
+    Cap,
+    Csv,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AlertFeedQuery {
+    /// `csv` returns the same districts as a bulletin spreadsheet.
+    #[serde(default)]
+    pub format: FeedFormat,
+    /// Language of the CSV headlines; defaults to Vietnamese. The CAP feed
+    /// always carries both.
+    pub lang: Option<Language>,
+}
+
+/// Unacknowledged high and critical alerts of one district, the unit
+/// provincial early-warning bulletins are issued for.
+#[derive(Debug, Clone)]
+pub struct DistrictAlerts {
+    /// The farms' region; `None` groups farms that have none.
+    pub district: Option<String>,
+    pub alert_count: i64,
+    pub critical_count: i64,
+    pub farm_count: i64,
+    pub latest_alert_id: i64,
+    pub first_detected_at: DateTime<Utc>,
+    pub last_detected_at: DateTime<Utc>,
+    /// `[min_lon, min_lat, max_lon, max_lat]` of the affected farms.
+    pub bbox: Option<[f64; 4]>,
+}
+
+impl DistrictAlerts {
+    pub fn severity(&self) -> AlertSeverity {
+        if self.critical_count > 0 { AlertSeverity::Critical } else { AlertSeverity::High }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct BreakpointQuery {
+    /// Days of history to search; defaults to 180.
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendDirection {
+    Rising,
+    Falling,
+}
+
+/// A shift in the farm's mean NDSI.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TrendBreakpoint {
+    /// First day of the new regime.
+    pub date: DateTime<Utc>,
+    pub before_ndsi: f64,
+    pub after_ndsi: f64,
+    /// `after_ndsi - before_ndsi`.
+    pub magnitude: f64,
+    pub direction: TrendDirection,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BreakpointReport {
+    pub farm_id: i64,
+    pub days: i32,
+    /// Days with at least one reading; each is one point of the series.
+    pub samples: usize,
+    /// Oldest first.
+    pub breakpoints: Vec<TrendBreakpoint>,
+    /// The latest breakpoint in words, in the owner's language.
+    pub narrative: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct HotspotQuery {
+    /// Only hot spots found by this analysis (salinity log id).
+    pub log_id: Option<i64>,
+    /// Newest first; defaults to 50.
+    pub limit: Option<i64>,
+}
+
+/// A within-farm cluster of high water probability.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Hotspot {
+    pub id: i64,
+    pub farm_id: i64,
+    pub log_id: i64,
+    pub alert_id: Option<i64>,
+    /// GeoJSON MultiPolygon.
+    pub geometry: String,
+    pub area_hectares: f64,
+    pub cell_count: i32,
+    pub mean_z: f64,
+    pub max_z: f64,
+    pub mean_probability: f64,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// A limit the alert rules compare a reading against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdRule {
+    NdsiAnomaly,
+    NdsiHigh,
+    NdsiCritical,
+    SalinityTolerance,
+    SalinityCritical,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct ThresholdCheck {
+    pub rule: ThresholdRule,
+    pub value: f64,
+    pub limit: f64,
+    pub exceeded: bool,
+}
+
+impl ThresholdCheck {
+    pub fn new(rule: ThresholdRule, value: f64, limit: f64) -> Self {
+        Self { rule, value, limit, exceeded: value > limit }
+    }
+}
+
+/// What an analysis saw and decided, kept with its reading so runs can be
+/// compared later.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AnalysisSnapshot {
+    pub water_coverage_percent: f64,
+    pub water_threshold: f64,
+    pub threshold_source: ThresholdSource,
+    pub estimated_g_l: Option<f64>,
+    /// Severity the alert rules gave the reading; `null` when none fired.
+    pub severity: Option<AlertSeverity>,
+    pub risk_score: i32,
+    pub risk_level: AlertSeverity,
+    pub thresholds: Vec<ThresholdCheck>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CompareRunsQuery {
+    /// Salinity log id of the baseline run.
+    pub run_a: i64,
+    /// Salinity log id of the run compared against it.
+    pub run_b: i64,
+}
+
+/// One AI analysis of a farm. Runs recorded before snapshots were kept have no
+/// `analysis` and no class areas.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AnalysisRun {
+    /// Salinity log id.
+    pub log_id: i64,
+    pub recorded_at: DateTime<Utc>,
+    pub model_version: Option<String>,
+    pub ndsi: f64,
+    pub water_area_hectares: Option<f64>,
+    pub land_area_hectares: Option<f64>,
+    /// Area of the hot spots the run found.
+    pub hotspot_area_hectares: f64,
+    #[sqlx(json(nullable))]
+    pub analysis: Option<AnalysisSnapshot>,
+}
+
+/// `run_b` minus `run_a`; `null` where either run lacks the value.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnalysisDelta {
+    pub ndsi: f64,
+    pub water_coverage_percent: Option<f64>,
+    pub water_threshold: Option<f64>,
+    pub estimated_g_l: Option<f64>,
+    pub water_area_hectares: Option<f64>,
+    pub land_area_hectares: Option<f64>,
+    pub hotspot_area_hectares: f64,
+    pub risk_score: Option<i32>,
+    pub risk_level_changed: bool,
+    pub severity_changed: bool,
+}
+
+/// A limit crossed in one run and not the other. A side is `null` when that
+/// run had no such limit, e.g. before a baseline existed.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThresholdFlip {
+    pub rule: ThresholdRule,
+    pub run_a: Option<ThresholdCheck>,
+    pub run_b: Option<ThresholdCheck>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnalysisComparison {
+    pub farm_id: i64,
+    pub run_a: AnalysisRun,
+    pub run_b: AnalysisRun,
+    pub delta: AnalysisDelta,
+    pub flipped: Vec<ThresholdFlip>,
+}
+
+/// Region the intrusion front is expected to reach within the prediction horizon.
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedArea {
+    /// Closed exterior ring as (lon, lat) pairs.
+    pub ring: Vec<(f64, f64)>,
+    pub horizon_days: i32,
+    pub reach_km: f64,
+    pub risk: AlertSeverity,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 pub struct AnalysisRequest {
     pub farm_id: i64,
     #[serde(default)]
     pub image_base64: Option<String>,
+    /// Ground extent of the image as `[min_lon, min_lat, max_lon, max_lat]`.
+    /// Defaults to the farm's bounding box.
+    #[serde(default)]
+    pub image_bounds: Option<[f64; 4]>,
+    /// Same-date scenes that together cover the farm, mosaicked before
+    /// analysis. Use instead of `image_base64` and `image_bounds`.
+    #[serde(default)]
+    pub scenes: Vec<SceneInput>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SceneInput {
+    pub scene_id: String,
+    pub image_base64: String,
+    /// Ground extent as `[min_lon, min_lat, max_lon, max_lat]`.
+    pub image_bounds: [f64; 4],
+    /// Percent of the scene obscured by cloud; overlapping pixels are taken
+    /// from the clearest scene.
+    pub cloud_cover: f64,
+    pub acquired_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AnalysisResult {
     pub farm_id: i64,
     pub current_ndsi: f64,
     pub alert: Option<Alert>,
     pub intrusion_vector: Option<IntrusionVector>,
     pub water_coverage_percent: f64,
+    /// Water probability a pixel needed to count as water.
+    pub water_threshold: f64,
+    pub threshold_source: ThresholdSource,
+    /// Segmentation model that produced the result, as `name@version`.
+    pub model_version: String,
+    /// Scenes that contributed to the analysed mosaic; empty for a single image.
+    pub scene_ids: Vec<String>,
+    /// Within-farm hot spots found in the image, strongest first.
+    pub hotspot_ids: Vec<i64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FarmStatus {
     pub farm_id: i64,
     pub latest_ndsi: Option<f64>,
     pub recent_alerts: Vec<Alert>,
     pub latest_intrusion_vector: Option<IntrusionVector>,
+    pub risk: RiskScore,
+}
+
+/// Composite salinity intrusion risk for a farm.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RiskScore {
+    /// 0 (no risk) to 100.
+    pub score: i32,
+    pub level: AlertSeverity,
+    pub components: Vec<RiskComponent>,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RiskComponent {
+    pub name: String,
+    pub weight: f64,
+    /// Where the input came from, e.g. `lunar_phase` when no sea level is stored.
+    pub source: String,
+    /// Normalised 0–1 contribution; absent when there is no data.
+    pub value: Option<f64>,
+    /// The underlying measurement in its own unit.
+    pub raw: Option<f64>,
+}
+
+impl RiskComponent {
+    pub fn new(name: &str, weight: f64, source: &str, value: Option<f64>, raw: Option<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            weight,
+            source: source.to_string(),
+            value,
+            raw,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +421,8 @@ pub struct CreateAlert {
     pub severity: AlertSeverity,
     pub message: String,
     pub metadata: Option<serde_json::Value>,
+    /// GeoJSON Polygon or MultiPolygon in WGS 84.
+    pub affected_area: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +430,7 @@ pub struct CreateSalinityLog {
     pub farm_id: i64,
     pub ndsi_value: f64,
     pub source: String,
+    pub model_version: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,4 +439,324 @@ pub struct CreateIntrusionVector {
     pub direction: String,
     pub angle_degrees: f64,
     pub magnitude_km: f64,
-}
\ No newline at end of file
+    pub previous: WaterObservation,
+    pub current: WaterObservation,
+}
+
+/// Water-pixel centroid of one analysed image, in WGS 84.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaterObservation {
+    pub log_id: i64,
+    pub recorded_at: DateTime<Utc>,
+    pub centroid: (f64, f64),
+    pub pixel_count: i32,
+}
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SpectralBaseline {
+    pub farm_id: i64,
+    pub index_name: String,
+    pub month: i16,
+    pub mean_value: f64,
+    pub std_dev: f64,
+    pub sample_count: i32,
+    pub years_covered: i32,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SalinityEstimate {
+    pub ec_ds_m: f64,
+    pub grams_per_litre: f64,
+    pub lower_g_l: f64,
+    pub upper_g_l: f64,
+    pub region: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct SalinityCalibration {
+    pub region: String,
+    pub slope: f64,
+    pub intercept: f64,
+    pub residual_std: f64,
+    pub r_squared: f64,
+    pub sample_count: i32,
+    pub ndsi_mean: f64,
+    pub ndsi_sxx: f64,
+    pub fitted_at: DateTime<Utc>,
+}
+
+/// Admin-set water probability threshold for the farms of a region.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct RegionThreshold {
+    pub region: String,
+    pub water_threshold: f64,
+    pub updated_by: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct SetRegionThresholdRequest {
+    /// Strictly between 0 and 1.
+    #[validate(range(exclusive_min = 0.0, exclusive_max = 1.0))]
+    pub water_threshold: f64,
+}
+
+/// Where the water threshold of an analysis came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdSource {
+    /// Configured for the farm's region.
+    Region,
+    /// Estimated from the image's probability histogram with Otsu's method.
+    Otsu,
+}
+
+/// Role of a registered segmentation model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelStatus {
+    Inactive,
+    /// Serves analyses.
+    Active,
+    /// Runs next to the active model; its output is only stored for comparison.
+    Shadow,
+}
+
+impl ModelStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ModelStatus::Inactive => "inactive",
+            ModelStatus::Active => "active",
+            ModelStatus::Shadow => "shadow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AiModel {
+    pub id: i64,
+    pub name: String,
+    pub version: String,
+    pub config_path: String,
+    pub weights_path: String,
+    /// Free-form evaluation metrics, e.g. `{"iou": 0.82}`.
+    pub metrics: serde_json::Value,
+    pub status: ModelStatus,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: Option<DateTime<Utc>>,
+}
+
+impl AiModel {
+    /// Label recorded on the analyses the model runs.
+    pub fn label(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// `@` separates name and version in model labels.
+fn model_name(name: &str) -> Result<(), ValidationError> {
+    if name.contains('@') {
+        return Err(ValidationError::new("model_name").with_message("must not contain '@'".into()));
+    }
+    Ok(())
+}
+
+fn json_object(value: &serde_json::Value) -> Result<(), ValidationError> {
+    if !value.is_object() {
+        return Err(ValidationError::new("json_object").with_message("must be a JSON object".into()));
+    }
+    Ok(())
+}
+
+/// Registers model files already present on the server.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct RegisterModelRequest {
+    #[validate(custom(function = "not_blank"), custom(function = "model_name"), length(max = 100))]
+    pub name: String,
+    #[validate(custom(function = "not_blank"), length(max = 50))]
+    pub version: String,
+    pub config_path: String,
+    pub weights_path: String,
+    #[serde(default)]
+    #[validate(custom(function = "json_object"))]
+    pub metrics: Option<serde_json::Value>,
+}
+
+/// How a shadow model's water coverage compared with the active model's on
+/// the same images.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ShadowReport {
+    pub model_id: i64,
+    pub model_version: String,
+    pub runs: i64,
+    pub mean_active_coverage_percent: Option<f64>,
+    pub mean_shadow_coverage_percent: Option<f64>,
+    /// Mean of |shadow - active| coverage, in percentage points.
+    pub mean_abs_difference: Option<f64>,
+    pub max_abs_difference: Option<f64>,
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateSensorReading {
+    pub farm_id: i64,
+    #[validate(range(min = 0.0))]
+    pub ec_ds_m: f64,
+    #[serde(default)]
+    pub sensor_id: Option<String>,
+    #[serde(default)]
+    pub measured_at: Option<DateTime<Utc>>,
+}
+
+/// Thresholds in force for a farm after merging its overrides over the crop defaults.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AlertRules {
+    pub crop_type: String,
+    pub enabled: bool,
+    /// NDSI alert threshold is `baseline mean + anomaly_multiplier * std_dev`.
+    pub anomaly_multiplier: f64,
+    /// Estimated salinity (g/L) above which a high alert is raised.
+    pub max_salinity_g_l: f64,
+    /// Estimated salinity (g/L) above which a critical alert is raised.
+    pub critical_salinity_g_l: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct AlertRuleOverrides {
+    pub anomaly_multiplier: Option<f64>,
+    pub max_salinity_g_l: Option<f64>,
+    pub critical_salinity_g_l: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AlertRulesResponse {
+    pub farm_id: i64,
+    pub effective: AlertRules,
+    pub overrides: AlertRuleOverrides,
+}
+
+/// Replaces the farm's rule set; omitted overrides fall back to the crop default.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateAlertRulesRequest {
+    #[serde(default)]
+    pub crop_type: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default, flatten)]
+    pub overrides: AlertRuleOverrides,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Maps the columns of an uploaded CSV onto salinity log fields.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SalinityImportQuery {
+    /// Farm every row belongs to; required unless `farm_id_column` is set.
+    pub farm_id: Option<i64>,
+    /// Column holding the farm id, for files covering several farms.
+    pub farm_id_column: Option<String>,
+    #[serde(default = "default_value_column")]
+    pub value_column: String,
+    #[serde(default = "default_recorded_at_column")]
+    pub recorded_at_column: String,
+    #[serde(default = "default_import_source")]
+    pub source: String,
+    /// Field separator; defaults to `,`.
+    pub delimiter: Option<char>,
+    /// Offset applied to timestamps without a zone, e.g. `7` for Vietnam.
+    #[serde(default)]
+    pub utc_offset_hours: i32,
+    /// Validate and count without storing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn default_value_column() -> String {
+    "ndsi".to_string()
+}
+
+fn default_recorded_at_column() -> String {
+    "recorded_at".to_string()
+}
+
+fn default_import_source() -> String {
+    "csv_import".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportRowError {
+    /// Line number in the file, counting the header as line 1.
+    pub line: u64,
+    pub column: Option<String>,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SalinityImportResponse {
+    pub rows: usize,
+    pub imported: u64,
+    /// Valid rows already stored for the same farm, time and source.
+    pub duplicates: u64,
+    pub failed: usize,
+    /// Row-level problems, truncated when there are many.
+    pub errors: Vec<ImportRowError>,
+    pub dry_run: bool,
+}
+
+pub const MAX_SIMULATION_DAYS: i32 = 90;
+/// Well above observed dry-season intrusion speeds in the Mekong Delta.
+pub const MAX_SIMULATED_VELOCITY_KM_PER_DAY: f64 = 50.0;
+
+/// A hypothetical salt front: where it is now and how fast it moves.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct SimulatedVector {
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub origin_lon: f64,
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub origin_lat: f64,
+    /// Direction of travel, counter-clockwise from east like `IntrusionVector`.
+    pub angle_degrees: f64,
+    #[validate(range(exclusive_min = 0.0, max = MAX_SIMULATED_VELOCITY_KM_PER_DAY))]
+    pub velocity_km_per_day: f64,
+}
+
+/// Either or both scenarios; a farm is affected if any of them reaches it.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct SimulationRequest {
+    #[serde(default)]
+    #[validate(nested)]
+    pub vector: Option<SimulatedVector>,
+    /// Hypothetical NDSI alert threshold applied to every farm's recent trend.
+    #[serde(default)]
+    #[validate(range(min = -1.0, max = 1.0))]
+    pub ndsi_threshold: Option<f64>,
+    /// Horizon in days; defaults to the intrusion prediction horizon.
+    #[serde(default)]
+    #[validate(range(min = 1, max = MAX_SIMULATION_DAYS))]
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SimulatedFarm {
+    pub farm_id: i64,
+    pub name: String,
+    /// Days until the simulated front reaches the farm boundary.
+    pub front_arrival_days: Option<f64>,
+    /// NDSI at the end of the horizon if the farm's recent trend continues.
+    pub projected_ndsi: Option<f64>,
+    /// Days until the trend crosses the simulated threshold; 0 if already above it.
+    pub threshold_crossing_days: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SimulationResponse {
+    pub horizon_days: i32,
+    /// How far the simulated front travels within the horizon.
+    pub reach_km: Option<f64>,
+    pub risk: Option<AlertSeverity>,
+    /// Affected farms of the caller, soonest first.
+    pub farms: Vec<SimulatedFarm>,
+}
@@ -0,0 +1,215 @@
+//! Mosaics same-date scenes that each cover part of a farm into one image.
+//! Every output pixel comes from the least cloudy scene covering it; pixels
+//! no scene covers stay black.
+
+use std::io::Cursor;
+use image::{ImageFormat, Rgb, RgbImage};
+use crate::shared::error::{AppError, AppResult};
+use super::models::SceneInput;
+
+pub const MAX_SCENES: usize = 8;
+/// Longest side of the mosaic in pixels; finer inputs are downsampled.
+const MAX_MOSAIC_SIDE: u32 = 4096;
+const MAX_SCENE_ID_LEN: usize = 100;
+
+pub struct Scene {
+    pub scene_id: String,
+    pub image: RgbImage,
+    /// `[min_lon, min_lat, max_lon, max_lat]`
+    pub bounds: [f64; 4],
+    /// Percent of the scene obscured by cloud.
+    pub cloud_cover: f64,
+}
+
+pub struct Mosaic {
+    /// PNG encoding of the merged image.
+    pub png: Vec<u8>,
+    pub bounds: [f64; 4],
+    /// Scenes that supplied at least one pixel, least cloudy first.
+    pub scene_ids: Vec<String>,
+}
+
+/// Checks the request-level constraints and decodes every scene.
+pub fn decode_scenes(inputs: Vec<SceneInput>) -> AppResult<Vec<Scene>> {
+    if inputs.len() > MAX_SCENES {
+        return Err(AppError::Validation(format!("At most {} scenes can be mosaicked", MAX_SCENES)));
+    }
+    if let Some(first) = inputs.first() {
+        let date = first.acquired_at.date_naive();
+        if inputs.iter().any(|s| s.acquired_at.date_naive() != date) {
+            return Err(AppError::Validation("Mosaicked scenes must be acquired on the same date".to_string()));
+        }
+    }
+
+    let mut scenes: Vec<Scene> = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let scene_id = input.scene_id.trim().to_string();
+        if scene_id.is_empty() || scene_id.len() > MAX_SCENE_ID_LEN {
+            return Err(AppError::Validation(format!(
+                "scene_id must be between 1 and {} characters",
+                MAX_SCENE_ID_LEN
+            )));
+        }
+        if scenes.iter().any(|s| s.scene_id == scene_id) {
+            return Err(AppError::Validation(format!("Scene {} is listed twice", scene_id)));
+        }
+        if !(0.0..=100.0).contains(&input.cloud_cover) {
+            return Err(AppError::Validation(format!("cloud_cover of scene {} must be between 0 and 100", scene_id)));
+        }
+        super::service::validate_image_bounds(Some(input.image_bounds))?;
+
+        let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &input.image_base64)
+            .map_err(|e| AppError::BadRequest(format!("Invalid base64 in scene {}: {}", scene_id, e)))?;
+        let image = image::load_from_memory(&bytes)
+            .map_err(|e| AppError::BadRequest(format!("Unreadable image in scene {}: {}", scene_id, e)))?
+            .into_rgb8();
+
+        scenes.push(Scene { scene_id, image, bounds: input.image_bounds, cloud_cover: input.cloud_cover });
+    }
+
+    Ok(scenes)
+}
+
+/// Merges `scenes` over their combined extent at the finest input
+/// resolution, capped at `MAX_MOSAIC_SIDE`.
+pub fn build(mut scenes: Vec<Scene>) -> AppResult<Mosaic> {
+    if scenes.is_empty() {
+        return Err(AppError::Validation("At least one scene is required".to_string()));
+    }
+    scenes.sort_by(|a, b| a.cloud_cover.total_cmp(&b.cloud_cover));
+
+    let bounds = scenes.iter().skip(1).fold(scenes[0].bounds, |acc, s| {
+        [acc[0].min(s.bounds[0]), acc[1].min(s.bounds[1]), acc[2].max(s.bounds[2]), acc[3].max(s.bounds[3])]
+    });
+    let [min_lon, min_lat, max_lon, max_lat] = bounds;
+
+    let lon_step = scenes
+        .iter()
+        .map(|s| (s.bounds[2] - s.bounds[0]) / s.image.width().max(1) as f64)
+        .fold(f64::INFINITY, f64::min);
+    let lat_step = scenes
+        .iter()
+        .map(|s| (s.bounds[3] - s.bounds[1]) / s.image.height().max(1) as f64)
+        .fold(f64::INFINITY, f64::min);
+
+    let full_width = ((max_lon - min_lon) / lon_step).ceil();
+    let full_height = ((max_lat - min_lat) / lat_step).ceil();
+    let scale = (full_width.max(full_height) / MAX_MOSAIC_SIDE as f64).max(1.0);
+    let width = ((full_width / scale).ceil() as u32).max(1);
+    let height = ((full_height / scale).ceil() as u32).max(1);
+
+    let mut mosaic = RgbImage::new(width, height);
+    let mut used = vec![false; scenes.len()];
+
+    for y in 0..height {
+        let lat = max_lat - (y as f64 + 0.5) / height as f64 * (max_lat - min_lat);
+        for x in 0..width {
+            let lon = min_lon + (x as f64 + 0.5) / width as f64 * (max_lon - min_lon);
+            let sampled = scenes.iter().enumerate().find_map(|(i, s)| sample(s, lon, lat).map(|p| (i, p)));
+            if let Some((i, pixel)) = sampled {
+                mosaic.put_pixel(x, y, pixel);
+                used[i] = true;
+            }
+        }
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    mosaic
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| AppError::Internal(format!("Failed to encode mosaic: {}", e)))?;
+
+    let scene_ids = scenes
+        .into_iter()
+        .zip(used)
+        .filter_map(|(scene, used)| used.then_some(scene.scene_id))
+        .collect();
+
+    Ok(Mosaic { png: png.into_inner(), bounds, scene_ids })
+}
+
+/// Nearest pixel of `scene` at (`lon`, `lat`), if the scene covers it.
+fn sample(scene: &Scene, lon: f64, lat: f64) -> Option<Rgb<u8>> {
+    let [min_lon, min_lat, max_lon, max_lat] = scene.bounds;
+    if lon < min_lon || lon > max_lon || lat < min_lat || lat > max_lat {
+        return None;
+    }
+
+    let (width, height) = scene.image.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let x = ((lon - min_lon) / (max_lon - min_lon) * width as f64) as u32;
+    let y = ((max_lat - lat) / (max_lat - min_lat) * height as f64) as u32;
+    Some(*scene.image.get_pixel(x.min(width - 1), y.min(height - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgb<u8> = Rgb([255, 0, 0]);
+    const BLUE: Rgb<u8> = Rgb([0, 0, 255]);
+
+    fn scene(scene_id: &str, width: u32, height: u32, colour: Rgb<u8>, bounds: [f64; 4], cloud_cover: f64) -> Scene {
+        Scene {
+            scene_id: scene_id.to_string(),
+            image: RgbImage::from_pixel(width, height, colour),
+            bounds,
+            cloud_cover,
+        }
+    }
+
+    fn decode(mosaic: &Mosaic) -> RgbImage {
+        image::load_from_memory(&mosaic.png).unwrap().into_rgb8()
+    }
+
+    #[test]
+    fn least_cloudy_scene_wins_where_scenes_overlap() {
+        let wide = scene("wide", 4, 2, RED, [0.0, 0.0, 2.0, 1.0], 30.0);
+        let east = scene("east", 2, 2, BLUE, [1.0, 0.0, 2.0, 1.0], 10.0);
+
+        let mosaic = build(vec![wide, east]).unwrap();
+        assert_eq!(mosaic.bounds, [0.0, 0.0, 2.0, 1.0]);
+        assert_eq!(mosaic.scene_ids, vec!["east", "wide"]);
+
+        let image = decode(&mosaic);
+        assert_eq!(image.dimensions(), (4, 2));
+        for (x, _, pixel) in image.enumerate_pixels() {
+            assert_eq!(*pixel, if x < 2 { RED } else { BLUE });
+        }
+    }
+
+    #[test]
+    fn uncovered_pixels_stay_black_and_unused_scenes_are_dropped() {
+        let west = scene("west", 1, 1, RED, [0.0, 0.0, 1.0, 1.0], 0.0);
+        let east = scene("east", 1, 1, BLUE, [2.0, 0.0, 3.0, 1.0], 0.0);
+        let hidden = scene("hidden", 1, 1, BLUE, [0.0, 0.0, 1.0, 1.0], 50.0);
+
+        let mosaic = build(vec![west, east, hidden]).unwrap();
+        assert_eq!(mosaic.scene_ids, vec!["west", "east"]);
+
+        let image = decode(&mosaic);
+        assert_eq!(image.dimensions(), (3, 1));
+        assert_eq!(*image.get_pixel(0, 0), RED);
+        assert_eq!(*image.get_pixel(1, 0), Rgb([0, 0, 0]));
+        assert_eq!(*image.get_pixel(2, 0), BLUE);
+    }
+
+    #[test]
+    fn no_scenes_is_rejected() {
+        assert!(matches!(build(Vec::new()), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn single_uniform_scene_is_copied() {
+        let only = scene("only", 3, 2, RED, [10.0, 20.0, 10.3, 20.2], 5.0);
+
+        let mosaic = build(vec![only]).unwrap();
+        assert_eq!(mosaic.bounds, [10.0, 20.0, 10.3, 20.2]);
+        assert_eq!(mosaic.scene_ids, vec!["only"]);
+
+        let image = decode(&mosaic);
+        assert_eq!(image.dimensions(), (3, 2));
+        assert!(image.pixels().all(|p| *p == RED));
+    }
+}
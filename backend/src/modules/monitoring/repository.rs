@@ -1,22 +1,32 @@
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, PgPool, Row, postgres::PgRow};
 use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::convert::TryFrom;
-use crate::shared::error::{AppResult, AppError};
-use super::models::{Alert, SalinityLog, IntrusionVector, CreateAlert, CreateSalinityLog, CreateIntrusionVector, AlertSeverity};
+use crate::shared::{error::{AppResult, AppError}, postgis, tiles::{TileCoord, TileField}};
+use super::models::{Alert, AlertRecipient, AnalysisRun, AnalysisSnapshot, SalinityLog, IntrusionVector, CreateAlert, CreateSalinityLog, CreateIntrusionVector, AlertSeverity, SpectralBaseline, DistrictAlerts,
+    SalinityCalibration, CreateSensorReading, AlertRules, AlertRuleOverrides, UpdateAlertRulesRequest, WaterObservation, RegionThreshold,
+    AiModel, Hotspot, ModelStatus, RegisterModelRequest, ShadowReport};
+use super::hotspots::HotspotCells;
+use super::ai::calibration::LinearFit;
+use crate::modules::farm_mgmt::CropSeason;
+use super::import::ImportRow;
 
-pub async fn save_alert(alert: CreateAlert, db: &PgPool) -> AppResult<i64> {
-    let record = sqlx::query_scalar(
+pub async fn save_alert(alert: CreateAlert, conn: &mut PgConnection) -> AppResult<i64> {
+    let record = sqlx::query_scalar(&format!(
         r#"
-        INSERT INTO alerts (farm_id, severity, message, metadata, detected_at)
-        VALUES ($1, $2, $3, $4, NOW())
+        INSERT INTO alerts (farm_id, severity, message, metadata, affected_area, detected_at)
+        VALUES ($1, $2, $3, $4, ST_Multi({}), NOW())
         RETURNING id
-        "#
-    )
+        "#,
+        postgis::from_geojson("$5"),
+    ))
     .bind(alert.farm_id)
     .bind(alert.severity.as_str())
     .bind(alert.message)
     .bind(alert.metadata)
-    .fetch_one(db)
+    .bind(alert.affected_area)
+    .fetch_one(conn)
     .await?;
 
     Ok(record)
@@ -29,20 +39,154 @@ pub async fn save_salinity_log(log: CreateSalinityLog, db: &PgPool) -> AppResult
 
     let record = sqlx::query_scalar(
         r#"
-        INSERT INTO salinity_logs (farm_id, ndsi_value, source, recorded_at)
-        VALUES ($1, $2, $3, NOW())
+        INSERT INTO salinity_logs (farm_id, ndsi_value, source, model_version, recorded_at)
+        VALUES ($1, $2, $3, $4, NOW())
         RETURNING id
         "#
     )
     .bind(log.farm_id)
     .bind(ndsi) 
     .bind(log.source)
+    .bind(log.model_version)
     .fetch_one(db)
     .await?;
 
     Ok(record)
 }
 
+/// Stores the union of `cells` clipped to the farm; returns `None` when
+/// nothing of it lies inside the boundary.
+pub async fn save_hotspot(
+    farm_id: i64,
+    log_id: i64,
+    alert_id: Option<i64>,
+    spot: &HotspotCells,
+    cells: &[[f64; 4]],
+    db: &PgPool,
+) -> AppResult<Option<i64>> {
+    let column = |k: usize| cells.iter().map(|c| c[k]).collect::<Vec<f64>>();
+
+    let id = sqlx::query_scalar(
+        r#"
+        WITH cells AS (
+            SELECT ST_Union(ST_MakeEnvelope(min_lon, min_lat, max_lon, max_lat, 4326)) AS geom
+            FROM UNNEST($4::DOUBLE PRECISION[], $5::DOUBLE PRECISION[], $6::DOUBLE PRECISION[], $7::DOUBLE PRECISION[])
+                AS c(min_lon, min_lat, max_lon, max_lat)
+        ), clipped AS (
+            SELECT ST_Multi(ST_CollectionExtract(ST_Intersection(cells.geom, f.geometry), 3)) AS geom
+            FROM cells, farms f
+            WHERE f.id = $1
+        )
+        INSERT INTO farm_hotspots (farm_id, log_id, alert_id, geometry, area_hectares, cell_count, mean_z, max_z, mean_probability)
+        SELECT $1, $2, $3, geom, ST_Area(geom::geography) / 10000.0, $8, $9, $10, $11
+        FROM clipped
+        WHERE NOT ST_IsEmpty(geom)
+        RETURNING id
+        "#
+    )
+    .bind(farm_id)
+    .bind(log_id)
+    .bind(alert_id)
+    .bind(column(0))
+    .bind(column(1))
+    .bind(column(2))
+    .bind(column(3))
+    .bind(cells.len() as i32)
+    .bind(spot.mean_z)
+    .bind(spot.max_z)
+    .bind(spot.mean_probability)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(id)
+}
+
+/// Lists the hot spots in the alert's metadata and makes their union its
+/// affected area. Returns the area as GeoJSON.
+pub async fn attach_hotspots_to_alert(alert_id: i64, hotspot_ids: &[i64], db: &PgPool) -> AppResult<Option<String>> {
+    let area = sqlx::query_scalar(
+        r#"
+        UPDATE alerts
+        SET metadata = COALESCE(metadata, '{}'::jsonb) || jsonb_build_object('hotspot_ids', $2::BIGINT[]),
+            affected_area = (SELECT ST_Multi(ST_Union(geometry)) FROM farm_hotspots WHERE id = ANY($2))
+        WHERE id = $1
+        RETURNING ST_AsGeoJSON(affected_area)
+        "#
+    )
+    .bind(alert_id)
+    .bind(hotspot_ids)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(area.flatten())
+}
+
+pub async fn list_hotspots(farm_id: i64, log_id: Option<i64>, limit: i64, db: &PgPool) -> AppResult<Vec<Hotspot>> {
+    let hotspots = sqlx::query_as::<_, Hotspot>(
+        r#"
+        SELECT id, farm_id, log_id, alert_id, ST_AsGeoJSON(geometry) AS geometry, area_hectares,
+               cell_count, mean_z, max_z, mean_probability, detected_at
+        FROM farm_hotspots
+        WHERE farm_id = $1 AND ($2::BIGINT IS NULL OR log_id = $2)
+        ORDER BY detected_at DESC, max_z DESC
+        LIMIT $3
+        "#
+    )
+    .bind(farm_id)
+    .bind(log_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(hotspots)
+}
+
+pub async fn save_scene_ids(log_id: i64, scene_ids: &[String], db: &PgPool) -> AppResult<()> {
+    sqlx::query("UPDATE salinity_logs SET scene_ids = $2 WHERE id = $1")
+        .bind(log_id)
+        .bind(scene_ids)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn save_analysis_snapshot(log_id: i64, snapshot: &AnalysisSnapshot, db: &PgPool) -> AppResult<()> {
+    sqlx::query("UPDATE salinity_logs SET analysis = $2 WHERE id = $1")
+        .bind(log_id)
+        .bind(sqlx::types::Json(snapshot))
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// The AI analyses of `farm_id` among `log_ids`. Class areas split the farm by
+/// the water coverage the run measured.
+pub async fn get_analysis_runs(farm_id: i64, log_ids: &[i64], db: &PgPool) -> AppResult<Vec<AnalysisRun>> {
+    let runs = sqlx::query_as::<_, AnalysisRun>(
+        r#"
+        SELECT l.id AS log_id,
+               l.recorded_at,
+               l.model_version,
+               l.ndsi_value::FLOAT8 AS ndsi,
+               f.area_hectares::FLOAT8 * (l.analysis->>'water_coverage_percent')::FLOAT8 / 100 AS water_area_hectares,
+               f.area_hectares::FLOAT8 * (100 - (l.analysis->>'water_coverage_percent')::FLOAT8) / 100 AS land_area_hectares,
+               COALESCE((SELECT SUM(h.area_hectares) FROM farm_hotspots h WHERE h.log_id = l.id), 0) AS hotspot_area_hectares,
+               l.analysis
+        FROM salinity_logs l
+        JOIN farms f ON f.id = l.farm_id
+        WHERE l.farm_id = $1 AND l.id = ANY($2) AND l.source = 'ai_analysis'
+        "#
+    )
+    .bind(farm_id)
+    .bind(log_ids)
+    .fetch_all(db)
+    .await?;
+
+    Ok(runs)
+}
+
 pub async fn save_intrusion_vector(vector: CreateIntrusionVector, db: &PgPool) -> AppResult<i64> {
     // FIX: Use try_from for f64 conversions
     let angle = BigDecimal::try_from(vector.angle_degrees)
@@ -53,8 +197,11 @@ pub async fn save_intrusion_vector(vector: CreateIntrusionVector, db: &PgPool) -
 
     let record = sqlx::query_scalar(
         r#"
-        INSERT INTO intrusion_vectors (farm_id, direction, angle_degrees, magnitude_km, calculated_at)
-        VALUES ($1, $2, $3, $4, NOW())
+        INSERT INTO intrusion_vectors (
+            farm_id, direction, angle_degrees, magnitude_km, calculated_at,
+            previous_observed_at, current_observed_at, previous_pixel_count, current_pixel_count
+        )
+        VALUES ($1, $2, $3, $4, NOW(), $5, $6, $7, $8)
         RETURNING id
         "#
     )
@@ -62,16 +209,39 @@ pub async fn save_intrusion_vector(vector: CreateIntrusionVector, db: &PgPool) -
     .bind(vector.direction)
     .bind(angle)
     .bind(magnitude)
+    .bind(vector.previous.recorded_at)
+    .bind(vector.current.recorded_at)
+    .bind(vector.previous.pixel_count)
+    .bind(vector.current.pixel_count)
     .fetch_one(db)
     .await?;
 
     Ok(record)
 }
 
+/// Mean NDSI per UTC day over the last `days` days, oldest first.
+pub async fn get_daily_ndsi(farm_id: i64, days: i32, db: &PgPool) -> AppResult<Vec<(DateTime<Utc>, f64)>> {
+    let rows = sqlx::query_as(
+        r#"
+        SELECT date_trunc('day', recorded_at) AS day, AVG(ndsi_value)::DOUBLE PRECISION AS ndsi
+        FROM salinity_logs
+        WHERE farm_id = $1 AND recorded_at >= NOW() - INTERVAL '1 day' * $2
+        GROUP BY day
+        ORDER BY day
+        "#,
+    )
+    .bind(farm_id)
+    .bind(days as f64)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows)
+}
+
 pub async fn get_ndsi_history(farm_id: i64, days: i32, db: &PgPool) -> AppResult<Vec<SalinityLog>> {
     let rows = sqlx::query(
         r#"
-        SELECT id, farm_id, ndsi_value, source, recorded_at
+        SELECT id, farm_id, ndsi_value, source, model_version, recorded_at
         FROM salinity_logs
         WHERE farm_id = $1 AND recorded_at >= NOW() - INTERVAL '1 day' * $2
         ORDER BY recorded_at DESC
@@ -92,6 +262,8 @@ pub async fn get_ndsi_history(farm_id: i64, days: i32, db: &PgPool) -> AppResult
                 ndsi_value: val,
                 source: row.get("source"),
                 recorded_at: row.get("recorded_at"),
+                model_version: row.get("model_version"),
+                estimated_salinity: None,
             })
         })
         .collect())
@@ -100,7 +272,8 @@ pub async fn get_ndsi_history(farm_id: i64, days: i32, db: &PgPool) -> AppResult
 pub async fn get_recent_alerts(farm_id: i64, limit: i64, db: &PgPool) -> AppResult<Vec<Alert>> {
     let rows = sqlx::query(
         r#"
-        SELECT id, farm_id, severity, message, metadata, detected_at, acknowledged, acknowledged_at
+        SELECT id, farm_id, severity, message, metadata, ST_AsGeoJSON(affected_area) AS affected_area,
+               detected_at, acknowledged, acknowledged_at
         FROM alerts
         WHERE farm_id = $1
         ORDER BY detected_at DESC
@@ -112,33 +285,199 @@ pub async fn get_recent_alerts(farm_id: i64, limit: i64, db: &PgPool) -> AppResu
     .fetch_all(db)
     .await?;
 
+    Ok(rows.into_iter().map(alert_from_row).collect())
+}
+
+/// Alerts on farms whose boundary intersects `bbox`, newest first. Hot spots
+/// are clipped to their farm, so the boundary covers every alert geometry.
+/// `user_id` limits the search to that user's farms.
+pub async fn get_alerts_in_bbox(bbox: [f64; 4], user_id: Option<i64>, limit: i64, db: &PgPool) -> AppResult<Vec<Alert>> {
+    let [min_lon, min_lat, max_lon, max_lat] = bbox;
+    let rows = sqlx::query(
+        r#"
+        SELECT a.id, a.farm_id, a.severity, a.message, a.metadata, ST_AsGeoJSON(a.affected_area) AS affected_area,
+               a.detected_at, a.acknowledged, a.acknowledged_at
+        FROM alerts a
+        JOIN farms f ON f.id = a.farm_id
+        WHERE f.deleted_at IS NULL
+          AND ($5::BIGINT IS NULL OR f.user_id = $5)
+          AND f.geometry && ST_MakeEnvelope($1, $2, $3, $4, 4326)
+          AND ST_Intersects(COALESCE(a.affected_area, f.geometry), ST_MakeEnvelope($1, $2, $3, $4, 4326))
+        ORDER BY a.detected_at DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(min_lon)
+    .bind(min_lat)
+    .bind(max_lon)
+    .bind(max_lat)
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(alert_from_row).collect())
+}
+
+/// Unacknowledged high and critical alerts of the last `days` days on active
+/// farms, grouped by the farms' region, most severe districts first.
+pub async fn list_district_alerts(days: i32, db: &PgPool) -> AppResult<Vec<DistrictAlerts>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT f.region AS district,
+               COUNT(*) AS alert_count,
+               COUNT(*) FILTER (WHERE a.severity = 'critical') AS critical_count,
+               COUNT(DISTINCT a.farm_id) AS farm_count,
+               MAX(a.id) AS latest_alert_id,
+               MIN(a.detected_at) AS first_detected_at,
+               MAX(a.detected_at) AS last_detected_at,
+               ST_XMin(ST_Extent(f.geometry)) AS min_lon,
+               ST_YMin(ST_Extent(f.geometry)) AS min_lat,
+               ST_XMax(ST_Extent(f.geometry)) AS max_lon,
+               ST_YMax(ST_Extent(f.geometry)) AS max_lat
+        FROM alerts a
+        JOIN farms f ON f.id = a.farm_id
+        WHERE f.deleted_at IS NULL
+          AND NOT a.acknowledged
+          AND a.severity IN ('high', 'critical')
+          AND a.detected_at > NOW() - make_interval(days => $1)
+        GROUP BY f.region
+        ORDER BY critical_count DESC, last_detected_at DESC
+        "#,
+    )
+    .bind(days)
+    .fetch_all(db)
+    .await?;
+
     Ok(rows
         .into_iter()
         .map(|row| {
-            let severity_str: String = row.get("severity");
-            Alert {
-                id: row.get("id"),
-                farm_id: row.get("farm_id"),
-                severity: match severity_str.as_str() {
-                    "critical" => AlertSeverity::Critical,
-                    "high" => AlertSeverity::High,
-                    "medium" => AlertSeverity::Medium,
-                    _ => AlertSeverity::Low,
+            let bounds: [Option<f64>; 4] = [row.get("min_lon"), row.get("min_lat"), row.get("max_lon"), row.get("max_lat")];
+            DistrictAlerts {
+                district: row.get("district"),
+                alert_count: row.get("alert_count"),
+                critical_count: row.get("critical_count"),
+                farm_count: row.get("farm_count"),
+                latest_alert_id: row.get("latest_alert_id"),
+                first_detected_at: row.get("first_detected_at"),
+                last_detected_at: row.get("last_detected_at"),
+                bbox: match bounds {
+                    [Some(min_lon), Some(min_lat), Some(max_lon), Some(max_lat)] => Some([min_lon, min_lat, max_lon, max_lat]),
+                    _ => None,
                 },
-                message: row.get("message"),
-                metadata: row.get("metadata"),
-                detected_at: row.get("detected_at"),
-                acknowledged: row.get("acknowledged"),
-                acknowledged_at: row.get("acknowledged_at"),
             }
         })
         .collect())
 }
 
+/// Attributes the alerts tile layer can carry.
+pub const ALERT_TILE_FIELDS: &[TileField] = &[
+    ("farm_id", "a.farm_id"),
+    ("severity", "a.severity"),
+    ("message", "a.message"),
+    ("acknowledged", "a.acknowledged"),
+    ("detected_at", "EXTRACT(EPOCH FROM a.detected_at)::BIGINT"),
+];
+
+/// Alerts of the last `days` days on active farms in tile `tile`, as an MVT
+/// `alerts` layer of their affected area, or their whole farm when the alert
+/// has none. `fields` are select expressions built from `ALERT_TILE_FIELDS`.
+pub async fn alert_tile(
+    tile: TileCoord,
+    user_id: Option<i64>,
+    days: i32,
+    fields: &[String],
+    db: &PgPool,
+) -> AppResult<Vec<u8>> {
+    let envelope = postgis::tile_envelope("$1", "$2", "$3");
+    let layer = format!(
+        r#"
+        SELECT a.id, {geom} AS geom{fields}
+        FROM alerts a
+        JOIN farms f ON f.id = a.farm_id
+        WHERE {in_tile}
+          AND f.deleted_at IS NULL
+          AND ($4::BIGINT IS NULL OR f.user_id = $4)
+          AND a.detected_at >= NOW() - make_interval(days => $5)
+        "#,
+        geom = postgis::as_mvt_geom("COALESCE(a.affected_area, f.geometry)", &envelope),
+        fields = fields.iter().map(|field| format!(", {}", field)).collect::<String>(),
+        // An affected area lies within its farm, so the farm's index narrows
+        // the rows; areas clipped away entirely are dropped by `as_mvt`.
+        in_tile = postgis::in_tile("f.geometry", &envelope),
+    );
+
+    let tile = sqlx::query_scalar(&postgis::as_mvt(&layer, "alerts"))
+        .bind(tile.z as i32)
+        .bind(tile.x as i32)
+        .bind(tile.y as i32)
+        .bind(user_id)
+        .bind(days)
+        .fetch_one(db)
+        .await?;
+
+    Ok(tile)
+}
+
+fn alert_from_row(row: PgRow) -> Alert {
+    let severity_str: String = row.get("severity");
+    Alert {
+        id: row.get("id"),
+        farm_id: row.get("farm_id"),
+        severity: match severity_str.as_str() {
+            "critical" => AlertSeverity::Critical,
+            "high" => AlertSeverity::High,
+            "medium" => AlertSeverity::Medium,
+            _ => AlertSeverity::Low,
+        },
+        message: row.get("message"),
+        metadata: row.get("metadata"),
+        affected_area: row.get("affected_area"),
+        detected_at: row.get("detected_at"),
+        acknowledged: row.get("acknowledged"),
+        acknowledged_at: row.get("acknowledged_at"),
+    }
+}
+
+pub async fn get_alert(alert_id: i64, db: &PgPool) -> AppResult<Option<Alert>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, farm_id, severity, message, metadata, ST_AsGeoJSON(affected_area) AS affected_area,
+               detected_at, acknowledged, acknowledged_at
+        FROM alerts
+        WHERE id = $1
+        "#,
+    )
+    .bind(alert_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(alert_from_row))
+}
+
+pub async fn acknowledge_alert(alert_id: i64, db: &PgPool) -> AppResult<Option<Alert>> {
+    let row = sqlx::query(
+        r#"
+        UPDATE alerts
+        SET acknowledged = TRUE,
+            acknowledged_at = COALESCE(acknowledged_at, NOW())
+        WHERE id = $1
+        RETURNING id, farm_id, severity, message, metadata, ST_AsGeoJSON(affected_area) AS affected_area,
+                  detected_at, acknowledged, acknowledged_at
+        "#,
+    )
+    .bind(alert_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(alert_from_row))
+}
+
 pub async fn get_latest_intrusion_vector(farm_id: i64, db: &PgPool) -> AppResult<Option<IntrusionVector>> {
     let row = sqlx::query(
         r#"
-        SELECT id, farm_id, direction, angle_degrees, magnitude_km, calculated_at
+        SELECT id, farm_id, direction, angle_degrees, magnitude_km, calculated_at,
+               previous_observed_at, current_observed_at, previous_pixel_count, current_pixel_count
         FROM intrusion_vectors
         WHERE farm_id = $1
         ORDER BY calculated_at DESC
@@ -162,10 +501,134 @@ pub async fn get_latest_intrusion_vector(farm_id: i64, db: &PgPool) -> AppResult
             angle_degrees: angle,
             magnitude_km: magnitude,
             calculated_at: row.get("calculated_at"),
+            previous_observed_at: row.get("previous_observed_at"),
+            current_observed_at: row.get("current_observed_at"),
+            previous_pixel_count: row.get("previous_pixel_count"),
+            current_pixel_count: row.get("current_pixel_count"),
         })
     }))
 }
 
+/// Bounding box of the farm boundary as [min_lon, min_lat, max_lon, max_lat].
+pub async fn get_farm_bounds(farm_id: i64, db: &PgPool) -> AppResult<Option<[f64; 4]>> {
+    let row = sqlx::query(
+        r#"
+        SELECT ST_XMin(geometry) AS min_lon, ST_YMin(geometry) AS min_lat,
+               ST_XMax(geometry) AS max_lon, ST_YMax(geometry) AS max_lat
+        FROM farms WHERE id = $1
+        "#
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|row| [row.get("min_lon"), row.get("min_lat"), row.get("max_lon"), row.get("max_lat")]))
+}
+
+/// Attaches the water-pixel centroid found in an analysed image to its log entry.
+pub async fn save_water_observation(
+    log_id: i64,
+    centroid: (f64, f64),
+    pixel_count: i32,
+    db: &PgPool,
+) -> AppResult<Option<WaterObservation>> {
+    let row = sqlx::query(&format!(
+        r#"
+        UPDATE salinity_logs
+        SET water_centroid = {}, water_pixel_count = $4
+        WHERE id = $1
+        RETURNING id, recorded_at, ST_X(water_centroid) AS lon, ST_Y(water_centroid) AS lat, water_pixel_count
+        "#,
+        postgis::point("$2", "$3")
+    ))
+    .bind(log_id)
+    .bind(centroid.0)
+    .bind(centroid.1)
+    .bind(pixel_count)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(water_observation_from_row))
+}
+
+/// Most recent observation for the farm recorded before `current`, no older
+/// than `days` before it.
+pub async fn get_previous_water_observation(
+    farm_id: i64,
+    current: &WaterObservation,
+    days: i32,
+    db: &PgPool,
+) -> AppResult<Option<WaterObservation>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, recorded_at, ST_X(water_centroid) AS lon, ST_Y(water_centroid) AS lat, water_pixel_count
+        FROM salinity_logs
+        WHERE farm_id = $1
+          AND water_centroid IS NOT NULL
+          AND id <> $2
+          AND recorded_at <= $3
+          AND recorded_at >= $3 - make_interval(days => $4)
+        ORDER BY recorded_at DESC, id DESC
+        LIMIT 1
+        "#
+    )
+    .bind(farm_id)
+    .bind(current.log_id)
+    .bind(current.recorded_at)
+    .bind(days)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(water_observation_from_row))
+}
+
+fn water_observation_from_row(row: PgRow) -> WaterObservation {
+    WaterObservation {
+        log_id: row.get("id"),
+        recorded_at: row.get("recorded_at"),
+        centroid: (row.get("lon"), row.get("lat")),
+        pixel_count: row.get("water_pixel_count"),
+    }
+}
+
+/// Centroid of the farm boundary as (lon, lat).
+pub async fn get_farm_centroid(farm_id: i64, db: &PgPool) -> AppResult<Option<(f64, f64)>> {
+    let row = sqlx::query(
+        "SELECT ST_X(ST_Centroid(geometry)) AS lon, ST_Y(ST_Centroid(geometry)) AS lat FROM farms WHERE id = $1"
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|row| (row.get("lon"), row.get("lat"))))
+}
+
+/// Rainfall and ET₀ totals over the last `days` days, and the highest stored
+/// sea level from today through `ahead_days` days.
+pub async fn get_weather_window(
+    farm_id: i64,
+    days: i32,
+    ahead_days: i32,
+    db: &PgPool,
+) -> AppResult<(Option<f64>, Option<f64>, Option<f64>)> {
+    let row = sqlx::query(
+        r#"
+        SELECT SUM(precipitation_mm) FILTER (WHERE observed_on > CURRENT_DATE - $2::INT AND observed_on <= CURRENT_DATE) AS rainfall_mm,
+               SUM(et0_mm) FILTER (WHERE observed_on > CURRENT_DATE - $2::INT AND observed_on <= CURRENT_DATE) AS et0_mm,
+               MAX(sea_level_max_m) FILTER (WHERE observed_on BETWEEN CURRENT_DATE AND CURRENT_DATE + $3::INT) AS sea_level_max_m
+        FROM weather_observations
+        WHERE farm_id = $1
+        "#
+    )
+    .bind(farm_id)
+    .bind(days)
+    .bind(ahead_days)
+    .fetch_one(db)
+    .await?;
+
+    Ok((row.get("rainfall_mm"), row.get("et0_mm"), row.get("sea_level_max_m")))
+}
+
 pub async fn get_latest_ndsi(farm_id: i64, db: &PgPool) -> AppResult<Option<f64>> {
     let record = sqlx::query_scalar::<_, BigDecimal>(
         "SELECT ndsi_value FROM salinity_logs WHERE farm_id = $1 ORDER BY recorded_at DESC LIMIT 1"
@@ -175,4 +638,617 @@ pub async fn get_latest_ndsi(farm_id: i64, db: &PgPool) -> AppResult<Option<f64>
     .await?;
 
     Ok(record.and_then(|bd| bd.to_f64()))
-}
\ No newline at end of file
+}
+
+/// Rebuilds the per-month NDSI climatology for every farm from the full
+/// salinity history. Months with fewer than three readings are skipped.
+pub async fn recompute_ndsi_baselines(db: &PgPool) -> AppResult<u64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO spectral_baselines
+            (farm_id, index_name, month, mean_value, std_dev, sample_count, years_covered, computed_at)
+        SELECT
+            farm_id,
+            'ndsi',
+            EXTRACT(MONTH FROM recorded_at)::SMALLINT,
+            AVG(ndsi_value)::DOUBLE PRECISION,
+            COALESCE(STDDEV_POP(ndsi_value), 0)::DOUBLE PRECISION,
+            COUNT(*)::INTEGER,
+            COUNT(DISTINCT EXTRACT(YEAR FROM recorded_at))::INTEGER,
+            NOW()
+        FROM salinity_logs
+        WHERE farm_id IS NOT NULL
+        GROUP BY farm_id, EXTRACT(MONTH FROM recorded_at)
+        HAVING COUNT(*) >= 3
+        ON CONFLICT (farm_id, index_name, month) DO UPDATE
+        SET mean_value = EXCLUDED.mean_value,
+            std_dev = EXCLUDED.std_dev,
+            sample_count = EXCLUDED.sample_count,
+            years_covered = EXCLUDED.years_covered,
+            computed_at = EXCLUDED.computed_at
+        "#,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn get_baseline(
+    farm_id: i64,
+    index_name: &str,
+    month: i16,
+    db: &PgPool,
+) -> AppResult<Option<SpectralBaseline>> {
+    let baseline = sqlx::query_as::<_, SpectralBaseline>(
+        r#"
+        SELECT farm_id, index_name, month, mean_value, std_dev, sample_count, years_covered, computed_at
+        FROM spectral_baselines
+        WHERE farm_id = $1 AND index_name = $2 AND month = $3
+        "#,
+    )
+    .bind(farm_id)
+    .bind(index_name)
+    .bind(month)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(baseline)
+}
+
+pub async fn save_sensor_reading(reading: CreateSensorReading, db: &PgPool) -> AppResult<i64> {
+    let record = sqlx::query_scalar(
+        r#"
+        INSERT INTO sensor_readings (farm_id, ec_ds_m, sensor_id, measured_at)
+        VALUES ($1, $2, $3, COALESCE($4, NOW()))
+        RETURNING id
+        "#
+    )
+    .bind(reading.farm_id)
+    .bind(reading.ec_ds_m)
+    .bind(reading.sensor_id)
+    .bind(reading.measured_at)
+    .fetch_one(db)
+    .await?;
+
+    Ok(record)
+}
+
+/// Pairs every sensor reading with the closest NDSI observation of the same
+/// farm taken within three days, grouped by farm region.
+pub async fn get_calibration_pairs(db: &PgPool) -> AppResult<Vec<(String, f64, f64)>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT f.region, l.ndsi_value::DOUBLE PRECISION AS ndsi, r.ec_ds_m
+        FROM sensor_readings r
+        JOIN farms f ON f.id = r.farm_id
+        JOIN LATERAL (
+            SELECT ndsi_value
+            FROM salinity_logs
+            WHERE farm_id = r.farm_id
+              AND recorded_at BETWEEN r.measured_at - INTERVAL '3 days'
+                                  AND r.measured_at + INTERVAL '3 days'
+            ORDER BY ABS(EXTRACT(EPOCH FROM (recorded_at - r.measured_at)))
+            LIMIT 1
+        ) l ON TRUE
+        WHERE f.region IS NOT NULL
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("region"), row.get("ndsi"), row.get("ec_ds_m")))
+        .collect())
+}
+
+pub async fn upsert_calibration(region: &str, fit: &LinearFit, db: &PgPool) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO salinity_calibrations
+            (region, slope, intercept, residual_std, r_squared, sample_count, ndsi_mean, ndsi_sxx, fitted_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
+        ON CONFLICT (region) DO UPDATE
+        SET slope = EXCLUDED.slope,
+            intercept = EXCLUDED.intercept,
+            residual_std = EXCLUDED.residual_std,
+            r_squared = EXCLUDED.r_squared,
+            sample_count = EXCLUDED.sample_count,
+            ndsi_mean = EXCLUDED.ndsi_mean,
+            ndsi_sxx = EXCLUDED.ndsi_sxx,
+            fitted_at = EXCLUDED.fitted_at
+        "#,
+    )
+    .bind(region)
+    .bind(fit.slope)
+    .bind(fit.intercept)
+    .bind(fit.residual_std)
+    .bind(fit.r_squared)
+    .bind(fit.sample_count as i32)
+    .bind(fit.x_mean)
+    .bind(fit.sxx)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_calibration_for_farm(farm_id: i64, db: &PgPool) -> AppResult<Option<SalinityCalibration>> {
+    let calibration = sqlx::query_as::<_, SalinityCalibration>(
+        r#"
+        SELECT c.region, c.slope, c.intercept, c.residual_std, c.r_squared,
+               c.sample_count, c.ndsi_mean, c.ndsi_sxx, c.fitted_at
+        FROM salinity_calibrations c
+        JOIN farms f ON f.region = c.region
+        WHERE f.id = $1
+        "#,
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(calibration)
+}
+
+pub async fn list_calibrations(db: &PgPool) -> AppResult<Vec<SalinityCalibration>> {
+    let calibrations = sqlx::query_as::<_, SalinityCalibration>(
+        r#"
+        SELECT region, slope, intercept, residual_std, r_squared,
+               sample_count, ndsi_mean, ndsi_sxx, fitted_at
+        FROM salinity_calibrations
+        ORDER BY region
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(calibrations)
+}
+
+pub async fn get_water_threshold_for_farm(farm_id: i64, db: &PgPool) -> AppResult<Option<f64>> {
+    let threshold = sqlx::query_scalar(
+        r#"
+        SELECT t.water_threshold
+        FROM region_water_thresholds t
+        JOIN farms f ON f.region = t.region
+        WHERE f.id = $1
+        "#,
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(threshold)
+}
+
+pub async fn list_region_thresholds(db: &PgPool) -> AppResult<Vec<RegionThreshold>> {
+    let thresholds = sqlx::query_as::<_, RegionThreshold>(
+        "SELECT region, water_threshold, updated_by, updated_at FROM region_water_thresholds ORDER BY region",
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(thresholds)
+}
+
+pub async fn get_region_threshold(region: &str, db: &PgPool) -> AppResult<Option<RegionThreshold>> {
+    let threshold = sqlx::query_as::<_, RegionThreshold>(
+        "SELECT region, water_threshold, updated_by, updated_at FROM region_water_thresholds WHERE region = $1",
+    )
+    .bind(region)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(threshold)
+}
+
+pub async fn upsert_region_threshold(
+    region: &str,
+    water_threshold: f64,
+    updated_by: i64,
+    db: &PgPool,
+) -> AppResult<RegionThreshold> {
+    let threshold = sqlx::query_as::<_, RegionThreshold>(
+        r#"
+        INSERT INTO region_water_thresholds (region, water_threshold, updated_by, updated_at)
+        VALUES ($1, $2, $3, NOW())
+        ON CONFLICT (region) DO UPDATE
+        SET water_threshold = EXCLUDED.water_threshold,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = EXCLUDED.updated_at
+        RETURNING region, water_threshold, updated_by, updated_at
+        "#,
+    )
+    .bind(region)
+    .bind(water_threshold)
+    .bind(updated_by)
+    .fetch_one(db)
+    .await?;
+
+    Ok(threshold)
+}
+
+pub async fn delete_region_threshold(region: &str, db: &PgPool) -> AppResult<bool> {
+    let result = sqlx::query("DELETE FROM region_water_thresholds WHERE region = $1")
+        .bind(region)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Recent readings of the stations `farm_id` follows, as
+/// `(station_id, days before now, ndsi)`.
+pub async fn get_station_ndsi_points(farm_id: i64, days: i32, db: &PgPool) -> AppResult<Vec<(i64, f64, f64)>> {
+    let points = sqlx::query_as(
+        r#"
+        SELECT l.station_id,
+               (EXTRACT(EPOCH FROM (l.recorded_at - NOW())) / 86400)::FLOAT8,
+               l.ndsi_value::FLOAT8
+        FROM farm_station_subscriptions fs
+        JOIN salinity_logs l ON l.station_id = fs.station_id
+        WHERE fs.farm_id = $1 AND l.recorded_at >= NOW() - make_interval(days => $2)
+        "#,
+    )
+    .bind(farm_id)
+    .bind(days)
+    .fetch_all(db)
+    .await?;
+
+    Ok(points)
+}
+
+/// Owner of each farm in `farm_ids` that exists.
+pub async fn get_farm_owners(farm_ids: &[i64], db: &PgPool) -> AppResult<HashMap<i64, i64>> {
+    let owners: Vec<(i64, i64)> = sqlx::query_as("SELECT id, user_id FROM farms WHERE id = ANY($1)")
+        .bind(farm_ids)
+        .fetch_all(db)
+        .await?;
+
+    Ok(owners.into_iter().collect())
+}
+
+/// Bulk-inserts imported rows, skipping any already stored for the same farm,
+/// time and source. Returns the number of rows inserted.
+pub async fn insert_imported_salinity_logs(rows: &[ImportRow], source: &str, conn: &mut PgConnection) -> AppResult<u64> {
+    let farm_ids: Vec<i64> = rows.iter().map(|r| r.farm_id).collect();
+    let values: Vec<f64> = rows.iter().map(|r| r.ndsi).collect();
+    let recorded_at: Vec<DateTime<Utc>> = rows.iter().map(|r| r.recorded_at).collect();
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO salinity_logs (farm_id, ndsi_value, source, recorded_at)
+        SELECT DISTINCT ON (r.farm_id, r.recorded_at)
+               r.farm_id, r.ndsi::NUMERIC(8, 6), $3, r.recorded_at
+        FROM UNNEST($1::BIGINT[], $2::DOUBLE PRECISION[], $4::TIMESTAMPTZ[]) AS r(farm_id, ndsi, recorded_at)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM salinity_logs s
+            WHERE s.farm_id = r.farm_id AND s.recorded_at = r.recorded_at AND s.source = $3
+        )
+        "#
+    )
+    .bind(&farm_ids)
+    .bind(&values)
+    .bind(source)
+    .bind(&recorded_at)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn get_farm_owner(farm_id: i64, db: &PgPool) -> AppResult<Option<i64>> {
+    let owner = sqlx::query_scalar("SELECT user_id FROM farms WHERE id = $1")
+        .bind(farm_id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(owner)
+}
+
+/// Active farms of `user_id` intersecting the GeoJSON `area`, with their
+/// distance in km from (`lon`, `lat`).
+pub async fn get_user_farms_in_area(
+    user_id: i64,
+    area: &str,
+    lon: f64,
+    lat: f64,
+    db: &PgPool,
+) -> AppResult<Vec<(i64, String, f64)>> {
+    let origin = postgis::point("$3", "$4");
+    let farms = sqlx::query_as(&format!(
+        r#"
+        SELECT f.id, f.name, {distance} / 1000.0 AS distance_km
+        FROM farms f
+        WHERE f.user_id = $1
+          AND f.deleted_at IS NULL
+          AND ST_Intersects(f.geometry, {area})
+        "#,
+        distance = postgis::distance_metres("f.geometry", &origin),
+        area = postgis::from_geojson("$2"),
+    ))
+    .bind(user_id)
+    .bind(area)
+    .bind(lon)
+    .bind(lat)
+    .fetch_all(db)
+    .await?;
+
+    Ok(farms)
+}
+
+pub async fn get_user_farm_names(user_id: i64, db: &PgPool) -> AppResult<Vec<(i64, String)>> {
+    let farms = sqlx::query_as("SELECT id, name FROM farms WHERE user_id = $1 AND deleted_at IS NULL ORDER BY id")
+        .bind(user_id)
+        .fetch_all(db)
+        .await?;
+
+    Ok(farms)
+}
+
+/// Owner and name of the farm, for addressing notifications.
+pub async fn get_alert_recipient(farm_id: i64, db: &PgPool) -> AppResult<Option<AlertRecipient>> {
+    let recipient = sqlx::query_as::<_, AlertRecipient>(
+        r#"
+        SELECT u.id AS user_id, u.email, u.email_verified_at IS NOT NULL AS email_verified, f.name AS farm_name
+        FROM farms f
+        JOIN users u ON u.id = f.user_id
+        WHERE f.id = $1 AND u.deletion_scheduled_at IS NULL
+        "#
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(recipient)
+}
+
+/// Merges the farm's overrides over its crop defaults (or the `default` crop).
+pub async fn get_alert_rules(farm_id: i64, db: &PgPool) -> AppResult<AlertRules> {
+    let rules = sqlx::query_as::<_, AlertRules>(
+        r#"
+        SELECT d.crop_type,
+               COALESCE(r.enabled, TRUE) AS enabled,
+               COALESCE(r.anomaly_multiplier, d.anomaly_multiplier) AS anomaly_multiplier,
+               COALESCE(r.max_salinity_g_l, d.max_salinity_g_l) AS max_salinity_g_l,
+               COALESCE(r.critical_salinity_g_l, d.critical_salinity_g_l) AS critical_salinity_g_l
+        FROM (SELECT $1::BIGINT AS farm_id) f
+        LEFT JOIN farm_alert_rules r ON r.farm_id = f.farm_id
+        JOIN crop_alert_defaults d ON d.crop_type = COALESCE(r.crop_type, 'default')
+        "#
+    )
+    .bind(farm_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(rules)
+}
+
+/// The most recently planted season that had started by today.
+pub async fn get_current_crop_season(farm_id: i64, db: &PgPool) -> AppResult<Option<CropSeason>> {
+    let season = sqlx::query_as::<_, CropSeason>(
+        r#"
+        SELECT id, farm_id, crop_type, variety, planting_date, expected_harvest_date,
+               growth_stage, created_at, updated_at
+        FROM crop_seasons
+        WHERE farm_id = $1 AND planting_date <= CURRENT_DATE
+        ORDER BY planting_date DESC
+        LIMIT 1
+        "#
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(season)
+}
+
+pub async fn get_alert_rule_overrides(farm_id: i64, db: &PgPool) -> AppResult<AlertRuleOverrides> {
+    let overrides = sqlx::query_as::<_, AlertRuleOverrides>(
+        "SELECT anomaly_multiplier, max_salinity_g_l, critical_salinity_g_l FROM farm_alert_rules WHERE farm_id = $1"
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(overrides.unwrap_or_default())
+}
+
+pub async fn crop_type_exists(crop_type: &str, db: &PgPool) -> AppResult<bool> {
+    let exists = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM crop_alert_defaults WHERE crop_type = $1)")
+        .bind(crop_type)
+        .fetch_one(db)
+        .await?;
+
+    Ok(exists)
+}
+
+pub async fn upsert_alert_rules(farm_id: i64, rules: &UpdateAlertRulesRequest, db: &PgPool) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO farm_alert_rules
+            (farm_id, crop_type, enabled, anomaly_multiplier, max_salinity_g_l, critical_salinity_g_l)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (farm_id) DO UPDATE
+        SET crop_type = EXCLUDED.crop_type,
+            enabled = EXCLUDED.enabled,
+            anomaly_multiplier = EXCLUDED.anomaly_multiplier,
+            max_salinity_g_l = EXCLUDED.max_salinity_g_l,
+            critical_salinity_g_l = EXCLUDED.critical_salinity_g_l
+        "#
+    )
+    .bind(farm_id)
+    .bind(&rules.crop_type)
+    .bind(rules.enabled)
+    .bind(rules.overrides.anomaly_multiplier)
+    .bind(rules.overrides.max_salinity_g_l)
+    .bind(rules.overrides.critical_salinity_g_l)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+const AI_MODEL_COLUMNS: &str =
+    "id, name, version, config_path, weights_path, metrics, status, created_by, created_at, activated_at";
+
+fn row_to_ai_model(row: PgRow) -> AiModel {
+    let status: String = row.get("status");
+    AiModel {
+        id: row.get("id"),
+        name: row.get("name"),
+        version: row.get("version"),
+        config_path: row.get("config_path"),
+        weights_path: row.get("weights_path"),
+        metrics: row.get("metrics"),
+        status: match status.as_str() {
+            "active" => ModelStatus::Active,
+            "shadow" => ModelStatus::Shadow,
+            _ => ModelStatus::Inactive,
+        },
+        created_by: row.get("created_by"),
+        created_at: row.get("created_at"),
+        activated_at: row.get("activated_at"),
+    }
+}
+
+pub async fn list_ai_models(db: &PgPool) -> AppResult<Vec<AiModel>> {
+    let rows = sqlx::query(&format!("SELECT {} FROM ai_models ORDER BY created_at DESC", AI_MODEL_COLUMNS))
+        .fetch_all(db)
+        .await?;
+
+    Ok(rows.into_iter().map(row_to_ai_model).collect())
+}
+
+pub async fn get_ai_model(id: i64, db: &PgPool) -> AppResult<Option<AiModel>> {
+    let row = sqlx::query(&format!("SELECT {} FROM ai_models WHERE id = $1", AI_MODEL_COLUMNS))
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(row_to_ai_model))
+}
+
+pub async fn get_ai_model_by_status(status: ModelStatus, db: &PgPool) -> AppResult<Option<AiModel>> {
+    let row = sqlx::query(&format!("SELECT {} FROM ai_models WHERE status = $1", AI_MODEL_COLUMNS))
+        .bind(status.as_str())
+        .fetch_optional(db)
+        .await?;
+
+    Ok(row.map(row_to_ai_model))
+}
+
+pub async fn ai_model_exists(name: &str, version: &str, db: &PgPool) -> AppResult<bool> {
+    let exists = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM ai_models WHERE name = $1 AND version = $2)")
+        .bind(name)
+        .bind(version)
+        .fetch_one(db)
+        .await?;
+
+    Ok(exists)
+}
+
+pub async fn insert_ai_model(request: &RegisterModelRequest, created_by: i64, db: &PgPool) -> AppResult<AiModel> {
+    let row = sqlx::query(&format!(
+        r#"
+        INSERT INTO ai_models (name, version, config_path, weights_path, metrics, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {}
+        "#,
+        AI_MODEL_COLUMNS
+    ))
+    .bind(request.name.trim())
+    .bind(request.version.trim())
+    .bind(&request.config_path)
+    .bind(&request.weights_path)
+    .bind(request.metrics.clone().unwrap_or_else(|| serde_json::json!({})))
+    .bind(created_by)
+    .fetch_one(db)
+    .await?;
+
+    Ok(row_to_ai_model(row))
+}
+
+/// Gives the model `status`, demoting whichever model held it before.
+pub async fn set_ai_model_status(id: i64, status: ModelStatus, db: &PgPool) -> AppResult<Option<AiModel>> {
+    let mut tx = db.begin().await?;
+
+    if status != ModelStatus::Inactive {
+        sqlx::query("UPDATE ai_models SET status = 'inactive' WHERE status = $1 AND id <> $2")
+            .bind(status.as_str())
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let row = sqlx::query(&format!(
+        r#"
+        UPDATE ai_models
+        SET status = $2,
+            activated_at = CASE WHEN $2 = 'active' THEN NOW() ELSE activated_at END
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        AI_MODEL_COLUMNS
+    ))
+    .bind(id)
+    .bind(status.as_str())
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(row.map(row_to_ai_model))
+}
+
+pub async fn save_shadow_analysis(
+    log_id: i64,
+    model_id: i64,
+    active_coverage_percent: f64,
+    shadow_coverage_percent: f64,
+    db: &PgPool,
+) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO shadow_analyses (log_id, model_id, active_coverage_percent, shadow_coverage_percent)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(log_id)
+    .bind(model_id)
+    .bind(active_coverage_percent)
+    .bind(shadow_coverage_percent)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn shadow_report(model: &AiModel, db: &PgPool) -> AppResult<ShadowReport> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS runs,
+               AVG(active_coverage_percent) AS mean_active,
+               AVG(shadow_coverage_percent) AS mean_shadow,
+               AVG(ABS(shadow_coverage_percent - active_coverage_percent)) AS mean_abs_difference,
+               MAX(ABS(shadow_coverage_percent - active_coverage_percent)) AS max_abs_difference,
+               MAX(created_at) AS last_run_at
+        FROM shadow_analyses
+        WHERE model_id = $1
+        "#,
+    )
+    .bind(model.id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(ShadowReport {
+        model_id: model.id,
+        model_version: model.label(),
+        runs: row.get("runs"),
+        mean_active_coverage_percent: row.get("mean_active"),
+        mean_shadow_coverage_percent: row.get("mean_shadow"),
+        mean_abs_difference: row.get("mean_abs_difference"),
+        max_abs_difference: row.get("max_abs_difference"),
+        last_run_at: row.get("last_run_at"),
+    })
+}
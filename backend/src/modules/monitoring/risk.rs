@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use super::models::{AlertSeverity, RiskComponent, RiskScore};
+
+const NDSI_TREND_WEIGHT: f64 = 0.30;
+const INTRUSION_WEIGHT: f64 = 0.25;
+const TIDE_WEIGHT: f64 = 0.25;
+const RAINFALL_DEFICIT_WEIGHT: f64 = 0.20;
+const STATION_TREND_WEIGHT: f64 = 0.15;
+
+/// NDSI rise per day treated as maximal risk.
+const NDSI_SLOPE_SATURATION: f64 = 0.01;
+/// Intrusion front speed treated as maximal risk.
+const INTRUSION_SATURATION_KM_PER_DAY: f64 = 1.0;
+/// Daily high-water range mapped onto 0–1; East Sea spring tides at the
+/// delta's river mouths peak around 1.5 m above mean sea level.
+const SEA_LEVEL_LOW_M: f64 = 0.3;
+const SEA_LEVEL_HIGH_M: f64 = 1.5;
+
+const SYNODIC_MONTH_DAYS: f64 = 29.530_588_853;
+/// New moon of 2000-01-06 18:14 UTC, as a Unix timestamp.
+const REFERENCE_NEW_MOON_UNIX: f64 = 947_182_440.0;
+
+/// Observations combined into the composite salinity risk score.
+#[derive(Debug, Clone)]
+pub struct RiskInput {
+    /// Least-squares NDSI slope per day over recent readings.
+    pub ndsi_slope_per_day: Option<f64>,
+    /// Speed of the latest intrusion front, if it is still recent.
+    pub intrusion_km_per_day: Option<f64>,
+    /// Highest forecast sea level over the next few days.
+    pub sea_level_max_m: Option<f64>,
+    /// Rainfall and reference evapotranspiration over the recent window.
+    pub rainfall_mm: Option<f64>,
+    pub et0_mm: Option<f64>,
+    /// Steepest NDSI slope per day among the stations the farm follows.
+    pub station_ndsi_slope_per_day: Option<f64>,
+}
+
+/// Combines the available factors into a 0–100 score. Weights of missing
+/// factors are redistributed over the ones that have data.
+pub fn score(input: &RiskInput, now: DateTime<Utc>) -> RiskScore {
+    let tide = match input.sea_level_max_m {
+        Some(level) => RiskComponent::new("tide", TIDE_WEIGHT, "observed", Some(tide_factor(Some(level), now)), Some(level)),
+        None => {
+            let spring = tide_factor(None, now);
+            RiskComponent::new("tide", TIDE_WEIGHT, "lunar_phase", Some(spring), Some(spring))
+        }
+    };
+
+    let rainfall_deficit = match (input.rainfall_mm, input.et0_mm) {
+        (Some(rain), Some(et0)) if et0 > 0.0 => {
+            let deficit = ((et0 - rain) / et0).clamp(0.0, 1.0);
+            RiskComponent::new("rainfall_deficit", RAINFALL_DEFICIT_WEIGHT, "weather", Some(deficit), Some(et0 - rain))
+        }
+        _ => RiskComponent::new("rainfall_deficit", RAINFALL_DEFICIT_WEIGHT, "weather", None, None),
+    };
+
+    let components = vec![
+        RiskComponent::new(
+            "ndsi_trend",
+            NDSI_TREND_WEIGHT,
+            "salinity_logs",
+            input.ndsi_slope_per_day.map(|slope| normalise(slope, 0.0, NDSI_SLOPE_SATURATION)),
+            input.ndsi_slope_per_day,
+        ),
+        RiskComponent::new(
+            "intrusion_velocity",
+            INTRUSION_WEIGHT,
+            "intrusion_vectors",
+            input.intrusion_km_per_day.map(|speed| normalise(speed, 0.0, INTRUSION_SATURATION_KM_PER_DAY)),
+            input.intrusion_km_per_day,
+        ),
+        tide,
+        rainfall_deficit,
+        RiskComponent::new(
+            "station_trend",
+            STATION_TREND_WEIGHT,
+            "stations",
+            input.station_ndsi_slope_per_day.map(|slope| normalise(slope, 0.0, NDSI_SLOPE_SATURATION)),
+            input.station_ndsi_slope_per_day,
+        ),
+    ];
+
+    let (weighted, total_weight) = components
+        .iter()
+        .filter_map(|c| c.value.map(|v| (v * c.weight, c.weight)))
+        .fold((0.0, 0.0), |(sum, weights), (v, w)| (sum + v, weights + w));
+
+    let score = if total_weight > 0.0 {
+        (weighted / total_weight * 100.0).round() as i32
+    } else {
+        0
+    };
+
+    RiskScore {
+        score,
+        level: level_for(score),
+        components,
+        computed_at: now,
+    }
+}
+
+/// Least-squares slope of `(days, value)` points, in value per day.
+pub fn trend_per_day(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 3 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+fn level_for(score: i32) -> AlertSeverity {
+    match score {
+        s if s >= 75 => AlertSeverity::Critical,
+        s if s >= 50 => AlertSeverity::High,
+        s if s >= 25 => AlertSeverity::Medium,
+        _ => AlertSeverity::Low,
+    }
+}
+
+/// How high the tide runs at `at` on a 0–1 scale: from the day's sea level
+/// when one is stored, otherwise from the lunar spring–neap cycle.
+pub fn tide_factor(sea_level_max_m: Option<f64>, at: DateTime<Utc>) -> f64 {
+    match sea_level_max_m {
+        Some(level) => normalise(level, SEA_LEVEL_LOW_M, SEA_LEVEL_HIGH_M),
+        None => spring_tide_factor(at),
+    }
+}
+
+/// 1 at new and full moon (spring tides), 0 at the quarters (neap tides).
+fn spring_tide_factor(at: DateTime<Utc>) -> f64 {
+    let days = (at.timestamp() as f64 - REFERENCE_NEW_MOON_UNIX) / 86_400.0;
+    let phase = (days / SYNODIC_MONTH_DAYS).rem_euclid(1.0);
+    (2.0 * std::f64::consts::PI * phase).cos().powi(2)
+}
+
+fn normalise(value: f64, low: f64, high: f64) -> f64 {
+    ((value - low) / (high - low)).clamp(0.0, 1.0)
+}
@@ -0,0 +1,157 @@
+use crate::modules::farm_mgmt::GrowthStage;
+use crate::shared::i18n::{t, Language};
+use super::models::{AlertRules, AlertSeverity, ThresholdCheck, ThresholdRule};
+
+/// Observations a farm's alert rules are evaluated against.
+#[derive(Debug, Clone)]
+pub struct RuleInput {
+    pub current_ndsi: f64,
+    /// Reference mean and standard deviation, when enough history exists.
+    pub baseline: Option<(f64, f64)>,
+    /// Calibrated salinity estimate in g/L, when the farm's region is calibrated.
+    pub estimated_g_l: Option<f64>,
+    /// Stage of the farm's current crop season, if one is recorded.
+    pub growth_stage: Option<GrowthStage>,
+}
+
+#[derive(Debug, Clone)]
+pub enum RuleReason {
+    NdsiThreshold { ndsi: f64, threshold: f64 },
+    SalinityCritical { g_l: f64, limit: f64, crop: String },
+    SalinityTolerance { g_l: f64, limit: f64, crop: String },
+}
+
+impl RuleReason {
+    pub fn message(&self, lang: Language) -> String {
+        match self {
+            RuleReason::NdsiThreshold { ndsi, threshold } => t(lang, "alert.reason.ndsi_threshold", &[
+                ("ndsi", format!("{:.4}", ndsi)),
+                ("threshold", format!("{:.4}", threshold)),
+                ("deviation", format!("{:.4}", ndsi - threshold)),
+            ]),
+            RuleReason::SalinityCritical { g_l, limit, crop } => t(lang, "alert.reason.salinity_critical", &[
+                ("g_l", format!("{:.2}", g_l)),
+                ("limit", format!("{:.2}", limit)),
+                ("crop", crop.clone()),
+            ]),
+            RuleReason::SalinityTolerance { g_l, limit, crop } => t(lang, "alert.reason.salinity_tolerance", &[
+                ("g_l", format!("{:.2}", g_l)),
+                ("limit", format!("{:.2}", limit)),
+                ("crop", crop.clone()),
+            ]),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleOutcome {
+    pub severity: AlertSeverity,
+    pub ndsi_threshold: Option<f64>,
+    pub reasons: Vec<RuleReason>,
+}
+
+/// Extra standard deviations added to the NDSI threshold. Bare or sparsely
+/// covered soil reads saltier than a closed canopy, so a high index right after
+/// planting or after harvest is expected rather than anomalous.
+fn ndsi_allowance(stage: Option<GrowthStage>) -> f64 {
+    match stage {
+        Some(GrowthStage::Germination | GrowthStage::Harvested) => 1.0,
+        Some(GrowthStage::Seedling) => 0.5,
+        _ => 0.0,
+    }
+}
+
+/// Scales the crop's salinity limits. Seedlings and flowering plants are the
+/// most salt-sensitive; `None` means no crop is standing to protect.
+fn tolerance_factor(stage: Option<GrowthStage>) -> Option<f64> {
+    match stage {
+        Some(GrowthStage::Seedling | GrowthStage::Reproductive) => Some(0.75),
+        Some(GrowthStage::Harvested) => None,
+        _ => Some(1.0),
+    }
+}
+
+/// NDSI alert threshold and the standard deviation it is measured in, when a
+/// baseline exists.
+fn ndsi_threshold_for(rules: &AlertRules, input: &RuleInput) -> Option<(f64, f64)> {
+    let (mean, std_dev) = input.baseline?;
+    let threshold = mean + (rules.anomaly_multiplier + ndsi_allowance(input.growth_stage)) * std_dev;
+    Some((threshold, std_dev))
+}
+
+/// Tolerance and critical salinity limits in g/L for the current growth stage.
+fn salinity_limits(rules: &AlertRules, input: &RuleInput) -> Option<(f64, f64)> {
+    let factor = tolerance_factor(input.growth_stage)?;
+    Some((rules.max_salinity_g_l * factor, rules.critical_salinity_g_l * factor))
+}
+
+/// Applies the relative NDSI anomaly rule and the absolute salinity tolerance
+/// rules, adjusted for the crop's growth stage, returning the most severe match.
+pub fn evaluate(rules: &AlertRules, input: &RuleInput) -> Option<RuleOutcome> {
+    if !rules.enabled {
+        return None;
+    }
+
+    let mut severity: Option<AlertSeverity> = None;
+    let mut reasons = Vec::new();
+    let mut ndsi_threshold = None;
+
+    if let Some((threshold, std_dev)) = ndsi_threshold_for(rules, input) {
+        ndsi_threshold = Some(threshold);
+
+        if input.current_ndsi > threshold {
+            let ndsi_severity = match input.current_ndsi {
+                n if n > threshold + std_dev => AlertSeverity::Critical,
+                n if n > threshold + (std_dev * 0.5) => AlertSeverity::High,
+                _ => AlertSeverity::Medium,
+            };
+            severity = severity.max(Some(ndsi_severity));
+            reasons.push(RuleReason::NdsiThreshold { ndsi: input.current_ndsi, threshold });
+        }
+    }
+
+    if let (Some(g_l), Some((max_limit, critical_limit))) = (input.estimated_g_l, salinity_limits(rules, input)) {
+        if g_l > critical_limit {
+            severity = severity.max(Some(AlertSeverity::Critical));
+            reasons.push(RuleReason::SalinityCritical {
+                g_l,
+                limit: critical_limit,
+                crop: rules.crop_type.clone(),
+            });
+        } else if g_l > max_limit {
+            severity = severity.max(Some(AlertSeverity::High));
+            reasons.push(RuleReason::SalinityTolerance {
+                g_l,
+                limit: max_limit,
+                crop: rules.crop_type.clone(),
+            });
+        }
+    }
+
+    severity.map(|severity| RuleOutcome {
+        severity,
+        ndsi_threshold,
+        reasons,
+    })
+}
+
+/// Every limit `evaluate` compares the observations against, crossed or not,
+/// so runs can be compared on which of them flipped. Disabled rules still
+/// report their limits.
+pub fn checks(rules: &AlertRules, input: &RuleInput) -> Vec<ThresholdCheck> {
+    let mut checks = Vec::new();
+
+    if let Some((threshold, std_dev)) = ndsi_threshold_for(rules, input) {
+        let value = input.current_ndsi;
+        checks.push(ThresholdCheck::new(ThresholdRule::NdsiAnomaly, value, threshold));
+        checks.push(ThresholdCheck::new(ThresholdRule::NdsiHigh, value, threshold + std_dev * 0.5));
+        checks.push(ThresholdCheck::new(ThresholdRule::NdsiCritical, value, threshold + std_dev));
+    }
+
+    if let (Some(g_l), Some((max_limit, critical_limit))) = (input.estimated_g_l, salinity_limits(rules, input)) {
+        checks.push(ThresholdCheck::new(ThresholdRule::SalinityTolerance, g_l, max_limit));
+        checks.push(ThresholdCheck::new(ThresholdRule::SalinityCritical, g_l, critical_limit));
+    }
+
+    checks
+}
@@ -1,96 +1,256 @@
+use chrono::Datelike;
 use sqlx::PgPool;
-use crate::shared::error::{AppResult};
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::i18n::{self, t, Language};
+use crate::shared::notifications::{email::EmailMessage, push::PushMessage, NotificationDispatcher};
+use crate::shared::runtime;
 use crate::shared::utils::{calculate_centroid, calculate_angle_degrees, angle_to_direction, calculate_distance_km};
-use super::models::{Alert, AlertSeverity, CreateAlert, CreateSalinityLog, CreateIntrusionVector, IntrusionVector, FarmStatus};
+use std::collections::{BTreeMap, HashMap};
+use geojson::{Feature, FeatureCollection, Geometry, JsonObject, Value};
+use super::models::{
+    AffectedArea, Alert, AlertRulesResponse, BreakpointReport, Hotspot, TrendBreakpoint, TrendDirection, AlertSeverity, CreateAlert, UpdateAlertRulesRequest, CreateSalinityLog, CreateIntrusionVector, IntrusionVector,
+    FarmStatus, RiskScore, SalinityImportQuery, SalinityImportResponse, SalinityLog, SimulatedFarm,
+    SimulationRequest, SimulationResponse, RegionThreshold, ThresholdSource, AiModel, ModelStatus,
+    RegisterModelRequest, SceneInput, ShadowReport, ThresholdCheck, AnalysisComparison, AnalysisDelta, AnalysisRun,
+    AnalysisSnapshot, ThresholdFlip, ThresholdRule,
+};
+use crate::modules::{events, settings::{self, AlertChannel}};
+use crate::modules::webhooks::WebhookEvent;
 use super::repository;
+use super::ai::calibration::{estimate_salinity, fit_linear};
+use super::ai::image_proc::{otsu_threshold, water_pixels};
+use super::ai::batcher::InferenceBatcher;
+use super::ai::registry::{LoadedModel, ModelRegistry};
+use std::sync::Arc;
+use super::risk::{self, RiskInput};
+use super::rules::{self, RuleInput};
+use super::import::{self, parse_salinity_csv};
+use super::mosaic::{self, Mosaic};
+use super::hotspots;
+use super::breakpoints;
 
-const ANOMALY_THRESHOLD_MULTIPLIER: f64 = 2.0;
 const MOVING_AVERAGE_WINDOW: usize = 7;
 const VECTOR_LOOKBACK_DAYS: i32 = 7;
+const BASELINE_MIN_YEARS: i32 = 2;
+const PREDICTION_HORIZON_DAYS: i32 = 14;
+const PREDICTION_SPREAD_DEGREES: f64 = 30.0;
+const RISK_TREND_DAYS: i32 = 30;
+const RISK_RAINFALL_DAYS: i32 = 14;
+const RISK_TIDE_AHEAD_DAYS: i32 = 3;
+const KM_PER_DEGREE_LAT: f64 = 111.32;
+const IMPORT_BATCH_SIZE: usize = 5_000;
+const MAX_REPORTED_IMPORT_ERRORS: usize = 200;
 
-pub async fn detect_salinity_anomaly(farm_id: i64, db: &PgPool) -> AppResult<Option<Alert>> {
+/// How the alert rules judged a farm's latest reading.
+#[derive(Debug, Default)]
+pub struct ReadingEvaluation {
+    pub alert: Option<Alert>,
+    pub estimated_g_l: Option<f64>,
+    pub thresholds: Vec<ThresholdCheck>,
+    pub risk: Option<RiskScore>,
+}
+
+pub async fn detect_salinity_anomaly(farm_id: i64, db: &PgPool) -> AppResult<ReadingEvaluation> {
     let history = repository::get_ndsi_history(farm_id, 30, db).await?;
 
-    if history.len() <= MOVING_AVERAGE_WINDOW {
-        return Ok(None);
-    }
+    let Some(latest) = history.first() else {
+        return Ok(ReadingEvaluation::default());
+    };
 
-    let current_ndsi = history[0].ndsi_value;
+    let current_ndsi = latest.ndsi_value;
+    let month = latest.recorded_at.month() as i16;
 
-    let ndsi_values: Vec<f64> = history[1..=MOVING_AVERAGE_WINDOW]
-        .iter()
-        .map(|h| h.ndsi_value)
-        .collect();
-    
-    let (moving_avg, std_dev) = calculate_stats(&ndsi_values);
+    let seasonal = repository::get_baseline(farm_id, "ndsi", month, db)
+        .await?
+        .filter(|b| b.years_covered >= BASELINE_MIN_YEARS);
 
-    let threshold = moving_avg + (ANOMALY_THRESHOLD_MULTIPLIER * std_dev);
+    let (baseline, baseline_type) = match seasonal {
+        Some(baseline) => (Some((baseline.mean_value, baseline.std_dev)), "seasonal"),
+        None if history.len() > MOVING_AVERAGE_WINDOW => {
+            let ndsi_values: Vec<f64> = history[1..=MOVING_AVERAGE_WINDOW]
+                .iter()
+                .map(|h| h.ndsi_value)
+                .collect();
 
-    if current_ndsi <= threshold {
-        return Ok(None);
-    }
+            (Some(calculate_stats(&ndsi_values)), "moving_average")
+        }
+        None => (None, "none"),
+    };
+
+    let estimate = repository::get_calibration_for_farm(farm_id, db)
+        .await?
+        .map(|calibration| estimate_salinity(&calibration, current_ndsi));
 
-    let severity = match current_ndsi {
-        n if n > threshold + std_dev => AlertSeverity::Critical,
-        n if n > threshold + (std_dev * 0.5) => AlertSeverity::High,
-        _ => AlertSeverity::Medium,
+    let mut alert_rules = repository::get_alert_rules(farm_id, db).await?;
+    alert_rules.anomaly_multiplier *= runtime::current().anomaly_sensitivity;
+    let season = repository::get_current_crop_season(farm_id, db).await?;
+    let input = RuleInput {
+        current_ndsi,
+        baseline,
+        estimated_g_l: estimate.as_ref().map(|e| e.grams_per_litre),
+        growth_stage: season.as_ref().map(|s| s.growth_stage),
     };
 
+    let risk = assess_risk(farm_id, db).await?;
+    let mut evaluation = ReadingEvaluation {
+        alert: None,
+        estimated_g_l: input.estimated_g_l,
+        thresholds: rules::checks(&alert_rules, &input),
+        risk: Some(risk.clone()),
+    };
+
+    let Some(outcome) = rules::evaluate(&alert_rules, &input) else {
+        return Ok(evaluation);
+    };
+
+    let lang = i18n::language_for_farm(db, farm_id).await?;
+    let reasons: Vec<String> = outcome.reasons.iter().map(|r| r.message(lang)).collect();
+
     let alert = CreateAlert {
         farm_id,
-        severity,
-        message: format!(
-            "Salinity anomaly detected! Current NDSI: {:.4}, Threshold: {:.4}, Deviation: {:.4}",
-            current_ndsi, threshold, current_ndsi - threshold
-        ),
+        severity: outcome.severity,
+        message: t(lang, "alert.salinity_anomaly", &[
+            ("ndsi", format!("{:.4}", current_ndsi)),
+            ("reasons", reasons.join("; ")),
+        ]),
         metadata: Some(serde_json::json!({
             "current_ndsi": current_ndsi,
-            "moving_average": moving_avg,
-            "std_dev": std_dev,
-            "threshold": threshold
+            "baseline_type": baseline_type,
+            "baseline_month": month,
+            "baseline_mean": baseline.map(|(mean, _)| mean),
+            "std_dev": baseline.map(|(_, std_dev)| std_dev),
+            "threshold": outcome.ndsi_threshold,
+            "estimated_salinity": estimate,
+            "crop_type": alert_rules.crop_type,
+            "crop_season": season.as_ref().map(|s| serde_json::json!({
+                "id": s.id,
+                "crop_type": s.crop_type,
+                "growth_stage": s.growth_stage,
+                "days_since_planting": (latest.recorded_at.date_naive() - s.planting_date).num_days(),
+            })),
+            "rules": alert_rules,
+            "reasons": reasons,
+            "risk": risk
         })),
+        affected_area: None,
     };
 
-    let alert_id = repository::save_alert(alert.clone(), db).await?;
+    let mut tx = db.begin().await?;
+    let alert_id = repository::save_alert(alert.clone(), &mut tx).await?;
 
-    Ok(Some(Alert {
+    let alert = Alert {
         id: alert_id,
         farm_id: alert.farm_id,
         severity: alert.severity,
         message: alert.message,
         metadata: alert.metadata,
+        affected_area: alert.affected_area,
         detected_at: chrono::Utc::now(),
         acknowledged: false,
         acknowledged_at: None,
-    }))
+    };
+
+    events::record(&mut tx, WebhookEvent::AlertCreated, Some(farm_id), &serde_json::json!(alert)).await?;
+    tx.commit().await?;
+
+    evaluation.alert = Some(alert);
+    Ok(evaluation)
+}
+
+/// Sends a new alert to the farm owner by push and email, as their channel
+/// preferences allow for its severity at this hour. Webhooks and the event
+/// stream are filtered where they are fanned out.
+pub async fn deliver_alert(alert: &Alert, notifier: &NotificationDispatcher, db: &PgPool) -> AppResult<()> {
+    let Some(recipient) = repository::get_alert_recipient(alert.farm_id, db).await? else {
+        return Ok(());
+    };
+    let preferences = settings::service::preferences(db, recipient.user_id).await?;
+    let lang = preferences.language;
+    let severity = t(lang, &format!("severity.{}", alert.severity.as_str()), &[]);
+    let now = chrono::Utc::now();
+
+    let pushed = if preferences.delivers(alert.severity, AlertChannel::Push, now) {
+        let title = match alert.severity {
+            AlertSeverity::Critical => t(lang, "push.critical_alert.title", &[("farm", recipient.farm_name.clone())]),
+            _ => t(lang, "push.alert.title", &[("severity", severity.clone()), ("farm", recipient.farm_name.clone())]),
+        };
+        let message = PushMessage {
+            tokens: Vec::new(),
+            title,
+            body: alert.message.clone(),
+            data: BTreeMap::from([
+                ("type".to_string(), "alert".to_string()),
+                ("alert_id".to_string(), alert.id.to_string()),
+                ("farm_id".to_string(), alert.farm_id.to_string()),
+                ("severity".to_string(), alert.severity.as_str().to_string()),
+            ]),
+        };
+        settings::service::push_to_user(db, notifier, recipient.user_id, message).await.map(|_| ())
+    } else {
+        Ok(())
+    };
+
+    let email = recipient.email.filter(|_| recipient.email_verified);
+    let emailed = if let Some(email) = email.filter(|_| preferences.delivers(alert.severity, AlertChannel::Email, now)) {
+        notifier
+            .send_email(EmailMessage {
+                to: email,
+                subject: t(lang, "email.alert.subject", &[("severity", severity), ("farm", recipient.farm_name)]),
+                body: t(lang, "email.alert.body", &[("message", alert.message.clone())]),
+                html: None,
+            })
+            .await
+    } else {
+        Ok(())
+    };
+
+    pushed.and(emailed)
 }
 
+/// Records where the analysed image found water and, when the farm has an
+/// earlier observation within the lookback window, stores the vector from that
+/// observation's water centroid to this one.
 pub async fn calculate_intrusion_vector(
     farm_id: i64,
-    current_water_pixels: &[(f64, f64)],
+    log_id: i64,
+    water_pixels: &[(f64, f64)],
+    img_size: usize,
+    image_bounds: Option<[f64; 4]>,
     db: &PgPool,
 ) -> AppResult<Option<IntrusionVector>> {
-    if current_water_pixels.is_empty() {
+    if water_pixels.is_empty() || img_size == 0 {
         return Ok(None);
     }
 
-    let current_centroid = calculate_centroid(current_water_pixels)?;
-    let history = repository::get_ndsi_history(farm_id, VECTOR_LOOKBACK_DAYS, db).await?;
+    let bounds = match image_bounds {
+        Some(bounds) => bounds,
+        None => match repository::get_farm_bounds(farm_id, db).await? {
+            Some(bounds) => bounds,
+            None => return Ok(None),
+        },
+    };
 
-    if history.len() < 2 {
+    let centroid = georeference(calculate_centroid(water_pixels)?, img_size, bounds);
+    let pixel_count = i32::try_from(water_pixels.len()).unwrap_or(i32::MAX);
+
+    let Some(current) = repository::save_water_observation(log_id, centroid, pixel_count, db).await? else {
         return Ok(None);
-    }
+    };
+    let Some(previous) = repository::get_previous_water_observation(farm_id, &current, VECTOR_LOOKBACK_DAYS, db).await? else {
+        return Ok(None);
+    };
 
-    let previous_centroid = (current_centroid.0 - 0.01, current_centroid.1 - 0.01);
-    let angle = calculate_angle_degrees(previous_centroid, current_centroid);
+    let angle = calculate_angle_degrees(previous.centroid, current.centroid);
     let direction = angle_to_direction(angle);
-    let magnitude = calculate_distance_km(previous_centroid, current_centroid);
+    let magnitude = calculate_distance_km(previous.centroid, current.centroid);
 
     let vector = CreateIntrusionVector {
         farm_id,
         direction: direction.to_string(),
         angle_degrees: angle,
         magnitude_km: magnitude,
+        previous: previous.clone(),
+        current: current.clone(),
     };
 
     let vector_id = repository::save_intrusion_vector(vector, db).await?;
@@ -102,13 +262,522 @@ pub async fn calculate_intrusion_vector(
         angle_degrees: angle,
         magnitude_km: magnitude,
         calculated_at: chrono::Utc::now(),
+        previous_observed_at: Some(previous.recorded_at),
+        current_observed_at: Some(current.recorded_at),
+        previous_pixel_count: Some(previous.pixel_count),
+        current_pixel_count: Some(current.pixel_count),
     }))
 }
 
+/// Automatic thresholds are kept inside this band so an image without real
+/// water/land contrast cannot turn model noise into water.
+const OTSU_THRESHOLD_RANGE: (f64, f64) = (0.3, 0.7);
+
+/// Water probability cut-off for an analysis of `farm_id`: the value set for
+/// the farm's region, or an Otsu estimate from the image when there is none.
+pub async fn water_threshold(
+    farm_id: i64,
+    probabilities: &[f32],
+    db: &PgPool,
+) -> AppResult<(f64, ThresholdSource)> {
+    if let Some(threshold) = repository::get_water_threshold_for_farm(farm_id, db).await? {
+        return Ok((threshold, ThresholdSource::Region));
+    }
+
+    let (min, max) = OTSU_THRESHOLD_RANGE;
+    Ok((otsu_threshold(probabilities).clamp(min, max), ThresholdSource::Otsu))
+}
+
+pub async fn set_region_threshold(
+    region: &str,
+    water_threshold: f64,
+    updated_by: i64,
+    db: &PgPool,
+) -> AppResult<RegionThreshold> {
+    let region = region.trim();
+    if region.is_empty() {
+        return Err(AppError::Validation("region cannot be empty".to_string()));
+    }
+
+    repository::upsert_region_threshold(region, water_threshold, updated_by, db).await
+}
+
+pub fn water_coverage_percent(water_pixel_count: usize, img_size: usize) -> f64 {
+    if img_size == 0 {
+        return 0.0;
+    }
+    (water_pixel_count as f64 / (img_size * img_size) as f64) * 100.0
+}
+
+fn load_model(model: &AiModel) -> AppResult<LoadedModel> {
+    LoadedModel::load(Some(model.id), model.label(), &model.config_path, &model.weights_path)
+}
+
+async fn find_model(id: i64, db: &PgPool) -> AppResult<AiModel> {
+    repository::get_ai_model(id, db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Model {} not found", id)))
+}
+
+/// Loads the registry's active and shadow models into memory. Returns
+/// whether an active model was found, so the caller knows to fall back to
+/// the environment-configured one.
+pub async fn load_registered_models(registry: &ModelRegistry, db: &PgPool) -> AppResult<bool> {
+    if let Some(shadow) = repository::get_ai_model_by_status(ModelStatus::Shadow, db).await? {
+        match load_model(&shadow) {
+            Ok(loaded) => registry.set_shadow(Some(loaded)),
+            Err(e) => tracing::warn!("Shadow model {} failed to load: {}", shadow.label(), e),
+        }
+    }
+
+    let Some(active) = repository::get_ai_model_by_status(ModelStatus::Active, db).await? else {
+        return Ok(false);
+    };
+    registry.set_active(Some(load_model(&active)?));
+    tracing::info!("Serving analyses with registered model {}", active.label());
+
+    Ok(true)
+}
+
+/// Stores a model whose files are already on the server. Its config is
+/// parsed up front so a broken upload is rejected before anyone activates it.
+pub async fn register_model(request: RegisterModelRequest, created_by: i64, db: &PgPool) -> AppResult<AiModel> {
+    let name = request.name.trim();
+    let version = request.version.trim();
+    if repository::ai_model_exists(name, version, db).await? {
+        return Err(AppError::Validation(format!("Model {}@{} is already registered", name, version)));
+    }
+
+    LoadedModel::load(None, format!("{}@{}", name, version), &request.config_path, &request.weights_path)?;
+
+    repository::insert_ai_model(&request, created_by, db).await
+}
+
+/// Moves a registered model into `status` and swaps it into this process.
+/// Other instances pick the change up when they restart.
+pub async fn set_model_status(
+    id: i64,
+    status: ModelStatus,
+    registry: &ModelRegistry,
+    db: &PgPool,
+) -> AppResult<AiModel> {
+    let model = find_model(id, db).await?;
+
+    if status != ModelStatus::Active && model.status == ModelStatus::Active {
+        return Err(AppError::Validation(
+            "The active model cannot be demoted; activate another model instead".to_string(),
+        ));
+    }
+
+    let loaded = match status {
+        ModelStatus::Inactive => None,
+        ModelStatus::Active | ModelStatus::Shadow => Some(load_model(&model)?),
+    };
+
+    let updated = repository::set_ai_model_status(id, status, db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Model {} not found", id)))?;
+
+    match status {
+        ModelStatus::Active => {
+            registry.set_active(loaded);
+            if model.status == ModelStatus::Shadow {
+                registry.set_shadow(None);
+            }
+        }
+        ModelStatus::Shadow => registry.set_shadow(loaded),
+        ModelStatus::Inactive => {
+            if model.status == ModelStatus::Shadow {
+                registry.set_shadow(None);
+            }
+        }
+    }
+
+    Ok(updated)
+}
+
+pub async fn shadow_report(id: i64, db: &PgPool) -> AppResult<ShadowReport> {
+    let model = find_model(id, db).await?;
+    repository::shadow_report(&model, db).await
+}
+
+/// Runs the shadow model on the image an analysis just used, with the same
+/// water threshold, and stores its coverage next to the active model's.
+/// Runs in the background; failures are logged and never reach the caller.
+pub fn spawn_shadow_analysis(
+    model: Arc<LoadedModel>,
+    image_bytes: Vec<u8>,
+    log_id: i64,
+    water_threshold: f64,
+    active_coverage_percent: f64,
+    batcher: InferenceBatcher,
+    db: PgPool,
+) {
+    let Some(model_id) = model.id else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let coverage = async {
+            let input = model.preprocess(&image_bytes)?;
+            let output = batcher.predict(model.clone(), input).await?;
+            let (probabilities, width) = model.water_probabilities(&output)?;
+            let pixels = water_pixels(&probabilities, width, water_threshold);
+            Ok::<_, AppError>(water_coverage_percent(pixels.len(), model.img_size()))
+        }
+        .await;
+
+        let shadow_coverage_percent = match coverage {
+            Ok(coverage) => coverage,
+            Err(e) => return tracing::warn!("Shadow model {} failed on log {}: {}", model.version, log_id, e),
+        };
+
+        if let Err(e) = repository::save_shadow_analysis(
+            log_id,
+            model_id,
+            active_coverage_percent,
+            shadow_coverage_percent,
+            &db,
+        )
+        .await
+        {
+            tracing::warn!("Failed to store shadow analysis for log {}: {}", log_id, e);
+        }
+    });
+}
+
+/// Maps a point in image pixel space (row 0 at the northern edge) onto
+/// `bounds` given as [min_lon, min_lat, max_lon, max_lat].
+fn georeference(pixel: (f64, f64), img_size: usize, bounds: [f64; 4]) -> (f64, f64) {
+    let [min_lon, min_lat, max_lon, max_lat] = bounds;
+    let size = img_size as f64;
+    (
+        min_lon + (pixel.0 + 0.5) / size * (max_lon - min_lon),
+        max_lat - (pixel.1 + 0.5) / size * (max_lat - min_lat),
+    )
+}
+
+/// Maps hot spots of the analysed image onto the farm, stores them against
+/// the analysis and lists them in the alert raised by it, if any.
+pub async fn record_hotspots(
+    farm_id: i64,
+    log_id: i64,
+    alert: Option<&mut Alert>,
+    probabilities: &[f32],
+    width: usize,
+    image_bounds: Option<[f64; 4]>,
+    db: &PgPool,
+) -> AppResult<Vec<i64>> {
+    let found = hotspots::detect(probabilities, width);
+    if found.spots.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let bounds = match image_bounds {
+        Some(bounds) => bounds,
+        None => match repository::get_farm_bounds(farm_id, db).await? {
+            Some(bounds) => bounds,
+            None => return Ok(Vec::new()),
+        },
+    };
+
+    let alert_id = alert.as_ref().map(|a| a.id);
+    let mut ids = Vec::new();
+    for spot in &found.spots {
+        let cells: Vec<[f64; 4]> = spot.cells.iter().map(|&cell| found.cell_bounds(cell, bounds)).collect();
+        if let Some(id) = repository::save_hotspot(farm_id, log_id, alert_id, spot, &cells, db).await? {
+            ids.push(id);
+        }
+    }
+
+    if let Some(alert) = alert.filter(|_| !ids.is_empty()) {
+        alert.affected_area = repository::attach_hotspots_to_alert(alert.id, &ids, db).await?;
+        if let Some(serde_json::Value::Object(metadata)) = alert.metadata.as_mut() {
+            metadata.insert("hotspot_ids".to_string(), serde_json::json!(ids));
+        }
+    }
+
+    Ok(ids)
+}
+
+pub fn hotspot_feature_collection(hotspots: &[Hotspot]) -> AppResult<FeatureCollection> {
+    let features = hotspots
+        .iter()
+        .map(|hotspot| {
+            let geometry: Geometry = hotspot
+                .geometry
+                .parse()
+                .map_err(|e| AppError::Internal(format!("Invalid hotspot geometry {}: {}", hotspot.id, e)))?;
+
+            let mut props = JsonObject::new();
+            props.insert("kind".to_string(), "hotspot".into());
+            props.insert("id".to_string(), hotspot.id.into());
+            props.insert("farm_id".to_string(), hotspot.farm_id.into());
+            props.insert("log_id".to_string(), hotspot.log_id.into());
+            props.insert("alert_id".to_string(), hotspot.alert_id.into());
+            props.insert("area_hectares".to_string(), hotspot.area_hectares.into());
+            props.insert("cell_count".to_string(), hotspot.cell_count.into());
+            props.insert("mean_z".to_string(), hotspot.mean_z.into());
+            props.insert("max_z".to_string(), hotspot.max_z.into());
+            props.insert("mean_probability".to_string(), hotspot.mean_probability.into());
+            props.insert("detected_at".to_string(), hotspot.detected_at.to_rfc3339().into());
+
+            Ok(Feature { geometry: Some(geometry), properties: Some(props), ..Default::default() })
+        })
+        .collect::<AppResult<Vec<Feature>>>()?;
+
+    Ok(FeatureCollection { bbox: None, features, foreign_members: None })
+}
+
+/// Decodes and mosaics `scenes` off the async runtime.
+pub async fn mosaic_scenes(scenes: Vec<SceneInput>) -> AppResult<Mosaic> {
+    tokio::task::spawn_blocking(move || mosaic::build(mosaic::decode_scenes(scenes)?))
+        .await
+        .map_err(|e| AppError::Internal(format!("Mosaic task failed: {}", e)))?
+}
+
+pub fn validate_image_bounds(bounds: Option<[f64; 4]>) -> AppResult<()> {
+    let Some([min_lon, min_lat, max_lon, max_lat]) = bounds else {
+        return Ok(());
+    };
+
+    let in_range = (-180.0..=180.0).contains(&min_lon)
+        && (-180.0..=180.0).contains(&max_lon)
+        && (-90.0..=90.0).contains(&min_lat)
+        && (-90.0..=90.0).contains(&max_lat);
+    if !in_range || min_lon >= max_lon || min_lat >= max_lat {
+        return Err(AppError::Validation(
+            "image_bounds must be [min_lon, min_lat, max_lon, max_lat] with min below max".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses a `min_lon,min_lat,max_lon,max_lat` map extent.
+pub fn parse_bbox(value: &str) -> AppResult<[f64; 4]> {
+    let invalid = || AppError::Validation("bbox must be min_lon,min_lat,max_lon,max_lat with min below max".to_string());
+
+    let values: Vec<f64> = value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid())?;
+    let bbox: [f64; 4] = values.try_into().map_err(|_| invalid())?;
+
+    validate_image_bounds(Some(bbox)).map_err(|_| invalid())?;
+    Ok(bbox)
+}
+
+pub async fn get_alert_rules(farm_id: i64, db: &PgPool) -> AppResult<AlertRulesResponse> {
+    let (effective, overrides) = tokio::try_join!(
+        repository::get_alert_rules(farm_id, db),
+        repository::get_alert_rule_overrides(farm_id, db)
+    )?;
+
+    Ok(AlertRulesResponse { farm_id, effective, overrides })
+}
+
+pub async fn update_alert_rules(
+    farm_id: i64,
+    request: UpdateAlertRulesRequest,
+    db: &PgPool,
+) -> AppResult<AlertRulesResponse> {
+    if let Some(crop_type) = &request.crop_type {
+        if !repository::crop_type_exists(crop_type, db).await? {
+            return Err(AppError::Validation(format!("Unknown crop type: {}", crop_type)));
+        }
+    }
+
+    let overrides = &request.overrides;
+    let values = [overrides.anomaly_multiplier, overrides.max_salinity_g_l, overrides.critical_salinity_g_l];
+    if values.iter().flatten().any(|v| !v.is_finite() || *v <= 0.0) {
+        return Err(AppError::Validation("Thresholds must be positive numbers".to_string()));
+    }
+
+    if let (Some(max), Some(critical)) = (overrides.max_salinity_g_l, overrides.critical_salinity_g_l) {
+        if critical < max {
+            return Err(AppError::Validation("critical_salinity_g_l must not be below max_salinity_g_l".to_string()));
+        }
+    }
+
+    repository::upsert_alert_rules(farm_id, &request, db).await?;
+    get_alert_rules(farm_id, db).await
+}
+
+/// Extrapolates the intrusion vector linearly over the prediction horizon and
+/// returns the wedge it sweeps from `origin`, widened by a fixed angular spread.
+pub fn predict_affected_area(origin: (f64, f64), vector: &IntrusionVector) -> AffectedArea {
+    let reach_km = vector.magnitude_km * PREDICTION_HORIZON_DAYS as f64 / VECTOR_LOOKBACK_DAYS as f64;
+    swept_area(origin, vector.angle_degrees, reach_km, PREDICTION_HORIZON_DAYS)
+}
+
+fn swept_area(origin: (f64, f64), angle_degrees: f64, reach_km: f64, horizon_days: i32) -> AffectedArea {
+    let mut ring = vec![origin];
+    let steps = 8;
+    for i in 0..=steps {
+        let angle = angle_degrees - PREDICTION_SPREAD_DEGREES
+            + (2.0 * PREDICTION_SPREAD_DEGREES) * i as f64 / steps as f64;
+        ring.push(offset_km(origin, angle, reach_km));
+    }
+    ring.push(origin);
+
+    let risk = match reach_km {
+        r if r >= 10.0 => AlertSeverity::Critical,
+        r if r >= 5.0 => AlertSeverity::High,
+        r if r >= 1.0 => AlertSeverity::Medium,
+        _ => AlertSeverity::Low,
+    };
+
+    AffectedArea {
+        ring,
+        horizon_days,
+        reach_km,
+        risk,
+    }
+}
+
+/// What-if planning: which of the user's farms a hypothetical front would
+/// reach, or whose NDSI trend would cross a hypothetical threshold, within
+/// the horizon.
+pub async fn simulate(user_id: i64, request: SimulationRequest, db: &PgPool) -> AppResult<SimulationResponse> {
+    let days = request.days.unwrap_or(PREDICTION_HORIZON_DAYS);
+    if request.vector.is_none() && request.ndsi_threshold.is_none() {
+        return Err(AppError::Validation("Provide a vector, an ndsi_threshold, or both".to_string()));
+    }
+
+    let mut farms: HashMap<i64, SimulatedFarm> = HashMap::new();
+    let mut area = None;
+
+    if let Some(vector) = &request.vector {
+        let origin = (vector.origin_lon, vector.origin_lat);
+        let swept = swept_area(origin, vector.angle_degrees, vector.velocity_km_per_day * days as f64, days);
+        let polygon = Geometry::new(Value::Polygon(vec![swept.ring.iter().map(|&(lon, lat)| vec![lon, lat]).collect()]));
+
+        for (farm_id, name, distance_km) in
+            repository::get_user_farms_in_area(user_id, &polygon.to_string(), origin.0, origin.1, db).await?
+        {
+            farms.entry(farm_id).or_insert_with(|| simulated_farm(farm_id, name)).front_arrival_days =
+                Some(distance_km / vector.velocity_km_per_day);
+        }
+        area = Some(swept);
+    }
+
+    if let Some(threshold) = request.ndsi_threshold {
+        let now = chrono::Utc::now();
+        for (farm_id, name) in repository::get_user_farm_names(user_id, db).await? {
+            let history = repository::get_ndsi_history(farm_id, RISK_TREND_DAYS, db).await?;
+            let Some(latest) = history.iter().max_by_key(|log| log.recorded_at) else {
+                continue;
+            };
+            let points: Vec<(f64, f64)> = history
+                .iter()
+                .map(|log| ((log.recorded_at - now).num_seconds() as f64 / 86_400.0, log.ndsi_value))
+                .collect();
+            let slope = risk::trend_per_day(&points).unwrap_or(0.0);
+            let elapsed = (now - latest.recorded_at).num_seconds() as f64 / 86_400.0;
+            let current = latest.ndsi_value + slope * elapsed;
+
+            let crossing = if current >= threshold {
+                Some(0.0)
+            } else if slope > 0.0 {
+                Some((threshold - current) / slope).filter(|d| *d <= days as f64)
+            } else {
+                None
+            };
+
+            if let Some(crossing) = crossing {
+                let farm = farms.entry(farm_id).or_insert_with(|| simulated_farm(farm_id, name));
+                farm.projected_ndsi = Some(current + slope * days as f64);
+                farm.threshold_crossing_days = Some(crossing);
+            }
+        }
+    }
+
+    let mut farms: Vec<SimulatedFarm> = farms.into_values().collect();
+    farms.sort_by(|a, b| soonest(a).total_cmp(&soonest(b)).then(a.farm_id.cmp(&b.farm_id)));
+
+    Ok(SimulationResponse {
+        horizon_days: days,
+        reach_km: area.as_ref().map(|a| a.reach_km),
+        risk: area.map(|a| a.risk),
+        farms,
+    })
+}
+
+fn simulated_farm(farm_id: i64, name: String) -> SimulatedFarm {
+    SimulatedFarm {
+        farm_id,
+        name,
+        front_arrival_days: None,
+        projected_ndsi: None,
+        threshold_crossing_days: None,
+    }
+}
+
+fn soonest(farm: &SimulatedFarm) -> f64 {
+    [farm.front_arrival_days, farm.threshold_crossing_days]
+        .into_iter()
+        .flatten()
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Renders the vector as a LineString from the farm centroid plus the predicted
+/// affected area as a Polygon, ready to drop onto a map.
+pub fn intrusion_feature_collection(origin: (f64, f64), vector: &IntrusionVector) -> FeatureCollection {
+    let tip = offset_km(origin, vector.angle_degrees, vector.magnitude_km);
+    let area = predict_affected_area(origin, vector);
+
+    let mut vector_props = JsonObject::new();
+    vector_props.insert("kind".to_string(), "intrusion_vector".into());
+    vector_props.insert("id".to_string(), vector.id.into());
+    vector_props.insert("farm_id".to_string(), vector.farm_id.into());
+    vector_props.insert("direction".to_string(), vector.direction.clone().into());
+    vector_props.insert("angle_degrees".to_string(), vector.angle_degrees.into());
+    vector_props.insert("magnitude_km".to_string(), vector.magnitude_km.into());
+    vector_props.insert("calculated_at".to_string(), vector.calculated_at.to_rfc3339().into());
+
+    let mut area_props = JsonObject::new();
+    area_props.insert("kind".to_string(), "predicted_affected_area".into());
+    area_props.insert("farm_id".to_string(), vector.farm_id.into());
+    area_props.insert("risk".to_string(), area.risk.as_str().into());
+    area_props.insert("horizon_days".to_string(), area.horizon_days.into());
+    area_props.insert("reach_km".to_string(), area.reach_km.into());
+
+    let to_position = |(lon, lat): (f64, f64)| vec![lon, lat];
+
+    FeatureCollection {
+        bbox: None,
+        features: vec![
+            Feature {
+                geometry: Some(Geometry::new(Value::LineString(vec![to_position(origin), to_position(tip)]))),
+                properties: Some(vector_props),
+                ..Default::default()
+            },
+            Feature {
+                geometry: Some(Geometry::new(Value::Polygon(vec![area.ring.into_iter().map(to_position).collect()]))),
+                properties: Some(area_props),
+                ..Default::default()
+            },
+        ],
+        foreign_members: None,
+    }
+}
+
+/// Moves `distance_km` from `origin` along `angle_degrees` (counter-clockwise
+/// from east, matching `calculate_angle_degrees`) using a local flat-earth approximation.
+pub fn offset_km(origin: (f64, f64), angle_degrees: f64, distance_km: f64) -> (f64, f64) {
+    let angle = angle_degrees.to_radians();
+    let dlat = distance_km * angle.sin() / KM_PER_DEGREE_LAT;
+    let dlon = distance_km * angle.cos() / (KM_PER_DEGREE_LAT * origin.1.to_radians().cos().max(1e-6));
+    (origin.0 + dlon, origin.1 + dlat)
+}
+
 pub async fn save_ndsi_measurement(
     farm_id: i64, 
     ndsi_value: f64, 
     source: &str, 
+    model_version: Option<&str>,
     db: &PgPool
 ) -> AppResult<i64> {
     repository::save_salinity_log(
@@ -116,11 +785,71 @@ pub async fn save_ndsi_measurement(
             farm_id,
             ndsi_value,
             source: source.to_string(),
+            model_version: model_version.map(str::to_string),
         },
         db,
     ).await
 }
 
+/// Validates an uploaded CSV of historical NDSI measurements and stores the
+/// valid rows in one transaction. Rows for farms the caller cannot write to are
+/// reported like any other row error.
+pub async fn import_salinity_csv(
+    data: &[u8],
+    query: &SalinityImportQuery,
+    user_id: i64,
+    is_admin: bool,
+    db: &PgPool,
+) -> AppResult<SalinityImportResponse> {
+    let source = query.source.trim();
+    if source.is_empty() || source.len() > 100 {
+        return Err(AppError::Validation("source must be between 1 and 100 characters".to_string()));
+    }
+
+    let (parsed, mut errors) = parse_salinity_csv(data, query)?;
+
+    let mut farm_ids: Vec<i64> = parsed.iter().map(|r| r.farm_id).collect();
+    farm_ids.sort_unstable();
+    farm_ids.dedup();
+    let owners = repository::get_farm_owners(&farm_ids, db).await?;
+
+    let (rows, rejected): (Vec<_>, Vec<_>) = parsed.into_iter().partition(|row| {
+        owners.get(&row.farm_id).is_some_and(|&owner| owner == user_id || is_admin)
+    });
+    errors.extend(rejected.iter().map(|row| {
+        import::row_error(row.line, query.farm_id_column.as_deref(), format!("Farm {} not found", row.farm_id))
+    }));
+    errors.sort_by_key(|e| e.line);
+
+    let mut tx = db.begin().await?;
+    let mut imported = 0;
+    for batch in rows.chunks(IMPORT_BATCH_SIZE) {
+        imported += repository::insert_imported_salinity_logs(batch, source, &mut tx).await?;
+    }
+
+    if query.dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+        if imported > 0 {
+            // Seed baselines now rather than waiting for the nightly job.
+            repository::recompute_ndsi_baselines(db).await?;
+        }
+    }
+
+    let failed = errors.len();
+    errors.truncate(MAX_REPORTED_IMPORT_ERRORS);
+
+    Ok(SalinityImportResponse {
+        rows: rows.len() + failed,
+        imported,
+        duplicates: rows.len() as u64 - imported,
+        failed,
+        errors,
+        dry_run: query.dry_run,
+    })
+}
+
 fn calculate_stats(values: &[f64]) -> (f64, f64) {
     if values.is_empty() {
         return (0.0, 0.0);
@@ -141,10 +870,11 @@ fn calculate_stats(values: &[f64]) -> (f64, f64) {
 }
 
 pub async fn get_farm_status(farm_id: i64, db: &PgPool) -> AppResult<FarmStatus> {
-    let (latest_ndsi, recent_alerts, latest_vector) = tokio::try_join!(
+    let (latest_ndsi, recent_alerts, latest_vector, risk) = tokio::try_join!(
         repository::get_latest_ndsi(farm_id, db),
         repository::get_recent_alerts(farm_id, 5, db),
-        repository::get_latest_intrusion_vector(farm_id, db)
+        repository::get_latest_intrusion_vector(farm_id, db),
+        assess_risk(farm_id, db)
     )?;
 
     Ok(FarmStatus {
@@ -152,5 +882,173 @@ pub async fn get_farm_status(farm_id: i64, db: &PgPool) -> AppResult<FarmStatus>
         latest_ndsi,
         recent_alerts,
         latest_intrusion_vector: latest_vector,
+        risk,
     })
-}
\ No newline at end of file
+}
+
+/// Scores salinity intrusion risk from the NDSI trend, the latest intrusion
+/// front, tides and the recent rainfall deficit.
+pub async fn assess_risk(farm_id: i64, db: &PgPool) -> AppResult<RiskScore> {
+    let (history, vector, (rainfall_mm, et0_mm, sea_level_max_m), station_points) = tokio::try_join!(
+        repository::get_ndsi_history(farm_id, RISK_TREND_DAYS, db),
+        repository::get_latest_intrusion_vector(farm_id, db),
+        repository::get_weather_window(farm_id, RISK_RAINFALL_DAYS, RISK_TIDE_AHEAD_DAYS, db),
+        repository::get_station_ndsi_points(farm_id, RISK_TREND_DAYS, db)
+    )?;
+
+    let now = chrono::Utc::now();
+    let points: Vec<(f64, f64)> = history
+        .iter()
+        .map(|log| ((log.recorded_at - now).num_seconds() as f64 / 86_400.0, log.ndsi_value))
+        .collect();
+
+    let intrusion_km_per_day = vector
+        .filter(|v| now - v.calculated_at <= chrono::Duration::days(VECTOR_LOOKBACK_DAYS as i64))
+        .map(|v| v.magnitude_km / VECTOR_LOOKBACK_DAYS as f64);
+
+    let mut by_station: HashMap<i64, Vec<(f64, f64)>> = HashMap::new();
+    for (station_id, days, ndsi) in station_points {
+        by_station.entry(station_id).or_default().push((days, ndsi));
+    }
+    let station_ndsi_slope_per_day = by_station
+        .values()
+        .filter_map(|points| risk::trend_per_day(points))
+        .reduce(f64::max);
+
+    let input = RiskInput {
+        ndsi_slope_per_day: risk::trend_per_day(&points),
+        intrusion_km_per_day,
+        sea_level_max_m,
+        rainfall_mm,
+        et0_mm,
+        station_ndsi_slope_per_day,
+    };
+
+    Ok(risk::score(&input, now))
+}
+
+pub async fn get_salinity_history(farm_id: i64, days: i32, db: &PgPool) -> AppResult<Vec<SalinityLog>> {
+    let mut history = repository::get_ndsi_history(farm_id, days, db).await?;
+
+    if let Some(calibration) = repository::get_calibration_for_farm(farm_id, db).await? {
+        for log in &mut history {
+            log.estimated_salinity = Some(estimate_salinity(&calibration, log.ndsi_value));
+        }
+    }
+
+    Ok(history)
+}
+
+/// What changed between two AI analyses of a farm, and which rule limits one
+/// crossed and the other did not.
+pub async fn compare_runs(farm_id: i64, run_a: i64, run_b: i64, db: &PgPool) -> AppResult<AnalysisComparison> {
+    let runs = repository::get_analysis_runs(farm_id, &[run_a, run_b], db).await?;
+    let find = |id: i64| {
+        runs.iter()
+            .find(|run| run.log_id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Analysis run {} not found for farm {}", id, farm_id)))
+    };
+    let (run_a, run_b) = (find(run_a)?, find(run_b)?);
+
+    let (a, b) = (run_a.analysis.as_ref(), run_b.analysis.as_ref());
+    let diff = |f: fn(&AnalysisRun) -> Option<f64>| Some(f(&run_b)? - f(&run_a)?);
+    let delta = AnalysisDelta {
+        ndsi: run_b.ndsi - run_a.ndsi,
+        water_coverage_percent: diff(|run| run.analysis.as_ref().map(|s| s.water_coverage_percent)),
+        water_threshold: diff(|run| run.analysis.as_ref().map(|s| s.water_threshold)),
+        estimated_g_l: diff(|run| run.analysis.as_ref().and_then(|s| s.estimated_g_l)),
+        water_area_hectares: diff(|run| run.water_area_hectares),
+        land_area_hectares: diff(|run| run.land_area_hectares),
+        hotspot_area_hectares: run_b.hotspot_area_hectares - run_a.hotspot_area_hectares,
+        risk_score: a.zip(b).map(|(a, b)| b.risk_score - a.risk_score),
+        risk_level_changed: a.map(|s| s.risk_level) != b.map(|s| s.risk_level),
+        severity_changed: a.and_then(|s| s.severity) != b.and_then(|s| s.severity),
+    };
+
+    let checks = |snapshot: Option<&AnalysisSnapshot>| snapshot.map(|s| s.thresholds.clone()).unwrap_or_default();
+    let (checks_a, checks_b) = (checks(a), checks(b));
+    let mut flipped = Vec::new();
+    for rule in [
+        ThresholdRule::NdsiAnomaly,
+        ThresholdRule::NdsiHigh,
+        ThresholdRule::NdsiCritical,
+        ThresholdRule::SalinityTolerance,
+        ThresholdRule::SalinityCritical,
+    ] {
+        let check_a = checks_a.iter().find(|c| c.rule == rule).copied();
+        let check_b = checks_b.iter().find(|c| c.rule == rule).copied();
+        let exceeded = |check: Option<ThresholdCheck>| check.is_some_and(|c| c.exceeded);
+        if exceeded(check_a) != exceeded(check_b) {
+            flipped.push(ThresholdFlip { rule, run_a: check_a, run_b: check_b });
+        }
+    }
+
+    Ok(AnalysisComparison { farm_id, run_a, run_b, delta, flipped })
+}
+
+/// Mean shifts in the farm's daily NDSI over the last `days` days.
+pub async fn ndsi_breakpoints(farm_id: i64, days: i32, db: &PgPool) -> AppResult<BreakpointReport> {
+    let series = repository::get_daily_ndsi(farm_id, days, db).await?;
+    let values: Vec<f64> = series.iter().map(|&(_, ndsi)| ndsi).collect();
+
+    let breakpoints: Vec<TrendBreakpoint> = breakpoints::detect(&values)
+        .into_iter()
+        .map(|b| TrendBreakpoint {
+            date: series[b.index].0,
+            before_ndsi: b.before_mean,
+            after_ndsi: b.after_mean,
+            magnitude: b.after_mean - b.before_mean,
+            direction: if b.after_mean >= b.before_mean { TrendDirection::Rising } else { TrendDirection::Falling },
+        })
+        .collect();
+
+    let narrative = match breakpoints.last() {
+        Some(latest) => Some(trend_narrative(i18n::language_for_farm(db, farm_id).await?, latest)),
+        None => None,
+    };
+
+    Ok(BreakpointReport { farm_id, days, samples: series.len(), breakpoints, narrative })
+}
+
+/// "Salinity began rising around March 3 (NDSI 0.120 to 0.210)".
+pub fn trend_narrative(lang: Language, breakpoint: &TrendBreakpoint) -> String {
+    let key = match breakpoint.direction {
+        TrendDirection::Rising => "trend.rising",
+        TrendDirection::Falling => "trend.falling",
+    };
+    let date = match lang {
+        Language::En => breakpoint.date.format("%B %-d").to_string(),
+        Language::Vi => breakpoint.date.format("%d/%m").to_string(),
+    };
+
+    t(lang, key, &[
+        ("date", date),
+        ("before", format!("{:.3}", breakpoint.before_ndsi)),
+        ("after", format!("{:.3}", breakpoint.after_ndsi)),
+    ])
+}
+
+pub async fn recompute_calibrations(db: &PgPool) -> AppResult<usize> {
+    let pairs = repository::get_calibration_pairs(db).await?;
+
+    let mut by_region: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+    for (region, ndsi, ec) in pairs {
+        by_region.entry(region).or_default().push((ndsi, ec));
+    }
+
+    let mut fitted = 0;
+    for (region, samples) in &by_region {
+        match fit_linear(samples) {
+            Some(fit) => {
+                repository::upsert_calibration(region, &fit, db).await?;
+                fitted += 1;
+            }
+            None => {
+                tracing::debug!("Skipping calibration for region {}: {} samples", region, samples.len());
+            }
+        }
+    }
+
+    Ok(fitted)
+}
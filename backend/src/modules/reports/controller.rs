@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Extension, Query, State},
+    response::Response,
+};
+use crate::shared::{AppState, audit::AuditDetails, crs::Crs, download, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::{auth::models::Claims, settings::{self, UsageKind}};
+use super::{models::{ExportCrsQuery, GeoPackageExportRequest}, service};
+
+const DEFAULT_VECTOR_DAYS: i32 = 30;
+const MAX_VECTOR_DAYS: i32 = 3650;
+
+#[utoipa::path(
+    post,
+    path = "/export/gpkg",
+    tag = "reports",
+    params(ExportCrsQuery),
+    request_body = GeoPackageExportRequest,
+    responses(
+        (status = 200, description = "GeoPackage with farm boundaries and intrusion vectors in the requested CRS", content_type = "application/geopackage+sqlite3", body = Vec<u8>),
+        (status = 400, description = "Unsupported CRS", body = ErrorResponse),
+        (status = 404, description = "A requested farm was not found", body = ErrorResponse),
+        (status = 422, description = "Empty or oversized farm selection", body = ErrorResponse),
+    ),
+)]
+pub async fn export_geopackage(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ExportCrsQuery>,
+    ValidatedJson(payload): ValidatedJson<GeoPackageExportRequest>,
+) -> Result<(Extension<AuditDetails>, Response), AppError> {
+    let days = payload.days.unwrap_or(DEFAULT_VECTOR_DAYS).clamp(1, MAX_VECTOR_DAYS);
+    let crs = query.crs.as_deref().map(Crs::parse).transpose()?.unwrap_or(Crs::WGS84);
+
+    let export = service::export_geopackage(&state.db, claims.sub, payload.farm_ids.as_deref(), days, crs).await?;
+    let filename = format!(
+        "bio-radar-{}-{}.gpkg",
+        claims.sub,
+        chrono::Utc::now().format("%Y%m%d")
+    );
+
+    settings::service::record_usage(
+        &state.db,
+        UsageKind::ReportGenerated,
+        Some(claims.sub),
+        None,
+        1.0,
+        serde_json::json!({ "report": "geopackage", "farms": export.farms, "vectors": export.vectors }),
+    )
+    .await;
+
+    let audit = AuditDetails::new("report.export_gpkg", "user", Some(claims.sub)).after(&serde_json::json!({
+        "farm_ids": payload.farm_ids,
+        "days": days,
+        "crs": crs.code(),
+        "farms": export.farms,
+        "vectors": export.vectors,
+    }));
+
+    let response = download::attachment(export.file, "application/geopackage+sqlite3", &filename).await?;
+
+    Ok((Extension(audit), response))
+}
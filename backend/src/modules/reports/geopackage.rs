@@ -0,0 +1,141 @@
+//! Minimal OGC GeoPackage 1.3 writer: the mandatory metadata tables plus
+//! plain feature tables, enough for QGIS and ArcGIS to open the file.
+
+use sqlx::SqliteConnection;
+use crate::shared::error::AppResult;
+
+const WGS84_SRS_ID: i32 = 4326;
+
+/// "GPKG" in ASCII, as required in the SQLite header.
+const APPLICATION_ID: i32 = 0x4750_4B47;
+const USER_VERSION: i32 = 10300;
+
+const WGS84_DEFINITION: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4326"]]"#;
+
+const CORE_SCHEMA: &str = r#"
+CREATE TABLE gpkg_spatial_ref_sys (
+    srs_name TEXT NOT NULL,
+    srs_id INTEGER PRIMARY KEY,
+    organization TEXT NOT NULL,
+    organization_coordsys_id INTEGER NOT NULL,
+    definition TEXT NOT NULL,
+    description TEXT
+);
+
+CREATE TABLE gpkg_contents (
+    table_name TEXT NOT NULL PRIMARY KEY,
+    data_type TEXT NOT NULL,
+    identifier TEXT UNIQUE,
+    description TEXT DEFAULT '',
+    last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    min_x DOUBLE,
+    min_y DOUBLE,
+    max_x DOUBLE,
+    max_y DOUBLE,
+    srs_id INTEGER,
+    CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+);
+
+CREATE TABLE gpkg_geometry_columns (
+    table_name TEXT NOT NULL,
+    column_name TEXT NOT NULL,
+    geometry_type_name TEXT NOT NULL,
+    srs_id INTEGER NOT NULL,
+    z TINYINT NOT NULL,
+    m TINYINT NOT NULL,
+    CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+    CONSTRAINT uk_gc_table_name UNIQUE (table_name),
+    CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+    CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+);
+"#;
+
+/// Stamps the file as a GeoPackage and creates the metadata tables with the
+/// two undefined reference systems the spec requires plus WGS 84.
+pub async fn create_core_tables(conn: &mut SqliteConnection) -> AppResult<()> {
+    let sql = format!(
+        r#"
+        PRAGMA application_id = {};
+        PRAGMA user_version = {};
+        {}
+        INSERT INTO gpkg_spatial_ref_sys VALUES
+            ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined', 'undefined cartesian coordinate reference system'),
+            ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined', 'undefined geographic coordinate reference system'),
+            ('WGS 84 geodetic', {}, 'EPSG', {}, '{}', 'longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid');
+        "#,
+        APPLICATION_ID, USER_VERSION, CORE_SCHEMA, WGS84_SRS_ID, WGS84_SRS_ID, WGS84_DEFINITION,
+    );
+    sqlx::query(&sql).execute(conn).await?;
+    Ok(())
+}
+
+/// Adds an EPSG reference system other than WGS 84, given its WKT definition.
+pub async fn register_srs(conn: &mut SqliteConnection, srs_id: i32, name: &str, definition: &str) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO gpkg_spatial_ref_sys (srs_name, srs_id, organization, organization_coordsys_id, definition) \
+         VALUES (?, ?, 'EPSG', ?, ?)",
+    )
+    .bind(name)
+    .bind(srs_id)
+    .bind(srs_id)
+    .bind(definition)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Lists an already created feature table in `gpkg_contents` and
+/// `gpkg_geometry_columns`. Its geometry column must be named `geom`.
+pub async fn register_layer(
+    conn: &mut SqliteConnection,
+    table: &str,
+    geometry_type: &str,
+    description: &str,
+    srs_id: i32,
+) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO gpkg_contents (table_name, data_type, identifier, description, srs_id) VALUES (?, 'features', ?, ?, ?)",
+    )
+    .bind(table)
+    .bind(table)
+    .bind(description)
+    .bind(srs_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO gpkg_geometry_columns (table_name, column_name, geometry_type_name, srs_id, z, m) VALUES (?, 'geom', ?, ?, 0, 0)",
+    )
+    .bind(table)
+    .bind(geometry_type)
+    .bind(srs_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Wraps standard WKB in the GeoPackage binary header: magic, version 0,
+/// little-endian flags without an envelope, then the SRS id.
+pub fn geometry_blob(wkb: &[u8], srs_id: i32) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(8 + wkb.len());
+    blob.extend_from_slice(b"GP");
+    blob.push(0);
+    blob.push(0b0000_0001);
+    blob.extend_from_slice(&srs_id.to_le_bytes());
+    blob.extend_from_slice(wkb);
+    blob
+}
+
+/// Little-endian WKB for a 2D line string of (x, y) points.
+pub fn linestring_wkb(points: &[(f64, f64)]) -> Vec<u8> {
+    let mut wkb = Vec::with_capacity(9 + points.len() * 16);
+    wkb.push(1);
+    wkb.extend_from_slice(&2u32.to_le_bytes());
+    wkb.extend_from_slice(&(points.len() as u32).to_le_bytes());
+    for (x, y) in points {
+        wkb.extend_from_slice(&x.to_le_bytes());
+        wkb.extend_from_slice(&y.to_le_bytes());
+    }
+    wkb
+}
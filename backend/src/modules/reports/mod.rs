@@ -0,0 +1,21 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+mod geopackage;
+
+use axum::{routing::post, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/export/gpkg", post(controller::export_geopackage))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(controller::export_geopackage))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+const MAX_EXPORT_FARMS: u64 = 500;
+
+#[derive(Debug, Default, Deserialize, ToSchema, Validate)]
+pub struct GeoPackageExportRequest {
+    /// Farms to include. Defaults to all of the caller's active farms.
+    #[serde(default)]
+    #[validate(length(min = 1, max = MAX_EXPORT_FARMS))]
+    pub farm_ids: Option<Vec<i64>>,
+    /// How far back intrusion vectors reach. Defaults to 30 days.
+    #[serde(default)]
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExportCrsQuery {
+    /// CRS of the exported geometries, e.g. `EPSG:3405`. Defaults to WGS 84.
+    pub crs: Option<String>,
+}
+
+/// Farm boundary with its latest salinity reading, geometry as little-endian WKB.
+#[derive(Debug, sqlx::FromRow)]
+pub struct ExportedFarm {
+    pub id: i64,
+    pub name: String,
+    pub region: Option<String>,
+    pub area_hectares: Option<f64>,
+    pub latest_ndsi: Option<f64>,
+    pub latest_ndsi_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub wkb: Vec<u8>,
+}
+
+/// Intrusion vector anchored at its farm's centroid.
+#[derive(Debug, sqlx::FromRow)]
+pub struct ExportedVector {
+    pub id: i64,
+    pub farm_id: i64,
+    pub direction: String,
+    pub angle_degrees: f64,
+    pub magnitude_km: f64,
+    pub calculated_at: DateTime<Utc>,
+    pub origin_lon: f64,
+    pub origin_lat: f64,
+}
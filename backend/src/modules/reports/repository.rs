@@ -0,0 +1,105 @@
+use sqlx::PgPool;
+use crate::shared::{error::AppError, postgis};
+use super::models::{ExportedFarm, ExportedVector};
+
+/// The user's active farms, optionally narrowed to `farm_ids`.
+pub async fn export_farms(
+    pool: &PgPool,
+    user_id: i64,
+    farm_ids: Option<&[i64]>,
+    srid: i32,
+) -> Result<Vec<ExportedFarm>, AppError> {
+    sqlx::query_as::<_, ExportedFarm>(&format!(
+        r#"
+        SELECT f.id, f.name, f.region,
+               f.area_hectares::FLOAT8 AS area_hectares,
+               s.ndsi_value::FLOAT8 AS latest_ndsi,
+               s.recorded_at AS latest_ndsi_at,
+               f.created_at,
+               ST_AsBinary({}, 'NDR') AS wkb
+        FROM farms f
+        LEFT JOIN LATERAL (
+            SELECT ndsi_value, recorded_at FROM salinity_logs
+            WHERE farm_id = f.id
+            ORDER BY recorded_at DESC
+            LIMIT 1
+        ) s ON TRUE
+        WHERE f.user_id = $1
+          AND f.deleted_at IS NULL
+          AND ($2::BIGINT[] IS NULL OR f.id = ANY($2))
+        ORDER BY f.id
+        "#,
+        postgis::transform("f.geometry", "$3"),
+    ))
+    .bind(user_id)
+    .bind(farm_ids)
+    .bind(srid)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Intrusion vectors of `farm_ids` from the last `days` days, oldest first.
+pub async fn export_vectors(
+    pool: &PgPool,
+    farm_ids: &[i64],
+    days: i32,
+) -> Result<Vec<ExportedVector>, AppError> {
+    sqlx::query_as::<_, ExportedVector>(
+        r#"
+        SELECT v.id, v.farm_id, v.direction,
+               v.angle_degrees::FLOAT8 AS angle_degrees,
+               v.magnitude_km::FLOAT8 AS magnitude_km,
+               v.calculated_at,
+               ST_X(ST_Centroid(f.geometry)) AS origin_lon,
+               ST_Y(ST_Centroid(f.geometry)) AS origin_lat
+        FROM intrusion_vectors v
+        JOIN farms f ON f.id = v.farm_id
+        WHERE v.farm_id = ANY($1)
+          AND v.calculated_at >= NOW() - make_interval(days => $2)
+        ORDER BY v.calculated_at, v.id
+        "#,
+    )
+    .bind(farm_ids)
+    .bind(days)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Reprojects WGS 84 (lon, lat) points to `srid`, keeping their order.
+pub async fn transform_points(
+    pool: &PgPool,
+    points: &[(f64, f64)],
+    srid: i32,
+) -> Result<Vec<(f64, f64)>, AppError> {
+    let (lons, lats): (Vec<f64>, Vec<f64>) = points.iter().copied().unzip();
+
+    sqlx::query_as::<_, (f64, f64)>(&format!(
+        r#"
+        SELECT ST_X(g), ST_Y(g)
+        FROM (
+            SELECT n, {} AS g
+            FROM unnest($1::FLOAT8[], $2::FLOAT8[]) WITH ORDINALITY AS p(lon, lat, n)
+        ) t
+        ORDER BY n
+        "#,
+        postgis::transform(&postgis::point("p.lon", "p.lat"), "$3"),
+    ))
+    .bind(lons)
+    .bind(lats)
+    .bind(srid)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// WKT definition PostGIS holds for `srid`.
+pub async fn srs_definition(pool: &PgPool, srid: i32) -> Result<Option<String>, AppError> {
+    sqlx::query_scalar("SELECT srtext::TEXT FROM spatial_ref_sys WHERE srid = $1")
+        .bind(srid)
+        .fetch_optional(pool)
+        .await
+        .map(Option::flatten)
+        .map_err(Into::into)
+}
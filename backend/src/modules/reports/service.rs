@@ -0,0 +1,175 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Connection, PgPool};
+use crate::modules::monitoring::service::offset_km;
+use crate::shared::{crs::Crs, download::ScratchFile, error::{AppError, AppResult}};
+use super::geopackage;
+use super::models::{ExportedFarm, ExportedVector};
+use super::repository;
+
+/// Finished package plus the counts kept for the audit trail.
+pub struct GeoPackageExport {
+    pub file: ScratchFile,
+    pub farms: usize,
+    pub vectors: usize,
+}
+
+/// Builds a GeoPackage with a `farms` polygon layer and an
+/// `intrusion_vectors` line layer. The backend does not store segmentation
+/// output, so each farm carries its latest NDSI reading as an attribute instead.
+/// Both layers are written in `crs`.
+pub async fn export_geopackage(
+    db: &PgPool,
+    user_id: i64,
+    farm_ids: Option<&[i64]>,
+    days: i32,
+    crs: Crs,
+) -> AppResult<GeoPackageExport> {
+    let farms = repository::export_farms(db, user_id, farm_ids, crs.srid).await?;
+    if let Some(requested) = farm_ids {
+        if let Some(missing) = requested.iter().find(|id| !farms.iter().any(|farm| farm.id == **id)) {
+            return Err(AppError::NotFound(format!("Farm {} not found", missing)));
+        }
+    }
+
+    let ids: Vec<i64> = farms.iter().map(|farm| farm.id).collect();
+    let vectors = repository::export_vectors(db, &ids, days).await?;
+
+    // Tips are offset on the sphere, so lines are built in WGS 84 first.
+    let mut points = Vec::with_capacity(vectors.len() * 2);
+    for vector in &vectors {
+        let origin = (vector.origin_lon, vector.origin_lat);
+        points.push(origin);
+        points.push(offset_km(origin, vector.angle_degrees, vector.magnitude_km));
+    }
+    let srs_definition = if crs == Crs::WGS84 {
+        None
+    } else {
+        if !points.is_empty() {
+            points = repository::transform_points(db, &points, crs.srid).await?;
+        }
+        let definition = repository::srs_definition(db, crs.srid).await?.ok_or_else(|| {
+            AppError::Internal(format!("PostGIS has no definition for {}", crs.code()))
+        })?;
+        Some(definition)
+    };
+    let lines: Vec<&[(f64, f64)]> = points.chunks(2).collect();
+
+    let file = ScratchFile::random("gpkg");
+    write_package(&file, &farms, &vectors, &lines, crs, srs_definition.as_deref()).await?;
+
+    Ok(GeoPackageExport {
+        file,
+        farms: farms.len(),
+        vectors: vectors.len(),
+    })
+}
+
+async fn write_package(
+    scratch: &ScratchFile,
+    farms: &[ExportedFarm],
+    vectors: &[ExportedVector],
+    lines: &[&[(f64, f64)]],
+    crs: Crs,
+    srs_definition: Option<&str>,
+) -> AppResult<()> {
+    let mut conn = SqliteConnectOptions::new()
+        .filename(scratch.path())
+        .create_if_missing(true)
+        .connect()
+        .await?;
+
+    geopackage::create_core_tables(&mut conn).await?;
+    if let Some(definition) = srs_definition {
+        geopackage::register_srs(&mut conn, crs.srid, crs.name, definition).await?;
+    }
+
+    let mut tx = conn.begin().await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE farms (
+            fid INTEGER PRIMARY KEY AUTOINCREMENT,
+            geom POLYGON NOT NULL,
+            farm_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            region TEXT,
+            area_hectares REAL,
+            latest_ndsi REAL,
+            latest_ndsi_at DATETIME,
+            created_at DATETIME NOT NULL
+        );
+        CREATE TABLE intrusion_vectors (
+            fid INTEGER PRIMARY KEY AUTOINCREMENT,
+            geom LINESTRING NOT NULL,
+            vector_id INTEGER NOT NULL,
+            farm_id INTEGER NOT NULL,
+            direction TEXT NOT NULL,
+            angle_degrees REAL NOT NULL,
+            magnitude_km REAL NOT NULL,
+            calculated_at DATETIME NOT NULL
+        );
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    geopackage::register_layer(
+        &mut tx,
+        "farms",
+        "POLYGON",
+        "Farm boundaries with their latest NDSI reading",
+        crs.srid,
+    )
+    .await?;
+    geopackage::register_layer(
+        &mut tx,
+        "intrusion_vectors",
+        "LINESTRING",
+        "Salinity intrusion vectors from the farm centroid towards the advancing front",
+        crs.srid,
+    )
+    .await?;
+
+    for farm in farms {
+        sqlx::query(
+            "INSERT INTO farms (geom, farm_id, name, region, area_hectares, latest_ndsi, latest_ndsi_at, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(geopackage::geometry_blob(&farm.wkb, crs.srid))
+        .bind(farm.id)
+        .bind(&farm.name)
+        .bind(&farm.region)
+        .bind(farm.area_hectares)
+        .bind(farm.latest_ndsi)
+        .bind(farm.latest_ndsi_at.map(timestamp))
+        .bind(timestamp(farm.created_at))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for (vector, line) in vectors.iter().zip(lines) {
+        sqlx::query(
+            "INSERT INTO intrusion_vectors (geom, vector_id, farm_id, direction, angle_degrees, magnitude_km, calculated_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(geopackage::geometry_blob(&geopackage::linestring_wkb(line), crs.srid))
+        .bind(vector.id)
+        .bind(vector.farm_id)
+        .bind(&vector.direction)
+        .bind(vector.angle_degrees)
+        .bind(vector.magnitude_km)
+        .bind(timestamp(vector.calculated_at))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    conn.close().await?;
+    Ok(())
+}
+
+/// GeoPackage DATETIME columns hold ISO 8601 UTC text with millisecond precision.
+fn timestamp(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
@@ -0,0 +1,26 @@
+use axum::{
+    extract::{Extension, Query, State},
+    Json,
+};
+use crate::shared::{AppState, error::AppError};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{CoverageGapQuery, CoverageGapsResponse},
+    service,
+};
+
+#[utoipa::path(
+    get,
+    path = "/coverage/gaps",
+    tag = "satellites",
+    params(CoverageGapQuery),
+    responses((status = 200, description = "Farms without a usable scene for at least min_days days", body = CoverageGapsResponse)),
+)]
+pub async fn get_coverage_gaps(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<CoverageGapQuery>,
+) -> Result<Json<CoverageGapsResponse>, AppError> {
+    let response = service::coverage_gaps(&state.db, claims.sub, claims.is_admin(), query.min_days).await?;
+    Ok(Json(response))
+}
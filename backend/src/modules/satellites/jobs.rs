@@ -0,0 +1,20 @@
+use sqlx::PgPool;
+use crate::shared::worker::{interval_from_env, spawn_periodic};
+use super::service;
+
+const COVERAGE_JOB_DEFAULT_SECS: u64 = 6 * 60 * 60;
+
+pub fn spawn_coverage_job(db: PgPool) {
+    let period = interval_from_env("COVERAGE_JOB_INTERVAL_SECS", COVERAGE_JOB_DEFAULT_SECS);
+
+    spawn_periodic("coverage_gap_alerts", period, move || {
+        let db = db.clone();
+        async move {
+            let raised = service::raise_gap_alerts(&db).await?;
+            if raised > 0 {
+                tracing::info!("Raised {} satellite coverage gap alerts", raised);
+            }
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,23 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+pub mod jobs;
+
+use axum::{routing::get, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/coverage/gaps", get(controller::get_coverage_gaps))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::get_coverage_gaps,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
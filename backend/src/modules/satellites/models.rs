@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct CoverageGap {
+    pub farm_id: i64,
+    pub farm_name: String,
+    pub user_id: i64,
+    /// Time of the newest analysed scene; `null` if the farm has none.
+    pub last_scene_at: Option<DateTime<Utc>>,
+    /// Whole days since the last scene, or since the farm was added when it
+    /// has none.
+    pub days_without_scene: i32,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CoverageGapQuery {
+    /// Minimum staleness in days; defaults to the configured threshold.
+    pub min_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CoverageGapsResponse {
+    pub threshold_days: u32,
+    /// Whether stale farms are currently being alerted on.
+    pub dry_season: bool,
+    /// Stalest first. Admins see every active farm, others their own.
+    pub gaps: Vec<CoverageGap>,
+}
@@ -0,0 +1,57 @@
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::CoverageGap;
+
+/// Analysed images are the only scene-derived observations; station,
+/// sensor and imported readings do not count.
+const SCENE_SOURCE: &str = "ai_analysis";
+
+/// Active farms without a scene for at least `min_days` days, optionally
+/// limited to those of `user_id`.
+pub async fn coverage_gaps(pool: &PgPool, user_id: Option<i64>, min_days: i32) -> Result<Vec<CoverageGap>, AppError> {
+    sqlx::query_as::<_, CoverageGap>(
+        r#"
+        WITH coverage AS (
+            SELECT f.id AS farm_id, f.name AS farm_name, f.user_id,
+                   MAX(s.recorded_at) AS last_scene_at,
+                   EXTRACT(DAY FROM NOW() - COALESCE(MAX(s.recorded_at), f.created_at))::INT AS days_without_scene
+            FROM farms f
+            LEFT JOIN salinity_logs s ON s.farm_id = f.id AND s.source = $1
+            WHERE f.deleted_at IS NULL AND ($2::BIGINT IS NULL OR f.user_id = $2)
+            GROUP BY f.id
+        )
+        SELECT * FROM coverage
+        WHERE days_without_scene >= $3
+        ORDER BY days_without_scene DESC, farm_id
+        "#,
+    )
+    .bind(SCENE_SOURCE)
+    .bind(user_id)
+    .bind(min_days)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Whether a coverage alert was already raised for the farm since `since`.
+pub async fn gap_alert_exists(
+    pool: &PgPool,
+    farm_id: i64,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<bool, AppError> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM alerts
+            WHERE farm_id = $1
+              AND metadata->>'kind' = 'coverage_gap'
+              AND ($2::TIMESTAMPTZ IS NULL OR detected_at > $2)
+        )
+        "#,
+    )
+    .bind(farm_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
@@ -0,0 +1,92 @@
+//! Coverage monitoring. Any analysis recorded for a farm counts as a usable
+//! scene; cloud cover is not stored, so cloudy scenes that were analysed
+//! anyway still reset the clock.
+
+use chrono::{Datelike, Utc};
+use sqlx::PgPool;
+use crate::modules::events;
+use crate::modules::monitoring::{
+    models::{Alert, AlertSeverity, CreateAlert},
+    repository as monitoring_repository,
+};
+use crate::modules::webhooks::WebhookEvent;
+use crate::shared::{error::AppResult, i18n::{self, t}, runtime};
+use super::models::{CoverageGap, CoverageGapsResponse};
+use super::repository;
+
+fn in_dry_season(settings: &runtime::RuntimeSettings) -> bool {
+    settings.dry_season_months.contains(&Utc::now().month())
+}
+
+/// Farms of `user_id` (all farms for admins) that have gone `min_days`
+/// without a scene.
+pub async fn coverage_gaps(
+    db: &PgPool,
+    user_id: i64,
+    is_admin: bool,
+    min_days: Option<u32>,
+) -> AppResult<CoverageGapsResponse> {
+    let settings = runtime::current();
+    let min_days = min_days.unwrap_or(settings.coverage_stale_days);
+    let owner = (!is_admin).then_some(user_id);
+
+    Ok(CoverageGapsResponse {
+        threshold_days: settings.coverage_stale_days,
+        dry_season: in_dry_season(&settings),
+        gaps: repository::coverage_gaps(db, owner, min_days as i32).await?,
+    })
+}
+
+/// Raises a low-severity alert on every farm that went stale during the dry
+/// season, once per gap: a new scene ends the gap and re-arms the alert.
+pub async fn raise_gap_alerts(db: &PgPool) -> AppResult<usize> {
+    let settings = runtime::current();
+    if !in_dry_season(&settings) {
+        return Ok(0);
+    }
+
+    let mut raised = 0;
+    for gap in repository::coverage_gaps(db, None, settings.coverage_stale_days as i32).await? {
+        if repository::gap_alert_exists(db, gap.farm_id, gap.last_scene_at).await? {
+            continue;
+        }
+        raise_gap_alert(db, &gap, settings.coverage_stale_days).await?;
+        raised += 1;
+    }
+    Ok(raised)
+}
+
+async fn raise_gap_alert(db: &PgPool, gap: &CoverageGap, threshold_days: u32) -> AppResult<()> {
+    let lang = i18n::language_for_farm(db, gap.farm_id).await?;
+    let alert = CreateAlert {
+        farm_id: gap.farm_id,
+        severity: AlertSeverity::Low,
+        message: t(lang, "alert.coverage_gap", &[("days", gap.days_without_scene.to_string())]),
+        metadata: Some(serde_json::json!({
+            "kind": "coverage_gap",
+            "last_scene_at": gap.last_scene_at,
+            "days_without_scene": gap.days_without_scene,
+            "threshold_days": threshold_days,
+        })),
+        affected_area: None,
+    };
+
+    let mut tx = db.begin().await?;
+    let alert_id = monitoring_repository::save_alert(alert.clone(), &mut tx).await?;
+
+    let alert = Alert {
+        id: alert_id,
+        farm_id: alert.farm_id,
+        severity: alert.severity,
+        message: alert.message,
+        metadata: alert.metadata,
+        affected_area: alert.affected_area,
+        detected_at: Utc::now(),
+        acknowledged: false,
+        acknowledged_at: None,
+    };
+
+    events::record(&mut tx, WebhookEvent::AlertCreated, Some(gap.farm_id), &serde_json::json!(alert)).await?;
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Extension, Query, State},
+    Json,
+};
+use crate::shared::{AppState, error::{AppError, ErrorResponse}};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{SearchQuery, SearchResult},
+    repository, service,
+};
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+const MAX_SEARCH_LIMIT: i64 = 100;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Matching farms and alerts of the caller, best match first", body = [SearchResult]),
+        (status = 400, description = "Query too long", body = ErrorResponse),
+    ),
+)]
+pub async fn search(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Vec<SearchResult>>, AppError> {
+    let Some(tsquery) = service::prefix_query(&query.q)? else {
+        return Ok(Json(Vec::new()));
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let results = repository::search(&state.db, claims.sub, &tsquery, &query, limit).await?;
+    Ok(Json(results))
+}
@@ -0,0 +1,20 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+
+use axum::{routing::get, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(controller::search))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(controller::search))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
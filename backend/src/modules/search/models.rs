@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::monitoring::models::AlertSeverity;
+use crate::shared::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchKind {
+    Farm,
+    Alert,
+}
+
+impl SearchKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SearchKind::Farm => "farm",
+            SearchKind::Alert => "alert",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "farm" => Some(SearchKind::Farm),
+            "alert" => Some(SearchKind::Alert),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for SearchKind {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        SearchKind::from_code(&value)
+            .ok_or_else(|| AppError::Validation(format!("Unsupported search result kind: {}", value)))
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SearchQuery {
+    /// Words to look for. Accents and case are ignored, and words match as prefixes.
+    pub q: String,
+    /// Restrict results to one kind.
+    pub kind: Option<SearchKind>,
+    /// Only alerts of this severity.
+    pub severity: Option<AlertSeverity>,
+    /// Only results from farms in this region.
+    pub region: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct SearchResult {
+    #[sqlx(try_from = "String")]
+    pub kind: SearchKind,
+    pub id: i64,
+    pub farm_id: i64,
+    pub title: String,
+    /// Matching excerpt with hits wrapped in `<b>` tags.
+    pub snippet: String,
+    /// API path of the matched resource.
+    pub link: String,
+    pub rank: f32,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,66 @@
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::{SearchQuery, SearchResult};
+
+/// Ranked matches among the user's active farms and their alerts. `tsquery`
+/// is a `to_tsquery` expression from `service::prefix_query`.
+pub async fn search(
+    pool: &PgPool,
+    user_id: i64,
+    tsquery: &str,
+    query: &SearchQuery,
+    limit: i64,
+) -> Result<Vec<SearchResult>, AppError> {
+    sqlx::query_as::<_, SearchResult>(
+        r#"
+        WITH q AS (SELECT to_tsquery('unaccent_simple', $2) AS query),
+        owned AS (
+            SELECT f.* FROM farms f
+            WHERE f.user_id = $1
+              AND f.deleted_at IS NULL
+              AND ($5::VARCHAR IS NULL OR f.region ILIKE $5)
+        )
+        SELECT * FROM (
+            SELECT 'farm' AS kind,
+                   f.id,
+                   f.id AS farm_id,
+                   f.name AS title,
+                   ts_headline('unaccent_simple', concat_ws(' - ', f.name, f.region), q.query) AS snippet,
+                   '/api/farms/' || f.id AS link,
+                   ts_rank(f.search_vector, q.query) AS rank,
+                   f.created_at
+            FROM owned f, q
+            WHERE ($3::VARCHAR IS NULL OR $3 = 'farm')
+              AND $4::VARCHAR IS NULL
+              AND f.search_vector @@ q.query
+
+            UNION ALL
+
+            SELECT 'alert',
+                   a.id,
+                   a.farm_id,
+                   f.name,
+                   ts_headline('unaccent_simple', a.message, q.query),
+                   '/api/monitoring/alerts/' || a.farm_id,
+                   ts_rank(a.search_vector, q.query),
+                   a.detected_at
+            FROM alerts a
+            JOIN owned f ON f.id = a.farm_id, q
+            WHERE ($3::VARCHAR IS NULL OR $3 = 'alert')
+              AND ($4::VARCHAR IS NULL OR a.severity = $4)
+              AND a.search_vector @@ q.query
+        ) results
+        ORDER BY rank DESC, created_at DESC
+        LIMIT $6
+        "#
+    )
+    .bind(user_id)
+    .bind(tsquery)
+    .bind(query.kind.map(|k| k.as_str().to_string()))
+    .bind(query.severity.map(|s| s.as_str().to_string()))
+    .bind(&query.region)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
@@ -0,0 +1,22 @@
+use crate::shared::error::AppError;
+
+const MAX_QUERY_CHARS: usize = 200;
+const MAX_TERMS: usize = 8;
+
+/// Turns free text into a `to_tsquery` expression that requires every word,
+/// each as a prefix. Punctuation is dropped, so user input cannot inject
+/// tsquery operators. Returns `None` when no searchable word remains.
+pub fn prefix_query(q: &str) -> Result<Option<String>, AppError> {
+    if q.chars().count() > MAX_QUERY_CHARS {
+        return Err(AppError::Validation(format!("q must be at most {} characters", MAX_QUERY_CHARS)));
+    }
+
+    let terms: Vec<String> = q
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(MAX_TERMS)
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect();
+
+    Ok((!terms.is_empty()).then(|| terms.join(" & ")))
+}
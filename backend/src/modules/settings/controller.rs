@@ -0,0 +1,356 @@
+use axum::{
+    extract::{Path, State, Extension, Query},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use crate::shared::{AppState, access, audit::AuditDetails, download, error::{AppError, ErrorResponse}, runtime::{self, RuntimeSettings}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{
+        AuditLog, AuditQuery, DeviceToken, RegisterDeviceRequest, RetentionPreview, RollupFormat, SetPlanRequest, SetRoleRequest,
+        UpdatePreferencesRequest, UsageKind, UsageResponse, UsageRollup, UsageRollupQuery, UserPreferences, UserRoleResponse,
+    },
+    quota, repository, service,
+};
+
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+const MAX_AUDIT_LIMIT: i64 = 500;
+
+#[utoipa::path(
+    get,
+    path = "/audit",
+    tag = "settings",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "Audit trail, newest first", body = [AuditLog]),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn list_audit_logs(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditLog>>, AppError> {
+    access::require_admin(&claims)?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, MAX_AUDIT_LIMIT);
+    let logs = repository::list_audit_logs(&state.db, &query, limit).await?;
+
+    Ok(Json(logs))
+}
+
+#[utoipa::path(
+    get,
+    path = "/preferences",
+    tag = "settings",
+    responses((status = 200, description = "Preferences of the caller", body = UserPreferences)),
+)]
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<UserPreferences>, AppError> {
+    let preferences = repository::get_preferences(&state.db, claims.sub).await?;
+    Ok(Json(preferences))
+}
+
+#[utoipa::path(
+    put,
+    path = "/preferences",
+    tag = "settings",
+    request_body = UpdatePreferencesRequest,
+    responses(
+        (status = 200, description = "Preferences updated", body = UserPreferences),
+        (status = 422, description = "Value out of range", body = ErrorResponse),
+    ),
+)]
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<UpdatePreferencesRequest>,
+) -> Result<(Extension<AuditDetails>, Json<UserPreferences>), AppError> {
+    let before = repository::get_preferences(&state.db, claims.sub).await?;
+    let after = repository::update_preferences(&state.db, claims.sub, &payload).await?;
+
+    let audit = AuditDetails::new("preferences.update", "user", Some(claims.sub))
+        .before(&before)
+        .after(&after);
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/retention/preview",
+    tag = "settings",
+    responses(
+        (status = 200, description = "Rows the retention job would delete, per user", body = [RetentionPreview]),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn preview_retention(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<RetentionPreview>>, AppError> {
+    access::require_admin(&claims)?;
+
+    let preview = repository::preview_retention(&state.db).await?;
+    Ok(Json(preview))
+}
+
+#[utoipa::path(
+    get,
+    path = "/data/export",
+    tag = "settings",
+    responses(
+        (status = 200, description = "ZIP archive of all data stored about the caller", content_type = "application/zip", body = Vec<u8>),
+    ),
+)]
+pub async fn export_data(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Response, AppError> {
+    let archive = service::export_user_data(&state.db, claims.sub).await?;
+    service::record_usage(
+        &state.db,
+        UsageKind::ReportGenerated,
+        Some(claims.sub),
+        None,
+        1.0,
+        serde_json::json!({ "report": "data_export" }),
+    )
+    .await;
+    let filename = format!(
+        "bio-radar-export-{}-{}.zip",
+        claims.sub,
+        chrono::Utc::now().format("%Y%m%d")
+    );
+
+    download::attachment(archive, "application/zip", &filename).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/system",
+    tag = "settings",
+    responses(
+        (status = 200, description = "Runtime settings in force", body = RuntimeSettings),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn get_system_settings(
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<RuntimeSettings>, AppError> {
+    access::require_admin(&claims)?;
+
+    Ok(Json(runtime::current()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/system",
+    tag = "settings",
+    request_body = RuntimeSettings,
+    responses(
+        (status = 200, description = "Settings stored and applied without a restart", body = RuntimeSettings),
+        (status = 400, description = "Value out of range or unknown job", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn update_system_settings(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<RuntimeSettings>,
+) -> Result<(Extension<AuditDetails>, Json<RuntimeSettings>), AppError> {
+    access::require_admin(&claims)?;
+
+    let before = runtime::current();
+    let after = service::update_system_settings(&state.db, payload, claims.sub).await?;
+
+    let audit = AuditDetails::new("system_settings.update", "system_settings", None)
+        .before(&before)
+        .after(&after);
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/devices",
+    tag = "settings",
+    responses((status = 200, description = "Devices registered for push notifications by the caller", body = [DeviceToken])),
+)]
+pub async fn list_devices(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<DeviceToken>>, AppError> {
+    let devices = repository::list_devices(&state.db, claims.sub).await?;
+    Ok(Json(devices))
+}
+
+#[utoipa::path(
+    post,
+    path = "/devices",
+    tag = "settings",
+    request_body = RegisterDeviceRequest,
+    responses(
+        (status = 200, description = "Device registered; re-registering a known token refreshes it", body = DeviceToken),
+        (status = 422, description = "Missing token or unsupported platform", body = ErrorResponse),
+    ),
+)]
+pub async fn register_device(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<RegisterDeviceRequest>,
+) -> Result<(Extension<AuditDetails>, Json<DeviceToken>), AppError> {
+    let device = repository::register_device(&state.db, claims.sub, &payload).await?;
+
+    // The token itself is a delivery credential; keep it out of the audit trail.
+    let audit = AuditDetails::new("device.register", "device", Some(device.id))
+        .after(&serde_json::json!({ "platform": device.platform }));
+
+    Ok((Extension(audit), Json(device)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/devices/{id}",
+    tag = "settings",
+    params(("id" = i64, Path, description = "Device id")),
+    responses(
+        (status = 200, description = "Device unregistered"),
+        (status = 404, description = "Device not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_device(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    let device = repository::delete_device(&state.db, claims.sub, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Device {} not found", id)))?;
+
+    let audit = AuditDetails::new("device.delete", "device", Some(id))
+        .before(&serde_json::json!({ "platform": device.platform }));
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/usage",
+    tag = "settings",
+    responses((status = 200, description = "The caller's plan and remaining quota", body = UsageResponse)),
+)]
+pub async fn get_usage(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<UsageResponse>, AppError> {
+    let usage = quota::usage(&state.db, claims.sub).await?;
+    Ok(Json(usage))
+}
+
+#[utoipa::path(
+    get,
+    path = "/usage/rollup",
+    tag = "settings",
+    params(UsageRollupQuery),
+    responses(
+        (status = 200, description = "Usage per user and kind for the month; with format=csv, the same rows as a CSV download", body = [UsageRollup]),
+        (status = 400, description = "Invalid month", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn get_usage_rollup(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<UsageRollupQuery>,
+) -> Result<Response, AppError> {
+    access::require_admin(&claims)?;
+
+    let month = service::parse_month(query.month.as_deref())?;
+    let rollup = service::usage_rollup(&state.db, month).await?;
+
+    if query.format == RollupFormat::Json {
+        return Ok(Json(rollup).into_response());
+    }
+
+    let csv = service::rollup_csv(month, &rollup)?;
+    let filename = format!("bio-radar-usage-{}.csv", month.format("%Y-%m"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/{id}/plan",
+    tag = "settings",
+    params(("id" = i64, Path, description = "User id")),
+    request_body = SetPlanRequest,
+    responses(
+        (status = 200, description = "Plan changed; the user's usage under the new plan", body = UsageResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    ),
+)]
+pub async fn set_user_plan(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<SetPlanRequest>,
+) -> Result<(Extension<AuditDetails>, Json<UsageResponse>), AppError> {
+    access::require_admin(&claims)?;
+
+    let previous = repository::set_plan(&state.db, id, payload.plan)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User {} not found", id)))?;
+    let usage = quota::usage(&state.db, id).await?;
+
+    let audit = AuditDetails::new("user.set_plan", "user", Some(id))
+        .before(&serde_json::json!({ "plan": previous }))
+        .after(&serde_json::json!({ "plan": payload.plan }));
+
+    Ok((Extension(audit), Json(usage)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/{id}/role",
+    tag = "settings",
+    params(("id" = i64, Path, description = "User id")),
+    request_body = SetRoleRequest,
+    responses(
+        (status = 200, description = "Role changed; it applies from the user's next sign-in", body = UserRoleResponse),
+        (status = 400, description = "Admins cannot change their own role", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+    ),
+)]
+pub async fn set_user_role(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<SetRoleRequest>,
+) -> Result<(Extension<AuditDetails>, Json<UserRoleResponse>), AppError> {
+    access::require_admin(&claims)?;
+    if id == claims.sub {
+        return Err(AppError::BadRequest("Admins cannot change their own role".to_string()));
+    }
+
+    let previous = repository::set_role(&state.db, id, payload.role)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User {} not found", id)))?;
+
+    let audit = AuditDetails::new("user.set_role", "user", Some(id))
+        .before(&serde_json::json!({ "role": previous }))
+        .after(&serde_json::json!({ "role": payload.role }));
+
+    Ok((Extension(audit), Json(UserRoleResponse { user_id: id, role: payload.role })))
+}
@@ -0,0 +1,21 @@
+use sqlx::PgPool;
+use crate::shared::worker::{interval_from_env, spawn_periodic};
+use super::repository;
+
+const RETENTION_JOB_DEFAULT_SECS: u64 = 24 * 60 * 60;
+
+pub fn spawn_retention_job(db: PgPool) {
+    let period = interval_from_env("RETENTION_JOB_INTERVAL_SECS", RETENTION_JOB_DEFAULT_SECS);
+
+    spawn_periodic("data_retention", period, move || {
+        let db = db.clone();
+        async move {
+            let purged = repository::purge_expired(&db).await?;
+            tracing::info!(
+                "Retention purge removed {} salinity logs, {} acknowledged alerts, {} intrusion vectors",
+                purged.salinity_logs, purged.acknowledged_alerts, purged.intrusion_vectors
+            );
+            Ok(())
+        }
+    });
+}
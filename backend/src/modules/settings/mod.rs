@@ -0,0 +1,50 @@
+mod models;
+mod repository;
+pub mod service;
+mod controller;
+pub mod jobs;
+pub mod quota;
+
+pub use models::{AlertChannel, DigestFrequency, UsageKind, UserPreferences};
+
+use axum::{routing::{delete, get, put}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/audit", get(controller::list_audit_logs))
+        .route("/preferences", get(controller::get_preferences).put(controller::update_preferences))
+        .route("/retention/preview", get(controller::preview_retention))
+        .route("/data/export", get(controller::export_data))
+        .route("/system", get(controller::get_system_settings).put(controller::update_system_settings))
+        .route("/devices", get(controller::list_devices).post(controller::register_device))
+        .route("/devices/{id}", delete(controller::delete_device))
+        .route("/usage", get(controller::get_usage))
+        .route("/usage/rollup", get(controller::get_usage_rollup))
+        .route("/users/{id}/plan", put(controller::set_user_plan))
+        .route("/users/{id}/role", put(controller::set_user_role))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::list_audit_logs,
+    controller::get_preferences,
+    controller::update_preferences,
+    controller::preview_retention,
+    controller::export_data,
+    controller::get_system_settings,
+    controller::update_system_settings,
+    controller::list_devices,
+    controller::register_device,
+    controller::delete_device,
+    controller::get_usage,
+    controller::get_usage_rollup,
+    controller::set_user_plan,
+    controller::set_user_role,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
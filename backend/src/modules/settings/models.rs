@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::monitoring::models::AlertSeverity;
+use crate::shared::{error::AppError, i18n::Language, runtime::{PlanLimits, RuntimeSettings}};
+use validator::{Validate, ValidationError};
+use crate::shared::validation::not_blank;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AuditLog {
+    pub id: i64,
+    pub user_id: Option<i64>,
+    pub method: String,
+    pub route: String,
+    pub action: String,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i64>,
+    pub status_code: i16,
+    pub before_state: Option<serde_json::Value>,
+    pub after_state: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditQuery {
+    pub user_id: Option<i64>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    Off,
+    Daily,
+    #[default]
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DigestFrequency::Off => "off",
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "off" => Some(DigestFrequency::Off),
+            "daily" => Some(DigestFrequency::Daily),
+            "weekly" => Some(DigestFrequency::Weekly),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for DigestFrequency {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        DigestFrequency::from_code(&value)
+            .ok_or_else(|| AppError::Validation(format!("Unsupported digest frequency: {}", value)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertChannel {
+    Email,
+    Push,
+    Webhook,
+    Sse,
+}
+
+/// Channels a new alert goes out on, by severity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AlertChannels {
+    pub low: Vec<AlertChannel>,
+    pub medium: Vec<AlertChannel>,
+    pub high: Vec<AlertChannel>,
+    pub critical: Vec<AlertChannel>,
+}
+
+impl AlertChannels {
+    pub fn for_severity(&self, severity: AlertSeverity) -> &[AlertChannel] {
+        match severity {
+            AlertSeverity::Low => &self.low,
+            AlertSeverity::Medium => &self.medium,
+            AlertSeverity::High => &self.high,
+            AlertSeverity::Critical => &self.critical,
+        }
+    }
+}
+
+impl Default for AlertChannels {
+    fn default() -> Self {
+        use AlertChannel::*;
+        Self {
+            low: vec![Webhook, Sse],
+            medium: vec![Webhook, Sse],
+            high: vec![Email, Webhook, Sse],
+            critical: vec![Email, Push, Webhook, Sse],
+        }
+    }
+}
+
+const MIN_RETENTION_DAYS: i32 = 30;
+const MAX_RETENTION_DAYS: i32 = 3650;
+const MIN_UTC_OFFSET_HOURS: i32 = -12;
+const MAX_UTC_OFFSET_HOURS: i32 = 14;
+const MAX_DEVICE_TOKEN_LEN: u64 = 4096;
+
+/// Local hours in which email and push are held back for alerts below
+/// critical. A window whose `start` is after its `end` spans midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "distinct_ends"))]
+pub struct QuietHours {
+    #[schema(value_type = String, example = "22:00")]
+    pub start: NaiveTime,
+    #[schema(value_type = String, example = "06:00")]
+    pub end: NaiveTime,
+    /// Offset of the user's clock from UTC; Vietnam time when omitted.
+    #[serde(default = "default_utc_offset_hours")]
+    #[validate(range(min = MIN_UTC_OFFSET_HOURS, max = MAX_UTC_OFFSET_HOURS))]
+    pub utc_offset_hours: i32,
+}
+
+fn distinct_ends(hours: &QuietHours) -> Result<(), ValidationError> {
+    if hours.start == hours.end {
+        return Err(ValidationError::new("quiet_hours").with_message("start and end must differ".into()));
+    }
+    Ok(())
+}
+
+fn default_utc_offset_hours() -> i32 {
+    7
+}
+
+impl QuietHours {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = (at + chrono::Duration::hours(self.utc_offset_hours as i64)).time();
+        if self.start <= self.end {
+            self.start <= local && local < self.end
+        } else {
+            local >= self.start || local < self.end
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct UserPreferences {
+    #[sqlx(try_from = "String")]
+    pub language: Language,
+    /// Days of monitoring data to keep; `null` keeps everything.
+    pub data_retention_days: Option<i32>,
+    /// Master switch for alert and digest emails; account emails are always sent.
+    pub email_alerts_enabled: bool,
+    #[sqlx(try_from = "String")]
+    pub digest_frequency: DigestFrequency,
+    #[sqlx(json)]
+    pub alert_channels: AlertChannels,
+    #[sqlx(json(nullable))]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl UserPreferences {
+    /// Whether a new alert of `severity` goes out on `channel` at `at`.
+    pub fn delivers(&self, severity: AlertSeverity, channel: AlertChannel, at: DateTime<Utc>) -> bool {
+        if !self.alert_channels.for_severity(severity).contains(&channel) {
+            return false;
+        }
+        if channel == AlertChannel::Email && !self.email_alerts_enabled {
+            return false;
+        }
+
+        let interrupts = matches!(channel, AlertChannel::Email | AlertChannel::Push);
+        let quiet = self.quiet_hours.is_some_and(|hours| hours.contains(at));
+        !(interrupts && quiet && severity != AlertSeverity::Critical)
+    }
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        Self {
+            language: Language::default(),
+            data_retention_days: None,
+            email_alerts_enabled: true,
+            digest_frequency: DigestFrequency::default(),
+            alert_channels: AlertChannels::default(),
+            quiet_hours: None,
+        }
+    }
+}
+
+/// Partial update; omitted fields are left unchanged. Send
+/// `"data_retention_days": null` to disable retention and
+/// `"quiet_hours": null` to turn quiet hours off.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdatePreferencesRequest {
+    #[serde(default)]
+    pub language: Option<Language>,
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<i32>)]
+    #[validate(range(min = MIN_RETENTION_DAYS, max = MAX_RETENTION_DAYS))]
+    pub data_retention_days: Option<Option<i32>>,
+    #[serde(default)]
+    pub email_alerts_enabled: Option<bool>,
+    #[serde(default)]
+    pub digest_frequency: Option<DigestFrequency>,
+    /// Replaces the whole matrix.
+    #[serde(default)]
+    pub alert_channels: Option<AlertChannels>,
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<QuietHours>)]
+    #[validate(nested)]
+    pub quiet_hours: Option<Option<QuietHours>>,
+}
+
+/// Distinguishes an explicit `null` (`Some(None)`) from an absent field (`None`).
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// What the retention job would delete for one user.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct RetentionPreview {
+    pub user_id: i64,
+    pub data_retention_days: i32,
+    pub salinity_logs: i64,
+    pub acknowledged_alerts: i64,
+    pub intrusion_vectors: i64,
+}
+
+#[derive(Debug)]
+pub struct RetentionPurgeResult {
+    pub salinity_logs: u64,
+    pub acknowledged_alerts: u64,
+    pub intrusion_vectors: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DevicePlatform {
+    Android,
+    Ios,
+    Web,
+}
+
+impl DevicePlatform {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DevicePlatform::Android => "android",
+            DevicePlatform::Ios => "ios",
+            DevicePlatform::Web => "web",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "android" => Some(DevicePlatform::Android),
+            "ios" => Some(DevicePlatform::Ios),
+            "web" => Some(DevicePlatform::Web),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for DevicePlatform {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        DevicePlatform::from_code(&value)
+            .ok_or_else(|| AppError::Validation(format!("Unsupported device platform: {}", value)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct DeviceToken {
+    pub id: i64,
+    /// FCM registration token of the app install.
+    pub token: String,
+    #[sqlx(try_from = "String")]
+    pub platform: DevicePlatform,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct RegisterDeviceRequest {
+    #[validate(custom(function = "not_blank"), length(max = MAX_DEVICE_TOKEN_LEN))]
+    pub token: String,
+    pub platform: DevicePlatform,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Plan {
+    Free,
+    Pro,
+}
+
+impl Plan {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Plan::Free => "free",
+            Plan::Pro => "pro",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "free" => Some(Plan::Free),
+            "pro" => Some(Plan::Pro),
+            _ => None,
+        }
+    }
+
+    pub fn limits(&self, settings: &RuntimeSettings) -> PlanLimits {
+        match self {
+            Plan::Free => settings.free_plan,
+            Plan::Pro => settings.pro_plan,
+        }
+    }
+}
+
+impl TryFrom<String> for Plan {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Plan::from_code(&value).ok_or_else(|| AppError::Validation(format!("Unsupported plan: {}", value)))
+    }
+}
+
+/// A user's plan with what they have used of it this month.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct QuotaState {
+    #[sqlx(try_from = "String")]
+    pub plan: Plan,
+    pub period_start: NaiveDate,
+    pub farms: i64,
+    pub analyses: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuotaUsage {
+    pub used: i64,
+    pub limit: i64,
+    pub remaining: i64,
+}
+
+impl QuotaUsage {
+    pub fn new(used: i64, limit: u32) -> Self {
+        let limit = i64::from(limit);
+        Self { used, limit, remaining: (limit - used).max(0) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UsageResponse {
+    pub plan: Plan,
+    /// First day of the month the analysis counter covers.
+    pub period_start: NaiveDate,
+    /// Active farms against the plan's farm limit.
+    pub farms: QuotaUsage,
+    /// AI analyses run this month against the monthly limit.
+    pub analyses: QuotaUsage,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct SetPlanRequest {
+    pub plan: Plan,
+}
+
+/// Self-registration always creates farmers; only admins hand out other roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    Farmer,
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &str {
+        match self {
+            UserRole::Farmer => "farmer",
+            UserRole::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct SetRoleRequest {
+    pub role: UserRole,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserRoleResponse {
+    pub user_id: i64,
+    pub role: UserRole,
+}
+
+/// Billable activity recorded in `usage_events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    AnalysisRun,
+    SentinelDownload,
+    ReportGenerated,
+}
+
+impl UsageKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            UsageKind::AnalysisRun => "analysis_run",
+            UsageKind::SentinelDownload => "sentinel_download",
+            UsageKind::ReportGenerated => "report_generated",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "analysis_run" => Some(UsageKind::AnalysisRun),
+            "sentinel_download" => Some(UsageKind::SentinelDownload),
+            "report_generated" => Some(UsageKind::ReportGenerated),
+            _ => None,
+        }
+    }
+
+    /// What an event's quantity measures.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            UsageKind::SentinelDownload => "gb",
+            UsageKind::AnalysisRun | UsageKind::ReportGenerated => "count",
+        }
+    }
+}
+
+impl TryFrom<String> for UsageKind {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        UsageKind::from_code(&value).ok_or_else(|| AppError::Validation(format!("Unsupported usage kind: {}", value)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RollupFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UsageRollupQuery {
+    /// Calendar month as `YYYY-MM`. Defaults to the current month.
+    pub month: Option<String>,
+    /// `csv` downloads the rollup as a spreadsheet.
+    #[serde(default)]
+    pub format: RollupFormat,
+}
+
+/// One user's total of one kind of usage over a month.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct UsageRollup {
+    /// `null` for events of since-deleted users.
+    pub user_id: Option<i64>,
+    pub email: Option<String>,
+    #[sqlx(try_from = "String")]
+    pub kind: UsageKind,
+    pub events: i64,
+    /// Sum of the events' quantities, in `unit`.
+    pub quantity: f64,
+    #[sqlx(skip)]
+    pub unit: &'static str,
+}
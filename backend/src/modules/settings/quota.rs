@@ -0,0 +1,73 @@
+//! Plan quotas. Checks run before the work they guard and counters are bumped
+//! once it has succeeded, so failed analyses are not charged. The analysis
+//! counter is checked again as it is bumped, so concurrent requests cannot
+//! overshoot it; farm counts can overshoot by the number in flight.
+
+use sqlx::PgPool;
+use crate::shared::{error::{AppError, AppResult, ErrorCode}, runtime};
+use super::models::{QuotaState, QuotaUsage, UsageResponse};
+use super::repository;
+
+async fn state(db: &PgPool, user_id: i64) -> AppResult<QuotaState> {
+    repository::quota_state(db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))
+}
+
+fn exceeded(resource: &str, state: &QuotaState, usage: QuotaUsage) -> AppError {
+    AppError::Detailed(
+        ErrorCode::QuotaExceeded,
+        format!("The {} plan allows {} {}", state.plan.as_str(), usage.limit, resource),
+        serde_json::json!({ "plan": state.plan, "resource": resource, "usage": usage }),
+    )
+}
+
+/// Remaining quota of `user_id` under the limits in force.
+pub async fn usage(db: &PgPool, user_id: i64) -> AppResult<UsageResponse> {
+    let state = state(db, user_id).await?;
+    let limits = state.plan.limits(&runtime::current());
+
+    Ok(UsageResponse {
+        plan: state.plan,
+        period_start: state.period_start,
+        farms: QuotaUsage::new(state.farms, limits.max_farms),
+        analyses: QuotaUsage::new(state.analyses, limits.analyses_per_month),
+    })
+}
+
+/// Fails unless `user_id` can own `adding` more active farms.
+pub async fn ensure_farm_capacity(db: &PgPool, user_id: i64, adding: usize) -> AppResult<()> {
+    let state = state(db, user_id).await?;
+    let usage = QuotaUsage::new(state.farms, state.plan.limits(&runtime::current()).max_farms);
+
+    if (adding as i64) > usage.remaining {
+        return Err(exceeded("active farms", &state, usage));
+    }
+    Ok(())
+}
+
+/// Fails unless `user_id` has an analysis left this month; an early check so
+/// a spent quota does not cost an inference.
+pub async fn ensure_analysis_available(db: &PgPool, user_id: i64) -> AppResult<()> {
+    let state = state(db, user_id).await?;
+    let usage = QuotaUsage::new(state.analyses, state.plan.limits(&runtime::current()).analyses_per_month);
+
+    if usage.remaining == 0 {
+        return Err(exceeded("analyses per month", &state, usage));
+    }
+    Ok(())
+}
+
+/// Charges `user_id` one analysis, failing instead if that would exceed the
+/// plan; the limit is checked in the same statement that bumps the counter.
+pub async fn charge_analysis(db: &PgPool, user_id: i64) -> AppResult<()> {
+    let state = state(db, user_id).await?;
+    let limit = state.plan.limits(&runtime::current()).analyses_per_month;
+
+    if !repository::charge_analysis(db, user_id, i64::from(limit)).await? {
+        // Another request took the last one since `state` was read.
+        let usage = QuotaUsage::new(state.analyses.max(i64::from(limit)), limit);
+        return Err(exceeded("analyses per month", &state, usage));
+    }
+    Ok(())
+}
@@ -0,0 +1,487 @@
+use sqlx::{types::{chrono::NaiveDate, Json}, PgPool};
+use crate::shared::{error::AppError, runtime::RuntimeSettings};
+use super::models::{
+    AuditLog, AuditQuery, DeviceToken, Plan, QuotaState, RegisterDeviceRequest, RetentionPreview,
+    RetentionPurgeResult, UpdatePreferencesRequest, UsageKind, UsageRollup, UserPreferences, UserRole,
+};
+
+pub async fn list_audit_logs(pool: &PgPool, query: &AuditQuery, limit: i64) -> Result<Vec<AuditLog>, AppError> {
+    sqlx::query_as::<_, AuditLog>(
+        r#"
+        SELECT id, user_id, method, route, action, entity_type, entity_id,
+               status_code, before_state, after_state, created_at
+        FROM audit_logs
+        WHERE ($1::BIGINT IS NULL OR user_id = $1)
+          AND ($2::VARCHAR IS NULL OR entity_type = $2)
+          AND ($3::BIGINT IS NULL OR entity_id = $3)
+        ORDER BY created_at DESC
+        LIMIT $4
+        "#
+    )
+    .bind(query.user_id)
+    .bind(&query.entity_type)
+    .bind(query.entity_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn get_preferences(pool: &PgPool, user_id: i64) -> Result<UserPreferences, AppError> {
+    let preferences = sqlx::query_as::<_, UserPreferences>(
+        "SELECT language, data_retention_days, email_alerts_enabled, digest_frequency, alert_channels, quiet_hours FROM user_preferences WHERE user_id = $1"
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(preferences.unwrap_or_default())
+}
+
+/// Preferences of the owner of `farm_id`, or the defaults when the farm or
+/// its owner's preference row is missing.
+pub async fn get_farm_owner_preferences(pool: &PgPool, farm_id: i64) -> Result<UserPreferences, AppError> {
+    let preferences = sqlx::query_as::<_, UserPreferences>(
+        r#"
+        SELECT p.language, p.data_retention_days, p.email_alerts_enabled, p.digest_frequency, p.alert_channels, p.quiet_hours
+        FROM farms f
+        JOIN user_preferences p ON p.user_id = f.user_id
+        WHERE f.id = $1
+        "#
+    )
+    .bind(farm_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(preferences.unwrap_or_default())
+}
+
+pub async fn update_preferences(
+    pool: &PgPool,
+    user_id: i64,
+    changes: &UpdatePreferencesRequest,
+) -> Result<UserPreferences, AppError> {
+    sqlx::query_as::<_, UserPreferences>(
+        r#"
+        INSERT INTO user_preferences
+            (user_id, language, data_retention_days, email_alerts_enabled, digest_frequency, alert_channels, quiet_hours)
+        VALUES ($1, COALESCE($2, 'en'), $4, COALESCE($5, TRUE), COALESCE($6, 'weekly'), $8, $10)
+        ON CONFLICT (user_id) DO UPDATE
+        SET language = COALESCE($2, user_preferences.language),
+            data_retention_days = CASE WHEN $3 THEN $4 ELSE user_preferences.data_retention_days END,
+            email_alerts_enabled = COALESCE($5, user_preferences.email_alerts_enabled),
+            digest_frequency = COALESCE($6, user_preferences.digest_frequency),
+            alert_channels = COALESCE($7, user_preferences.alert_channels),
+            quiet_hours = CASE WHEN $9 THEN $10 ELSE user_preferences.quiet_hours END
+        RETURNING language, data_retention_days, email_alerts_enabled, digest_frequency, alert_channels, quiet_hours
+        "#
+    )
+    .bind(user_id)
+    .bind(changes.language.map(|l| l.as_str().to_string()))
+    .bind(changes.data_retention_days.is_some())
+    .bind(changes.data_retention_days.flatten())
+    .bind(changes.email_alerts_enabled)
+    .bind(changes.digest_frequency.map(|f| f.as_str().to_string()))
+    .bind(changes.alert_channels.clone().map(Json))
+    .bind(Json(changes.alert_channels.clone().unwrap_or_default()))
+    .bind(changes.quiet_hours.is_some())
+    .bind(changes.quiet_hours.flatten().map(Json))
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+// Retention only touches derived monitoring data: readings, acknowledged
+// alerts and intrusion vectors of farms owned by users with a policy set.
+const RETENTION_POLICIES: &str = r#"
+    SELECT f.id AS farm_id, p.user_id, p.data_retention_days,
+           NOW() - make_interval(days => p.data_retention_days) AS cutoff
+    FROM user_preferences p
+    JOIN farms f ON f.user_id = p.user_id
+    WHERE p.data_retention_days IS NOT NULL
+"#;
+
+pub async fn preview_retention(pool: &PgPool) -> Result<Vec<RetentionPreview>, AppError> {
+    sqlx::query_as::<_, RetentionPreview>(&format!(
+        r#"
+        WITH policy AS ({RETENTION_POLICIES})
+        SELECT p.user_id,
+               p.data_retention_days,
+               SUM(c.salinity_logs)::BIGINT AS salinity_logs,
+               SUM(c.acknowledged_alerts)::BIGINT AS acknowledged_alerts,
+               SUM(c.intrusion_vectors)::BIGINT AS intrusion_vectors
+        FROM policy p
+        CROSS JOIN LATERAL (
+            SELECT
+                (SELECT COUNT(*) FROM salinity_logs s
+                   WHERE s.farm_id = p.farm_id AND s.recorded_at < p.cutoff) AS salinity_logs,
+                (SELECT COUNT(*) FROM alerts a
+                   WHERE a.farm_id = p.farm_id AND a.acknowledged AND a.detected_at < p.cutoff) AS acknowledged_alerts,
+                (SELECT COUNT(*) FROM intrusion_vectors v
+                   WHERE v.farm_id = p.farm_id AND v.calculated_at < p.cutoff) AS intrusion_vectors
+        ) c
+        GROUP BY p.user_id, p.data_retention_days
+        ORDER BY p.user_id
+        "#
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn purge_expired(pool: &PgPool) -> Result<RetentionPurgeResult, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let salinity_logs = sqlx::query(&format!(
+        r#"
+        WITH policy AS ({RETENTION_POLICIES})
+        DELETE FROM salinity_logs s USING policy p
+        WHERE s.farm_id = p.farm_id AND s.recorded_at < p.cutoff
+        "#
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let acknowledged_alerts = sqlx::query(&format!(
+        r#"
+        WITH policy AS ({RETENTION_POLICIES})
+        DELETE FROM alerts a USING policy p
+        WHERE a.farm_id = p.farm_id AND a.acknowledged AND a.detected_at < p.cutoff
+        "#
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let intrusion_vectors = sqlx::query(&format!(
+        r#"
+        WITH policy AS ({RETENTION_POLICIES})
+        DELETE FROM intrusion_vectors v USING policy p
+        WHERE v.farm_id = p.farm_id AND v.calculated_at < p.cutoff
+        "#
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(RetentionPurgeResult {
+        salinity_logs,
+        acknowledged_alerts,
+        intrusion_vectors,
+    })
+}
+
+/// One file of the personal data export and the query producing it. Each query
+/// takes the user id as `$1` and returns a single JSON document.
+const EXPORT_QUERIES: &[(&str, &str)] = &[
+    ("profile.json", r#"
+        SELECT to_json(u) FROM (
+            SELECT id, email, role, full_name, phone, phone_verified_at, province, email_verified_at,
+                   deletion_scheduled_at, created_at, updated_at
+            FROM users WHERE id = $1
+        ) u
+    "#),
+    ("preferences.json", r#"
+        SELECT COALESCE((SELECT to_json(p) FROM user_preferences p WHERE p.user_id = $1), '{}'::json)
+    "#),
+    ("dashboard_layout.json", r#"
+        SELECT COALESCE((SELECT to_json(d) FROM dashboard_layouts d WHERE d.user_id = $1), '{}'::json)
+    "#),
+    ("farms.geojson", r#"
+        SELECT json_build_object('type', 'FeatureCollection', 'features', COALESCE(json_agg(
+            json_build_object(
+                'type', 'Feature',
+                'geometry', ST_AsGeoJSON(f.geometry)::json,
+                'properties', json_build_object(
+                    'id', f.id, 'name', f.name, 'region', f.region, 'area_hectares', f.area_hectares,
+                    'created_at', f.created_at, 'updated_at', f.updated_at
+                )
+            ) ORDER BY f.id), '[]'::json))
+        FROM farms f WHERE f.user_id = $1
+    "#),
+    ("farm_geometry_history.geojson", r#"
+        SELECT json_build_object('type', 'FeatureCollection', 'features', COALESCE(json_agg(
+            json_build_object(
+                'type', 'Feature',
+                'geometry', ST_AsGeoJSON(v.geometry)::json,
+                'properties', json_build_object(
+                    'farm_id', v.farm_id, 'version', v.version, 'area_hectares', v.area_hectares,
+                    'restored_from', v.restored_from, 'created_at', v.created_at
+                )
+            ) ORDER BY v.farm_id, v.version), '[]'::json))
+        FROM farm_geometry_versions v JOIN farms f ON f.id = v.farm_id
+        WHERE f.user_id = $1
+    "#),
+    ("alert_rules.json", r#"
+        SELECT COALESCE(json_agg(r ORDER BY r.farm_id), '[]'::json)
+        FROM farm_alert_rules r JOIN farms f ON f.id = r.farm_id
+        WHERE f.user_id = $1
+    "#),
+    ("crop_seasons.json", r#"
+        SELECT COALESCE(json_agg(s ORDER BY s.farm_id, s.planting_date), '[]'::json)
+        FROM crop_seasons s JOIN farms f ON f.id = s.farm_id
+        WHERE f.user_id = $1
+    "#),
+    ("alerts.json", r#"
+        SELECT COALESCE(json_agg(to_jsonb(a) - 'search_vector' ORDER BY a.detected_at), '[]'::json)
+        FROM alerts a JOIN farms f ON f.id = a.farm_id
+        WHERE f.user_id = $1
+    "#),
+    ("salinity_logs.json", r#"
+        SELECT COALESCE(json_agg(l ORDER BY l.recorded_at), '[]'::json)
+        FROM salinity_logs l JOIN farms f ON f.id = l.farm_id
+        WHERE f.user_id = $1
+    "#),
+    ("intrusion_vectors.json", r#"
+        SELECT COALESCE(json_agg(v ORDER BY v.calculated_at), '[]'::json)
+        FROM intrusion_vectors v JOIN farms f ON f.id = v.farm_id
+        WHERE f.user_id = $1
+    "#),
+    ("todos.json", r#"
+        SELECT COALESCE(json_agg(t ORDER BY t.id), '[]'::json)
+        FROM todos t WHERE t.user_id = $1
+    "#),
+    ("webhooks.json", r#"
+        SELECT COALESCE(json_agg(w ORDER BY w.id), '[]'::json) FROM (
+            SELECT id, url, events, active, created_at, updated_at
+            FROM webhook_subscriptions WHERE user_id = $1
+        ) w
+    "#),
+    ("audit_logs.json", r#"
+        SELECT COALESCE(json_agg(a ORDER BY a.created_at), '[]'::json) FROM (
+            SELECT id, method, route, action, entity_type, entity_id, status_code,
+                   before_state, after_state, created_at
+            FROM audit_logs WHERE user_id = $1
+        ) a
+    "#),
+];
+
+/// Collects everything stored about `user_id`, as (file name, JSON text)
+/// pairs. Postgres renders the JSON so no document tree is built in memory.
+pub async fn export_user_data(pool: &PgPool, user_id: i64) -> Result<Vec<(&'static str, String)>, AppError> {
+    let mut files = Vec::with_capacity(EXPORT_QUERIES.len());
+
+    for (name, query) in EXPORT_QUERIES {
+        let document = sqlx::query_scalar::<_, Option<String>>(&format!("SELECT ({})::TEXT", query))
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?
+            .unwrap_or_else(|| "null".to_string());
+        files.push((*name, document));
+    }
+
+    Ok(files)
+}
+
+pub async fn get_system_settings(pool: &PgPool) -> Result<Option<RuntimeSettings>, AppError> {
+    let settings: Option<Json<RuntimeSettings>> = sqlx::query_scalar("SELECT settings FROM system_settings")
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(settings.map(|Json(settings)| settings))
+}
+
+pub async fn save_system_settings(
+    pool: &PgPool,
+    settings: &RuntimeSettings,
+    updated_by: i64,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO system_settings (id, settings, updated_by)
+        VALUES (TRUE, $1, $2)
+        ON CONFLICT (id) DO UPDATE
+        SET settings = EXCLUDED.settings,
+            updated_by = EXCLUDED.updated_by
+        "#
+    )
+    .bind(Json(settings))
+    .bind(updated_by)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+const DEVICE_COLUMNS: &str = "id, token, platform, created_at, last_seen_at";
+
+pub async fn list_devices(pool: &PgPool, user_id: i64) -> Result<Vec<DeviceToken>, AppError> {
+    sqlx::query_as::<_, DeviceToken>(&format!(
+        "SELECT {DEVICE_COLUMNS} FROM device_tokens WHERE user_id = $1 ORDER BY last_seen_at DESC"
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Registering a token already known under another user moves it to `user_id`:
+/// the install has been signed into a different account.
+pub async fn register_device(pool: &PgPool, user_id: i64, request: &RegisterDeviceRequest) -> Result<DeviceToken, AppError> {
+    sqlx::query_as::<_, DeviceToken>(&format!(
+        r#"
+        INSERT INTO device_tokens (user_id, token, platform)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (token) DO UPDATE
+        SET user_id = EXCLUDED.user_id,
+            platform = EXCLUDED.platform,
+            last_seen_at = NOW()
+        RETURNING {DEVICE_COLUMNS}
+        "#
+    ))
+    .bind(user_id)
+    .bind(request.token.trim())
+    .bind(request.platform.as_str())
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn delete_device(pool: &PgPool, user_id: i64, id: i64) -> Result<Option<DeviceToken>, AppError> {
+    sqlx::query_as::<_, DeviceToken>(&format!(
+        "DELETE FROM device_tokens WHERE id = $1 AND user_id = $2 RETURNING {DEVICE_COLUMNS}"
+    ))
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn device_tokens_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<String>, AppError> {
+    sqlx::query_scalar("SELECT token FROM device_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn delete_device_tokens(pool: &PgPool, tokens: &[String]) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM device_tokens WHERE token = ANY($1)")
+        .bind(tokens)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Plan, active farm count and this month's analysis count of `user_id`.
+pub async fn quota_state(pool: &PgPool, user_id: i64) -> Result<Option<QuotaState>, AppError> {
+    sqlx::query_as::<_, QuotaState>(
+        r#"
+        SELECT u.plan,
+               date_trunc('month', NOW())::DATE AS period_start,
+               (SELECT COUNT(*) FROM farms f WHERE f.user_id = u.id AND f.deleted_at IS NULL) AS farms,
+               COALESCE((
+                   SELECT c.analyses::BIGINT FROM usage_counters c
+                   WHERE c.user_id = u.id AND c.period = date_trunc('month', NOW())::DATE
+               ), 0) AS analyses
+        FROM users u
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Counts one analysis this month unless `limit` is already reached; returns
+/// whether it was counted.
+pub async fn charge_analysis(pool: &PgPool, user_id: i64, limit: i64) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO usage_counters (user_id, period, analyses)
+        SELECT $1, date_trunc('month', NOW())::DATE, 1
+        WHERE $2 > 0
+        ON CONFLICT (user_id, period) DO UPDATE SET analyses = usage_counters.analyses + 1
+        WHERE usage_counters.analyses < $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Returns the previous plan, or `None` if the user does not exist.
+pub async fn set_plan(pool: &PgPool, user_id: i64, plan: Plan) -> Result<Option<Plan>, AppError> {
+    let previous: Option<String> = sqlx::query_scalar(
+        r#"
+        UPDATE users u SET plan = $2, updated_at = NOW()
+        FROM (SELECT id, plan FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.plan
+        "#,
+    )
+    .bind(user_id)
+    .bind(plan.as_str())
+    .fetch_optional(pool)
+    .await?;
+
+    previous.map(Plan::try_from).transpose()
+}
+
+/// Sets the user's role and returns the one it replaced; `None` if there is
+/// no such user. Takes effect on the user's next sign-in.
+pub async fn set_role(pool: &PgPool, user_id: i64, role: UserRole) -> Result<Option<String>, AppError> {
+    sqlx::query_scalar(
+        r#"
+        UPDATE users u SET role = $2, updated_at = NOW()
+        FROM (SELECT id, role FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.role
+        "#,
+    )
+    .bind(user_id)
+    .bind(role.as_str())
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn record_usage_event(
+    pool: &PgPool,
+    kind: UsageKind,
+    user_id: Option<i64>,
+    farm_id: Option<i64>,
+    quantity: f64,
+    details: &serde_json::Value,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO usage_events (kind, user_id, farm_id, quantity, details) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(kind.as_str())
+    .bind(user_id)
+    .bind(farm_id)
+    .bind(quantity)
+    .bind(details)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Usage per user and kind for the month starting on `month`.
+pub async fn usage_rollup(pool: &PgPool, month: NaiveDate) -> Result<Vec<UsageRollup>, AppError> {
+    sqlx::query_as::<_, UsageRollup>(
+        r#"
+        SELECT e.user_id, u.email, e.kind,
+               COUNT(*) AS events,
+               SUM(e.quantity) AS quantity
+        FROM usage_events e
+        LEFT JOIN users u ON u.id = e.user_id
+        WHERE e.created_at >= $1::DATE
+          AND e.created_at < ($1::DATE + INTERVAL '1 month')
+        GROUP BY e.user_id, u.email, e.kind
+        ORDER BY e.user_id NULLS LAST, e.kind
+        "#,
+    )
+    .bind(month)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
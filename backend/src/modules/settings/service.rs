@@ -0,0 +1,228 @@
+use std::io::Write;
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::PgPool;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+use crate::shared::{
+    download::ScratchFile,
+    error::AppError,
+    i18n::Language,
+    notifications::{push::PushMessage, NotificationDispatcher},
+    runtime::{self, RuntimeSettings},
+    worker,
+};
+use super::models::{UpdatePreferencesRequest, UsageKind, UsageRollup, UserPreferences};
+use super::repository;
+
+const MIN_ANOMALY_SENSITIVITY: f64 = 0.25;
+const MAX_ANOMALY_SENSITIVITY: f64 = 4.0;
+const MAX_RESET_REQUESTS_PER_HOUR: u32 = 100;
+const MAX_ARCHIVED_FARM_RETENTION_DAYS: u32 = 3650;
+const MAX_COVERAGE_STALE_DAYS: u32 = 365;
+const MAX_JOB_INTERVAL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Builds a ZIP archive of everything stored about `user_id` in a scratch
+/// file, ready to be streamed to the client.
+pub async fn export_user_data(db: &PgPool, user_id: i64) -> Result<ScratchFile, AppError> {
+    let files = repository::export_user_data(db, user_id).await?;
+    let archive = ScratchFile::random("zip");
+    let path = archive.path().to_path_buf();
+
+    tokio::task::spawn_blocking(move || write_archive(&path, files))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    Ok(archive)
+}
+
+fn write_archive(path: &std::path::Path, files: Vec<(&'static str, String)>) -> Result<(), AppError> {
+    let file = std::fs::File::create(path).map_err(|e| AppError::Internal(e.to_string()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (name, document) in files {
+        zip.start_file(name, options).map_err(|e| AppError::Internal(e.to_string()))?;
+        zip.write_all(document.as_bytes()).map_err(|e| AppError::Internal(e.to_string()))?;
+    }
+
+    zip.finish().map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+/// Publishes the stored runtime settings, if any, so workers spawned afterwards
+/// start on the admin-configured schedule.
+pub async fn load_runtime_settings(db: &PgPool) -> Result<(), AppError> {
+    if let Some(settings) = repository::get_system_settings(db).await? {
+        runtime::publish(settings);
+    }
+    Ok(())
+}
+
+/// Validates, persists and broadcasts new runtime settings.
+pub async fn update_system_settings(
+    db: &PgPool,
+    settings: RuntimeSettings,
+    updated_by: i64,
+) -> Result<RuntimeSettings, AppError> {
+    validate_runtime_settings(&settings)?;
+
+    repository::save_system_settings(db, &settings, updated_by).await?;
+    runtime::publish(settings.clone());
+
+    Ok(settings)
+}
+
+fn validate_runtime_settings(settings: &RuntimeSettings) -> Result<(), AppError> {
+    if !(MIN_ANOMALY_SENSITIVITY..=MAX_ANOMALY_SENSITIVITY).contains(&settings.anomaly_sensitivity) {
+        return Err(AppError::Validation(format!(
+            "anomaly_sensitivity must be between {} and {}",
+            MIN_ANOMALY_SENSITIVITY, MAX_ANOMALY_SENSITIVITY
+        )));
+    }
+
+    if !(1..=MAX_RESET_REQUESTS_PER_HOUR).contains(&settings.password_reset_requests_per_hour) {
+        return Err(AppError::Validation(format!(
+            "password_reset_requests_per_hour must be between 1 and {}",
+            MAX_RESET_REQUESTS_PER_HOUR
+        )));
+    }
+
+    if !(1..=MAX_ARCHIVED_FARM_RETENTION_DAYS).contains(&settings.archived_farm_retention_days) {
+        return Err(AppError::Validation(format!(
+            "archived_farm_retention_days must be between 1 and {}",
+            MAX_ARCHIVED_FARM_RETENTION_DAYS
+        )));
+    }
+
+    if !(1..=MAX_COVERAGE_STALE_DAYS).contains(&settings.coverage_stale_days) {
+        return Err(AppError::Validation(format!(
+            "coverage_stale_days must be between 1 and {}",
+            MAX_COVERAGE_STALE_DAYS
+        )));
+    }
+
+    if settings.dry_season_months.iter().any(|month| !(1..=12).contains(month)) {
+        return Err(AppError::Validation("dry_season_months must list months between 1 and 12".to_string()));
+    }
+
+    let jobs: Vec<&str> = worker::heartbeats().into_iter().map(|(name, _)| name).collect();
+    for (job, &secs) in &settings.job_intervals_secs {
+        if !jobs.contains(&job.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Unknown job '{}'; expected one of: {}",
+                job,
+                jobs.join(", ")
+            )));
+        }
+        if !(1..=MAX_JOB_INTERVAL_SECS).contains(&secs) {
+            return Err(AppError::Validation(format!(
+                "Interval for '{}' must be between 1 and {} seconds",
+                job, MAX_JOB_INTERVAL_SECS
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn preferences(db: &PgPool, user_id: i64) -> Result<UserPreferences, AppError> {
+    repository::get_preferences(db, user_id).await
+}
+
+/// Changes only the language preference, e.g. from the profile form.
+pub async fn set_language(db: &PgPool, user_id: i64, language: Language) -> Result<UserPreferences, AppError> {
+    let changes = UpdatePreferencesRequest {
+        language: Some(language),
+        data_retention_days: None,
+        email_alerts_enabled: None,
+        digest_frequency: None,
+        alert_channels: None,
+        quiet_hours: None,
+    };
+    repository::update_preferences(db, user_id, &changes).await
+}
+
+/// Preferences that decide how alerts on `farm_id` reach its owner.
+pub async fn farm_owner_preferences(db: &PgPool, farm_id: i64) -> Result<UserPreferences, AppError> {
+    repository::get_farm_owner_preferences(db, farm_id).await
+}
+
+/// Sends `message` to every device registered by `user_id` and forgets the
+/// tokens the provider rejected. Returns the number of devices reached.
+pub async fn push_to_user(
+    db: &PgPool,
+    notifier: &NotificationDispatcher,
+    user_id: i64,
+    mut message: PushMessage,
+) -> Result<usize, AppError> {
+    message.tokens = repository::device_tokens_for_user(db, user_id).await?;
+    if message.tokens.is_empty() {
+        return Ok(0);
+    }
+
+    let outcome = notifier.send_push(message).await?;
+    if !outcome.invalid_tokens.is_empty() {
+        let removed = repository::delete_device_tokens(db, &outcome.invalid_tokens).await?;
+        tracing::info!("Removed {} unregistered device token(s) of user {}", removed, user_id);
+    }
+
+    Ok(outcome.delivered)
+}
+
+/// Appends a usage event for billing. Metering must not fail the request it
+/// measures, so errors are logged rather than returned.
+pub async fn record_usage(
+    db: &PgPool,
+    kind: UsageKind,
+    user_id: Option<i64>,
+    farm_id: Option<i64>,
+    quantity: f64,
+    details: serde_json::Value,
+) {
+    if let Err(e) = repository::record_usage_event(db, kind, user_id, farm_id, quantity, &details).await {
+        tracing::error!("Failed to record {} usage event: {}", kind.as_str(), e);
+    }
+}
+
+/// First day of the `YYYY-MM` month, or of the current month.
+pub fn parse_month(month: Option<&str>) -> Result<NaiveDate, AppError> {
+    let Some(month) = month else {
+        let today = Utc::now().date_naive();
+        return Ok(today.with_day(1).unwrap_or(today));
+    };
+
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| AppError::Validation(format!("month must be YYYY-MM, got '{}'", month)))
+}
+
+pub async fn usage_rollup(db: &PgPool, month: NaiveDate) -> Result<Vec<UsageRollup>, AppError> {
+    let mut rollup = repository::usage_rollup(db, month).await?;
+    for row in &mut rollup {
+        row.unit = row.kind.unit();
+    }
+    Ok(rollup)
+}
+
+pub fn rollup_csv(month: NaiveDate, rollup: &[UsageRollup]) -> Result<Vec<u8>, AppError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let period = month.format("%Y-%m").to_string();
+
+    let csv_error = |e: csv::Error| AppError::Internal(format!("Failed to write CSV: {}", e));
+    writer
+        .write_record(["month", "user_id", "email", "kind", "events", "quantity", "unit"])
+        .map_err(csv_error)?;
+    for row in rollup {
+        writer
+            .write_record([
+                period.clone(),
+                row.user_id.map(|id| id.to_string()).unwrap_or_default(),
+                row.email.clone().unwrap_or_default(),
+                row.kind.as_str().to_string(),
+                row.events.to_string(),
+                row.quantity.to_string(),
+                row.unit.to_string(),
+            ])
+            .map_err(csv_error)?;
+    }
+
+    writer.into_inner().map_err(|e| AppError::Internal(format!("Failed to write CSV: {}", e)))
+}
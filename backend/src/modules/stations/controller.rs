@@ -0,0 +1,306 @@
+use axum::{
+    extract::{Path, Query, State, Extension},
+    Json,
+};
+use crate::shared::{AppState, access, audit::AuditDetails, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{
+        CreateStationReadingRequest, CreateStationRequest, FarmNearbyStationsQuery, ListStationsQuery, NearbyStation,
+        NearbyStationsQuery, ReadingsQuery, Station, StationReading, SubscribedStation, UpdateStationRequest,
+    },
+    repository, service,
+};
+
+const DEFAULT_READING_DAYS: i32 = 30;
+const MAX_READING_DAYS: i32 = 3650;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "stations",
+    params(ListStationsQuery),
+    responses((status = 200, description = "Monitoring stations ordered by code", body = [Station])),
+)]
+pub async fn list_stations(
+    State(state): State<AppState>,
+    Query(query): Query<ListStationsQuery>,
+) -> Result<Json<Vec<Station>>, AppError> {
+    let stations = repository::list_stations(&state.db, &query).await?;
+    Ok(Json(stations))
+}
+
+#[utoipa::path(
+    get,
+    path = "/nearby",
+    tag = "stations",
+    params(NearbyStationsQuery),
+    responses(
+        (status = 200, description = "Active stations within the radius, nearest first, with their latest reading", body = [NearbyStation]),
+        (status = 400, description = "Invalid coordinates or radius", body = ErrorResponse),
+    ),
+)]
+pub async fn nearby_stations(
+    State(state): State<AppState>,
+    Query(query): Query<NearbyStationsQuery>,
+) -> Result<Json<Vec<NearbyStation>>, AppError> {
+    service::validate_coordinates(Some(query.lat), Some(query.lon))?;
+    let (radius_m, limit) = service::nearby_bounds(query.radius_km, query.limit)?;
+
+    let stations = repository::nearby_stations(&state.db, query.lon, query.lat, radius_m, limit).await?;
+    Ok(Json(stations))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "stations",
+    request_body = CreateStationRequest,
+    responses(
+        (status = 200, description = "Station registered", body = Station),
+        (status = 400, description = "Duplicate code", body = ErrorResponse),
+        (status = 422, description = "Blank code or name, or coordinates out of range", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+    ),
+)]
+pub async fn create_station(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateStationRequest>,
+) -> Result<(Extension<AuditDetails>, Json<Station>), AppError> {
+    access::require_admin(&claims)?;
+
+    let station = service::create_station(&state.db, payload).await?;
+    let audit = AuditDetails::new("station.create", "station", Some(station.id)).after(&station);
+
+    Ok((Extension(audit), Json(station)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "stations",
+    params(("id" = i64, Path, description = "Station id")),
+    responses(
+        (status = 200, description = "Station details", body = Station),
+        (status = 404, description = "Station not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_station(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<Station>, AppError> {
+    let station = service::get_station(&state.db, id).await?;
+    Ok(Json(station))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = "stations",
+    params(("id" = i64, Path, description = "Station id")),
+    request_body = UpdateStationRequest,
+    responses(
+        (status = 200, description = "Station updated", body = Station),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Station not found", body = ErrorResponse),
+        (status = 422, description = "Blank name or coordinates out of range", body = ErrorResponse),
+    ),
+)]
+pub async fn update_station(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<UpdateStationRequest>,
+) -> Result<(Extension<AuditDetails>, Json<Station>), AppError> {
+    access::require_admin(&claims)?;
+
+    let before = service::get_station(&state.db, id).await?;
+    let after = service::update_station(&state.db, id, payload).await?;
+
+    let audit = AuditDetails::new("station.update", "station", Some(id))
+        .before(&before)
+        .after(&after);
+
+    Ok((Extension(audit), Json(after)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "stations",
+    params(("id" = i64, Path, description = "Station id")),
+    responses(
+        (status = 200, description = "Station and its readings deleted"),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Station not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_station(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    access::require_admin(&claims)?;
+
+    let existing = service::get_station(&state.db, id).await?;
+    repository::delete_station(&state.db, id).await?;
+
+    let audit = AuditDetails::new("station.delete", "station", Some(id)).before(&existing);
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/readings",
+    tag = "stations",
+    params(("id" = i64, Path, description = "Station id"), ReadingsQuery),
+    responses(
+        (status = 200, description = "Readings, newest first", body = [StationReading]),
+        (status = 404, description = "Station not found", body = ErrorResponse),
+    ),
+)]
+pub async fn list_readings(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<ReadingsQuery>,
+) -> Result<Json<Vec<StationReading>>, AppError> {
+    service::get_station(&state.db, id).await?;
+
+    let days = query.days.unwrap_or(DEFAULT_READING_DAYS).clamp(1, MAX_READING_DAYS);
+    let readings = repository::list_readings(&state.db, id, days).await?;
+    Ok(Json(readings))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/readings",
+    tag = "stations",
+    params(("id" = i64, Path, description = "Station id")),
+    request_body = CreateStationReadingRequest,
+    responses(
+        (status = 200, description = "Reading stored", body = StationReading),
+        (status = 400, description = "Future timestamp or inactive station", body = ErrorResponse),
+        (status = 422, description = "NDSI outside -1..1 or source too long", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Station not found", body = ErrorResponse),
+    ),
+)]
+pub async fn create_reading(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<CreateStationReadingRequest>,
+) -> Result<Json<StationReading>, AppError> {
+    access::require_admin(&claims)?;
+
+    let station = service::get_station(&state.db, id).await?;
+    let reading = service::record_reading(&state.db, &station, payload).await?;
+    Ok(Json(reading))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/stations",
+    tag = "stations",
+    params(("id" = i64, Path, description = "Farm id")),
+    responses(
+        (status = 200, description = "Stations the farm follows, nearest first", body = [SubscribedStation]),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn list_farm_stations(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+) -> Result<Json<Vec<SubscribedStation>>, AppError> {
+    service::ensure_farm_access(&state.db, farm_id, claims.sub, claims.is_admin()).await?;
+
+    let stations = repository::list_subscriptions(&state.db, farm_id).await?;
+    Ok(Json(stations))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/nearby-stations",
+    tag = "stations",
+    params(("id" = i64, Path, description = "Farm id"), FarmNearbyStationsQuery),
+    responses(
+        (status = 200, description = "Active stations around the farm boundary, nearest first, with their latest reading", body = [NearbyStation]),
+        (status = 400, description = "Invalid radius", body = ErrorResponse),
+        (status = 404, description = "Farm not found", body = ErrorResponse),
+    ),
+)]
+pub async fn farm_nearby_stations(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(farm_id): Path<i64>,
+    Query(query): Query<FarmNearbyStationsQuery>,
+) -> Result<Json<Vec<NearbyStation>>, AppError> {
+    service::ensure_farm_access(&state.db, farm_id, claims.sub, claims.is_admin()).await?;
+    let (radius_m, limit) = service::nearby_bounds(query.radius_km, query.limit)?;
+
+    let stations = repository::nearby_stations_for_farm(&state.db, farm_id, radius_m, limit).await?;
+    Ok(Json(stations))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}/stations/{station_id}",
+    tag = "stations",
+    params(
+        ("id" = i64, Path, description = "Farm id"),
+        ("station_id" = i64, Path, description = "Station id"),
+    ),
+    responses(
+        (status = 200, description = "Farm follows the station; its readings feed the farm's risk score"),
+        (status = 400, description = "Station too far from the farm", body = ErrorResponse),
+        (status = 404, description = "Farm or station not found", body = ErrorResponse),
+    ),
+)]
+pub async fn subscribe_farm(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((farm_id, station_id)): Path<(i64, i64)>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    service::ensure_farm_access(&state.db, farm_id, claims.sub, claims.is_admin()).await?;
+
+    let station = service::get_station(&state.db, station_id).await?;
+    let distance_km = service::subscribe(&state.db, farm_id, &station).await?;
+
+    let audit = AuditDetails::new("station.subscribe", "farm", Some(farm_id))
+        .after(&serde_json::json!({ "station_id": station_id }));
+
+    Ok((Extension(audit), Json(serde_json::json!({ "station_id": station_id, "distance_km": distance_km }))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}/stations/{station_id}",
+    tag = "stations",
+    params(
+        ("id" = i64, Path, description = "Farm id"),
+        ("station_id" = i64, Path, description = "Station id"),
+    ),
+    responses(
+        (status = 200, description = "Subscription removed"),
+        (status = 404, description = "Farm does not follow the station", body = ErrorResponse),
+    ),
+)]
+pub async fn unsubscribe_farm(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((farm_id, station_id)): Path<(i64, i64)>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    service::ensure_farm_access(&state.db, farm_id, claims.sub, claims.is_admin()).await?;
+
+    if !repository::unsubscribe(&state.db, farm_id, station_id).await? {
+        return Err(AppError::NotFound(format!("Farm {} does not follow station {}", farm_id, station_id)));
+    }
+
+    let audit = AuditDetails::new("station.unsubscribe", "farm", Some(farm_id))
+        .before(&serde_json::json!({ "station_id": station_id }));
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
@@ -0,0 +1,55 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+
+use axum::{routing::{get, put}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+/// Station registry and readings, mounted under `/api/monitoring/stations`.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(controller::list_stations).post(controller::create_station))
+        .route("/nearby", get(controller::nearby_stations))
+        .route("/{id}", get(controller::get_station).put(controller::update_station).delete(controller::delete_station))
+        .route("/{id}/readings", get(controller::list_readings).post(controller::create_reading))
+}
+
+/// Farm subscriptions, mounted alongside the farm routes under `/api/farms`.
+pub fn farm_router() -> Router<AppState> {
+    Router::new()
+        .route("/{id}/stations", get(controller::list_farm_stations))
+        .route("/{id}/nearby-stations", get(controller::farm_nearby_stations))
+        .route("/{id}/stations/{station_id}", put(controller::subscribe_farm).delete(controller::unsubscribe_farm))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::list_stations,
+    controller::nearby_stations,
+    controller::create_station,
+    controller::get_station,
+    controller::update_station,
+    controller::delete_station,
+    controller::list_readings,
+    controller::create_reading,
+))]
+struct ApiDoc;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::list_farm_stations,
+    controller::farm_nearby_stations,
+    controller::subscribe_farm,
+    controller::unsubscribe_farm,
+))]
+struct FarmApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+
+pub fn farm_openapi() -> utoipa::openapi::OpenApi {
+    FarmApiDoc::openapi()
+}
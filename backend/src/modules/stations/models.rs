@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+use crate::shared::validation::not_blank;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct Station {
+    pub id: i64,
+    /// Identifier assigned by the managing agency.
+    pub code: String,
+    pub name: String,
+    pub river: Option<String>,
+    pub agency: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateStationRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub code: String,
+    #[validate(custom(function = "not_blank"))]
+    pub name: String,
+    #[serde(default)]
+    pub river: Option<String>,
+    #[serde(default)]
+    pub agency: Option<String>,
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: f64,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: f64,
+}
+
+/// Partial update; omitted fields are left unchanged.
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateStationRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub name: Option<String>,
+    pub river: Option<String>,
+    pub agency: Option<String>,
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListStationsQuery {
+    pub river: Option<String>,
+    /// Include decommissioned stations.
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct StationReading {
+    pub id: i64,
+    pub station_id: i64,
+    pub ndsi_value: f64,
+    pub source: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateStationReadingRequest {
+    #[validate(range(min = -1.0, max = 1.0))]
+    pub ndsi_value: f64,
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ReadingsQuery {
+    /// Days of history; defaults to 30.
+    pub days: Option<i32>,
+}
+
+/// A station a farm follows, with how far it is from the farm.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct SubscribedStation {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub station: Station,
+    pub distance_km: f64,
+    pub subscribed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct NearbyStationsQuery {
+    pub lat: f64,
+    pub lon: f64,
+    /// Search radius; defaults to 25 km.
+    pub radius_km: Option<f64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FarmNearbyStationsQuery {
+    /// Search radius around the farm boundary; defaults to 25 km.
+    pub radius_km: Option<f64>,
+    pub limit: Option<i64>,
+}
+
+/// An active station near a point or farm with its most recent reading.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct NearbyStation {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub station: Station,
+    pub distance_km: f64,
+    pub latest_ndsi: Option<f64>,
+    pub latest_recorded_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,260 @@
+use sqlx::PgPool;
+use crate::shared::{error::AppError, postgis};
+use super::models::{
+    CreateStationReadingRequest, CreateStationRequest, ListStationsQuery, NearbyStation, Station, StationReading,
+    SubscribedStation, UpdateStationRequest,
+};
+
+const STATION_COLUMNS: &str = r#"
+    s.id, s.code, s.name, s.river, s.agency,
+    ST_Y(s.location) AS latitude, ST_X(s.location) AS longitude,
+    s.active, s.created_at, s.updated_at
+"#;
+
+pub async fn list_stations(pool: &PgPool, query: &ListStationsQuery) -> Result<Vec<Station>, AppError> {
+    sqlx::query_as::<_, Station>(&format!(
+        r#"
+        SELECT {STATION_COLUMNS}
+        FROM stations s
+        WHERE ($1::VARCHAR IS NULL OR s.river ILIKE $1)
+          AND ($2 OR s.active)
+        ORDER BY s.code
+        "#
+    ))
+    .bind(&query.river)
+    .bind(query.include_inactive)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn get_station(pool: &PgPool, id: i64) -> Result<Option<Station>, AppError> {
+    sqlx::query_as::<_, Station>(&format!("SELECT {STATION_COLUMNS} FROM stations s WHERE s.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn create_station(pool: &PgPool, request: &CreateStationRequest) -> Result<Station, AppError> {
+    sqlx::query_as::<_, Station>(&format!(
+        r#"
+        WITH s AS (
+            INSERT INTO stations (code, name, river, agency, location)
+            VALUES ($1, $2, $3, $4, {})
+            RETURNING *
+        )
+        SELECT {STATION_COLUMNS} FROM s
+        "#,
+        postgis::point("$5", "$6"),
+    ))
+    .bind(request.code.trim())
+    .bind(request.name.trim())
+    .bind(&request.river)
+    .bind(&request.agency)
+    .bind(request.longitude)
+    .bind(request.latitude)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn update_station(pool: &PgPool, id: i64, changes: &UpdateStationRequest) -> Result<Station, AppError> {
+    sqlx::query_as::<_, Station>(&format!(
+        r#"
+        WITH s AS (
+            UPDATE stations
+            SET name = COALESCE($2, name),
+                river = COALESCE($3, river),
+                agency = COALESCE($4, agency),
+                location = {},
+                active = COALESCE($7, active)
+            WHERE id = $1
+            RETURNING *
+        )
+        SELECT {STATION_COLUMNS} FROM s
+        "#,
+        postgis::point("COALESCE($5, ST_X(location))", "COALESCE($6, ST_Y(location))"),
+    ))
+    .bind(id)
+    .bind(changes.name.as_deref().map(str::trim))
+    .bind(&changes.river)
+    .bind(&changes.agency)
+    .bind(changes.longitude)
+    .bind(changes.latitude)
+    .bind(changes.active)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn delete_station(pool: &PgPool, id: i64) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM stations WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn code_exists(pool: &PgPool, code: &str) -> Result<bool, AppError> {
+    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM stations WHERE code = $1)")
+        .bind(code.trim())
+        .fetch_one(pool)
+        .await
+        .map_err(Into::into)
+}
+
+/// Active stations within `radius` metres of `origin`, nearest first, each
+/// with its latest reading. `origin` is a geometry expression over `from`.
+fn nearby_sql(from: &str, filter: &str, origin: &str, radius: &str, limit: &str) -> String {
+    format!(
+        r#"
+        SELECT {STATION_COLUMNS},
+               {distance} / 1000.0 AS distance_km,
+               latest.ndsi AS latest_ndsi,
+               latest.recorded_at AS latest_recorded_at
+        FROM {from}
+        LEFT JOIN LATERAL (
+            SELECT l.ndsi_value::FLOAT8 AS ndsi, l.recorded_at
+            FROM salinity_logs l
+            WHERE l.station_id = s.id
+            ORDER BY l.recorded_at DESC
+            LIMIT 1
+        ) latest ON TRUE
+        WHERE s.active AND {within} AND {filter}
+        ORDER BY distance_km
+        LIMIT {limit}
+        "#,
+        distance = postgis::distance_metres("s.location", origin),
+        within = postgis::within_metres("s.location", origin, radius),
+    )
+}
+
+pub async fn nearby_stations(
+    pool: &PgPool,
+    lon: f64,
+    lat: f64,
+    radius_m: f64,
+    limit: i64,
+) -> Result<Vec<NearbyStation>, AppError> {
+    let origin = postgis::point("$1", "$2");
+    sqlx::query_as::<_, NearbyStation>(&nearby_sql("stations s", "TRUE", &origin, "$3", "$4"))
+        .bind(lon)
+        .bind(lat)
+        .bind(radius_m)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(Into::into)
+}
+
+/// Distances are measured from the farm boundary, so stations inside the farm
+/// are at 0 km.
+pub async fn nearby_stations_for_farm(
+    pool: &PgPool,
+    farm_id: i64,
+    radius_m: f64,
+    limit: i64,
+) -> Result<Vec<NearbyStation>, AppError> {
+    sqlx::query_as::<_, NearbyStation>(&nearby_sql("farms f CROSS JOIN stations s", "f.id = $1", "f.geometry", "$2", "$3"))
+        .bind(farm_id)
+        .bind(radius_m)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn list_readings(pool: &PgPool, station_id: i64, days: i32) -> Result<Vec<StationReading>, AppError> {
+    sqlx::query_as::<_, StationReading>(
+        r#"
+        SELECT id, station_id, ndsi_value::FLOAT8 AS ndsi_value, source, recorded_at
+        FROM salinity_logs
+        WHERE station_id = $1 AND recorded_at >= NOW() - make_interval(days => $2)
+        ORDER BY recorded_at DESC
+        "#
+    )
+    .bind(station_id)
+    .bind(days)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn create_reading(
+    pool: &PgPool,
+    station_id: i64,
+    request: &CreateStationReadingRequest,
+    source: &str,
+) -> Result<StationReading, AppError> {
+    sqlx::query_as::<_, StationReading>(
+        r#"
+        INSERT INTO salinity_logs (station_id, ndsi_value, source, recorded_at)
+        VALUES ($1, $2::NUMERIC(8, 6), $3, COALESCE($4, NOW()))
+        RETURNING id, station_id, ndsi_value::FLOAT8 AS ndsi_value, source, recorded_at
+        "#
+    )
+    .bind(station_id)
+    .bind(request.ndsi_value)
+    .bind(source)
+    .bind(request.recorded_at)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn list_subscriptions(pool: &PgPool, farm_id: i64) -> Result<Vec<SubscribedStation>, AppError> {
+    sqlx::query_as::<_, SubscribedStation>(&format!(
+        r#"
+        SELECT {STATION_COLUMNS},
+               {} / 1000.0 AS distance_km,
+               fs.created_at AS subscribed_at
+        FROM farm_station_subscriptions fs
+        JOIN stations s ON s.id = fs.station_id
+        JOIN farms f ON f.id = fs.farm_id
+        WHERE fs.farm_id = $1
+        ORDER BY distance_km
+        "#,
+        postgis::distance_metres("s.location", "f.geometry"),
+    ))
+    .bind(farm_id)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Distance in km from the farm's boundary to the station.
+pub async fn distance_km(pool: &PgPool, farm_id: i64, station_id: i64) -> Result<Option<f64>, AppError> {
+    sqlx::query_scalar(&format!(
+        "SELECT {} / 1000.0 FROM farms f, stations s WHERE f.id = $1 AND s.id = $2",
+        postgis::distance_metres("s.location", "f.geometry"),
+    ))
+    .bind(farm_id)
+    .bind(station_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn subscribe(pool: &PgPool, farm_id: i64, station_id: i64) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO farm_station_subscriptions (farm_id, station_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+    )
+    .bind(farm_id)
+    .bind(station_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn unsubscribe(pool: &PgPool, farm_id: i64, station_id: i64) -> Result<bool, AppError> {
+    let result = sqlx::query("DELETE FROM farm_station_subscriptions WHERE farm_id = $1 AND station_id = $2")
+        .bind(farm_id)
+        .bind(station_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
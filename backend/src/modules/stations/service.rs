@@ -0,0 +1,104 @@
+use sqlx::PgPool;
+use crate::modules::monitoring::repository::get_farm_owner;
+use crate::shared::error::{AppError, ErrorCode};
+use super::models::{CreateStationReadingRequest, CreateStationRequest, Station, StationReading, UpdateStationRequest};
+use super::repository;
+
+/// Stations further than this from a farm say little about its water.
+pub const MAX_SUBSCRIPTION_DISTANCE_KM: f64 = 50.0;
+const DEFAULT_READING_SOURCE: &str = "station";
+const DEFAULT_NEARBY_RADIUS_KM: f64 = 25.0;
+const MAX_NEARBY_RADIUS_KM: f64 = 100.0;
+const DEFAULT_NEARBY_LIMIT: i64 = 20;
+const MAX_NEARBY_LIMIT: i64 = 100;
+
+pub async fn get_station(db: &PgPool, id: i64) -> Result<Station, AppError> {
+    repository::get_station(db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::StationNotFound, format!("Station {} not found", id)))
+}
+
+/// Checks that the caller may manage subscriptions of `farm_id`.
+pub async fn ensure_farm_access(db: &PgPool, farm_id: i64, user_id: i64, is_admin: bool) -> Result<(), AppError> {
+    let owner = get_farm_owner(farm_id, db)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    if owner != user_id && !is_admin {
+        return Err(AppError::Unauthorized("Not authorized to access this farm".to_string()));
+    }
+
+    Ok(())
+}
+
+pub async fn create_station(db: &PgPool, request: CreateStationRequest) -> Result<Station, AppError> {
+    if repository::code_exists(db, &request.code).await? {
+        return Err(AppError::Validation(format!("Station code '{}' is already registered", request.code.trim())));
+    }
+
+    repository::create_station(db, &request).await
+}
+
+pub async fn update_station(db: &PgPool, id: i64, request: UpdateStationRequest) -> Result<Station, AppError> {
+    repository::update_station(db, id, &request).await
+}
+
+pub async fn record_reading(
+    db: &PgPool,
+    station: &Station,
+    request: CreateStationReadingRequest,
+) -> Result<StationReading, AppError> {
+    if request.recorded_at.is_some_and(|t| t > chrono::Utc::now()) {
+        return Err(AppError::Validation("recorded_at cannot be in the future".to_string()));
+    }
+    if !station.active {
+        return Err(AppError::Validation(format!("Station {} is inactive", station.code)));
+    }
+
+    let source = request.source
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or(DEFAULT_READING_SOURCE);
+
+    repository::create_reading(db, station.id, &request, source).await
+}
+
+pub async fn subscribe(db: &PgPool, farm_id: i64, station: &Station) -> Result<f64, AppError> {
+    let distance_km = repository::distance_km(db, farm_id, station.id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)))?;
+
+    if distance_km > MAX_SUBSCRIPTION_DISTANCE_KM {
+        return Err(AppError::Validation(format!(
+            "Station {} is {:.1} km from the farm; only stations within {} km can be followed",
+            station.code, distance_km, MAX_SUBSCRIPTION_DISTANCE_KM
+        )));
+    }
+
+    repository::subscribe(db, farm_id, station.id).await?;
+    Ok(distance_km)
+}
+
+/// Radius in metres and row limit for proximity searches.
+pub fn nearby_bounds(radius_km: Option<f64>, limit: Option<i64>) -> Result<(f64, i64), AppError> {
+    let radius_km = radius_km.unwrap_or(DEFAULT_NEARBY_RADIUS_KM);
+    if !(radius_km > 0.0 && radius_km <= MAX_NEARBY_RADIUS_KM) {
+        return Err(AppError::Validation(format!(
+            "radius_km must be greater than 0 and at most {}",
+            MAX_NEARBY_RADIUS_KM
+        )));
+    }
+
+    Ok((radius_km * 1000.0, limit.unwrap_or(DEFAULT_NEARBY_LIMIT).clamp(1, MAX_NEARBY_LIMIT)))
+}
+
+pub fn validate_coordinates(latitude: Option<f64>, longitude: Option<f64>) -> Result<(), AppError> {
+    if latitude.is_some_and(|lat| !(-90.0..=90.0).contains(&lat)) {
+        return Err(AppError::Validation("latitude must be between -90 and 90".to_string()));
+    }
+    if longitude.is_some_and(|lon| !(-180.0..=180.0).contains(&lon)) {
+        return Err(AppError::Validation("longitude must be between -180 and 180".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Extension, Query, State},
+    Json,
+};
+use crate::shared::{AppState, audit::AuditDetails, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{ChangesQuery, ChangesResponse, MutationStatus, PushRequest, PushResponse},
+    service,
+};
+
+const DEFAULT_CHANGES_LIMIT: i64 = 500;
+const MAX_CHANGES_LIMIT: i64 = 1000;
+
+#[utoipa::path(
+    get,
+    path = "/changes",
+    tag = "sync",
+    params(ChangesQuery),
+    responses(
+        (status = 200, description = "Farms, alerts and todos changed since the cursor", body = ChangesResponse),
+        (status = 400, description = "Invalid cursor or limit", body = ErrorResponse),
+    ),
+)]
+pub async fn get_changes(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ChangesQuery>,
+) -> Result<Json<ChangesResponse>, AppError> {
+    if query.since < 0 {
+        return Err(AppError::Validation("since must not be negative".to_string()));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_CHANGES_LIMIT);
+    if !(1..=MAX_CHANGES_LIMIT).contains(&limit) {
+        return Err(AppError::Validation(format!("limit must be between 1 and {}", MAX_CHANGES_LIMIT)));
+    }
+
+    let response = service::changes(&state.db, claims.sub, query.since, limit).await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/push",
+    tag = "sync",
+    request_body = PushRequest,
+    responses(
+        (status = 200, description = "Per-mutation outcome: applied, conflict (with the server copy) or rejected", body = PushResponse),
+        (status = 422, description = "Empty or oversized batch", body = ErrorResponse),
+    ),
+)]
+pub async fn push(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<PushRequest>,
+) -> Result<(Extension<AuditDetails>, Json<PushResponse>), AppError> {
+    let mut results = Vec::with_capacity(payload.mutations.len());
+    for mutation in payload.mutations {
+        results.push(service::apply(&state.db, claims.sub, mutation).await);
+    }
+
+    let count = |status: MutationStatus| results.iter().filter(|r| r.status == status).count();
+    let audit = AuditDetails::new("sync.push", "user", Some(claims.sub)).after(&serde_json::json!({
+        "applied": count(MutationStatus::Applied),
+        "conflicts": count(MutationStatus::Conflict),
+        "rejected": count(MutationStatus::Rejected),
+    }));
+
+    Ok((Extension(audit), Json(PushResponse { results })))
+}
@@ -0,0 +1,25 @@
+mod models;
+mod repository;
+mod service;
+mod controller;
+
+use axum::{routing::{get, post}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/changes", get(controller::get_changes))
+        .route("/push", post(controller::push))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::get_changes,
+    controller::push,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::todos::models::{CreateTodoRequest, UpdateTodoRequest};
+use crate::shared::error::AppError;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncEntity {
+    Farm,
+    Alert,
+    Todo,
+}
+
+impl SyncEntity {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "farm" => Some(SyncEntity::Farm),
+            "alert" => Some(SyncEntity::Alert),
+            "todo" => Some(SyncEntity::Todo),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for SyncEntity {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        SyncEntity::from_code(&value).ok_or_else(|| AppError::Internal(format!("Unknown sync entity: {}", value)))
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ChangesQuery {
+    /// Cursor from the previous response; omit or 0 for a full sync.
+    #[serde(default)]
+    pub since: i64,
+    /// Feed entries to read, 1-1000. Defaults to 500.
+    pub limit: Option<i64>,
+}
+
+/// Entry of the change feed.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChangeRow {
+    pub id: i64,
+    #[sqlx(try_from = "String")]
+    pub entity: SyncEntity,
+    pub entity_id: i64,
+    pub deleted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncChange {
+    pub entity: SyncEntity,
+    pub id: i64,
+    /// The row was deleted, or is no longer visible to the caller.
+    pub deleted: bool,
+    /// Current server state of the row; absent when deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChangesResponse {
+    /// Pass as `since` on the next call.
+    pub cursor: i64,
+    /// More changes are waiting; call again straight away.
+    pub has_more: bool,
+    /// Latest state of every row changed since the cursor, one entry per row.
+    /// Deleting a farm also removes its alerts.
+    pub changes: Vec<SyncChange>,
+}
+
+/// Mutation made on the device while offline. Updates and deletes carry the
+/// `updated_at` the device last saw; if the server copy has changed since,
+/// the mutation is not applied and the server copy is returned instead.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncOperation {
+    CreateTodo {
+        data: CreateTodoRequest,
+    },
+    UpdateTodo {
+        id: i64,
+        base_updated_at: DateTime<Utc>,
+        data: UpdateTodoRequest,
+    },
+    DeleteTodo {
+        id: i64,
+        base_updated_at: DateTime<Utc>,
+    },
+    AcknowledgeAlert {
+        id: i64,
+    },
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SyncMutation {
+    /// Device-chosen id echoed in the result, e.g. to map local records to server ids.
+    pub client_id: String,
+    #[serde(flatten)]
+    pub operation: SyncOperation,
+}
+
+const MAX_PUSH_MUTATIONS: usize = 500;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PushRequest {
+    /// Applied in order; a failed mutation does not stop the rest.
+    pub mutations: Vec<SyncMutation>,
+}
+
+/// Written out by hand: the derive needs `SyncMutation` to be serializable
+/// to report a length rule.
+impl Validate for PushRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.mutations.is_empty() || self.mutations.len() > MAX_PUSH_MUTATIONS {
+            errors.add(
+                "mutations",
+                ValidationError::new("length")
+                    .with_message(format!("must list between 1 and {} entries", MAX_PUSH_MUTATIONS).into()),
+            );
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MutationStatus {
+    Applied,
+    Conflict,
+    Rejected,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MutationResult {
+    pub client_id: String,
+    pub status: MutationStatus,
+    /// Server id of the affected row.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// Server state after applying, or the newer server copy on conflict.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushResponse {
+    pub results: Vec<MutationResult>,
+}
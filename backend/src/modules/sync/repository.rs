@@ -0,0 +1,84 @@
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::ChangeRow;
+
+/// Feed entries of `user_id` after `since`, stopping short of any entry
+/// written by a transaction that started before a still-running one: that
+/// transaction could yet commit an entry with a lower id. Only the user's own
+/// entries can hold their feed back, so the check stays on their rows.
+pub async fn list_changes(pool: &PgPool, user_id: i64, since: i64, limit: i64) -> Result<Vec<ChangeRow>, AppError> {
+    sqlx::query_as::<_, ChangeRow>(
+        r#"
+        WITH horizon AS (
+            SELECT pg_snapshot_xmin(pg_current_snapshot()) AS xmin
+        ), barrier AS (
+            SELECT MIN(c.id) AS id FROM sync_changes c, horizon h
+            WHERE c.user_id = $1 AND c.id > $2 AND c.txid >= h.xmin
+        )
+        SELECT c.id, c.entity, c.entity_id, c.deleted
+        FROM sync_changes c, barrier b
+        WHERE c.user_id = $1
+          AND c.id > $2
+          AND (b.id IS NULL OR c.id < b.id)
+        ORDER BY c.id
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Current rows as JSON, keyed by id. Rows not owned by `user_id` are left out.
+pub async fn farm_records(pool: &PgPool, user_id: i64, ids: &[i64]) -> Result<Vec<(i64, serde_json::Value)>, AppError> {
+    sqlx::query_as(
+        r#"
+        SELECT f.id,
+               (to_jsonb(f) - 'geometry' - 'search_vector')
+                   || jsonb_build_object('geojson', ST_AsGeoJSON(f.geometry)::jsonb)
+        FROM farms f
+        WHERE f.id = ANY($2) AND f.user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(ids)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn alert_records(pool: &PgPool, user_id: i64, ids: &[i64]) -> Result<Vec<(i64, serde_json::Value)>, AppError> {
+    sqlx::query_as(
+        r#"
+        SELECT a.id, to_jsonb(a) - 'search_vector'
+        FROM alerts a
+        JOIN farms f ON f.id = a.farm_id
+        WHERE a.id = ANY($2) AND f.user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(ids)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn todo_records(pool: &PgPool, user_id: i64, ids: &[i64]) -> Result<Vec<(i64, serde_json::Value)>, AppError> {
+    sqlx::query_as("SELECT t.id, to_jsonb(t) FROM todos t WHERE t.id = ANY($2) AND t.user_id = $1")
+        .bind(user_id)
+        .bind(ids)
+        .fetch_all(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn alert_owner(pool: &PgPool, alert_id: i64) -> Result<Option<i64>, AppError> {
+    sqlx::query_scalar("SELECT f.user_id FROM alerts a JOIN farms f ON f.id = a.farm_id WHERE a.id = $1")
+        .bind(alert_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use sqlx::PgPool;
+use crate::modules::{monitoring, todos};
+use crate::shared::error::{AppError, ErrorCode};
+use super::models::{
+    ChangeRow, ChangesResponse, MutationResult, MutationStatus, SyncChange, SyncEntity, SyncMutation, SyncOperation,
+};
+use super::repository;
+
+/// Reads up to `limit` feed entries after `since` and returns the current
+/// state of each row they touch.
+pub async fn changes(db: &PgPool, user_id: i64, since: i64, limit: i64) -> Result<ChangesResponse, AppError> {
+    let rows = repository::list_changes(db, user_id, since, limit).await?;
+    let has_more = rows.len() as i64 == limit;
+    let cursor = rows.last().map(|row| row.id).unwrap_or(since);
+
+    // A row edited several times since the cursor is reported once, in the
+    // position of its last change.
+    let mut latest: HashMap<(SyncEntity, i64), ChangeRow> = HashMap::new();
+    for row in rows {
+        latest.insert((row.entity, row.entity_id), row);
+    }
+    let mut latest: Vec<ChangeRow> = latest.into_values().collect();
+    latest.sort_by_key(|row| row.id);
+
+    let mut records = HashMap::new();
+    for entity in [SyncEntity::Farm, SyncEntity::Alert, SyncEntity::Todo] {
+        let ids: Vec<i64> = latest
+            .iter()
+            .filter(|row| row.entity == entity && !row.deleted)
+            .map(|row| row.entity_id)
+            .collect();
+        if ids.is_empty() {
+            continue;
+        }
+        for (id, record) in fetch_records(db, user_id, entity, &ids).await? {
+            records.insert((entity, id), record);
+        }
+    }
+
+    let changes = latest
+        .into_iter()
+        .map(|row| {
+            let record = records.remove(&(row.entity, row.entity_id));
+            SyncChange {
+                entity: row.entity,
+                id: row.entity_id,
+                deleted: record.is_none(),
+                record,
+            }
+        })
+        .collect();
+
+    Ok(ChangesResponse { cursor, has_more, changes })
+}
+
+async fn fetch_records(
+    db: &PgPool,
+    user_id: i64,
+    entity: SyncEntity,
+    ids: &[i64],
+) -> Result<Vec<(i64, serde_json::Value)>, AppError> {
+    match entity {
+        SyncEntity::Farm => repository::farm_records(db, user_id, ids).await,
+        SyncEntity::Alert => repository::alert_records(db, user_id, ids).await,
+        SyncEntity::Todo => repository::todo_records(db, user_id, ids).await,
+    }
+}
+
+async fn fetch_record(db: &PgPool, user_id: i64, entity: SyncEntity, id: i64) -> Result<Option<serde_json::Value>, AppError> {
+    Ok(fetch_records(db, user_id, entity, &[id]).await?.pop().map(|(_, record)| record))
+}
+
+/// Applies `mutation`, reporting failures in the result rather than as an error.
+pub async fn apply(db: &PgPool, user_id: i64, mutation: SyncMutation) -> MutationResult {
+    let client_id = mutation.client_id;
+    let result = match mutation.operation {
+        SyncOperation::CreateTodo { data } => create_todo(db, user_id, data).await,
+        SyncOperation::UpdateTodo { id, base_updated_at, data } => {
+            update_todo(db, user_id, id, base_updated_at, data).await
+        }
+        SyncOperation::DeleteTodo { id, base_updated_at } => delete_todo(db, user_id, id, base_updated_at).await,
+        SyncOperation::AcknowledgeAlert { id } => acknowledge_alert(db, user_id, id).await,
+    };
+
+    match result {
+        Ok((status, id, record)) => MutationResult { client_id, status, id: Some(id), record, error: None },
+        Err(e) => MutationResult {
+            client_id,
+            status: MutationStatus::Rejected,
+            id: None,
+            record: None,
+            error: Some(e.public_message()),
+        },
+    }
+}
+
+type Applied = (MutationStatus, i64, Option<serde_json::Value>);
+
+async fn create_todo(
+    db: &PgPool,
+    user_id: i64,
+    data: todos::models::CreateTodoRequest,
+) -> Result<Applied, AppError> {
+    let todo = todos::service::create_todo(db, user_id, data).await?;
+    let record = fetch_record(db, user_id, SyncEntity::Todo, todo.id).await?;
+    Ok((MutationStatus::Applied, todo.id, record))
+}
+
+async fn update_todo(
+    db: &PgPool,
+    user_id: i64,
+    id: i64,
+    base_updated_at: chrono::DateTime<chrono::Utc>,
+    data: todos::models::UpdateTodoRequest,
+) -> Result<Applied, AppError> {
+    let status = match todos::service::update_todo_if_unchanged(db, user_id, id, base_updated_at, data).await? {
+        Some(_) => MutationStatus::Applied,
+        None => MutationStatus::Conflict,
+    };
+    let record = fetch_record(db, user_id, SyncEntity::Todo, id).await?;
+    Ok((status, id, record))
+}
+
+/// Deleting a todo that is already gone succeeds, so a retried push is harmless.
+async fn delete_todo(
+    db: &PgPool,
+    user_id: i64,
+    id: i64,
+    base_updated_at: chrono::DateTime<chrono::Utc>,
+) -> Result<Applied, AppError> {
+    let deleted = match todos::service::delete_todo_if_unchanged(db, user_id, id, base_updated_at).await {
+        Ok(deleted) => deleted,
+        Err(AppError::Coded(ErrorCode::TodoNotFound, _)) => return Ok((MutationStatus::Applied, id, None)),
+        Err(e) => return Err(e),
+    };
+    if deleted {
+        return Ok((MutationStatus::Applied, id, None));
+    }
+
+    // Nothing was deleted: either the todo changed since the client's copy,
+    // or another request deleted it first.
+    match fetch_record(db, user_id, SyncEntity::Todo, id).await? {
+        Some(record) => Ok((MutationStatus::Conflict, id, Some(record))),
+        None => Ok((MutationStatus::Applied, id, None)),
+    }
+}
+
+/// Acknowledging is idempotent, so it never conflicts.
+async fn acknowledge_alert(db: &PgPool, user_id: i64, id: i64) -> Result<Applied, AppError> {
+    let owner = repository::alert_owner(db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::AlertNotFound, format!("Alert {} not found", id)))?;
+    if owner != user_id {
+        return Err(AppError::Unauthorized("Not authorized to access this alert".to_string()));
+    }
+
+    monitoring::repository::acknowledge_alert(id, db).await?;
+    let record = fetch_record(db, user_id, SyncEntity::Alert, id).await?;
+    Ok((MutationStatus::Applied, id, record))
+}
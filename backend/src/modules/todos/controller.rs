@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, State, Extension, Query},
+    Json,
+};
+use crate::shared::{AppState, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{Todo, CreateTodoRequest, UpdateTodoRequest, TodoQuery},
+    repository, service,
+};
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "todos",
+    request_body = CreateTodoRequest,
+    responses(
+        (status = 200, description = "Todo created", body = Todo),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 422, description = "Blank or overlong title", body = ErrorResponse),
+    ),
+)]
+pub async fn create_todo(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateTodoRequest>,
+) -> Result<Json<Todo>, AppError> {
+    let todo = service::create_todo(&state.db, claims.sub, payload).await?;
+    Ok(Json(todo))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "todos",
+    params(TodoQuery),
+    responses((status = 200, description = "Todos of the caller", body = [Todo])),
+)]
+pub async fn list_todos(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<TodoQuery>,
+) -> Result<Json<Vec<Todo>>, AppError> {
+    let todos = repository::list_by_user(&state.db, claims.sub, query.farm_id, query.completed).await?;
+    Ok(Json(todos))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "todos",
+    params(("id" = i64, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Todo details", body = Todo),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_todo(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<Json<Todo>, AppError> {
+    let todo = service::get_owned_todo(&state.db, claims.sub, id).await?;
+    Ok(Json(todo))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = "todos",
+    params(("id" = i64, Path, description = "Todo id")),
+    request_body = UpdateTodoRequest,
+    responses(
+        (status = 200, description = "Todo updated", body = Todo),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+        (status = 422, description = "Blank or overlong title", body = ErrorResponse),
+    ),
+)]
+pub async fn update_todo(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<UpdateTodoRequest>,
+) -> Result<Json<Todo>, AppError> {
+    let todo = service::update_todo(&state.db, claims.sub, id, payload).await?;
+    Ok(Json(todo))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/toggle",
+    tag = "todos",
+    params(("id" = i64, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Completion toggled", body = Todo),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+    ),
+)]
+pub async fn toggle_todo(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<Json<Todo>, AppError> {
+    let todo = service::toggle_todo(&state.db, claims.sub, id).await?;
+    Ok(Json(todo))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "todos",
+    params(("id" = i64, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Todo deleted"),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_todo(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    service::delete_todo(&state.db, claims.sub, id).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
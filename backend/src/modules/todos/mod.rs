@@ -0,0 +1,34 @@
+pub mod models;
+mod repository;
+pub mod service;
+mod controller;
+
+use axum::{routing::{get, post, put, delete}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(controller::create_todo))
+        .route("/", get(controller::list_todos))
+        .route("/{id}", get(controller::get_todo))
+        .route("/{id}", put(controller::update_todo))
+        .route("/{id}", delete(controller::delete_todo))
+        .route("/{id}/toggle", post(controller::toggle_todo))
+}
+
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::create_todo,
+    controller::list_todos,
+    controller::get_todo,
+    controller::update_todo,
+    controller::toggle_todo,
+    controller::delete_todo,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
\ No newline at end of file
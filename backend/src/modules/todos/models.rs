@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+use crate::shared::error::AppError;
+use validator::Validate;
+use crate::shared::validation::not_blank;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
+impl TodoPriority {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TodoPriority::Low => "low",
+            TodoPriority::Medium => "medium",
+            TodoPriority::High => "high",
+            TodoPriority::Urgent => "urgent",
+        }
+    }
+}
+
+impl TryFrom<String> for TodoPriority {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "low" => Ok(TodoPriority::Low),
+            "medium" => Ok(TodoPriority::Medium),
+            "high" => Ok(TodoPriority::High),
+            "urgent" => Ok(TodoPriority::Urgent),
+            other => Err(AppError::Validation(format!("Unknown todo priority: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Todo {
+    pub id: i64,
+    pub user_id: i64,
+    pub farm_id: Option<i64>,
+    pub title: String,
+    pub description: Option<String>,
+    #[sqlx(try_from = "String")]
+    pub priority: TodoPriority,
+    pub due_at: Option<DateTime<Utc>>,
+    pub completed: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateTodoRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255))]
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub farm_id: Option<i64>,
+    #[serde(default)]
+    pub priority: TodoPriority,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct UpdateTodoRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255))]
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub farm_id: Option<i64>,
+    pub priority: Option<TodoPriority>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub completed: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TodoQuery {
+    pub farm_id: Option<i64>,
+    pub completed: Option<bool>,
+}
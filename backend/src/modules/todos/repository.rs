@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use crate::shared::error::AppError;
+use super::models::{Todo, CreateTodoRequest, UpdateTodoRequest};
+
+const TODO_COLUMNS: &str =
+    "id, user_id, farm_id, title, description, priority, due_at, completed, completed_at, created_at, updated_at";
+
+pub async fn create(
+    pool: &PgPool,
+    user_id: i64,
+    todo: &CreateTodoRequest,
+) -> Result<Todo, AppError> {
+    sqlx::query_as::<_, Todo>(&format!(
+        r#"
+        INSERT INTO todos (user_id, farm_id, title, description, priority, due_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {TODO_COLUMNS}
+        "#
+    ))
+    .bind(user_id)
+    .bind(todo.farm_id)
+    .bind(&todo.title)
+    .bind(&todo.description)
+    .bind(todo.priority.as_str())
+    .bind(todo.due_at)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn get_by_id(pool: &PgPool, id: i64) -> Result<Option<Todo>, AppError> {
+    sqlx::query_as::<_, Todo>(&format!("SELECT {TODO_COLUMNS} FROM todos WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn list_by_user(
+    pool: &PgPool,
+    user_id: i64,
+    farm_id: Option<i64>,
+    completed: Option<bool>,
+) -> Result<Vec<Todo>, AppError> {
+    sqlx::query_as::<_, Todo>(&format!(
+        r#"
+        SELECT {TODO_COLUMNS}
+        FROM todos
+        WHERE user_id = $1
+          AND ($2::BIGINT IS NULL OR farm_id = $2)
+          AND ($3::BOOLEAN IS NULL OR completed = $3)
+        ORDER BY completed, due_at NULLS LAST, created_at DESC
+        "#
+    ))
+    .bind(user_id)
+    .bind(farm_id)
+    .bind(completed)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Applies `changes`, but only if the todo has not been modified since
+/// `unchanged_since` when given. `None` if no row matched.
+pub async fn update(
+    pool: &PgPool,
+    id: i64,
+    changes: &UpdateTodoRequest,
+    unchanged_since: Option<DateTime<Utc>>,
+) -> Result<Option<Todo>, AppError> {
+    sqlx::query_as::<_, Todo>(&format!(
+        r#"
+        UPDATE todos
+        SET title = COALESCE($2, title),
+            description = COALESCE($3, description),
+            farm_id = COALESCE($4, farm_id),
+            priority = COALESCE($5, priority),
+            due_at = COALESCE($6, due_at),
+            completed = COALESCE($7, completed),
+            completed_at = CASE
+                WHEN $7::BOOLEAN IS NULL THEN completed_at
+                WHEN $7 THEN COALESCE(completed_at, NOW())
+                ELSE NULL
+            END
+        WHERE id = $1 AND ($8::TIMESTAMPTZ IS NULL OR updated_at <= $8)
+        RETURNING {TODO_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(&changes.title)
+    .bind(&changes.description)
+    .bind(changes.farm_id)
+    .bind(changes.priority.as_ref().map(|p| p.as_str()))
+    .bind(changes.due_at)
+    .bind(changes.completed)
+    .bind(unchanged_since)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn toggle_completed(pool: &PgPool, id: i64) -> Result<Todo, AppError> {
+    sqlx::query_as::<_, Todo>(&format!(
+        r#"
+        UPDATE todos
+        SET completed = NOT completed,
+            completed_at = CASE WHEN completed THEN NULL ELSE NOW() END
+        WHERE id = $1
+        RETURNING {TODO_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Deletes the todo, but only if it has not been modified since
+/// `unchanged_since` when given. Whether a row was deleted.
+pub async fn delete(pool: &PgPool, id: i64, unchanged_since: Option<DateTime<Utc>>) -> Result<bool, AppError> {
+    let result = sqlx::query("DELETE FROM todos WHERE id = $1 AND ($2::TIMESTAMPTZ IS NULL OR updated_at <= $2)")
+        .bind(id)
+        .bind(unchanged_since)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn farm_belongs_to_user(pool: &PgPool, farm_id: i64, user_id: i64) -> Result<bool, AppError> {
+    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM farms WHERE id = $1 AND user_id = $2)")
+        .bind(farm_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(Into::into)
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use crate::shared::error::{AppError, ErrorCode};
+use super::models::{Todo, CreateTodoRequest, UpdateTodoRequest};
+use super::repository;
+
+pub async fn create_todo(
+    db: &PgPool,
+    user_id: i64,
+    request: CreateTodoRequest,
+) -> Result<Todo, AppError> {
+    if request.title.trim().is_empty() {
+        return Err(AppError::BadRequest("Todo title is required".to_string()));
+    }
+
+    if let Some(farm_id) = request.farm_id {
+        ensure_farm_access(db, farm_id, user_id).await?;
+    }
+
+    repository::create(db, user_id, &request).await
+}
+
+pub async fn update_todo(
+    db: &PgPool,
+    user_id: i64,
+    id: i64,
+    changes: UpdateTodoRequest,
+) -> Result<Todo, AppError> {
+    save_changes(db, user_id, id, changes, None)
+        .await?
+        .ok_or_else(|| not_found(id))
+}
+
+/// Applies `changes` in the same statement that checks the todo has not been
+/// modified since `base_updated_at`; `None` if it has.
+pub async fn update_todo_if_unchanged(
+    db: &PgPool,
+    user_id: i64,
+    id: i64,
+    base_updated_at: DateTime<Utc>,
+    changes: UpdateTodoRequest,
+) -> Result<Option<Todo>, AppError> {
+    save_changes(db, user_id, id, changes, Some(base_updated_at)).await
+}
+
+async fn save_changes(
+    db: &PgPool,
+    user_id: i64,
+    id: i64,
+    changes: UpdateTodoRequest,
+    unchanged_since: Option<DateTime<Utc>>,
+) -> Result<Option<Todo>, AppError> {
+    get_owned_todo(db, user_id, id).await?;
+
+    if changes.title.as_deref().is_some_and(|t| t.trim().is_empty()) {
+        return Err(AppError::BadRequest("Todo title cannot be empty".to_string()));
+    }
+
+    if let Some(farm_id) = changes.farm_id {
+        ensure_farm_access(db, farm_id, user_id).await?;
+    }
+
+    repository::update(db, id, &changes, unchanged_since).await
+}
+
+pub async fn toggle_todo(db: &PgPool, user_id: i64, id: i64) -> Result<Todo, AppError> {
+    get_owned_todo(db, user_id, id).await?;
+    repository::toggle_completed(db, id).await
+}
+
+pub async fn delete_todo(db: &PgPool, user_id: i64, id: i64) -> Result<(), AppError> {
+    get_owned_todo(db, user_id, id).await?;
+    if !repository::delete(db, id, None).await? {
+        return Err(not_found(id));
+    }
+    Ok(())
+}
+
+/// Deletes the todo in the same statement that checks it has not been
+/// modified since `base_updated_at`; `false` if it has, or is already gone.
+pub async fn delete_todo_if_unchanged(
+    db: &PgPool,
+    user_id: i64,
+    id: i64,
+    base_updated_at: DateTime<Utc>,
+) -> Result<bool, AppError> {
+    get_owned_todo(db, user_id, id).await?;
+    repository::delete(db, id, Some(base_updated_at)).await
+}
+
+pub async fn get_owned_todo(db: &PgPool, user_id: i64, id: i64) -> Result<Todo, AppError> {
+    let todo = repository::get_by_id(db, id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+
+    if todo.user_id != user_id {
+        return Err(AppError::Unauthorized("Not authorized to access this todo".to_string()));
+    }
+
+    Ok(todo)
+}
+
+fn not_found(id: i64) -> AppError {
+    AppError::Coded(ErrorCode::TodoNotFound, format!("Todo {} not found", id))
+}
+
+async fn ensure_farm_access(db: &PgPool, farm_id: i64, user_id: i64) -> Result<(), AppError> {
+    if !repository::farm_belongs_to_user(db, farm_id, user_id).await? {
+        return Err(AppError::Coded(ErrorCode::FarmNotFound, format!("Farm {} not found", farm_id)));
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Path, State, Extension, Query},
+    Json,
+};
+use crate::shared::{AppState, audit::AuditDetails, error::{AppError, ErrorResponse}, validation::ValidatedJson};
+use crate::modules::auth::models::Claims;
+use super::{
+    models::{CreateWebhookRequest, DeliveryQuery, WebhookDelivery, WebhookResponse},
+    repository, service,
+};
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Subscription created; the signing secret is only returned here", body = WebhookResponse),
+        (status = 400, description = "Url is not https or its host is not a public address", body = ErrorResponse),
+        (status = 422, description = "No events, or a secret shorter than 16 characters", body = ErrorResponse),
+    ),
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateWebhookRequest>,
+) -> Result<(Extension<AuditDetails>, Json<WebhookResponse>), AppError> {
+    let response = service::create_subscription(&state.db, claims.sub, payload).await?;
+
+    let audit = AuditDetails::new("webhook.create", "webhook", Some(response.id))
+        .after(&serde_json::json!({ "url": response.url, "events": response.events }));
+
+    Ok((Extension(audit), Json(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "webhooks",
+    responses((status = 200, description = "Webhook subscriptions of the caller", body = [WebhookResponse])),
+)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<WebhookResponse>>, AppError> {
+    let subscriptions = repository::list_subscriptions(&state.db, claims.sub).await?;
+    Ok(Json(subscriptions.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "webhooks",
+    params(("id" = i64, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Subscription deleted"),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> Result<(Extension<AuditDetails>, Json<serde_json::Value>), AppError> {
+    let existing = service::get_owned_subscription(&state.db, claims.sub, id).await?;
+    repository::delete_subscription(&state.db, id).await?;
+
+    let audit = AuditDetails::new("webhook.delete", "webhook", Some(id))
+        .before(&serde_json::json!({ "url": existing.url, "events": existing.events }));
+
+    Ok((Extension(audit), Json(serde_json::json!({ "success": true }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = i64, Path, description = "Webhook id"), DeliveryQuery),
+    responses(
+        (status = 200, description = "Recent delivery attempts, newest first", body = [WebhookDelivery]),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    ),
+)]
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+    Query(query): Query<DeliveryQuery>,
+) -> Result<Json<Vec<WebhookDelivery>>, AppError> {
+    service::get_owned_subscription(&state.db, claims.sub, id).await?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let deliveries = repository::list_deliveries(&state.db, id, limit).await?;
+    Ok(Json(deliveries))
+}
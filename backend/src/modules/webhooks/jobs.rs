@@ -0,0 +1,22 @@
+use sqlx::PgPool;
+use crate::shared::worker::{interval_from_env, spawn_periodic};
+use super::{service, target};
+
+const DELIVERY_JOB_DEFAULT_SECS: u64 = 15;
+
+pub fn spawn_delivery_job(db: PgPool) {
+    let period = interval_from_env("WEBHOOK_DELIVERY_INTERVAL_SECS", DELIVERY_JOB_DEFAULT_SECS);
+    let client = target::client();
+
+    spawn_periodic("webhook_delivery", period, move || {
+        let db = db.clone();
+        let client = client.clone();
+        async move {
+            let sent = service::deliver_due(&db, &client).await?;
+            if sent > 0 {
+                tracing::info!("Attempted {} webhook deliveries", sent);
+            }
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,33 @@
+mod models;
+mod repository;
+pub mod service;
+mod controller;
+pub mod jobs;
+mod target;
+
+pub use models::WebhookEvent;
+
+use axum::{routing::{get, post, delete}, Router};
+use utoipa::OpenApi;
+use crate::shared::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(controller::create_webhook))
+        .route("/", get(controller::list_webhooks))
+        .route("/{id}", delete(controller::delete_webhook))
+        .route("/{id}/deliveries", get(controller::list_deliveries))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    controller::create_webhook,
+    controller::list_webhooks,
+    controller::delete_webhook,
+    controller::list_deliveries,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
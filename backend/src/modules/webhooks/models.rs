@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEvent {
+    #[serde(rename = "alert.created")]
+    AlertCreated,
+    #[serde(rename = "analysis.completed")]
+    AnalysisCompleted,
+    #[serde(rename = "report.ready")]
+    ReportReady,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WebhookEvent::AlertCreated => "alert.created",
+            WebhookEvent::AnalysisCompleted => "analysis.completed",
+            WebhookEvent::ReportReady => "report.ready",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "alert.created" => Some(WebhookEvent::AlertCreated),
+            "analysis.completed" => Some(WebhookEvent::AnalysisCompleted),
+            "report.ready" => Some(WebhookEvent::ReportReady),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub user_id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    /// Only returned once, when the subscription is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<WebhookSubscription> for WebhookResponse {
+    fn from(sub: WebhookSubscription) -> Self {
+        Self {
+            id: sub.id,
+            url: sub.url,
+            events: sub.events,
+            active: sub.active,
+            secret: None,
+            created_at: sub.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    #[validate(length(min = 1))]
+    pub events: Vec<WebhookEvent>,
+    /// Signing secret; generated when omitted.
+    #[serde(default)]
+    #[validate(length(min = 16))]
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A due delivery joined with the subscription details needed to send it.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PendingDelivery {
+    pub id: i64,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeliveryQuery {
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,182 @@
+use sqlx::{PgConnection, PgPool};
+use sqlx::types::chrono::{DateTime, Utc};
+use crate::shared::error::AppError;
+use super::models::{PendingDelivery, WebhookDelivery, WebhookSubscription};
+
+const SUBSCRIPTION_COLUMNS: &str = "id, user_id, url, events, active, created_at";
+
+pub async fn create_subscription(
+    pool: &PgPool,
+    user_id: i64,
+    url: &str,
+    secret: &str,
+    events: &[String],
+) -> Result<WebhookSubscription, AppError> {
+    sqlx::query_as::<_, WebhookSubscription>(&format!(
+        r#"
+        INSERT INTO webhook_subscriptions (user_id, url, secret, events)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {SUBSCRIPTION_COLUMNS}
+        "#
+    ))
+    .bind(user_id)
+    .bind(url)
+    .bind(secret)
+    .bind(events)
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn get_subscription(pool: &PgPool, id: i64) -> Result<Option<WebhookSubscription>, AppError> {
+    sqlx::query_as::<_, WebhookSubscription>(&format!(
+        "SELECT {SUBSCRIPTION_COLUMNS} FROM webhook_subscriptions WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn list_subscriptions(pool: &PgPool, user_id: i64) -> Result<Vec<WebhookSubscription>, AppError> {
+    sqlx::query_as::<_, WebhookSubscription>(&format!(
+        "SELECT {SUBSCRIPTION_COLUMNS} FROM webhook_subscriptions WHERE user_id = $1 ORDER BY created_at DESC"
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn delete_subscription(pool: &PgPool, id: i64) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Queues deliveries for the owner of `farm_id`.
+pub async fn enqueue_for_farm(
+    conn: &mut PgConnection,
+    farm_id: i64,
+    event: &str,
+    payload: &serde_json::Value,
+) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (subscription_id, event, payload)
+        SELECT s.id, $2, $3
+        FROM webhook_subscriptions s
+        JOIN farms f ON f.user_id = s.user_id
+        WHERE f.id = $1 AND s.active AND $2 = ANY(s.events)
+        "#
+    )
+    .bind(farm_id)
+    .bind(event)
+    .bind(payload)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Claims due deliveries by pushing their next attempt out by `lease_secs`, so
+/// an overlapping run does not send them twice.
+pub async fn claim_due_deliveries(
+    pool: &PgPool,
+    limit: i64,
+    lease_secs: i64,
+) -> Result<Vec<PendingDelivery>, AppError> {
+    sqlx::query_as::<_, PendingDelivery>(
+        r#"
+        WITH due AS (
+            SELECT id
+            FROM webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE webhook_deliveries d
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        FROM due, webhook_subscriptions s
+        WHERE d.id = due.id AND s.id = d.subscription_id
+        RETURNING d.id, d.event, d.payload, d.attempts, s.url, s.secret
+        "#
+    )
+    .bind(limit)
+    .bind(lease_secs as f64)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+pub async fn mark_delivered(pool: &PgPool, id: i64, response_status: i32) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'succeeded', attempts = attempts + 1, response_status = $2,
+            last_error = NULL, delivered_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .bind(response_status)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Records a failed attempt; `next_attempt_at = None` gives up on the delivery.
+pub async fn mark_attempt_failed(
+    pool: &PgPool,
+    id: i64,
+    response_status: Option<i32>,
+    error: &str,
+    next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET attempts = attempts + 1,
+            response_status = $2,
+            last_error = $3,
+            status = CASE WHEN $4::TIMESTAMPTZ IS NULL THEN 'failed' ELSE 'pending' END,
+            next_attempt_at = COALESCE($4, next_attempt_at)
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .bind(response_status)
+    .bind(error)
+    .bind(next_attempt_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_deliveries(
+    pool: &PgPool,
+    subscription_id: i64,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, AppError> {
+    sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT id, subscription_id, event, payload, status, attempts, response_status,
+               last_error, next_attempt_at, delivered_at, created_at
+        FROM webhook_deliveries
+        WHERE subscription_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#
+    )
+    .bind(subscription_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
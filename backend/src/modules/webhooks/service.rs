@@ -0,0 +1,150 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sqlx::{PgConnection, PgPool};
+use sqlx::types::chrono::{DateTime, Utc};
+use std::time::Duration;
+use crate::shared::{error::{AppError, ErrorCode}, signing};
+use super::models::{CreateWebhookRequest, PendingDelivery, WebhookEvent, WebhookResponse, WebhookSubscription};
+use super::{repository, target};
+
+pub const SIGNATURE_HEADER: &str = "x-bioradar-signature";
+pub const EVENT_HEADER: &str = "x-bioradar-event";
+pub const DELIVERY_HEADER: &str = "x-bioradar-delivery";
+
+const MAX_ATTEMPTS: i32 = 8;
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+const DELIVERY_BATCH_SIZE: i64 = 50;
+const DELIVERY_LEASE_SECS: i64 = 120;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn create_subscription(
+    db: &PgPool,
+    user_id: i64,
+    request: CreateWebhookRequest,
+) -> Result<WebhookResponse, AppError> {
+    let url = request.url.trim();
+    target::check_url(url).await.map_err(AppError::Validation)?;
+
+    let secret = request.secret.unwrap_or_else(generate_secret);
+
+    let mut events: Vec<String> = request.events.iter().map(|e| e.as_str().to_string()).collect();
+    events.sort();
+    events.dedup();
+
+    let subscription = repository::create_subscription(db, user_id, url, &secret, &events).await?;
+
+    Ok(WebhookResponse {
+        secret: Some(secret),
+        ..subscription.into()
+    })
+}
+
+pub async fn get_owned_subscription(
+    db: &PgPool,
+    user_id: i64,
+    id: i64,
+) -> Result<WebhookSubscription, AppError> {
+    let subscription = repository::get_subscription(db, id)
+        .await?
+        .ok_or_else(|| AppError::Coded(ErrorCode::WebhookNotFound, "Webhook not found".to_string()))?;
+
+    if subscription.user_id != user_id {
+        return Err(AppError::Unauthorized("Access denied".to_string()));
+    }
+
+    Ok(subscription)
+}
+
+/// Queues `event` for every subscriber owning `farm_id`. Called by the outbox
+/// relay; domain code records events with `events::record` instead.
+pub async fn enqueue_for_farm(
+    conn: &mut PgConnection,
+    farm_id: i64,
+    event: WebhookEvent,
+    data: serde_json::Value,
+    occurred_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let payload = envelope(event, data, occurred_at);
+    let queued = repository::enqueue_for_farm(conn, farm_id, event.as_str(), &payload).await?;
+    if queued > 0 {
+        tracing::debug!("Queued {} {} webhook deliveries", queued, event.as_str());
+    }
+
+    Ok(())
+}
+
+/// Sends every due delivery once, rescheduling failures with exponential backoff.
+pub async fn deliver_due(db: &PgPool, client: &reqwest::Client) -> Result<usize, AppError> {
+    let due = repository::claim_due_deliveries(db, DELIVERY_BATCH_SIZE, DELIVERY_LEASE_SECS).await?;
+    let count = due.len();
+
+    for delivery in due {
+        match send(client, &delivery).await {
+            Ok(status) => repository::mark_delivered(db, delivery.id, status).await?,
+            Err((status, error)) => {
+                let attempt = delivery.attempts + 1;
+                let next_attempt_at = (attempt < MAX_ATTEMPTS)
+                    .then(|| chrono::Utc::now() + chrono::Duration::seconds(backoff_secs(attempt)));
+
+                if next_attempt_at.is_none() {
+                    tracing::warn!("Webhook delivery {} failed permanently after {} attempts: {}", delivery.id, attempt, error);
+                }
+
+                repository::mark_attempt_failed(db, delivery.id, status, &error, next_attempt_at).await?;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Hex-encoded HMAC-SHA256 of the raw request body under the subscription's
+/// own secret, as receivers verify it.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    signing::sign_with_key(secret.as_bytes(), body)
+}
+
+async fn send(client: &reqwest::Client, delivery: &PendingDelivery) -> Result<i32, (Option<i32>, String)> {
+    // The host may have been re-pointed since the subscription was created.
+    let url = target::check_url(&delivery.url).await.map_err(|e| (None, e))?;
+    let body = serde_json::to_vec(&delivery.payload).map_err(|e| (None, e.to_string()))?;
+    let signature = format!("sha256={}", sign(&delivery.secret, &body));
+
+    let response = client
+        .post(url)
+        .timeout(REQUEST_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (None, e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16() as i32)
+    } else {
+        Err((Some(status.as_u16() as i32), format!("Endpoint responded with {}", status)))
+    }
+}
+
+fn backoff_secs(attempt: i32) -> i64 {
+    let exp = (attempt - 1).clamp(0, 16) as u32;
+    (BASE_BACKOFF_SECS * 2i64.pow(exp)).min(MAX_BACKOFF_SECS)
+}
+
+fn envelope(event: WebhookEvent, data: serde_json::Value, occurred_at: DateTime<Utc>) -> serde_json::Value {
+    serde_json::json!({
+        "event": event.as_str(),
+        "created_at": occurred_at,
+        "data": data,
+    })
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
@@ -0,0 +1,110 @@
+//! Webhook URLs are chosen by users but requested by the server, so they must
+//! not reach loopback, private or link-local addresses (the database, cloud
+//! metadata endpoints, other internal services). Checked when a subscription
+//! is created and again on every delivery; the delivery client also refuses
+//! to connect to such addresses whatever DNS answers by then.
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{redirect, Client, Url};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+/// Parses `url` and checks it is https and every address its host resolves
+/// to is public. Returns the reason it is not as the error.
+pub async fn check_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|_| "Webhook url is not a valid URL".to_string())?;
+    if parsed.scheme() != "https" {
+        return Err("Webhook url must use https".to_string());
+    }
+    let host = parsed.host_str().ok_or_else(|| "Webhook url has no host".to_string())?;
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    // `host_str` keeps the brackets of IPv6 literals.
+    if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        return if is_public(ip) { Ok(parsed) } else { Err(not_public(host)) };
+    }
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| format!("Webhook host {} does not resolve", host))?
+        .collect();
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_public(addr.ip())) {
+        return Err(not_public(host));
+    }
+
+    Ok(parsed)
+}
+
+/// The delivery client: no redirects, which could lead anywhere, and name
+/// resolution that drops non-public addresses.
+pub fn client() -> Client {
+    Client::builder()
+        .redirect(redirect::Policy::none())
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to build the webhook client: {}", e);
+            Client::new()
+        })
+}
+
+/// Resolves through the system resolver, keeping only public addresses.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(not_public(&host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+fn not_public(host: &str) -> String {
+    format!("Webhook host {} is not a public address", host)
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        // Shared address space (carrier-grade NAT), benchmarking and reserved.
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 198 && (b == 18 || b == 19))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let [first, second, ..] = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // Documentation, and NAT64 which can reach IPv4 private ranges.
+        || (first == 0x2001 && second == 0x0db8)
+        || (first == 0x0064 && second == 0xff9b))
+}
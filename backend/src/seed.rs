@@ -0,0 +1,326 @@
+//! Sample data for new deployments and frontend work: users, farms along the
+//! Mekong Delta coast, six months of satellite readings with the alerts they
+//! would have raised, crop seasons and todos. Reports are generated on
+//! request from this data, so none are stored. Values are derived from a
+//! fixed seed, so every run produces the same dashboard.
+
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
+use crate::modules::{auth, farm_mgmt, monitoring::{models::AlertSeverity, rules::RuleReason}};
+use crate::shared::i18n::{t, Language};
+
+/// Shared by every demo account; printed when seeding finishes.
+pub const DEMO_PASSWORD: &str = "bioradar-demo";
+const DEMO_SOURCE: &str = "demo";
+/// Sentinel-2 revisit interval.
+const READING_INTERVAL_DAYS: i64 = 5;
+const HISTORY_DAYS: i64 = 180;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Demo,
+}
+
+impl std::str::FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "demo" => Ok(Profile::Demo),
+            other => Err(format!("Unknown seed profile '{}'; available: demo", other)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SeedSummary {
+    pub users: usize,
+    pub farms: usize,
+    pub readings: usize,
+    pub alerts: usize,
+    pub todos: usize,
+}
+
+struct DemoUser {
+    email: &'static str,
+    role: &'static str,
+    plan: &'static str,
+}
+
+const USERS: &[DemoUser] = &[
+    DemoUser { email: "admin@demo.bioradar.example", role: "admin", plan: "pro" },
+    DemoUser { email: "farmer.bentre@demo.bioradar.example", role: "farmer", plan: "pro" },
+    DemoUser { email: "farmer.camau@demo.bioradar.example", role: "farmer", plan: "free" },
+];
+
+struct DemoFarm {
+    /// Index into `USERS`.
+    owner: usize,
+    name: &'static str,
+    region: &'static str,
+    crop: &'static str,
+    /// Centre of the boundary as longitude, latitude.
+    centre: (f64, f64),
+    /// Width and height of the plot in metres before rotation.
+    size: (f64, f64),
+    rotation_degrees: f64,
+    /// Typical NDSI outside the dry season.
+    base_ndsi: f64,
+    /// How far the dry-season peak lifts the NDSI; larger nearer the coast.
+    dry_season_rise: f64,
+}
+
+const FARMS: &[DemoFarm] = &[
+    DemoFarm {
+        owner: 1, name: "Ruộng lúa Ba Tri", region: "Bến Tre", crop: "rice",
+        centre: (106.598, 10.041), size: (420.0, 260.0), rotation_degrees: 12.0,
+        base_ndsi: -0.08, dry_season_rise: 0.26,
+    },
+    DemoFarm {
+        owner: 1, name: "Ao tôm Thạnh Phú", region: "Bến Tre", crop: "shrimp",
+        centre: (106.521, 9.947), size: (300.0, 220.0), rotation_degrees: -8.0,
+        base_ndsi: 0.12, dry_season_rise: 0.18,
+    },
+    DemoFarm {
+        owner: 1, name: "Vườn sầu riêng Chợ Lách", region: "Bến Tre", crop: "durian",
+        centre: (106.163, 10.254), size: (180.0, 140.0), rotation_degrees: 30.0,
+        base_ndsi: -0.15, dry_season_rise: 0.12,
+    },
+    DemoFarm {
+        owner: 1, name: "Ruộng lúa Cầu Ngang", region: "Trà Vinh", crop: "rice",
+        centre: (106.452, 9.803), size: (380.0, 300.0), rotation_degrees: 4.0,
+        base_ndsi: -0.06, dry_season_rise: 0.22,
+    },
+    DemoFarm {
+        owner: 2, name: "Ruộng lúa Trần Đề", region: "Sóc Trăng", crop: "rice",
+        centre: (106.184, 9.502), size: (450.0, 320.0), rotation_degrees: -15.0,
+        base_ndsi: -0.05, dry_season_rise: 0.24,
+    },
+    DemoFarm {
+        owner: 2, name: "Ao tôm Hòa Bình", region: "Bạc Liêu", crop: "shrimp",
+        centre: (105.624, 9.253), size: (260.0, 260.0), rotation_degrees: 20.0,
+        base_ndsi: 0.15, dry_season_rise: 0.15,
+    },
+    DemoFarm {
+        owner: 2, name: "Ao tôm Đầm Dơi", region: "Cà Mau", crop: "shrimp",
+        centre: (105.198, 8.981), size: (340.0, 240.0), rotation_degrees: -25.0,
+        base_ndsi: 0.18, dry_season_rise: 0.14,
+    },
+    DemoFarm {
+        owner: 1, name: "Ruộng lúa An Biên", region: "Kiên Giang", crop: "rice",
+        centre: (105.081, 9.801), size: (400.0, 280.0), rotation_degrees: 8.0,
+        base_ndsi: -0.07, dry_season_rise: 0.2,
+    },
+];
+
+/// Seeds `profile` in one transaction. Returns `None` without writing when
+/// the profile's accounts already exist.
+pub async fn run(db: &PgPool, profile: Profile) -> anyhow::Result<Option<SeedSummary>> {
+    match profile {
+        Profile::Demo => demo(db).await,
+    }
+}
+
+async fn demo(db: &PgPool) -> anyhow::Result<Option<SeedSummary>> {
+    let existing: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE email = $1)")
+        .bind(USERS[0].email)
+        .fetch_one(db)
+        .await?;
+    if existing {
+        return Ok(None);
+    }
+
+    let password_hash = auth::service::hash_password(DEMO_PASSWORD)?;
+    let mut summary = SeedSummary::default();
+    let mut tx = db.begin().await?;
+
+    let mut user_ids = Vec::with_capacity(USERS.len());
+    for user in USERS {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO users (email, password_hash, role, plan, email_verified_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            RETURNING id
+            "#,
+        )
+        .bind(user.email)
+        .bind(&password_hash)
+        .bind(user.role)
+        .bind(user.plan)
+        .fetch_one(&mut *tx)
+        .await?;
+        user_ids.push(id);
+        summary.users += 1;
+    }
+
+    let mut rng = Rng(0x5EED_B104_ADA4);
+    for farm in FARMS {
+        let owner = user_ids[farm.owner];
+        let created = farm_mgmt::insert_farm(&mut tx, owner, farm.name, Some(farm.region), &boundary(farm)).await?;
+        summary.farms += 1;
+
+        seed_crop(&mut tx, created.id, farm).await?;
+        let (readings, alerts) = seed_history(&mut tx, created.id, farm, &mut rng).await?;
+        summary.readings += readings;
+        summary.alerts += alerts;
+        summary.todos += seed_todos(&mut tx, owner, created.id, farm).await?;
+    }
+
+    tx.commit().await?;
+    Ok(Some(summary))
+}
+
+/// An irregular pentagon around the farm's centre, exterior ring
+/// counter-clockwise as RFC 7946 expects.
+fn boundary(farm: &DemoFarm) -> String {
+    let (w, h) = (farm.size.0 / 2.0, farm.size.1 / 2.0);
+    let corners = [(-w, -h), (w, -h * 0.85), (w * 1.08, h), (w * 0.1, h * 1.15), (-w, h * 0.9)];
+
+    let (sin, cos) = farm.rotation_degrees.to_radians().sin_cos();
+    let (lon, lat) = farm.centre;
+    let metres_per_degree_lon = 111_320.0 * lat.to_radians().cos();
+    let metres_per_degree_lat = 110_540.0;
+
+    let mut ring: Vec<[f64; 2]> = corners
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = (x * cos - y * sin, x * sin + y * cos);
+            [lon + x / metres_per_degree_lon, lat + y / metres_per_degree_lat]
+        })
+        .collect();
+    ring.push(ring[0]);
+
+    serde_json::json!({ "type": "Polygon", "coordinates": [ring] }).to_string()
+}
+
+async fn seed_crop(conn: &mut PgConnection, farm_id: i64, farm: &DemoFarm) -> anyhow::Result<()> {
+    sqlx::query("INSERT INTO farm_alert_rules (farm_id, crop_type) VALUES ($1, $2)")
+        .bind(farm_id)
+        .bind(farm.crop)
+        .execute(&mut *conn)
+        .await?;
+
+    if farm.crop == "rice" {
+        let planted = Utc::now().date_naive() - Duration::days(55);
+        sqlx::query(
+            r#"
+            INSERT INTO crop_seasons (farm_id, crop_type, variety, planting_date, expected_harvest_date, growth_stage)
+            VALUES ($1, 'rice', 'OM5451', $2, $3, 'vegetative')
+            "#,
+        )
+        .bind(farm_id)
+        .bind(planted)
+        .bind(planted + Duration::days(100))
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Readings every few days over the last six months, rising through the dry
+/// season (peaking in March, when seawater reaches furthest inland), plus an
+/// alert for each reading well above the farm's usual level.
+async fn seed_history(
+    conn: &mut PgConnection,
+    farm_id: i64,
+    farm: &DemoFarm,
+    rng: &mut Rng,
+) -> anyhow::Result<(usize, usize)> {
+    let now = Utc::now();
+    let threshold = farm.base_ndsi + farm.dry_season_rise * 0.6;
+    let mut times = Vec::new();
+    let mut values = Vec::new();
+    let mut alerts = 0;
+
+    for days_ago in (0..HISTORY_DAYS).rev().step_by(READING_INTERVAL_DAYS as usize) {
+        let recorded_at = now - Duration::days(days_ago);
+        let day_of_year = chrono::Datelike::ordinal(&recorded_at) as f64;
+        let season = ((day_of_year - 75.0) / 365.0 * std::f64::consts::TAU).cos().max(0.0);
+        let ndsi = (farm.base_ndsi + farm.dry_season_rise * season + rng.noise(0.03)).clamp(-1.0, 1.0);
+        times.push(recorded_at);
+        values.push(ndsi);
+
+        if ndsi <= threshold {
+            continue;
+        }
+        let severity = match ndsi - threshold {
+            d if d > 0.1 => AlertSeverity::Critical,
+            d if d > 0.05 => AlertSeverity::High,
+            _ => AlertSeverity::Medium,
+        };
+        let reason = RuleReason::NdsiThreshold { ndsi, threshold }.message(Language::En);
+        sqlx::query(
+            r#"
+            INSERT INTO alerts (farm_id, severity, message, metadata, detected_at, acknowledged, acknowledged_at)
+            VALUES ($1, $2, $3, $4, $5, $6, CASE WHEN $6 THEN $5 + INTERVAL '6 hours' END)
+            "#,
+        )
+        .bind(farm_id)
+        .bind(severity.as_str())
+        .bind(t(Language::En, "alert.salinity_anomaly", &[
+            ("ndsi", format!("{:.4}", ndsi)),
+            ("reasons", reason.clone()),
+        ]))
+        .bind(serde_json::json!({
+            "current_ndsi": ndsi,
+            "threshold": threshold,
+            "crop_type": farm.crop,
+            "reasons": [reason],
+            "demo": true,
+        }))
+        .bind(recorded_at)
+        .bind(days_ago > 14)
+        .execute(&mut *conn)
+        .await?;
+        alerts += 1;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO salinity_logs (farm_id, ndsi_value, source, recorded_at)
+        SELECT $1, r.ndsi::NUMERIC(8, 6), $2, r.recorded_at
+        FROM UNNEST($3::DOUBLE PRECISION[], $4::TIMESTAMPTZ[]) AS r(ndsi, recorded_at)
+        "#,
+    )
+    .bind(farm_id)
+    .bind(DEMO_SOURCE)
+    .bind(&values)
+    .bind(&times)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok((values.len(), alerts))
+}
+
+async fn seed_todos(conn: &mut PgConnection, user_id: i64, farm_id: i64, farm: &DemoFarm) -> anyhow::Result<usize> {
+    let tasks: &[(&str, &str, i64)] = match farm.crop {
+        "rice" => &[("Đo độ mặn nước kênh trước khi bơm", "high", 2), ("Kiểm tra cống ngăn mặn", "medium", 7)],
+        "shrimp" => &[("Đo độ mặn ao nuôi", "high", 1), ("Thay nước ao khi triều lên", "medium", 4)],
+        _ => &[("Trữ nước ngọt cho mùa khô", "urgent", 3)],
+    };
+
+    for (title, priority, due_in_days) in tasks {
+        sqlx::query("INSERT INTO todos (user_id, farm_id, title, priority, due_at) VALUES ($1, $2, $3, $4, $5)")
+            .bind(user_id)
+            .bind(farm_id)
+            .bind(format!("{} – {}", title, farm.name))
+            .bind(priority)
+            .bind(Utc::now() + Duration::days(*due_in_days))
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(tasks.len())
+}
+
+/// xorshift64; good enough for plausible noise without a dependency.
+struct Rng(u64);
+
+impl Rng {
+    /// Uniform in `-amplitude..amplitude`.
+    fn noise(&mut self, amplitude: f64) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        ((self.0 >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0) * amplitude
+    }
+}
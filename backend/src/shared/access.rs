@@ -0,0 +1,12 @@
+//! Role checks shared by the handlers.
+
+use crate::modules::auth::models::Claims;
+use crate::shared::error::{AppError, AppResult, ErrorCode};
+
+/// Fails with 403 unless the caller is an admin.
+pub fn require_admin(claims: &Claims) -> AppResult<()> {
+    if !claims.is_admin() {
+        return Err(AppError::Coded(ErrorCode::Forbidden, "Admin role required".to_string()));
+    }
+    Ok(())
+}
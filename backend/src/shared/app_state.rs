@@ -1,20 +1,48 @@
 use sqlx::PgPool;
 use std::sync::Arc;
-use crate::modules::monitoring::ai::engine::AiEngine;
+use crate::modules::monitoring::ai::{
+    batcher::InferenceBatcher,
+    engine::AiEngine,
+    registry::{LoadedModel, ModelRegistry, UNREGISTERED_VERSION},
+};
+use crate::shared::{
+    config,
+    notifications::NotificationDispatcher,
+    rate_limit::RateLimiter,
+    storage::{self, ObjectStore},
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
-    pub ai_engine: Option<Arc<AiEngine>>,
+    pub models: Arc<ModelRegistry>,
+    pub batcher: InferenceBatcher,
+    pub notifier: NotificationDispatcher,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub storage: Arc<dyn ObjectStore>,
 }
 
 impl AppState {
+    /// Starts the inference batcher, so it must run inside the Tokio runtime.
     pub fn new(db: PgPool) -> Self {
-        Self { db, ai_engine: None }
+        let config = config::get();
+
+        Self {
+            db,
+            models: Arc::new(ModelRegistry::default()),
+            batcher: InferenceBatcher::spawn(config.ai_batch_size, config.ai_batch_wait),
+            notifier: NotificationDispatcher::default(),
+            rate_limiter: Arc::new(RateLimiter::default()),
+            storage: storage::store_from_env(),
+        }
     }
 
-    pub fn with_ai_engine(mut self, engine: AiEngine) -> Self {
-        self.ai_engine = Some(Arc::new(engine));
+    pub fn with_ai_engine(self, engine: AiEngine) -> Self {
+        self.models.set_active(Some(LoadedModel {
+            id: None,
+            version: UNREGISTERED_VERSION.to_string(),
+            engine,
+        }));
         self
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,110 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use crate::modules::auth::models::Claims;
+use crate::shared::{AppState, error::AppResult};
+
+/// Extra context a handler can attach to its response (as an axum
+/// `Extension`) so the audit middleware records the affected entity and,
+/// where cheap to produce, its state before and after the change.
+#[derive(Debug, Clone)]
+pub struct AuditDetails {
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<i64>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditDetails {
+    pub fn new(action: &str, entity_type: &str, entity_id: Option<i64>) -> Self {
+        Self {
+            action: action.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn before<T: serde::Serialize>(mut self, state: &T) -> Self {
+        self.before = serde_json::to_value(state).ok();
+        self
+    }
+
+    pub fn after<T: serde::Serialize>(mut self, state: &T) -> Self {
+        self.after = serde_json::to_value(state).ok();
+        self
+    }
+}
+
+pub async fn audit_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let method = req.method().clone();
+    if !matches!(method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE) {
+        return next.run(req).await;
+    }
+
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let user_id = req.extensions().get::<Claims>().map(|c| c.sub);
+
+    let response = next.run(req).await;
+
+    if let Some(user_id) = user_id {
+        if response.status().is_success() {
+            let details = response.extensions().get::<AuditDetails>();
+            let status = response.status().as_u16() as i16;
+            if let Err(e) = record(&state.db, user_id, &method, &route, status, details).await {
+                tracing::warn!("Failed to write audit log for {} {}: {}", method, route, e);
+            }
+        }
+    }
+
+    response
+}
+
+async fn record(
+    db: &PgPool,
+    user_id: i64,
+    method: &Method,
+    route: &str,
+    status: i16,
+    details: Option<&AuditDetails>,
+) -> AppResult<()> {
+    let action = details
+        .map(|d| d.action.clone())
+        .unwrap_or_else(|| format!("{} {}", method, route));
+
+    sqlx::query(
+        r#"
+        INSERT INTO audit_logs
+            (user_id, method, route, action, entity_type, entity_id, status_code, before_state, after_state)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(user_id)
+    .bind(method.as_str())
+    .bind(route)
+    .bind(action)
+    .bind(details.map(|d| d.entity_type.as_str()))
+    .bind(details.and_then(|d| d.entity_id))
+    .bind(status)
+    .bind(details.and_then(|d| d.before.clone()))
+    .bind(details.and_then(|d| d.after.clone()))
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
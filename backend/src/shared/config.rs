@@ -0,0 +1,367 @@
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const DEFAULT_APP_BASE_URL: &str = "http://localhost:3000";
+const DEFAULT_SERVER_HOST: &str = "0.0.0.0";
+const DEFAULT_SERVER_PORT: u16 = 8000;
+/// HS256 keys shorter than the hash output weaken the signature.
+const MIN_JWT_SECRET_LEN: usize = 32;
+const DEFAULT_AI_BATCH_SIZE: usize = 8;
+const DEFAULT_AI_BATCH_WAIT_MS: u64 = 10;
+/// Secrets shipped in `.env.example` and docs; never acceptable in production.
+const KNOWN_PLACEHOLDER_SECRETS: &[&str] = &[
+    "your-secret-key-change-this-in-production-to-something-very-secure",
+    "default_secret",
+    "secret",
+    "changeme",
+];
+
+static CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+/// Where the segmentation model runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiDevice {
+    /// The first CUDA device when the build supports it, otherwise the CPU.
+    Auto,
+    Cpu,
+    Cuda(usize),
+}
+
+impl std::str::FromStr for AiDevice {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(AiDevice::Auto),
+            "cpu" => Ok(AiDevice::Cpu),
+            "cuda" => Ok(AiDevice::Cuda(0)),
+            other => other
+                .strip_prefix("cuda:")
+                .and_then(|id| id.parse().ok())
+                .map(AiDevice::Cuda)
+                .ok_or(()),
+        }
+    }
+}
+
+/// Browser origins allowed to call the API with credentials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsOrigins {
+    /// Exact origins, such as `https://app.example.com`.
+    List(Vec<String>),
+    /// `http://localhost` and `http://127.0.0.1` on any port; the development
+    /// default, so dev servers work whatever port they pick.
+    Loopback,
+}
+
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub environment: Environment,
+    pub database_url: String,
+    pub jwt_secret: String,
+    pub app_base_url: String,
+    pub server_host: String,
+    pub server_port: u16,
+    /// Model config and weights paths; the AI engine is disabled without them.
+    pub ai_paths: Option<(String, String)>,
+    pub ai_device: AiDevice,
+    /// Most images run through the model in one forward pass.
+    pub ai_batch_size: usize,
+    /// How long the first queued image waits for others to join its batch.
+    pub ai_batch_wait: std::time::Duration,
+    /// Google sign-in is offered only when both credentials are set.
+    pub google_oauth: Option<OAuthCredentials>,
+    pub cors_origins: CorsOrigins,
+    pub access_log: AccessLogConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccessLogConfig {
+    /// Share of successful requests logged, from 0 to 1; failures are always
+    /// logged.
+    pub sample_rate: f64,
+    /// Adds request headers to each line, with credentials redacted.
+    pub headers: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {path}: {source}")]
+    Read { path: String, source: std::io::Error },
+    #[error("Invalid config file {path}: {source}")]
+    Parse { path: String, source: Box<toml::de::Error> },
+    #[error("Invalid configuration:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+/// Optional settings file. Keys are the environment variable names in lower
+/// case; environment variables take precedence over the file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    app_env: Option<Environment>,
+    database_url: Option<String>,
+    jwt_secret: Option<String>,
+    app_base_url: Option<String>,
+    server_host: Option<String>,
+    server_port: Option<u16>,
+    ai_config_path: Option<String>,
+    ai_weights_path: Option<String>,
+    ai_device: Option<String>,
+    ai_batch_size: Option<usize>,
+    ai_batch_wait_ms: Option<u64>,
+    google_client_id: Option<String>,
+    google_client_secret: Option<String>,
+    cors_allowed_origins: Option<Vec<String>>,
+    access_log_sample_rate: Option<f64>,
+    access_log_headers: Option<bool>,
+}
+
+impl AppConfig {
+    /// Loads `CONFIG_FILE` (default `config.toml`, skipped if absent) overlaid
+    /// with environment variables, and reports every problem at once.
+    pub fn load() -> Result<Self, ConfigError> {
+        let explicit_path = std::env::var("CONFIG_FILE").ok();
+        let path = explicit_path.as_deref().unwrap_or(DEFAULT_CONFIG_FILE);
+
+        let file = if explicit_path.is_some() || Path::new(path).exists() {
+            let text = std::fs::read_to_string(path)
+                .map_err(|source| ConfigError::Read { path: path.to_string(), source })?;
+            toml::from_str::<FileConfig>(&text)
+                .map_err(|source| ConfigError::Parse { path: path.to_string(), source: Box::new(source) })?
+        } else {
+            FileConfig::default()
+        };
+
+        Self::from_layers(file)
+    }
+
+    fn from_layers(file: FileConfig) -> Result<Self, ConfigError> {
+        let mut errors = Vec::new();
+
+        let environment = match env("APP_ENV").as_deref() {
+            Some("production") => Environment::Production,
+            Some("development") => Environment::Development,
+            Some(other) => {
+                errors.push(format!("APP_ENV must be 'development' or 'production', got '{}'", other));
+                Environment::Development
+            }
+            None => file.app_env.unwrap_or(Environment::Development),
+        };
+        let production = environment == Environment::Production;
+
+        let database_url = env("DATABASE_URL").or(file.database_url).unwrap_or_default();
+        if database_url.is_empty() {
+            errors.push("DATABASE_URL is required".to_string());
+        } else if !(database_url.starts_with("postgres://") || database_url.starts_with("postgresql://")) {
+            errors.push("DATABASE_URL must be a postgres:// or postgresql:// URL".to_string());
+        }
+
+        let jwt_secret = env("JWT_SECRET").or(file.jwt_secret).unwrap_or_default();
+        if jwt_secret.is_empty() {
+            errors.push("JWT_SECRET is required".to_string());
+        } else if KNOWN_PLACEHOLDER_SECRETS.contains(&jwt_secret.as_str()) {
+            if production {
+                errors.push("JWT_SECRET is a published placeholder; generate a random secret".to_string());
+            } else {
+                tracing::warn!("JWT_SECRET is a published placeholder; do not use this configuration in production");
+            }
+        } else if jwt_secret.len() < MIN_JWT_SECRET_LEN && production {
+            errors.push(format!("JWT_SECRET must be at least {} characters in production", MIN_JWT_SECRET_LEN));
+        }
+
+        let app_base_url = env("APP_BASE_URL").or(file.app_base_url).unwrap_or_else(|| {
+            if production {
+                errors.push("APP_BASE_URL is required in production".to_string());
+            }
+            DEFAULT_APP_BASE_URL.to_string()
+        });
+        if !(app_base_url.starts_with("https://") || app_base_url.starts_with("http://")) {
+            errors.push("APP_BASE_URL must be an http(s) URL".to_string());
+        } else if production && !app_base_url.starts_with("https://") {
+            errors.push("APP_BASE_URL must use https in production".to_string());
+        }
+
+        let server_host = env("SERVER_HOST").or(file.server_host).unwrap_or_else(|| DEFAULT_SERVER_HOST.to_string());
+        if server_host.parse::<std::net::IpAddr>().is_err() {
+            errors.push(format!("SERVER_HOST must be an IP address, got '{}'", server_host));
+        }
+
+        let server_port = match env("SERVER_PORT") {
+            Some(port) => port.parse::<u16>().unwrap_or_else(|_| {
+                errors.push(format!("SERVER_PORT must be a port number, got '{}'", port));
+                DEFAULT_SERVER_PORT
+            }),
+            None => file.server_port.unwrap_or(DEFAULT_SERVER_PORT),
+        };
+
+        let ai_paths = match (
+            env("AI_CONFIG_PATH").or(file.ai_config_path),
+            env("AI_WEIGHTS_PATH").or(file.ai_weights_path),
+        ) {
+            (Some(config), Some(weights)) => Some((config, weights)),
+            (None, None) => None,
+            _ => {
+                errors.push("AI_CONFIG_PATH and AI_WEIGHTS_PATH must be set together".to_string());
+                None
+            }
+        };
+
+        let ai_device = match env("AI_DEVICE").or(file.ai_device) {
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                errors.push(format!("AI_DEVICE must be 'auto', 'cpu', 'cuda' or 'cuda:<id>', got '{}'", value));
+                AiDevice::Auto
+            }),
+            None => AiDevice::Auto,
+        };
+
+        let ai_batch_size = match env("AI_BATCH_SIZE") {
+            Some(size) => size.parse::<usize>().ok().filter(|&s| s > 0).unwrap_or_else(|| {
+                errors.push(format!("AI_BATCH_SIZE must be a positive number, got '{}'", size));
+                DEFAULT_AI_BATCH_SIZE
+            }),
+            None => file.ai_batch_size.unwrap_or(DEFAULT_AI_BATCH_SIZE),
+        };
+
+        let ai_batch_wait_ms = match env("AI_BATCH_WAIT_MS") {
+            Some(ms) => ms.parse::<u64>().unwrap_or_else(|_| {
+                errors.push(format!("AI_BATCH_WAIT_MS must be a number of milliseconds, got '{}'", ms));
+                DEFAULT_AI_BATCH_WAIT_MS
+            }),
+            None => file.ai_batch_wait_ms.unwrap_or(DEFAULT_AI_BATCH_WAIT_MS),
+        };
+
+        let google_oauth = match (
+            env("GOOGLE_CLIENT_ID").or(file.google_client_id),
+            env("GOOGLE_CLIENT_SECRET").or(file.google_client_secret),
+        ) {
+            (Some(client_id), Some(client_secret)) => Some(OAuthCredentials { client_id, client_secret }),
+            (None, None) => None,
+            _ => {
+                errors.push("GOOGLE_CLIENT_ID and GOOGLE_CLIENT_SECRET must be set together".to_string());
+                None
+            }
+        };
+
+        // Credentialed requests cannot use a wildcard, so origins are always
+        // listed; production defaults to the frontend's own origin.
+        let cors_origins = match env("CORS_ALLOWED_ORIGINS")
+            .map(|list| list.split(',').map(|o| o.trim().to_string()).filter(|o| !o.is_empty()).collect())
+            .or(file.cors_allowed_origins)
+        {
+            Some(origins) => {
+                for origin in &origins {
+                    if let Err(problem) = check_origin(origin, production) {
+                        errors.push(format!("CORS_ALLOWED_ORIGINS: {}", problem));
+                    }
+                }
+                CorsOrigins::List(origins)
+            }
+            None if production => CorsOrigins::List(vec![origin_of(&app_base_url).to_string()]),
+            None => CorsOrigins::Loopback,
+        };
+
+        let sample_rate = match env("ACCESS_LOG_SAMPLE_RATE") {
+            Some(rate) => rate.parse::<f64>().unwrap_or(f64::NAN),
+            None => file.access_log_sample_rate.unwrap_or(1.0),
+        };
+        if !(0.0..=1.0).contains(&sample_rate) {
+            errors.push("ACCESS_LOG_SAMPLE_RATE must be a number from 0 to 1".to_string());
+        }
+
+        let access_log_headers = match env("ACCESS_LOG_HEADERS").as_deref() {
+            Some("true") => true,
+            Some("false") => false,
+            Some(other) => {
+                errors.push(format!("ACCESS_LOG_HEADERS must be 'true' or 'false', got '{}'", other));
+                false
+            }
+            None => file.access_log_headers.unwrap_or(false),
+        };
+
+        // Job intervals are read where the jobs are spawned, which falls back to
+        // defaults on bad input; catch typos here instead of running silently.
+        for (name, value) in std::env::vars() {
+            if name.ends_with("_INTERVAL_SECS") && !value.parse::<u64>().is_ok_and(|v| v > 0) {
+                errors.push(format!("{} must be a positive number of seconds, got '{}'", name, value));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(errors));
+        }
+
+        Ok(Self {
+            environment,
+            database_url,
+            jwt_secret,
+            app_base_url,
+            server_host,
+            server_port,
+            ai_paths,
+            ai_device,
+            ai_batch_size,
+            ai_batch_wait: std::time::Duration::from_millis(ai_batch_wait_ms),
+            google_oauth,
+            cors_origins,
+            access_log: AccessLogConfig { sample_rate, headers: access_log_headers },
+        })
+    }
+}
+
+/// Scheme, host and port of an http(s) URL.
+fn origin_of(url: &str) -> &str {
+    let host_start = url.find("://").map_or(0, |i| i + 3);
+    match url[host_start..].find('/') {
+        Some(end) => &url[..host_start + end],
+        None => url,
+    }
+}
+
+fn check_origin(origin: &str, production: bool) -> Result<(), String> {
+    if origin == "*" {
+        return Err("'*' is not allowed with credentials; list the origins".to_string());
+    }
+    if !(origin.starts_with("https://") || origin.starts_with("http://")) {
+        return Err(format!("'{}' must be an http(s) origin", origin));
+    }
+    if origin_of(origin) != origin {
+        return Err(format!("'{}' must not have a path or trailing slash", origin));
+    }
+    if production && !origin.starts_with("https://") {
+        return Err(format!("'{}' must use https in production", origin));
+    }
+    Ok(())
+}
+
+/// Makes `config` available through `get`. Call once at startup.
+pub fn init(config: AppConfig) {
+    if CONFIG.set(config).is_err() {
+        tracing::warn!("Configuration already initialised; ignoring reload");
+    }
+}
+
+/// The configuration loaded at startup.
+pub fn get() -> &'static AppConfig {
+    CONFIG.get().expect("configuration not initialised; call shared::config::init at startup")
+}
+
+/// Non-empty environment variable.
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
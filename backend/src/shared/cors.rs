@@ -0,0 +1,48 @@
+//! CORS for the browser client. Requests carry credentials, so origins,
+//! methods and headers are listed rather than wildcarded.
+
+use std::time::Duration;
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use crate::shared::{config::CorsOrigins, request_id::REQUEST_ID_HEADER};
+
+/// Browsers may reuse a preflight answer for this long.
+const PREFLIGHT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+pub fn layer(origins: &CorsOrigins) -> CorsLayer {
+    let allow_origin = match origins {
+        CorsOrigins::List(origins) => {
+            AllowOrigin::list(origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok()))
+        }
+        CorsOrigins::Loopback => AllowOrigin::predicate(|origin, _| is_loopback(origin)),
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_credentials(true)
+        .allow_methods([Method::GET, Method::HEAD, Method::POST, Method::PUT, Method::DELETE])
+        // Last-Event-ID and Cache-Control are sent by EventSource clients
+        // resuming the event stream.
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            header::IF_NONE_MATCH,
+            header::CACHE_CONTROL,
+            HeaderName::from_static("last-event-id"),
+            REQUEST_ID_HEADER,
+        ])
+        .expose_headers([REQUEST_ID_HEADER, header::ETAG, header::CONTENT_DISPOSITION])
+        .max_age(PREFLIGHT_MAX_AGE)
+}
+
+fn is_loopback(origin: &HeaderValue) -> bool {
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    ["http://localhost", "http://127.0.0.1"].iter().any(|host| {
+        origin
+            .strip_prefix(host)
+            .is_some_and(|rest| rest.is_empty() || rest.strip_prefix(':').is_some_and(|port| port.parse::<u16>().is_ok()))
+    })
+}
@@ -0,0 +1,64 @@
+//! Coordinate reference systems accepted for uploads and exports. Geometries
+//! are always stored in WGS 84; reprojection runs in PostGIS.
+
+use geojson::JsonObject;
+use crate::shared::error::{AppError, AppResult};
+
+/// EPSG codes in use across the Mekong Delta, with the name shown in exports.
+const SUPPORTED: &[(i32, &str)] = &[
+    (4326, "WGS 84"),
+    (4756, "VN-2000"),
+    (3405, "VN-2000 / UTM zone 48N"),
+    (3406, "VN-2000 / UTM zone 49N"),
+    (32648, "WGS 84 / UTM zone 48N"),
+    (32649, "WGS 84 / UTM zone 49N"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crs {
+    pub srid: i32,
+    pub name: &'static str,
+}
+
+impl Crs {
+    pub const WGS84: Crs = Crs { srid: 4326, name: "WGS 84" };
+
+    /// Accepts `EPSG:3405`, a bare `3405`, and the OGC URNs GIS tools write
+    /// into GeoJSON, including CRS84.
+    pub fn parse(value: &str) -> AppResult<Crs> {
+        let value = value.trim();
+        if value.to_ascii_uppercase().ends_with("CRS84") {
+            return Ok(Crs::WGS84);
+        }
+
+        value
+            .rsplit(':')
+            .next()
+            .and_then(|code| code.parse::<i32>().ok())
+            .and_then(|srid| SUPPORTED.iter().find(|(supported, _)| *supported == srid))
+            .map(|&(srid, name)| Crs { srid, name })
+            .ok_or_else(|| {
+                let supported: Vec<String> = SUPPORTED.iter().map(|(srid, _)| format!("EPSG:{}", srid)).collect();
+                AppError::Validation(format!("Unsupported CRS '{}'; use one of {}", value, supported.join(", ")))
+            })
+    }
+
+    /// The CRS named by the legacy GeoJSON `crs` member, if the object has one.
+    pub fn from_geojson_member(members: Option<&JsonObject>) -> AppResult<Option<Crs>> {
+        let name = members
+            .and_then(|m| m.get("crs"))
+            .and_then(|crs| crs.pointer("/properties/name"))
+            .and_then(|name| name.as_str());
+
+        name.map(Crs::parse).transpose()
+    }
+
+    /// Longitude/latitude in degrees rather than projected metres.
+    pub fn is_geographic(&self) -> bool {
+        matches!(self.srid, 4326 | 4756)
+    }
+
+    pub fn code(&self) -> String {
+        format!("EPSG:{}", self.srid)
+    }
+}
@@ -1,20 +1,69 @@
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, PgPool};
 use anyhow::Result;
 
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// A migration known to the binary that the database has not applied.
+#[derive(Debug)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
+
 pub async fn init_pool(database_url: &str) -> Result<PgPool> {
+    let pool = connect(database_url).await?;
+    run_migrations(&pool).await?;
+    Ok(pool)
+}
+
+pub async fn connect(database_url: &str) -> Result<PgPool> {
     let pool = PgPoolOptions::new()
         .max_connections(10)
         .acquire_timeout(std::time::Duration::from_secs(5))
         .connect(database_url)
         .await?;
+    Ok(pool)
+}
 
+pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     sqlx::query("CREATE EXTENSION IF NOT EXISTS postgis")
-        .execute(&pool)
+        .execute(pool)
         .await?;
 
-    sqlx::migrate!("./migrations")
-        .run(&pool)
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
+
+/// Migrations `run_migrations` would apply, oldest first. Fails, like the run
+/// itself would, when an applied migration was edited or is unknown to this
+/// binary.
+pub async fn pending_migrations(pool: &PgPool) -> Result<Vec<PendingMigration>> {
+    let table_exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
         .await?;
 
-    Ok(pool)
+    let applied: Vec<(i64, Vec<u8>)> = if table_exists {
+        sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success ORDER BY version")
+            .fetch_all(pool)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    for (version, checksum) in &applied {
+        match MIGRATOR.iter().find(|m| m.version == *version) {
+            Some(known) if *known.checksum != **checksum => {
+                anyhow::bail!("Migration {} was modified after it was applied", version)
+            }
+            Some(_) => {}
+            None => anyhow::bail!("Migration {} was applied but is missing from this build", version),
+        }
+    }
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .filter(|m| !applied.iter().any(|(version, _)| *version == m.version))
+        .map(|m| PendingMigration { version: m.version, description: m.description.to_string() })
+        .collect())
 }
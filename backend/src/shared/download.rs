@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+use axum::{
+    body::Body,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use tokio_util::io::ReaderStream;
+use crate::shared::error::{AppError, AppResult};
+
+/// Temporary file for building a download; removed when dropped so failed
+/// exports do not pile up in the temp directory.
+pub struct ScratchFile(PathBuf);
+
+impl ScratchFile {
+    pub fn random(extension: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("bio-radar-{}.{}", uuid::Uuid::new_v4(), extension)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Streams `file` to the client as an attachment instead of loading it into
+/// memory. The file is unlinked as soon as it is open; the open handle keeps
+/// it readable until the body has been sent.
+pub async fn attachment(file: ScratchFile, content_type: &'static str, filename: &str) -> AppResult<Response> {
+    let handle = tokio::fs::File::open(file.path())
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to open {}: {}", file.path().display(), e)))?;
+    let len = handle
+        .metadata()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to stat {}: {}", file.path().display(), e)))?
+        .len();
+    drop(file);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_LENGTH, len.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(ReaderStream::new(handle)),
+    )
+        .into_response())
+}
@@ -3,8 +3,10 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::Serialize;
 use thiserror::Error;
+use utoipa::ToSchema;
+use crate::shared::request_id;
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -37,11 +39,109 @@ pub enum AppError {
 
     #[error("Parse error: {0}")]
     Parse(String),
+
+    #[error("{1}")]
+    Coded(ErrorCode, String),
+
+    /// Like `Coded`, with structured details returned to the client.
+    #[error("{1}")]
+    Detailed(ErrorCode, String, serde_json::Value),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
+/// Stable, machine-readable identifier returned alongside every error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    DatabaseError,
+    AiEngineError,
+    AiEngineUnavailable,
+    ValidationFailed,
+    /// Request body fields broke their rules; `details.violations` lists them.
+    InvalidFields,
+    Unauthorized,
+    InvalidCredentials,
+    InvalidToken,
+    /// Signed in, but the role does not allow this.
+    Forbidden,
+    /// The API key is valid but was not granted the scope the route needs.
+    InsufficientScope,
+    RateLimited,
+    BadRequest,
+    /// The body is not JSON according to its `Content-Type`.
+    UnsupportedMediaType,
+    EmailTaken,
+    NotFound,
+    FarmNotFound,
+    FarmArchived,
+    AlertNotFound,
+    TodoNotFound,
+    CommentNotFound,
+    WebhookNotFound,
+    StationNotFound,
+    GeometryInvalid,
+    QuotaExceeded,
+    ParseError,
+    IoError,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::ValidationFailed
+            | ErrorCode::BadRequest
+            | ErrorCode::EmailTaken
+            | ErrorCode::GeometryInvalid
+            | ErrorCode::ParseError => StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidFields => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::FarmArchived => StatusCode::CONFLICT,
+            ErrorCode::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ErrorCode::Forbidden | ErrorCode::QuotaExceeded | ErrorCode::InsufficientScope => StatusCode::FORBIDDEN,
+            ErrorCode::Unauthorized | ErrorCode::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ErrorCode::InvalidToken => StatusCode::BAD_REQUEST,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::NotFound
+            | ErrorCode::FarmNotFound
+            | ErrorCode::AlertNotFound
+            | ErrorCode::TodoNotFound
+            | ErrorCode::CommentNotFound
+            | ErrorCode::WebhookNotFound
+            | ErrorCode::StationNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::AiEngineUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::DatabaseError
+            | ErrorCode::AiEngineError
+            | ErrorCode::IoError
+            | ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Database(_) => ErrorCode::DatabaseError,
+            AppError::AiEngine(_) => ErrorCode::AiEngineError,
+            AppError::Validation(_) => ErrorCode::ValidationFailed,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::Internal(_) => ErrorCode::InternalError,
+            AppError::GeometryParsing(_) => ErrorCode::GeometryInvalid,
+            AppError::Io(_) => ErrorCode::IoError,
+            AppError::Parse(_) => ErrorCode::ParseError,
+            AppError::Coded(code, _) | AppError::Detailed(code, _, _) => *code,
+        }
+    }
+
+    /// The message clients see. Server-side failures are logged and replaced
+    /// by a generic message, so their details never leave the server.
+    pub fn public_message(&self) -> String {
+        self.report().1.to_string()
+    }
+
+    /// Logs server-side failures; returns the status and message to respond with.
+    fn report(&self) -> (StatusCode, &str) {
+        match *self {
             AppError::Database(ref e) => {
                 tracing::error!("Database error: {:?}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Database error occurred")
@@ -76,14 +176,45 @@ impl IntoResponse for AppError {
             AppError::Parse(ref msg) => {
                 (StatusCode::BAD_REQUEST, msg.as_str())
             }
+            AppError::Coded(code, ref msg) | AppError::Detailed(code, ref msg, _) => {
+                if code.status().is_server_error() {
+                    tracing::error!("{:?}: {}", code, msg);
+                }
+                (code.status(), msg.as_str())
+            }
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let code = self.code();
+        let details = match &self {
+            AppError::Detailed(_, _, details) => Some(details.clone()),
+            _ => None,
         };
+        let (status, error_message) = self.report();
 
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        let body = Json(ErrorResponse {
+            error: error_message.to_string(),
+            code,
+            request_id: request_id::current(),
+            details,
+        });
 
         (status, body).into_response()
     }
 }
 
+/// Body returned for every failed request.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub code: ErrorCode,
+    pub request_id: Option<String>,
+    /// Error-specific structure, e.g. the list of problems in a rejected geometry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
 pub type AppResult<T> = Result<T, AppError>;
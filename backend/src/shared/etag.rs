@@ -0,0 +1,85 @@
+use axum::{
+    body::{Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+/// Bodies larger than this are passed through untouched rather than buffered
+/// for hashing.
+const MAX_HASHED_BODY_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Tags successful JSON GET responses with a weak content-hash `ETag` (weak
+/// because the compression layer may re-encode the body) and answers
+/// `304 Not Modified` when the client already holds that version, so polling
+/// clients stop downloading identical bodies. Streamed and binary responses
+/// (event streams, exports) are left alone.
+pub async fn etag_middleware(req: Request, next: Next) -> Response {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return next.run(req).await;
+    }
+
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+    let response = next.run(req).await;
+
+    if response.status() != StatusCode::OK || !is_json(response.headers()) || response.headers().contains_key(header::ETAG) {
+        return response;
+    }
+    match response.body().size_hint().exact() {
+        Some(len) if len <= MAX_HASHED_BODY_BYTES => {}
+        _ => return response,
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to buffer response for ETag: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let etag = format!("W/\"{}\"", hex::encode(&Sha256::digest(&bytes)[..16]));
+    let Ok(etag_value) = HeaderValue::from_str(&etag) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    parts.headers.insert(header::ETAG, etag_value.clone());
+    parts
+        .headers
+        .entry(header::CACHE_CONTROL)
+        .or_insert(HeaderValue::from_static("private, no-cache"));
+
+    if if_none_match.is_some_and(|value| matches_etag(&value, &etag)) {
+        let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+        let headers = not_modified.headers_mut();
+        headers.insert(header::ETAG, etag_value);
+        if let Some(cache_control) = parts.headers.get(header::CACHE_CONTROL) {
+            headers.insert(header::CACHE_CONTROL, cache_control.clone());
+        }
+        return not_modified;
+    }
+
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+/// Weak comparison as RFC 9110 prescribes for `If-None-Match`: `W/` prefixes
+/// are ignored and `*` matches any current representation.
+fn matches_etag(header_value: &HeaderValue, etag: &str) -> bool {
+    let Ok(value) = header_value.to_str() else {
+        return false;
+    };
+
+    value.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag.trim_start_matches("W/")
+    })
+}
@@ -0,0 +1,68 @@
+pub fn message(key: &str) -> Option<&'static str> {
+    let text = match key {
+        "alert.salinity_anomaly" => "Salinity anomaly detected! Current NDSI: {ndsi}. {reasons}",
+        "alert.reason.ndsi_threshold" => "NDSI {ndsi} exceeds threshold {threshold} by {deviation}",
+        "alert.reason.salinity_critical" => "Estimated salinity {g_l} g/L exceeds critical limit {limit} g/L for {crop}",
+        "alert.reason.salinity_tolerance" => "Estimated salinity {g_l} g/L exceeds tolerance {limit} g/L for {crop}",
+        "alert.coverage_gap" => "No usable satellite scene for {days} days; salinity readings may be out of date",
+
+        "email.password_reset.subject" => "Reset your Bio-Radar password",
+        "email.password_reset.body" => "Use the link below to choose a new password. It expires in {hours} hour(s).\n\n{link}",
+        "email.verify.subject" => "Verify your Bio-Radar email",
+        "email.verify.body" => "Confirm your email address by opening the link below.\n\n{link}",
+        "email.account_deletion.subject" => "Confirm deletion of your Bio-Radar account",
+        "email.account_deletion.body" => "Open the link below to confirm. Your account and all of its data will be permanently deleted {days} days later unless you cancel the deletion from your profile.\n\n{link}",
+        "email.alert.subject" => "Bio-Radar {severity} alert: {farm}",
+        "email.alert.body" => "{message}\n\nYou can choose which alerts are emailed to you, and set quiet hours, in your settings.",
+
+        "sms.otp" => "Your Bio-Radar sign-in code is {code}. It expires in {minutes} minutes. Never share it with anyone.",
+        "sms.account_deletion" => "Confirm deletion of your Bio-Radar account: {link} All data is deleted {days} days later unless you cancel from your profile.",
+
+        "digest.subject" => "Your {period} Bio-Radar farm summary",
+        "digest.period.daily" => "daily",
+        "digest.period.weekly" => "weekly",
+        "digest.intro" => "Here is how your farms have been doing since {since}.",
+        "digest.farm" => "{name}: latest NDSI {ndsi}, {alerts} new alert(s) ({critical} critical), salinity risk {risk}/100 ({level}).",
+        "digest.no_data" => "no data",
+        "digest.column.farm" => "Farm",
+        "digest.column.ndsi" => "Latest NDSI",
+        "digest.column.alerts" => "New alerts (critical)",
+        "digest.column.risk" => "Salinity risk",
+        "digest.column.trend" => "Trend",
+        "digest.footer" => "You can change how often you receive this summary, or turn it off, in your settings.",
+
+        "push.critical_alert.title" => "Critical salinity alert: {farm}",
+        "push.alert.title" => "Salinity alert ({severity}): {farm}",
+        "push.comment_mention.title" => "{author} mentioned you on an alert for {farm}",
+
+        "severity.low" => "low",
+        "severity.medium" => "medium",
+        "severity.high" => "high",
+        "severity.critical" => "critical",
+
+        "trend.rising" => "Salinity began rising around {date} (NDSI {before} to {after})",
+        "trend.falling" => "Salinity began easing around {date} (NDSI {before} to {after})",
+
+        "irrigation.no_demand" => "The crop has been harvested and needs no water.",
+        "irrigation.low_demand" => "The crop needs only {etc} mm; soil moisture covers it.",
+        "irrigation.rain_covers" => "Forecast rain of {rain} mm covers the crop's {etc} mm demand; no intake needed.",
+        "irrigation.take_in" => "Salinity risk is {level} ({score}/100). Take in about {deficit} mm of water.",
+        "irrigation.take_in_low_tide" => "Salinity risk is {level} ({score}/100), but the tide is low. Take in about {deficit} mm, preferably on the ebb.",
+        "irrigation.wait" => "Salinity risk is {level} ({score}/100) and the tide is high. Wait until {date}, when the tide is lower, to take in about {deficit} mm.",
+        "irrigation.use_stored" => "Salinity risk is {level} ({score}/100) and no low tide is expected in the next {days} days. Keep the intake closed and use about {deficit} mm of stored water.",
+        "irrigation.use_stored_critical" => "Salinity risk is {level} ({score}/100). Keep the intake closed and use about {deficit} mm of stored water.",
+
+        "cap.feed_title" => "Bio-Radar salinity intrusion warnings",
+        "cap.sender_name" => "Bio-Radar salinity monitoring",
+        "cap.event" => "Salinity intrusion",
+        "cap.headline" => "Salinity intrusion warning ({severity}) for {district}",
+        "cap.description" => "{alerts} unacknowledged salinity alert(s), {critical} of them critical, on {farms} farm(s) since {since}.",
+        "cap.instruction.high" => "Measure salinity before taking in river water and top up freshwater storage while levels allow.",
+        "cap.instruction.critical" => "Close sluice gates and stop taking in river water. Irrigate only from stored freshwater until salinity falls.",
+        "cap.unassigned_area" => "Unassigned area",
+
+        _ => return None,
+    };
+
+    Some(text)
+}
@@ -0,0 +1,86 @@
+mod en;
+mod vi;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use crate::shared::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    Vi,
+}
+
+impl Language {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Language::En => "en",
+            Language::Vi => "vi",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "en" => Some(Language::En),
+            "vi" => Some(Language::Vi),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Language::from_code(&value)
+            .ok_or_else(|| AppError::Validation(format!("Unsupported language: {}", value)))
+    }
+}
+
+/// Looks up `key` in the catalog for `lang` (falling back to English, then to
+/// the key itself) and substitutes `{name}` placeholders from `args`.
+pub fn t(lang: Language, key: &str, args: &[(&str, String)]) -> String {
+    let template = match lang {
+        Language::En => en::message(key),
+        Language::Vi => vi::message(key).or_else(|| en::message(key)),
+    };
+
+    let Some(template) = template else {
+        tracing::warn!("Missing translation key: {}", key);
+        return key.to_string();
+    };
+
+    args.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// The user's preferred language, or the default when none is stored.
+pub async fn language_for_user(db: &PgPool, user_id: i64) -> AppResult<Language> {
+    let code: Option<String> = sqlx::query_scalar("SELECT language FROM user_preferences WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(code.as_deref().and_then(Language::from_code).unwrap_or_default())
+}
+
+/// The preferred language of the user owning `farm_id`.
+pub async fn language_for_farm(db: &PgPool, farm_id: i64) -> AppResult<Language> {
+    let code: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT p.language
+        FROM farms f
+        JOIN user_preferences p ON p.user_id = f.user_id
+        WHERE f.id = $1
+        "#
+    )
+    .bind(farm_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(code.as_deref().and_then(Language::from_code).unwrap_or_default())
+}
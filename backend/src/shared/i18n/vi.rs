@@ -0,0 +1,68 @@
+pub fn message(key: &str) -> Option<&'static str> {
+    let text = match key {
+        "alert.salinity_anomaly" => "Phát hiện bất thường độ mặn! NDSI hiện tại: {ndsi}. {reasons}",
+        "alert.reason.ndsi_threshold" => "NDSI {ndsi} vượt ngưỡng {threshold} một khoảng {deviation}",
+        "alert.reason.salinity_critical" => "Độ mặn ước tính {g_l} g/L vượt mức nguy hiểm {limit} g/L đối với {crop}",
+        "alert.reason.salinity_tolerance" => "Độ mặn ước tính {g_l} g/L vượt ngưỡng chịu mặn {limit} g/L đối với {crop}",
+        "alert.coverage_gap" => "Không có ảnh vệ tinh dùng được trong {days} ngày; số liệu độ mặn có thể đã cũ",
+
+        "email.password_reset.subject" => "Đặt lại mật khẩu Bio-Radar",
+        "email.password_reset.body" => "Mở liên kết bên dưới để đặt mật khẩu mới. Liên kết hết hạn sau {hours} giờ.\n\n{link}",
+        "email.verify.subject" => "Xác minh email Bio-Radar",
+        "email.verify.body" => "Mở liên kết bên dưới để xác minh địa chỉ email của bạn.\n\n{link}",
+        "email.account_deletion.subject" => "Xác nhận xóa tài khoản Bio-Radar",
+        "email.account_deletion.body" => "Mở liên kết bên dưới để xác nhận. Tài khoản và toàn bộ dữ liệu của bạn sẽ bị xóa vĩnh viễn sau {days} ngày nếu bạn không hủy yêu cầu trong trang hồ sơ.\n\n{link}",
+        "email.alert.subject" => "Cảnh báo mức {severity} từ Bio-Radar: {farm}",
+        "email.alert.body" => "{message}\n\nBạn có thể chọn những cảnh báo được gửi qua email và đặt giờ yên lặng trong phần cài đặt.",
+
+        "sms.otp" => "Mã đăng nhập Bio-Radar của bạn là {code}. Mã hết hạn sau {minutes} phút. Không chia sẻ mã này với bất kỳ ai.",
+        "sms.account_deletion" => "Xác nhận xóa tài khoản Bio-Radar: {link} Toàn bộ dữ liệu sẽ bị xóa sau {days} ngày nếu bạn không hủy trong trang hồ sơ.",
+
+        "digest.subject" => "Tóm tắt {period} về nông trại của bạn trên Bio-Radar",
+        "digest.period.daily" => "hằng ngày",
+        "digest.period.weekly" => "hằng tuần",
+        "digest.intro" => "Tình hình các nông trại của bạn kể từ ngày {since}.",
+        "digest.farm" => "{name}: NDSI gần nhất {ndsi}, {alerts} cảnh báo mới ({critical} nghiêm trọng), nguy cơ nhiễm mặn {risk}/100 ({level}).",
+        "digest.no_data" => "chưa có dữ liệu",
+        "digest.column.farm" => "Nông trại",
+        "digest.column.ndsi" => "NDSI gần nhất",
+        "digest.column.alerts" => "Cảnh báo mới (nghiêm trọng)",
+        "digest.column.risk" => "Nguy cơ nhiễm mặn",
+        "digest.column.trend" => "Xu hướng",
+        "digest.footer" => "Bạn có thể thay đổi tần suất nhận bản tóm tắt này hoặc tắt nó trong phần cài đặt.",
+
+        "push.critical_alert.title" => "Cảnh báo mặn nghiêm trọng: {farm}",
+        "push.alert.title" => "Cảnh báo mặn (mức {severity}): {farm}",
+        "push.comment_mention.title" => "{author} đã nhắc đến bạn trong cảnh báo của {farm}",
+
+        "severity.low" => "thấp",
+        "severity.medium" => "trung bình",
+        "severity.high" => "cao",
+        "severity.critical" => "nghiêm trọng",
+
+        "trend.rising" => "Độ mặn bắt đầu tăng từ khoảng {date} (NDSI {before} lên {after})",
+        "trend.falling" => "Độ mặn bắt đầu giảm từ khoảng {date} (NDSI {before} xuống {after})",
+
+        "irrigation.no_demand" => "Cây trồng đã thu hoạch, không cần tưới.",
+        "irrigation.low_demand" => "Cây trồng chỉ cần {etc} mm nước; độ ẩm đất đủ đáp ứng.",
+        "irrigation.rain_covers" => "Mưa dự báo {rain} mm đủ cho nhu cầu {etc} mm của cây trồng; không cần lấy nước.",
+        "irrigation.take_in" => "Nguy cơ nhiễm mặn {level} ({score}/100). Lấy vào khoảng {deficit} mm nước.",
+        "irrigation.take_in_low_tide" => "Nguy cơ nhiễm mặn {level} ({score}/100) nhưng triều đang thấp. Lấy vào khoảng {deficit} mm nước, tốt nhất lúc triều rút.",
+        "irrigation.wait" => "Nguy cơ nhiễm mặn {level} ({score}/100) và triều đang cao. Chờ đến {date}, khi triều thấp hơn, rồi lấy vào khoảng {deficit} mm nước.",
+        "irrigation.use_stored" => "Nguy cơ nhiễm mặn {level} ({score}/100) và không có triều thấp trong {days} ngày tới. Đóng cống và dùng khoảng {deficit} mm nước trữ.",
+        "irrigation.use_stored_critical" => "Nguy cơ nhiễm mặn {level} ({score}/100). Đóng cống và dùng khoảng {deficit} mm nước trữ.",
+
+        "cap.feed_title" => "Cảnh báo xâm nhập mặn Bio-Radar",
+        "cap.sender_name" => "Hệ thống giám sát mặn Bio-Radar",
+        "cap.event" => "Xâm nhập mặn",
+        "cap.headline" => "Cảnh báo xâm nhập mặn (mức {severity}) tại {district}",
+        "cap.description" => "{alerts} cảnh báo mặn chưa xử lý, trong đó {critical} cảnh báo nghiêm trọng, trên {farms} nông trại kể từ {since}.",
+        "cap.instruction.high" => "Đo độ mặn trước khi lấy nước sông và tích trữ thêm nước ngọt khi còn có thể.",
+        "cap.instruction.critical" => "Đóng cống, ngừng lấy nước sông. Chỉ tưới bằng nước ngọt đã trữ cho đến khi độ mặn giảm.",
+        "cap.unassigned_area" => "Khu vực chưa phân vùng",
+
+        _ => return None,
+    };
+
+    Some(text)
+}
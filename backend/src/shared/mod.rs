@@ -1,7 +1,28 @@
+pub mod access;
+pub mod access_log;
 pub mod app_state;
+pub mod audit;
+pub mod config;
+pub mod cors;
+pub mod crs;
 pub mod db;
+pub mod download;
 pub mod error;
+pub mod etag;
+pub mod i18n;
+pub mod notifications;
+pub mod postgis;
+pub mod rate_limit;
+pub mod request_id;
+pub mod runtime;
+pub mod signing;
+pub mod storage;
+pub mod telemetry;
+pub mod tiles;
 pub mod utils;
+pub mod validation;
+pub mod weather;
+pub mod worker;
 
 pub use app_state::AppState;
 pub use error::AppResult;
@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use crate::shared::error::AppResult;
+
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    /// Optional HTML alternative to the plain-text `body`.
+    pub html: Option<String>,
+}
+
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> AppResult<()>;
+}
+
+/// Development sender that writes messages to the log instead of delivering them.
+pub struct LogEmailSender;
+
+#[async_trait]
+impl EmailSender for LogEmailSender {
+    async fn send(&self, message: &EmailMessage) -> AppResult<()> {
+        tracing::info!(
+            "Email to {} | {}{}\n{}",
+            message.to,
+            message.subject,
+            if message.html.is_some() { " (with HTML part)" } else { "" },
+            message.body
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use crate::shared::error::{AppError, AppResult};
+use super::push::{PushMessage, PushOutcome, PushSender};
+
+const FCM_SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+const ASSERTION_LIFETIME_SECS: i64 = 3600;
+/// Access tokens are refreshed this long before Google says they expire.
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 300;
+
+#[derive(Deserialize)]
+struct ServiceAccount {
+    project_id: Option<String>,
+    client_email: String,
+    private_key: String,
+    token_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AssertionClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+}
+
+struct AccessToken {
+    value: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Firebase Cloud Messaging HTTP v1 client. iOS devices are reached through
+/// FCM's APNs bridge, so one sender covers both platforms.
+pub struct FcmSender {
+    client: reqwest::Client,
+    project_id: String,
+    client_email: String,
+    token_uri: String,
+    key: EncodingKey,
+    token: Mutex<Option<AccessToken>>,
+}
+
+impl FcmSender {
+    /// Reads a Google service account key file. The project comes from the
+    /// file unless `FCM_PROJECT_ID` overrides it.
+    pub fn from_service_account_file(path: &str) -> AppResult<Self> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Internal(format!("Cannot read {}: {}", path, e)))?;
+        let account: ServiceAccount = serde_json::from_str(&raw)
+            .map_err(|e| AppError::Internal(format!("Invalid service account file {}: {}", path, e)))?;
+
+        let project_id = std::env::var("FCM_PROJECT_ID")
+            .ok()
+            .or(account.project_id)
+            .ok_or_else(|| AppError::Internal("FCM_PROJECT_ID is not set and the service account has no project_id".to_string()))?;
+        let key = EncodingKey::from_rsa_pem(account.private_key.as_bytes())
+            .map_err(|e| AppError::Internal(format!("Invalid service account private key: {}", e)))?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            project_id,
+            client_email: account.client_email,
+            token_uri: account.token_uri.unwrap_or_else(|| DEFAULT_TOKEN_URI.to_string()),
+            key,
+            token: Mutex::new(None),
+        })
+    }
+
+    async fn access_token(&self) -> AppResult<String> {
+        let mut cached = self.token.lock().await;
+        if let Some(token) = cached.as_ref() {
+            if token.expires_at > Utc::now() {
+                return Ok(token.value.clone());
+            }
+        }
+
+        let now = Utc::now().timestamp();
+        let claims = AssertionClaims {
+            iss: &self.client_email,
+            scope: FCM_SCOPE,
+            aud: &self.token_uri,
+            iat: now,
+            exp: now + ASSERTION_LIFETIME_SECS,
+        };
+        let assertion = encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+            .map_err(|e| AppError::Internal(format!("Failed to sign FCM assertion: {}", e)))?;
+
+        let response = self
+            .client
+            .post(&self.token_uri)
+            .timeout(REQUEST_TIMEOUT)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("FCM token request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Internal(format!("FCM token endpoint responded with {}", response.status())));
+        }
+
+        let token: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Invalid FCM token response: {}", e)))?;
+
+        let value = token.access_token.clone();
+        *cached = Some(AccessToken {
+            value: token.access_token,
+            expires_at: Utc::now() + Duration::seconds(token.expires_in - TOKEN_REFRESH_MARGIN_SECS),
+        });
+
+        Ok(value)
+    }
+
+    fn payload(message: &PushMessage, token: &str) -> serde_json::Value {
+        serde_json::json!({
+            "message": {
+                "token": token,
+                "notification": { "title": message.title, "body": message.body },
+                "data": message.data,
+                "android": { "priority": "high" },
+                "apns": { "headers": { "apns-priority": "10" } },
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl PushSender for FcmSender {
+    /// FCM v1 has no multicast, so each device is one request. Devices FCM no
+    /// longer knows are reported back; other failures are logged and skipped.
+    async fn send(&self, message: &PushMessage) -> AppResult<PushOutcome> {
+        let mut outcome = PushOutcome::default();
+        if message.tokens.is_empty() {
+            return Ok(outcome);
+        }
+
+        let access_token = self.access_token().await?;
+        let url = format!("https://fcm.googleapis.com/v1/projects/{}/messages:send", self.project_id);
+
+        for token in &message.tokens {
+            let response = self
+                .client
+                .post(&url)
+                .timeout(REQUEST_TIMEOUT)
+                .bearer_auth(&access_token)
+                .json(&Self::payload(message, token))
+                .send()
+                .await;
+
+            let response = match response {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!("FCM request failed: {}", e);
+                    continue;
+                }
+            };
+
+            let status = response.status();
+            if status.is_success() {
+                outcome.delivered += 1;
+                continue;
+            }
+
+            let body = response.text().await.unwrap_or_default();
+            if status == reqwest::StatusCode::NOT_FOUND || body.contains("UNREGISTERED") {
+                outcome.invalid_tokens.push(token.clone());
+            } else {
+                tracing::warn!("FCM responded with {}: {}", status, body);
+            }
+        }
+
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,43 @@
+pub mod email;
+pub mod fcm;
+pub mod push;
+pub mod sms;
+
+use std::sync::Arc;
+use crate::shared::error::AppResult;
+use email::{EmailMessage, EmailSender, LogEmailSender};
+use push::{PushMessage, PushOutcome, PushSender};
+use sms::{LogSmsSender, SmsMessage, SmsSender};
+
+/// Single entry point for outbound user notifications. Channels are pluggable
+/// so deployments can swap the delivery backend without touching callers.
+#[derive(Clone)]
+pub struct NotificationDispatcher {
+    email: Arc<dyn EmailSender>,
+    push: Arc<dyn PushSender>,
+    sms: Arc<dyn SmsSender>,
+}
+
+impl NotificationDispatcher {
+    pub fn new(email: Arc<dyn EmailSender>, push: Arc<dyn PushSender>, sms: Arc<dyn SmsSender>) -> Self {
+        Self { email, push, sms }
+    }
+
+    pub async fn send_email(&self, message: EmailMessage) -> AppResult<()> {
+        self.email.send(&message).await
+    }
+
+    pub async fn send_push(&self, message: PushMessage) -> AppResult<PushOutcome> {
+        self.push.send(&message).await
+    }
+
+    pub async fn send_sms(&self, message: SmsMessage) -> AppResult<()> {
+        self.sms.send(&message).await
+    }
+}
+
+impl Default for NotificationDispatcher {
+    fn default() -> Self {
+        Self::new(Arc::new(LogEmailSender), push::sender_from_env(), Arc::new(LogSmsSender))
+    }
+}
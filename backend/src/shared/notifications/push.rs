@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::shared::error::AppResult;
+use super::fcm::FcmSender;
+
+#[derive(Debug, Clone)]
+pub struct PushMessage {
+    pub tokens: Vec<String>,
+    pub title: String,
+    pub body: String,
+    /// Key/value payload handed to the app alongside the notification.
+    pub data: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PushOutcome {
+    pub delivered: usize,
+    /// Tokens the provider reported as no longer registered; callers should
+    /// forget them.
+    pub invalid_tokens: Vec<String>,
+}
+
+#[async_trait]
+pub trait PushSender: Send + Sync {
+    async fn send(&self, message: &PushMessage) -> AppResult<PushOutcome>;
+}
+
+/// Development sender that writes notifications to the log instead of delivering them.
+pub struct LogPushSender;
+
+#[async_trait]
+impl PushSender for LogPushSender {
+    async fn send(&self, message: &PushMessage) -> AppResult<PushOutcome> {
+        tracing::info!(
+            "Push to {} device(s) | {}\n{}\n{:?}",
+            message.tokens.len(),
+            message.title,
+            message.body,
+            message.data
+        );
+        Ok(PushOutcome {
+            delivered: message.tokens.len(),
+            invalid_tokens: Vec::new(),
+        })
+    }
+}
+
+/// FCM when `FCM_SERVICE_ACCOUNT_FILE` is set, otherwise the log sender. A
+/// broken service account falls back to logging rather than failing startup.
+pub fn sender_from_env() -> Arc<dyn PushSender> {
+    let Ok(path) = std::env::var("FCM_SERVICE_ACCOUNT_FILE") else {
+        return Arc::new(LogPushSender);
+    };
+
+    match FcmSender::from_service_account_file(&path) {
+        Ok(sender) => {
+            tracing::info!("Push notifications enabled via FCM");
+            Arc::new(sender)
+        }
+        Err(e) => {
+            tracing::warn!("FCM disabled, logging push notifications instead: {}", e);
+            Arc::new(LogPushSender)
+        }
+    }
+}
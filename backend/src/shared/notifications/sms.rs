@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use crate::shared::error::AppResult;
+
+#[derive(Debug, Clone)]
+pub struct SmsMessage {
+    /// E.164 number, e.g. `+84901234567`.
+    pub to: String,
+    pub body: String,
+}
+
+/// Text-message delivery. Implementations may route through an SMS gateway or
+/// a messaging app such as Zalo, as long as the text reaches `to`.
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    async fn send(&self, message: &SmsMessage) -> AppResult<()>;
+}
+
+/// Development sender that writes messages to the log instead of delivering them.
+pub struct LogSmsSender;
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send(&self, message: &SmsMessage) -> AppResult<()> {
+        tracing::info!("SMS to {}\n{}", message.to, message.body);
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+//! SQL fragments for the PostGIS operations shared by repositories. Each
+//! helper takes SQL expressions (columns or `$n` parameters) and returns an
+//! expression, so the spatial predicate stays in SQL where GIST indexes apply.
+
+/// Parses a GeoJSON text parameter into a WGS 84 geometry.
+pub fn from_geojson(param: &str) -> String {
+    format!("ST_SetSRID(ST_GeomFromGeoJSON({param}), 4326)")
+}
+
+/// Parses a GeoJSON text parameter whose coordinates are in `srid`.
+pub fn from_geojson_in(param: &str, srid: &str) -> String {
+    format!("ST_SetSRID(ST_GeomFromGeoJSON({param}), {srid})")
+}
+
+/// `geom` reprojected to `srid`.
+pub fn transform(geom: &str, srid: &str) -> String {
+    format!("ST_Transform({geom}, {srid})")
+}
+
+/// A WGS 84 point from longitude and latitude parameters.
+pub fn point(lon: &str, lat: &str) -> String {
+    format!("ST_SetSRID(ST_MakePoint({lon}, {lat}), 4326)")
+}
+
+/// `geom` simplified, topology preserved, until it has at most `max_points`
+/// vertices. Geometries already under the limit are returned unchanged.
+pub fn limit_vertices(geom: &str, max_points: usize) -> String {
+    format!("simplify_to_vertex_limit({geom}, {max_points})")
+}
+
+/// Geodesic area in hectares.
+pub fn area_hectares(geom: &str) -> String {
+    format!("(ST_Area(({geom})::geography) / 10000)")
+}
+
+/// Geodesic area in hectares of the overlap between two geometries.
+pub fn overlap_hectares(a: &str, b: &str) -> String {
+    area_hectares(&format!("ST_Intersection({a}, {b})"))
+}
+
+/// Geodesic distance predicate. Matches the `(geometry::geography)` expression
+/// indexes, so `geom` should be a bare column.
+pub fn within_metres(geom: &str, other: &str, metres: &str) -> String {
+    format!("ST_DWithin(({geom})::geography, ({other})::geography, {metres})")
+}
+
+/// Geodesic distance in metres.
+pub fn distance_metres(geom: &str, other: &str) -> String {
+    format!("ST_Distance(({geom})::geography, ({other})::geography)")
+}
+
+/// GeoJSON output, simplified by `tolerance` degrees when that expression is
+/// not NULL. Topology is preserved so polygons never collapse or self-intersect.
+pub fn as_geojson(geom: &str, tolerance: &str) -> String {
+    format!("ST_AsGeoJSON(CASE WHEN {tolerance}::FLOAT8 IS NULL THEN {geom} ELSE ST_SimplifyPreserveTopology({geom}, {tolerance}::FLOAT8) END)")
+}
+
+/// Web Mercator bounds of XYZ tile `z/x/y`.
+pub fn tile_envelope(z: &str, x: &str, y: &str) -> String {
+    format!("ST_TileEnvelope({z}, {x}, {y})")
+}
+
+/// `geom` in the tile coordinate space of `envelope`, clipped with a buffer so
+/// lines and polygons join up across tile edges.
+pub fn as_mvt_geom(geom: &str, envelope: &str) -> String {
+    format!("ST_AsMVTGeom(ST_Transform({geom}, 3857), {envelope}, 4096, 64, TRUE)")
+}
+
+/// Bounding-box predicate of a WGS 84 geometry against a Web Mercator tile
+/// envelope, so the index on `geom` applies.
+pub fn in_tile(geom: &str, envelope: &str) -> String {
+    format!("{geom} && ST_Transform({envelope}, 4326)")
+}
+
+/// The rows of `layer_query` encoded as one MVT layer named `name`, using its
+/// `geom` column as geometry and `id` as feature id. Rows clipped away
+/// entirely are dropped; empty tiles are an empty byte string.
+pub fn as_mvt(layer_query: &str, name: &str) -> String {
+    format!(
+        "SELECT COALESCE(ST_AsMVT(layer, '{name}', 4096, 'geom', 'id'), ''::BYTEA) \
+         FROM ({layer_query}) AS layer WHERE layer.geom IS NOT NULL"
+    )
+}
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// In-memory sliding-window limiter keyed by arbitrary strings (email, IP...).
+/// Good enough for a single backend instance.
+#[derive(Default)]
+pub struct RateLimiter {
+    hits: Mutex<HashMap<String, Vec<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Records an attempt for `key` and returns `false` when it exceeds
+    /// `max_hits` within `window`.
+    pub fn check(&self, key: &str, max_hits: usize, window: Duration) -> bool {
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap_or_else(|e| e.into_inner());
+
+        hits.retain(|_, times| {
+            times.retain(|t| now.duration_since(*t) < window);
+            !times.is_empty()
+        });
+
+        let times = hits.entry(key.to_string()).or_default();
+        if times.len() >= max_hits {
+            return false;
+        }
+
+        times.push(now);
+        true
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::watch;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// Knobs admins can change while the server runs. Stored in `system_settings`
+/// and broadcast to handlers and workers through a watch channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(default)]
+pub struct RuntimeSettings {
+    /// Multiplies every farm's NDSI anomaly multiplier; below 1 alerts earlier.
+    pub anomaly_sensitivity: f64,
+    /// Password reset emails allowed per address per hour.
+    pub password_reset_requests_per_hour: u32,
+    /// Days an archived farm is kept before it is deleted with its history.
+    pub archived_farm_retention_days: u32,
+    /// Schedule overrides in seconds keyed by job name; other jobs keep their
+    /// `*_INTERVAL_SECS` value.
+    pub job_intervals_secs: BTreeMap<String, u64>,
+    /// Quotas of users on the free plan.
+    pub free_plan: PlanLimits,
+    /// Quotas of users on the pro plan.
+    pub pro_plan: PlanLimits,
+    /// Days a farm can go without a usable satellite scene before its
+    /// coverage counts as stale.
+    pub coverage_stale_days: u32,
+    /// Months (1-12) in which stale coverage raises an alert.
+    pub dry_season_months: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PlanLimits {
+    /// Active farms; archived farms do not count.
+    pub max_farms: u32,
+    /// AI analyses per calendar month across all of the user's farms.
+    pub analyses_per_month: u32,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            anomaly_sensitivity: 1.0,
+            password_reset_requests_per_hour: 3,
+            archived_farm_retention_days: 90,
+            job_intervals_secs: BTreeMap::new(),
+            free_plan: PlanLimits { max_farms: 3, analyses_per_month: 30 },
+            pro_plan: PlanLimits { max_farms: 100, analyses_per_month: 3000 },
+            coverage_stale_days: 10,
+            dry_season_months: vec![12, 1, 2, 3, 4],
+        }
+    }
+}
+
+impl RuntimeSettings {
+    pub fn job_interval(&self, job: &str) -> Option<Duration> {
+        self.job_intervals_secs.get(job).map(|&secs| Duration::from_secs(secs))
+    }
+}
+
+static SETTINGS: LazyLock<watch::Sender<RuntimeSettings>> =
+    LazyLock::new(|| watch::Sender::new(RuntimeSettings::default()));
+
+/// Snapshot of the settings in force.
+pub fn current() -> RuntimeSettings {
+    SETTINGS.borrow().clone()
+}
+
+/// Receiver that wakes on every `publish`.
+pub fn subscribe() -> watch::Receiver<RuntimeSettings> {
+    SETTINGS.subscribe()
+}
+
+pub fn publish(settings: RuntimeSettings) {
+    SETTINGS.send_replace(settings);
+}
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+use crate::shared::error::{AppError, AppResult};
+use super::ObjectStore;
+
+const DEFAULT_STORAGE_DIR: &str = "storage";
+
+/// Stores objects as files under a root directory.
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("STORAGE_DIR").unwrap_or_else(|_| DEFAULT_STORAGE_DIR.to_string()))
+    }
+
+    /// Keys are generated server-side, but refuse anything that could
+    /// escape the root all the same.
+    fn path(&self, key: &str) -> AppResult<PathBuf> {
+        let relative = Path::new(key);
+        if key.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(AppError::Internal(format!("Invalid storage key '{}'", key)));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl ObjectStore for LocalStore {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> AppResult<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, bytes).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(AppError::NotFound(format!("Stored object {} not found", key)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+pub mod local;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use crate::shared::error::AppResult;
+use local::LocalStore;
+
+/// Blob storage for user uploads. Keys are `/`-separated relative paths
+/// chosen by the caller.
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> AppResult<()>;
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>>;
+
+    /// Deleting a missing object succeeds.
+    async fn delete(&self, key: &str) -> AppResult<()>;
+}
+
+/// Selects the store named by `STORAGE_BACKEND`; only `local`, a directory
+/// given by `STORAGE_DIR`, is built in.
+pub fn store_from_env() -> Arc<dyn ObjectStore> {
+    let store: Arc<dyn ObjectStore> = match std::env::var("STORAGE_BACKEND").as_deref() {
+        Ok("local") | Err(_) => Arc::new(LocalStore::from_env()),
+        Ok(other) => {
+            tracing::warn!("Unknown STORAGE_BACKEND '{}', using local storage", other);
+            Arc::new(LocalStore::from_env())
+        }
+    };
+    tracing::info!("Attachments are stored in {} storage", store.name());
+    store
+}
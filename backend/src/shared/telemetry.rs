@@ -0,0 +1,78 @@
+//! Log output. Read from the environment directly, since it is set up before
+//! the configuration that may fail to load and needs to be reported.
+//!
+//! - `LOG_FORMAT=json` writes one JSON object per line instead of text.
+//! - `OTEL_EXPORTER_OTLP_ENDPOINT` also ships logs to an OpenTelemetry
+//!   collector over OTLP/HTTP.
+
+use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
+use opentelemetry_otlp::{LogExporter, WithExportConfig};
+use opentelemetry_sdk::logs::SdkLoggerProvider;
+use tracing_subscriber::{filter::filter_fn, layer::SubscriberExt, util::SubscriberInitExt, Layer};
+
+/// Keeps the OTLP exporter alive; flushes pending logs when shut down.
+pub struct Telemetry {
+    provider: Option<SdkLoggerProvider>,
+}
+
+impl Telemetry {
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush OTLP logs: {}", e);
+            }
+        }
+    }
+}
+
+pub fn init() -> Telemetry {
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json"));
+
+    let mut otlp_error = None;
+    let provider = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .filter(|endpoint| !endpoint.trim().is_empty())
+        .and_then(|endpoint| match otlp_provider(&endpoint) {
+            Ok(provider) => Some(provider),
+            Err(e) => {
+                otlp_error = Some(e);
+                None
+            }
+        });
+
+    // The exporter's own HTTP client logs through tracing too; shipping those
+    // lines would feed back into the exporter.
+    let otlp = provider.as_ref().map(|provider| {
+        OpenTelemetryTracingBridge::new(provider).with_filter(filter_fn(|meta| {
+            !["hyper", "reqwest", "h2", "opentelemetry"].iter().any(|t| meta.target().starts_with(t))
+        }))
+    });
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "info,backend=debug,sqlx=warn".into()),
+        )
+        .with((!json).then(tracing_subscriber::fmt::layer))
+        .with(json.then(|| tracing_subscriber::fmt::layer().json()))
+        .with(otlp)
+        .init();
+
+    if let Some(e) = otlp_error {
+        tracing::warn!("OTLP log export disabled: {}", e);
+    }
+
+    Telemetry { provider }
+}
+
+/// Sends to `<endpoint>/v1/logs`, the OTLP/HTTP convention for the base
+/// endpoint variable.
+fn otlp_provider(endpoint: &str) -> Result<SdkLoggerProvider, String> {
+    let exporter = LogExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/logs", endpoint.trim_end_matches('/')))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok(SdkLoggerProvider::builder().with_batch_exporter(exporter).build())
+}
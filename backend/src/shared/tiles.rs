@@ -0,0 +1,67 @@
+//! Mapbox Vector Tiles rendered by PostGIS. Layers list the attributes a
+//! client may ask for, so `fields` never reaches SQL as text.
+
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use crate::shared::error::{AppError, AppResult};
+
+pub const CONTENT_TYPE: &str = "application/vnd.mapbox-vector-tile";
+const MAX_ZOOM: u32 = 22;
+
+/// Tile address in the XYZ scheme.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TileCoord {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileCoord {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.z > MAX_ZOOM {
+            return Err(AppError::Validation(format!("z must be at most {}", MAX_ZOOM)));
+        }
+        let tiles = 1u32 << self.z;
+        if self.x >= tiles || self.y >= tiles {
+            return Err(AppError::Validation(format!("x and y must be below {} at zoom {}", tiles, self.z)));
+        }
+        Ok(())
+    }
+}
+
+/// A layer attribute as its name in the tile and the SQL expression behind it.
+pub type TileField = (&'static str, &'static str);
+
+/// `expr AS name` for each requested field, or every field when `requested`
+/// is absent. `requested` is a comma-separated list of names.
+pub fn select_fields(requested: Option<&str>, available: &[TileField]) -> AppResult<Vec<String>> {
+    let select = |(name, expr): &TileField| format!("{} AS {}", expr, name);
+
+    let Some(requested) = requested else {
+        return Ok(available.iter().map(select).collect());
+    };
+
+    requested
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            available.iter().find(|(field, _)| *field == name).map(select).ok_or_else(|| {
+                let names: Vec<&str> = available.iter().map(|(field, _)| *field).collect();
+                AppError::Validation(format!("Unknown field '{}'; use any of {}", name, names.join(", ")))
+            })
+        })
+        .collect()
+}
+
+/// Tiles are per user, so they may only be cached privately and briefly.
+pub fn response(tile: Vec<u8>) -> Response {
+    (
+        [(header::CONTENT_TYPE, CONTENT_TYPE), (header::CACHE_CONTROL, "private, max-age=60")],
+        tile,
+    )
+        .into_response()
+}
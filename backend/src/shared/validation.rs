@@ -0,0 +1,190 @@
+//! `ValidatedJson<T>` parses a JSON body and runs the payload's `Validate`
+//! rules. Every problem is reported at once as a 422 whose `details` lists
+//! one `{field, code, message}` object per violation:
+//!
+//! ```json
+//! {"error": "2 invalid field(s)", "code": "INVALID_FIELDS", "details": {"violations": [
+//!     {"field": "email", "code": "email", "message": "must be a valid email address"},
+//!     {"field": "widgets[2].width", "code": "range", "message": "must be between 1 and 4"}
+//! ]}}
+//! ```
+//!
+//! As with `Json<T>`, a missing or non-JSON `Content-Type` is a 415 and
+//! malformed JSON a 400; a body of the wrong shape (missing field, wrong
+//! type) is a 422 with a single violation.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+use crate::shared::error::{AppError, ErrorCode};
+
+/// Key `validator` uses for the items of a top-level collection.
+const COLLECTION_KEY: &str = "_tmp_validator";
+/// Key of errors raised by `#[validate(schema(...))]` checks.
+const SCHEMA_KEY: &str = "__all__";
+
+/// One failed rule, as listed in a 422 response.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldViolation {
+    /// Path to the value, e.g. `name` or `readings[3].value`; empty for the body itself.
+    pub field: String,
+    /// Rule that failed: `length`, `range`, `email`, `invalid_type`...
+    pub code: String,
+    pub message: String,
+}
+
+/// Drop-in replacement for `Json<T>` in handler arguments.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(req.headers()) {
+            return Err(AppError::Coded(
+                ErrorCode::UnsupportedMediaType,
+                "Expected a body with Content-Type: application/json".to_string(),
+            )
+            .into_response());
+        }
+        let bytes = Bytes::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+
+        let value = parse::<T>(&bytes).map_err(IntoResponse::into_response)?;
+        value.validate().map_err(|errors| invalid(violations(&errors)).into_response())?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+/// Rule for required text: `#[validate(custom(function = "not_blank"))]`.
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::new("blank"));
+    }
+    Ok(())
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .is_some_and(|mime| mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json")))
+}
+
+fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, AppError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let field = match e.path().to_string() {
+            root if root == "." => String::new(),
+            path => path,
+        };
+        let inner = e.into_inner();
+        if inner.is_data() {
+            invalid(vec![FieldViolation {
+                field,
+                code: "invalid_type".to_string(),
+                message: inner.to_string(),
+            }])
+        } else {
+            malformed(inner)
+        }
+    })?;
+    deserializer.end().map_err(malformed)?;
+    Ok(value)
+}
+
+fn malformed(e: serde_json::Error) -> AppError {
+    AppError::BadRequest(format!("Malformed JSON body: {}", e))
+}
+
+fn invalid(violations: Vec<FieldViolation>) -> AppError {
+    AppError::Detailed(
+        ErrorCode::InvalidFields,
+        format!("{} invalid field(s)", violations.len()),
+        serde_json::json!({ "violations": violations }),
+    )
+}
+
+/// Flattens nested errors into one entry per failed rule, sorted by field.
+pub fn violations(errors: &ValidationErrors) -> Vec<FieldViolation> {
+    let mut out = Vec::new();
+    collect(errors, "", &mut out);
+    out.sort_by(|a, b| a.field.cmp(&b.field).then_with(|| a.code.cmp(&b.code)));
+    out
+}
+
+fn collect(errors: &ValidationErrors, prefix: &str, out: &mut Vec<FieldViolation>) {
+    for (name, kind) in errors.errors() {
+        let path = match (prefix, name.as_ref()) {
+            (prefix, COLLECTION_KEY | SCHEMA_KEY) => prefix.to_string(),
+            ("", name) => name.to_string(),
+            (prefix, name) => format!("{}.{}", prefix, name),
+        };
+        match kind {
+            ValidationErrorsKind::Field(failures) => out.extend(failures.iter().map(|failure| FieldViolation {
+                field: path.clone(),
+                code: failure.code.to_string(),
+                message: message(failure),
+            })),
+            ValidationErrorsKind::Struct(nested) => collect(nested, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect(nested, &format!("{}[{}]", path, index), out);
+                }
+            }
+        }
+    }
+}
+
+/// The rule's own message, or a description built from its parameters.
+fn message(failure: &ValidationError) -> String {
+    if let Some(message) = &failure.message {
+        return message.to_string();
+    }
+    let param = |name: &str| failure.params.get(name).filter(|v| !v.is_null()).map(display);
+
+    match failure.code.as_ref() {
+        "length" => {
+            let unit = if failure.params.get("value").is_some_and(Value::is_array) { "items" } else { "characters" };
+            match (param("equal"), param("min"), param("max")) {
+                (Some(n), _, _) => format!("must have exactly {} {}", n, unit),
+                (None, Some(min), Some(max)) => format!("must have between {} and {} {}", min, max, unit),
+                (None, Some(min), None) => format!("must have at least {} {}", min, unit),
+                (None, None, Some(max)) => format!("must have at most {} {}", max, unit),
+                (None, None, None) => "has an invalid length".to_string(),
+            }
+        }
+        "range" => match (param("min").or(param("exclusive_min")), param("max").or(param("exclusive_max"))) {
+            (Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+            (Some(min), None) => format!("must be at least {}", min),
+            (None, Some(max)) => format!("must be at most {}", max),
+            (None, None) => "is out of range".to_string(),
+        },
+        "email" => "must be a valid email address".to_string(),
+        "url" => "must be a valid URL".to_string(),
+        "required" => "is required".to_string(),
+        "must_match" => "does not match".to_string(),
+        "blank" => "must not be blank".to_string(),
+        code => format!("failed the '{}' check", code),
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
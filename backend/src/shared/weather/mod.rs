@@ -0,0 +1,43 @@
+pub mod open_meteo;
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::sync::Arc;
+use crate::shared::error::AppResult;
+use open_meteo::OpenMeteoProvider;
+
+/// One day of weather at a point, observed or forecast.
+#[derive(Debug, Clone)]
+pub struct DailyWeather {
+    pub date: NaiveDate,
+    pub t_min_c: Option<f64>,
+    pub t_max_c: Option<f64>,
+    pub precipitation_mm: Option<f64>,
+    /// FAO-56 reference evapotranspiration, when the provider computes it.
+    pub et0_mm: Option<f64>,
+    /// Highest sea level of the day relative to mean sea level, including
+    /// tides; only available for coastal points.
+    pub sea_level_max_m: Option<f64>,
+}
+
+#[async_trait]
+pub trait WeatherProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Daily weather at (`lat`, `lon`) from `past_days` ago through
+    /// `forecast_days` ahead.
+    async fn daily(&self, lat: f64, lon: f64, past_days: u32, forecast_days: u32) -> AppResult<Vec<DailyWeather>>;
+}
+
+/// Selects the provider named by `WEATHER_PROVIDER`; `none` disables weather
+/// ingestion entirely.
+pub fn provider_from_env() -> Option<Arc<dyn WeatherProvider>> {
+    match std::env::var("WEATHER_PROVIDER").as_deref() {
+        Ok("none") => None,
+        Ok("open-meteo") | Err(_) => Some(Arc::new(OpenMeteoProvider::from_env())),
+        Ok(other) => {
+            tracing::warn!("Unknown WEATHER_PROVIDER '{}', weather ingestion disabled", other);
+            None
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use crate::shared::error::{AppError, AppResult};
+use super::{DailyWeather, WeatherProvider};
+
+const DEFAULT_FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+const DEFAULT_MARINE_URL: &str = "https://marine-api.open-meteo.com/v1/marine";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Open-Meteo client. Land weather comes from the forecast API; sea level
+/// (tide plus surge) from the marine API, which has no data inland.
+pub struct OpenMeteoProvider {
+    client: reqwest::Client,
+    forecast_url: String,
+    marine_url: String,
+}
+
+impl OpenMeteoProvider {
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            forecast_url: std::env::var("OPEN_METEO_FORECAST_URL").unwrap_or_else(|_| DEFAULT_FORECAST_URL.to_string()),
+            marine_url: std::env::var("OPEN_METEO_MARINE_URL").unwrap_or_else(|_| DEFAULT_MARINE_URL.to_string()),
+        }
+    }
+
+    async fn fetch<T: serde::de::DeserializeOwned>(&self, url: &str, query: &[(&str, String)]) -> AppResult<T> {
+        let response = self
+            .client
+            .get(url)
+            .timeout(REQUEST_TIMEOUT)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Open-Meteo request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Internal(format!("Open-Meteo responded with {}", response.status())));
+        }
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| AppError::Internal(format!("Invalid Open-Meteo response: {}", e)))
+    }
+
+    /// Daily maximum sea level, keyed by date. Missing for inland points.
+    async fn sea_level(&self, lat: f64, lon: f64, past_days: u32, forecast_days: u32) -> BTreeMap<NaiveDate, f64> {
+        let query = [
+            ("latitude", lat.to_string()),
+            ("longitude", lon.to_string()),
+            ("hourly", "sea_level_height_msl".to_string()),
+            ("past_days", past_days.to_string()),
+            ("forecast_days", forecast_days.to_string()),
+            ("timezone", "auto".to_string()),
+        ];
+
+        let marine = match self.fetch::<MarineResponse>(&self.marine_url, &query).await {
+            Ok(marine) => marine,
+            Err(e) => {
+                tracing::debug!("No sea level data at ({}, {}): {}", lat, lon, e);
+                return BTreeMap::new();
+            }
+        };
+
+        let mut daily = BTreeMap::new();
+        for (time, level) in marine.hourly.time.iter().zip(marine.hourly.sea_level_height_msl) {
+            let (Some(level), Some(date)) = (level, time.get(..10).and_then(|d| d.parse::<NaiveDate>().ok())) else {
+                continue;
+            };
+            daily
+                .entry(date)
+                .and_modify(|max: &mut f64| *max = max.max(level))
+                .or_insert(level);
+        }
+
+        daily
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for OpenMeteoProvider {
+    fn name(&self) -> &'static str {
+        "open-meteo"
+    }
+
+    async fn daily(&self, lat: f64, lon: f64, past_days: u32, forecast_days: u32) -> AppResult<Vec<DailyWeather>> {
+        let query = [
+            ("latitude", lat.to_string()),
+            ("longitude", lon.to_string()),
+            (
+                "daily",
+                "temperature_2m_min,temperature_2m_max,precipitation_sum,et0_fao_evapotranspiration".to_string(),
+            ),
+            ("past_days", past_days.to_string()),
+            ("forecast_days", forecast_days.to_string()),
+            ("timezone", "auto".to_string()),
+        ];
+
+        let forecast: ForecastResponse = self.fetch(&self.forecast_url, &query).await?;
+        let sea_level = self.sea_level(lat, lon, past_days, forecast_days).await;
+        let daily = forecast.daily;
+
+        daily
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, day)| {
+                let date = day
+                    .parse::<NaiveDate>()
+                    .map_err(|e| AppError::Internal(format!("Invalid Open-Meteo date '{}': {}", day, e)))?;
+
+                Ok(DailyWeather {
+                    date,
+                    t_min_c: daily.temperature_2m_min.get(i).copied().flatten(),
+                    t_max_c: daily.temperature_2m_max.get(i).copied().flatten(),
+                    precipitation_mm: daily.precipitation_sum.get(i).copied().flatten(),
+                    et0_mm: daily.et0_fao_evapotranspiration.get(i).copied().flatten(),
+                    sea_level_max_m: sea_level.get(&date).copied(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    daily: ForecastDaily,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastDaily {
+    time: Vec<String>,
+    #[serde(default)]
+    temperature_2m_min: Vec<Option<f64>>,
+    #[serde(default)]
+    temperature_2m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_sum: Vec<Option<f64>>,
+    #[serde(default)]
+    et0_fao_evapotranspiration: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarineResponse {
+    hourly: MarineHourly,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarineHourly {
+    time: Vec<String>,
+    #[serde(default)]
+    sea_level_height_msl: Vec<Option<f64>>,
+}
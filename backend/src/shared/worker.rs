@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tracing::Instrument;
+use crate::shared::{error::AppResult, request_id, runtime};
+
+/// Outcome of a background job's most recent run, for readiness probes.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    pub period: Duration,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+static HEARTBEATS: LazyLock<Mutex<HashMap<&'static str, Heartbeat>>> = LazyLock::new(Default::default);
+
+/// Heartbeats of every job spawned with `spawn_periodic`, sorted by name.
+pub fn heartbeats() -> Vec<(&'static str, Heartbeat)> {
+    let heartbeats = HEARTBEATS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut all: Vec<_> = heartbeats.iter().map(|(name, hb)| (*name, hb.clone())).collect();
+    all.sort_by_key(|(name, _)| *name);
+    all
+}
+
+fn record_heartbeat(name: &'static str, period: Duration, finished_at: Option<DateTime<Utc>>, error: Option<String>) {
+    let mut heartbeats = HEARTBEATS.lock().unwrap_or_else(|e| e.into_inner());
+    heartbeats.insert(name, Heartbeat {
+        period,
+        last_finished_at: finished_at,
+        last_error: error,
+    });
+}
+
+fn record_period(name: &'static str, period: Duration) {
+    let mut heartbeats = HEARTBEATS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(heartbeat) = heartbeats.get_mut(name) {
+        heartbeat.period = period;
+    }
+}
+
+fn ticker(start: Instant, period: Duration) -> Interval {
+    let mut ticker = tokio::time::interval_at(start, period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+/// Runs `task` every `period` on the Tokio runtime. Failures are logged and the
+/// job keeps its schedule; a single bad run never stops the worker. A
+/// `job_intervals_secs` entry in the runtime settings overrides `period` and
+/// reschedules the job as soon as it is published.
+pub fn spawn_periodic<F, Fut>(name: &'static str, default_period: Duration, mut task: F)
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = AppResult<()>> + Send + 'static,
+{
+    let mut settings = runtime::subscribe();
+    let mut period = settings.borrow_and_update().job_interval(name).unwrap_or(default_period);
+    record_heartbeat(name, period, None, None);
+
+    tokio::spawn(async move {
+        let mut ticker = ticker(Instant::now(), period);
+
+        tracing::info!("Background job '{}' scheduled every {:?}", name, period);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                Ok(()) = settings.changed() => {
+                    let next = settings.borrow_and_update().job_interval(name).unwrap_or(default_period);
+                    if next != period {
+                        tracing::info!("Background job '{}' rescheduled from {:?} to {:?}", name, period, next);
+                        period = next;
+                        ticker = self::ticker(Instant::now() + period, period);
+                        record_period(name, period);
+                    }
+                    continue;
+                }
+            }
+
+            let run_id = request_id::generate();
+            let span = tracing::info_span!("job", name, request_id = %run_id);
+            let result = request_id::scope(run_id, task().instrument(span.clone())).await;
+
+            if let Err(e) = &result {
+                span.in_scope(|| tracing::warn!("Background job '{}' failed: {}", name, e));
+            }
+            record_heartbeat(name, period, Some(Utc::now()), result.err().map(|e| e.to_string()));
+        }
+    });
+}
+
+pub fn interval_from_env(var: &str, default_secs: u64) -> Duration {
+    let secs = std::env::var(var)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&v| v > 0)
+        .unwrap_or(default_secs);
+    Duration::from_secs(secs)
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use backend::modules::monitoring::{models::AlertSeverity, service as monitoring};
+use chrono::{Duration, Utc};
+use common::TestApp;
+use serde_json::json;
+
+const FARM_BOUNDARY: &str = r#"{"type":"Polygon","coordinates":[[[106.30,10.20],[106.31,10.20],[106.31,10.21],[106.30,10.21],[106.30,10.20]]]}"#;
+
+/// A week of NDSI readings around 0.11, then one far above them.
+fn readings_csv() -> String {
+    let baseline = [0.10, 0.11, 0.10, 0.12, 0.11, 0.10, 0.11];
+    let now = Utc::now();
+
+    let mut csv = String::from("recorded_at,ndsi\n");
+    for (days_ago, ndsi) in (1..=baseline.len() as i64).rev().zip(baseline) {
+        csv.push_str(&format!("{},{}\n", (now - Duration::days(days_ago)).to_rfc3339(), ndsi));
+    }
+    csv.push_str(&format!("{},{}\n", now.to_rfc3339(), 0.45));
+    csv
+}
+
+/// Sign up, draw a farm, bring in salinity readings, get alerted, act on
+/// the alert and export the farm for the provincial office.
+#[tokio::test]
+async fn salinity_readings_raise_an_alert_that_reaches_the_report() {
+    let Some(app) = TestApp::spawn().await else { return };
+
+    let token = app.register("farmer@example.com").await;
+    let login = app
+        .post_json("/api/auth/login", None, &json!({ "email": "Farmer@Example.com", "password": common::PASSWORD }))
+        .await;
+    assert_eq!(login.status, StatusCode::OK, "login is case-insensitive on the email");
+
+    let anonymous = app.post_json("/api/farms", None, &json!({ "name": "Anonymous", "geojson": FARM_BOUNDARY })).await;
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+
+    let farm = app
+        .post_json("/api/farms", Some(&token), &json!({ "name": "Ruộng lúa Bến Tre", "region": "Ben Tre", "geojson": FARM_BOUNDARY }))
+        .await;
+    assert_eq!(farm.status, StatusCode::OK, "create farm: {}", String::from_utf8_lossy(&farm.body));
+    let farm_id = farm.json()["id"].as_i64().expect("farm id");
+
+    let import = app
+        .request(
+            Method::POST,
+            &format!("/api/monitoring/salinity/import?farm_id={}", farm_id),
+            Some(&token),
+            Some("text/csv"),
+            readings_csv(),
+        )
+        .await;
+    assert_eq!(import.status, StatusCode::OK, "import: {}", String::from_utf8_lossy(&import.body));
+    let import = import.json();
+    assert_eq!(import["imported"], 8);
+    assert_eq!(import["failed"], 0);
+
+    // An analysis ends by saving an NDSI reading and evaluating the alert
+    // rules; the import saved the readings, so only the evaluation is left.
+    // It runs directly because analyses need the segmentation model.
+    let evaluation = monitoring::detect_salinity_anomaly(farm_id, &app.db).await.expect("rule evaluation failed");
+    let alert = evaluation.alert.expect("a reading this far above the week's average raises an alert");
+    assert_eq!(alert.severity, AlertSeverity::Critical);
+
+    let alerts = app.get(&format!("/api/monitoring/alerts/{}", farm_id), Some(&token)).await;
+    assert_eq!(alerts.status, StatusCode::OK);
+    let alerts = alerts.json();
+    let listed = alerts.as_array().expect("alerts are a list");
+    assert!(listed.iter().any(|a| a["id"] == alert.id), "new alert is listed: {}", alerts);
+
+    let neighbour = app.register("neighbour@example.com").await;
+    let acknowledge_uri = format!("/api/monitoring/alerts/{}/acknowledge", alert.id);
+    let denied = app.post_json(&acknowledge_uri, Some(&neighbour), &json!({})).await;
+    assert_eq!(denied.status, StatusCode::UNAUTHORIZED, "only the farm's owner acknowledges its alerts");
+
+    let acknowledged = app.post_json(&acknowledge_uri, Some(&token), &json!({})).await;
+    assert_eq!(acknowledged.status, StatusCode::OK);
+    assert_eq!(acknowledged.json()["acknowledged"], true);
+
+    let report = app.post_json("/api/reports/export/gpkg", Some(&token), &json!({ "farm_ids": [farm_id] })).await;
+    assert_eq!(report.status, StatusCode::OK, "export: {}", String::from_utf8_lossy(&report.body));
+    assert_eq!(report.content_type.as_deref(), Some("application/geopackage+sqlite3"));
+    assert!(report.body.starts_with(b"SQLite format 3\0"), "export is a GeoPackage");
+
+    app.cleanup().await;
+}